@@ -922,7 +922,7 @@ impl ActiveThread {
                     );
                 }
             }
-            ThreadEvent::CheckpointChanged => cx.notify(),
+            ThreadEvent::CheckpointChanged | ThreadEvent::Compacted => cx.notify(),
         }
     }
 
@@ -1282,6 +1282,9 @@ impl ActiveThread {
 
         // Get all the data we need from thread before we start using it in closures
         let checkpoint = thread.checkpoint_for_message(message_id);
+        let is_compaction_boundary = thread
+            .compaction()
+            .is_some_and(|compaction| compaction.first_kept_message_id == message_id);
         let context = thread.context_for_message(message_id).collect::<Vec<_>>();
 
         let tool_uses = thread.tool_uses_for_message(message_id, cx);
@@ -1761,6 +1764,24 @@ impl ActiveThread {
             .when(is_first_message, |parent| {
                 parent.child(self.render_rules_item(cx))
             })
+            .when(is_compaction_boundary, |parent| {
+                parent.child(
+                    h_flex()
+                        .pt_2p5()
+                        .px_2p5()
+                        .w_full()
+                        .gap_1()
+                        .child(ui::Divider::horizontal())
+                        .child(
+                            Label::new(
+                                "Earlier messages were summarized to fit the context window",
+                            )
+                            .size(LabelSize::XSmall)
+                            .color(Color::Muted),
+                        )
+                        .child(ui::Divider::horizontal()),
+                )
+            })
             .child(styled_message)
             .when(!needs_confirmation && generating_label.is_some(), |this| {
                 this.child(
@@ -2666,6 +2687,31 @@ impl ActiveThread {
                                                 },
                                             ))
                                         })
+                                        .child({
+                                            let tool_id = tool_use.id.clone();
+                                            Button::new(
+                                                "always-allow-similar-tool-action",
+                                                "Always Allow This",
+                                            )
+                                            .label_size(LabelSize::Small)
+                                            .icon(IconName::Check)
+                                            .icon_position(IconPosition::Start)
+                                            .icon_size(IconSize::Small)
+                                            .icon_color(Color::Success)
+                                            .tooltip(Tooltip::text(
+                                                "Add a rule allowing similar tool uses to the current profile",
+                                            ))
+                                            .on_click(cx.listener(
+                                                move |this, event, window, cx| {
+                                                    this.handle_always_allow_similar_tool(
+                                                        tool_id.clone(),
+                                                        event,
+                                                        window,
+                                                        cx,
+                                                    )
+                                                },
+                                            ))
+                                        })
                                         .child(ui::Divider::vertical())
                                         .child({
                                             let tool_id = tool_use.id.clone();
@@ -2778,23 +2824,45 @@ impl ActiveThread {
         _window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        if let Some(PendingToolUseStatus::NeedsConfirmation(c)) = self
-            .thread
-            .read(cx)
+        self.thread.update(cx, |thread, cx| {
+            thread.allow_tool_use(&tool_use_id, cx);
+        });
+    }
+
+    fn handle_always_allow_similar_tool(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        event: &ClickEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let thread = self.thread.read(cx);
+        if let Some(PendingToolUseStatus::NeedsConfirmation(c)) = thread
             .pending_tool(&tool_use_id)
             .map(|tool_use| tool_use.status.clone())
         {
-            self.thread.update(cx, |thread, cx| {
-                thread.run_tool(
-                    c.tool_use_id.clone(),
-                    c.ui_text.clone(),
-                    c.input.clone(),
-                    &c.messages,
-                    c.tool.clone(),
-                    cx,
-                );
-            });
+            let rules = thread.allow_rules_for_tool_use(c.tool.as_ref(), &c.input, cx);
+            let settings = AssistantSettings::get_global(cx);
+            let profile_id = settings.default_profile.clone();
+            let profile = settings.profiles.get(&profile_id).cloned();
+            let fs = self
+                .workspace
+                .upgrade()
+                .map(|workspace| workspace.read(cx).app_state().fs.clone());
+            if let Some((fs, profile)) = fs.zip(profile) {
+                update_settings_file::<AssistantSettings>(fs, cx, move |settings, _| {
+                    for rule in rules {
+                        settings.add_tool_permission_rule(
+                            profile_id.clone(),
+                            profile.clone(),
+                            rule,
+                        );
+                    }
+                });
+            }
         }
+
+        self.handle_allow_tool(tool_use_id, event, window, cx);
     }
 
     fn handle_deny_tool(
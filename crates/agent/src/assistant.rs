@@ -104,8 +104,7 @@ pub fn init(
     prompt_builder: Arc<PromptBuilder>,
     cx: &mut App,
 ) {
-    AssistantSettings::register(cx);
-    thread_store::init(cx);
+    init_headless(cx);
     assistant_panel::init(cx);
 
     inline_assistant::init(
@@ -126,6 +125,12 @@ pub fn init(
     feature_gate_agent_actions(cx);
 }
 
+/// Initializes what is needed to run threads without the agent panel, such as in headless runs.
+pub fn init_headless(cx: &mut App) {
+    AssistantSettings::register(cx);
+    thread_store::init(cx);
+}
+
 fn feature_gate_agent_actions(cx: &mut App) {
     CommandPaletteFilter::update_global(cx, |filter, _cx| {
         filter.hide_namespace(NAMESPACE);
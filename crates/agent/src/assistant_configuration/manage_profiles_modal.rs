@@ -232,7 +232,11 @@ impl ManageProfilesModal {
                         .map(|profile| profile.enable_all_context_servers)
                         .unwrap_or_default(),
                     context_servers: base_profile
-                        .map(|profile| profile.context_servers)
+                        .as_ref()
+                        .map(|profile| profile.context_servers.clone())
+                        .unwrap_or_default(),
+                    tool_permissions: base_profile
+                        .map(|profile| profile.tool_permissions)
                         .unwrap_or_default(),
                 };
 
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 
 use assistant_settings::{
-    AgentProfile, AgentProfileContent, AgentProfileId, AssistantSettings, AssistantSettingsContent,
-    ContextServerPresetContent, VersionedAssistantSettingsContent,
+    AgentProfile, AgentProfileId, AssistantSettings, AssistantSettingsContent,
+    VersionedAssistantSettingsContent,
 };
 use assistant_tool::{ToolSource, ToolWorkingSet};
 use fs::Fs;
@@ -205,28 +205,9 @@ impl PickerDelegate for ToolPickerDelegate {
                 AssistantSettingsContent::Versioned(boxed) => {
                     if let VersionedAssistantSettingsContent::V2(ref mut settings) = **boxed {
                         let profiles = settings.profiles.get_or_insert_default();
-                        let profile =
-                            profiles
-                                .entry(profile_id)
-                                .or_insert_with(|| AgentProfileContent {
-                                    name: default_profile.name.into(),
-                                    tools: default_profile.tools,
-                                    enable_all_context_servers: Some(
-                                        default_profile.enable_all_context_servers,
-                                    ),
-                                    context_servers: default_profile
-                                        .context_servers
-                                        .into_iter()
-                                        .map(|(server_id, preset)| {
-                                            (
-                                                server_id,
-                                                ContextServerPresetContent {
-                                                    tools: preset.tools,
-                                                },
-                                            )
-                                        })
-                                        .collect(),
-                                });
+                        let profile = profiles
+                            .entry(profile_id)
+                            .or_insert_with(|| default_profile.into());
 
                         match tool.source {
                             ToolSource::Native => {
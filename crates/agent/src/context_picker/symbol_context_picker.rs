@@ -322,22 +322,27 @@ pub(crate) fn search_symbols(
         };
 
         const MAX_MATCHES: usize = 100;
-        let mut visible_matches = cx.background_executor().block(fuzzy::match_strings(
-            &visible_match_candidates,
-            &query,
-            false,
-            MAX_MATCHES,
-            &cancellation_flag,
-            cx.background_executor().clone(),
-        ));
-        let mut external_matches = cx.background_executor().block(fuzzy::match_strings(
-            &external_match_candidates,
-            &query,
-            false,
-            MAX_MATCHES - visible_matches.len().min(MAX_MATCHES),
-            &cancellation_flag,
-            cx.background_executor().clone(),
-        ));
+        let query = fuzzy::Query::parse(&query);
+        let mut visible_matches = cx
+            .background_executor()
+            .block(fuzzy::match_strings_with_query(
+                &visible_match_candidates,
+                &query,
+                false,
+                MAX_MATCHES,
+                &cancellation_flag,
+                cx.background_executor().clone(),
+            ));
+        let mut external_matches = cx
+            .background_executor()
+            .block(fuzzy::match_strings_with_query(
+                &external_match_candidates,
+                &query,
+                false,
+                MAX_MATCHES - visible_matches.len().min(MAX_MATCHES),
+                &cancellation_flag,
+                cx.background_executor().clone(),
+            ));
         let sort_key_for_match = |mat: &StringMatch| {
             let symbol = &symbols[mat.candidate_id];
             (Reverse(OrderedFloat(mat.score)), symbol.label.filter_text())
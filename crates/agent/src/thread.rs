@@ -5,8 +5,8 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::{Context as _, Result, anyhow};
-use assistant_settings::AssistantSettings;
-use assistant_tool::{ActionLog, Tool, ToolWorkingSet};
+use assistant_settings::{AssistantSettings, ToolPermission, ToolPermissionRule};
+use assistant_tool::{ActionLog, Tool, ToolWorkingSet, tool_permission_subjects};
 use chrono::{DateTime, Utc};
 use collections::{BTreeMap, HashMap};
 use feature_flags::{self, FeatureFlagAppExt};
@@ -35,7 +35,9 @@ use crate::thread_store::{
     SerializedMessage, SerializedMessageSegment, SerializedThread, SerializedToolResult,
     SerializedToolUse, SharedProjectContext,
 };
-use crate::tool_use::{PendingToolUse, ToolUse, ToolUseState, USING_TOOL_MARKER};
+use crate::tool_use::{
+    PendingToolUse, PendingToolUseStatus, ToolUse, ToolUseState, USING_TOOL_MARKER,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum RequestKind {
@@ -221,6 +223,16 @@ pub enum DetailedSummaryState {
     },
 }
 
+/// Older messages of a [`Thread`] that are replaced with a summary when sending the thread to the
+/// model, so that it fits in the model's context window.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ThreadCompaction {
+    /// The first message that is still sent to the model as is.
+    pub first_kept_message_id: MessageId,
+    pub summary: SharedString,
+    pub compacted_at: DateTime<Utc>,
+}
+
 #[derive(Default)]
 pub struct TotalTokenUsage {
     pub total: usize,
@@ -243,6 +255,7 @@ pub struct Thread {
     summary: Option<SharedString>,
     pending_summary: Task<Option<()>>,
     detailed_summary_state: DetailedSummaryState,
+    compactions: Vec<ThreadCompaction>,
     messages: Vec<Message>,
     next_message_id: MessageId,
     context: BTreeMap<ContextId, AssistantContext>,
@@ -260,9 +273,13 @@ pub struct Thread {
     pending_checkpoint: Option<ThreadCheckpoint>,
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     cumulative_token_usage: TokenUsage,
+    request_token_usage: TokenUsage,
     feedback: Option<ThreadFeedback>,
     message_feedback: HashMap<MessageId, ThreadFeedback>,
     last_auto_capture_at: Option<Instant>,
+    /// The model the thread last sent a chat request to, which follow-up requests, such as the
+    /// ones carrying tool results, are sent to as well.
+    model: Option<Arc<dyn LanguageModel>>,
 }
 
 impl Thread {
@@ -279,6 +296,7 @@ impl Thread {
             summary: None,
             pending_summary: Task::ready(None),
             detailed_summary_state: DetailedSummaryState::NotGenerated,
+            compactions: Vec::new(),
             messages: Vec::new(),
             next_message_id: MessageId(0),
             context: BTreeMap::default(),
@@ -301,9 +319,11 @@ impl Thread {
                     .shared()
             },
             cumulative_token_usage: TokenUsage::default(),
+            request_token_usage: TokenUsage::default(),
             feedback: None,
             message_feedback: HashMap::default(),
             last_auto_capture_at: None,
+            model: None,
         }
     }
 
@@ -332,6 +352,7 @@ impl Thread {
             summary: Some(serialized.summary),
             pending_summary: Task::ready(None),
             detailed_summary_state: serialized.detailed_summary_state,
+            compactions: serialized.compactions,
             messages: serialized
                 .messages
                 .into_iter()
@@ -367,9 +388,11 @@ impl Thread {
             action_log: cx.new(|_| ActionLog::new(project)),
             initial_project_snapshot: Task::ready(serialized.initial_project_snapshot).shared(),
             cumulative_token_usage: serialized.cumulative_token_usage,
+            request_token_usage: TokenUsage::default(),
             feedback: None,
             message_feedback: HashMap::default(),
             last_auto_capture_at: None,
+            model: None,
         }
     }
 
@@ -442,6 +465,16 @@ impl Thread {
         self.messages.iter()
     }
 
+    /// Returns the latest compaction of this thread, if its older messages were summarized.
+    pub fn compaction(&self) -> Option<&ThreadCompaction> {
+        self.compactions.last()
+    }
+
+    fn is_compacted(&self, id: MessageId) -> bool {
+        self.compaction()
+            .is_some_and(|compaction| id < compaction.first_kept_message_id)
+    }
+
     pub fn is_generating(&self) -> bool {
         !self.pending_completions.is_empty() || !self.all_tools_finished()
     }
@@ -582,6 +615,8 @@ impl Thread {
             self.context_by_message.remove(&deleted_message.id);
             self.checkpoints_by_message.remove(&deleted_message.id);
         }
+        self.compactions
+            .retain(|compaction| compaction.first_kept_message_id <= message_id);
         cx.notify();
     }
 
@@ -817,6 +852,7 @@ impl Thread {
                 initial_project_snapshot,
                 cumulative_token_usage: this.cumulative_token_usage.clone(),
                 detailed_summary_state: this.detailed_summary_state.clone(),
+                compactions: this.compactions.clone(),
             })
         })
     }
@@ -827,6 +863,14 @@ impl Thread {
         request_kind: RequestKind,
         cx: &mut Context<Self>,
     ) {
+        if matches!(request_kind, RequestKind::Chat) {
+            self.model = Some(model.clone());
+            if self.should_compact(model.as_ref(), cx) {
+                self.compact(model, request_kind, cx);
+                return;
+            }
+        }
+
         let mut request = self.to_completion_request(request_kind, cx);
         if model.supports_tools() {
             request.tools = {
@@ -887,7 +931,15 @@ impl Thread {
             log::error!("project_context not set.")
         }
 
-        for message in &self.messages {
+        if let Some(compaction_message) = self.compaction_request_message() {
+            request.messages.push(compaction_message);
+        }
+
+        for message in self
+            .messages
+            .iter()
+            .filter(|message| !self.is_compacted(message.id))
+        {
             let mut request_message = LanguageModelRequestMessage {
                 role: message.role,
                 content: Vec::new(),
@@ -1018,6 +1070,7 @@ impl Thread {
                                 stop_reason = reason;
                             }
                             LanguageModelCompletionEvent::UsageUpdate(token_usage) => {
+                                thread.request_token_usage = token_usage.clone();
                                 thread.cumulative_token_usage =
                                     thread.cumulative_token_usage.clone() + token_usage.clone()
                                         - current_token_usage.clone();
@@ -1307,6 +1360,199 @@ impl Thread {
         )
     }
 
+    /// Returns whether the last request to the model used enough of its context window that the
+    /// older messages should be summarized before sending the next one.
+    fn should_compact(&self, model: &dyn LanguageModel, cx: &App) -> bool {
+        let threshold = AssistantSettings::get_global(cx).auto_compact_threshold;
+        if threshold <= 0. {
+            return false;
+        }
+
+        let used_tokens = self.request_token_usage.total_tokens() as f32;
+        used_tokens >= threshold * model.max_token_count() as f32
+            && self.compaction_boundary().is_some()
+    }
+
+    /// Returns the index of the first message to keep when compacting the thread. Everything
+    /// before the last assistant message is summarized, so that the kept messages still start
+    /// with the tool uses their tool results refer to.
+    fn compaction_boundary(&self) -> Option<usize> {
+        let first_uncompacted_ix = self
+            .messages
+            .iter()
+            .position(|message| !self.is_compacted(message.id))?;
+        let boundary_ix = self
+            .messages
+            .iter()
+            .rposition(|message| message.role == Role::Assistant)?;
+        (boundary_ix > first_uncompacted_ix).then_some(boundary_ix)
+    }
+
+    /// Replaces the messages before the compaction boundary with a summary generated by the
+    /// given model, and then sends the thread to it.
+    fn compact(
+        &mut self,
+        model: Arc<dyn LanguageModel>,
+        request_kind: RequestKind,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(boundary_ix) = self.compaction_boundary() else {
+            return;
+        };
+        let first_kept_message_id = self.messages[boundary_ix].id;
+
+        let mut transcript = String::new();
+        if let Some(compaction) = self.compaction() {
+            writeln!(
+                transcript,
+                "Summary of the earlier conversation:\n{}\n",
+                compaction.summary
+            )
+            .ok();
+        }
+        for message in self.messages[..boundary_ix]
+            .iter()
+            .filter(|message| !self.is_compacted(message.id))
+        {
+            self.write_compaction_transcript(message, &mut transcript, cx);
+        }
+
+        let request = LanguageModelRequest {
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![
+                    transcript.into(),
+                    "The conversation above no longer fits in your context window. \
+                    Summarize it so that you can continue working without it. Include:\n\
+                    1. The user's requests, quoting the most recent one verbatim\n\
+                    2. Decisions made and constraints to respect\n\
+                    3. Files that were read or changed, with the details still relevant\n\
+                    4. What has been done and what remains to be done\n\
+                    Go straight to the summary, without any preamble."
+                        .into(),
+                ],
+                cache: false,
+            }],
+            tools: Vec::new(),
+            stop: Vec::new(),
+            temperature: None,
+        };
+
+        let pending_completion_id = post_inc(&mut self.completion_count);
+        let task = cx.spawn(async move |thread, cx| {
+            let summary = async {
+                let mut chunks = model.stream_completion_text(request, &cx).await?;
+                let mut summary = String::new();
+                while let Some(chunk) = chunks.stream.next().await {
+                    summary.push_str(&chunk?);
+                }
+                anyhow::Ok(summary)
+            }
+            .await;
+
+            thread
+                .update(cx, |thread, cx| {
+                    thread
+                        .pending_completions
+                        .retain(|completion| completion.id != pending_completion_id);
+                    thread.request_token_usage = TokenUsage::default();
+
+                    match summary {
+                        Ok(summary) if !summary.trim().is_empty() => {
+                            thread.compactions.push(ThreadCompaction {
+                                first_kept_message_id,
+                                summary: summary.into(),
+                                compacted_at: Utc::now(),
+                            });
+                            thread.touch_updated_at();
+                            cx.emit(ThreadEvent::Compacted);
+                            cx.notify();
+                        }
+                        Ok(_) => log::error!("failed to compact thread: empty summary"),
+                        Err(error) => log::error!("failed to compact thread: {error:#}"),
+                    }
+
+                    thread.send_to_model(model, request_kind, cx);
+                })
+                .ok();
+        });
+
+        self.pending_completions.push(PendingCompletion {
+            id: pending_completion_id,
+            _task: task,
+        });
+        cx.notify();
+    }
+
+    /// Writes a message of the thread, along with its tool uses and their results, into the
+    /// transcript that is summarized when compacting. Bulky tool results are truncated.
+    fn write_compaction_transcript(&self, message: &Message, transcript: &mut String, cx: &App) {
+        const MAX_TOOL_RESULT_LEN: usize = 4096;
+
+        let role = match message.role {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+            Role::System => "System",
+        };
+        writeln!(transcript, "{role}:").ok();
+        for segment in &message.segments {
+            // The context attached to messages is kept as is, and thinking is left out.
+            if let MessageSegment::Text(text) = segment {
+                writeln!(transcript, "{text}").ok();
+            }
+        }
+
+        for tool_use in self.tool_uses_for_message(message.id, cx) {
+            writeln!(
+                transcript,
+                "[Used tool `{}` with {}]",
+                tool_use.name, tool_use.input
+            )
+            .ok();
+        }
+        for tool_result in self.tool_results_for_message(message.id) {
+            let mut content = tool_result.content.as_ref();
+            let truncated = content.len() > MAX_TOOL_RESULT_LEN;
+            if truncated {
+                let mut end = MAX_TOOL_RESULT_LEN;
+                while !content.is_char_boundary(end) {
+                    end -= 1;
+                }
+                content = &content[..end];
+            }
+            writeln!(
+                transcript,
+                "[Tool result{}]\n{content}{}",
+                if tool_result.is_error { " (error)" } else { "" },
+                if truncated { "\n[truncated]" } else { "" }
+            )
+            .ok();
+        }
+        transcript.push('\n');
+    }
+
+    /// Returns the message that stands in for the compacted messages in requests, containing
+    /// their summary along with the context that was attached to them.
+    fn compaction_request_message(&self) -> Option<LanguageModelRequestMessage> {
+        let compaction = self.compaction()?;
+        let mut text = format!(
+            "Earlier messages of this conversation were replaced with this summary:\n\n{}",
+            compaction.summary
+        );
+        for message in &self.messages {
+            if self.is_compacted(message.id) && !message.context.is_empty() {
+                text.push_str("\n\n");
+                text.push_str(&message.context);
+            }
+        }
+
+        Some(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![MessageContent::Text(text)],
+            cache: true,
+        })
+    }
+
     pub fn use_pending_tools(&mut self, cx: &mut Context<Self>) -> Vec<PendingToolUse> {
         self.auto_capture_telemetry(cx);
         let request = self.to_completion_request(RequestKind::Chat, cx);
@@ -1321,9 +1567,26 @@ impl Thread {
 
         for tool_use in pending_tool_uses.iter() {
             if let Some(tool) = self.tools.tool(&tool_use.name, cx) {
-                if tool.needs_confirmation(&tool_use.input, cx)
-                    && !AssistantSettings::get_global(cx).always_allow_tool_actions
-                {
+                let permission = self.tool_permission(tool.as_ref(), &tool_use.input, cx);
+                let needs_confirmation = match permission {
+                    Some(ToolPermission::Deny) => {
+                        self.reject_tool_use(
+                            tool_use.id.clone(),
+                            tool_use.name.clone(),
+                            "Permission to run tool action denied by a rule",
+                            cx,
+                        );
+                        continue;
+                    }
+                    Some(ToolPermission::Ask) => true,
+                    Some(ToolPermission::Allow) => false,
+                    None => {
+                        tool.needs_confirmation(&tool_use.input, cx)
+                            && !AssistantSettings::get_global(cx).always_allow_tool_actions
+                    }
+                };
+
+                if needs_confirmation {
                     self.tool_use.confirm_tool_use(
                         tool_use.id.clone(),
                         tool_use.ui_text.clone(),
@@ -1348,6 +1611,92 @@ impl Thread {
         pending_tool_uses
     }
 
+    /// Returns what the tool permission rules of the current profile decide for running the
+    /// given tool with the given input.
+    fn tool_permission(
+        &self,
+        tool: &dyn Tool,
+        input: &serde_json::Value,
+        cx: &App,
+    ) -> Option<ToolPermission> {
+        let nested_tool_uses = tool.nested_tool_uses(input);
+        if nested_tool_uses.is_empty() {
+            let settings = AssistantSettings::get_global(cx);
+            return match tool_permission_subjects(tool, input) {
+                Some(subjects) => settings.tool_permission(&tool.name(), &subjects),
+                // When the subjects don't tell what the tool use does, rules can still deny it,
+                // but never allow it.
+                None => {
+                    match settings.tool_permission(&tool.name(), &tool.permission_subjects(input)) {
+                        Some(ToolPermission::Deny) => Some(ToolPermission::Deny),
+                        Some(_) => Some(ToolPermission::Ask),
+                        None => None,
+                    }
+                }
+            };
+        }
+
+        let permissions = nested_tool_uses
+            .iter()
+            .map(|(name, input)| {
+                let tool = self.tools.tool(name, cx)?;
+                self.tool_permission(tool.as_ref(), input, cx)
+            })
+            .collect::<Vec<_>>();
+        if permissions.contains(&Some(ToolPermission::Deny)) {
+            Some(ToolPermission::Deny)
+        } else if permissions.contains(&Some(ToolPermission::Ask)) {
+            Some(ToolPermission::Ask)
+        } else if permissions
+            .iter()
+            .all(|permission| *permission == Some(ToolPermission::Allow))
+        {
+            Some(ToolPermission::Allow)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the rules that allow running the given tool with input similar to the given one
+    /// without confirmation.
+    pub fn allow_rules_for_tool_use(
+        &self,
+        tool: &dyn Tool,
+        input: &serde_json::Value,
+        cx: &App,
+    ) -> Vec<ToolPermissionRule> {
+        let nested_tool_uses = tool.nested_tool_uses(input);
+        if !nested_tool_uses.is_empty() {
+            return nested_tool_uses
+                .iter()
+                .filter_map(|(name, input)| {
+                    let tool = self.tools.tool(name, cx)?;
+                    Some(self.allow_rules_for_tool_use(tool.as_ref(), input, cx))
+                })
+                .flatten()
+                .collect();
+        }
+
+        let tool_name: Arc<str> = tool.name().into();
+        let subjects = tool_permission_subjects(tool, input)
+            .unwrap_or_else(|| tool.permission_subjects(input));
+        if subjects.is_empty() {
+            return vec![ToolPermissionRule {
+                tool: tool_name,
+                pattern: None,
+                permission: ToolPermission::Allow,
+            }];
+        }
+        subjects
+            .iter()
+            .map(|subject| ToolPermissionRule {
+                tool: tool_name.clone(),
+                pattern: Some(tool.permission_pattern(subject)),
+                permission: ToolPermission::Allow,
+            })
+            .collect()
+    }
+
     pub fn run_tool(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -1411,8 +1760,7 @@ impl Thread {
         cx: &mut Context<Self>,
     ) {
         if self.all_tools_finished() {
-            let model_registry = LanguageModelRegistry::read_global(cx);
-            if let Some(ConfiguredModel { model, .. }) = model_registry.default_model() {
+            if let Some(model) = self.model(cx) {
                 self.attach_tool_results(cx);
                 if !canceled {
                     self.send_to_model(model, RequestKind::Chat, cx);
@@ -1832,13 +2180,22 @@ impl Thread {
             .detach();
     }
 
+    /// Returns the model the thread last sent a chat request to, or the default model if it
+    /// hasn't sent any yet.
+    pub fn model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.model.clone().or_else(|| {
+            LanguageModelRegistry::read_global(cx)
+                .default_model()
+                .map(|configured| configured.model)
+        })
+    }
+
     pub fn total_token_usage(&self, cx: &App) -> TotalTokenUsage {
-        let model_registry = LanguageModelRegistry::read_global(cx);
-        let Some(model) = model_registry.default_model() else {
+        let Some(model) = self.model(cx) else {
             return TotalTokenUsage::default();
         };
 
-        let max = model.model.max_token_count();
+        let max = model.max_token_count();
 
         #[cfg(debug_assertions)]
         let warning_threshold: f32 = std::env::var("ZED_THREAD_WARNING_THRESHOLD")
@@ -1861,6 +2218,40 @@ impl Thread {
         TotalTokenUsage { total, max, ratio }
     }
 
+    /// Runs a tool use that is waiting for the user's confirmation.
+    pub fn allow_tool_use(&mut self, tool_use_id: &LanguageModelToolUseId, cx: &mut Context<Self>) {
+        let Some(PendingToolUseStatus::NeedsConfirmation(confirmation)) = self
+            .pending_tool(tool_use_id)
+            .map(|tool_use| tool_use.status.clone())
+        else {
+            return;
+        };
+
+        self.run_tool(
+            confirmation.tool_use_id.clone(),
+            confirmation.ui_text.clone(),
+            confirmation.input.clone(),
+            &confirmation.messages,
+            confirmation.tool.clone(),
+            cx,
+        );
+    }
+
+    /// Reports to the model that a tool use wasn't allowed to run, letting it continue without
+    /// it, unlike [`Thread::deny_tool_use`] which stops the thread.
+    pub fn reject_tool_use(
+        &mut self,
+        tool_use_id: LanguageModelToolUseId,
+        tool_name: Arc<str>,
+        reason: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let err = Err(anyhow!("{reason}"));
+        self.tool_use
+            .insert_tool_output(tool_use_id.clone(), tool_name, err, cx);
+        self.tool_finished(tool_use_id, None, false, cx);
+    }
+
     pub fn deny_tool_use(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -1913,6 +2304,7 @@ pub enum ThreadEvent {
     },
     CheckpointChanged,
     ToolConfirmationNeeded,
+    Compacted,
 }
 
 impl EventEmitter<ThreadEvent> for Thread {}
@@ -1930,6 +2322,7 @@ mod tests {
     use context_server::ContextServerSettings;
     use editor::EditorSettings;
     use gpui::TestAppContext;
+    use language_model::fake_provider::FakeLanguageModel;
     use project::{FakeFs, Project};
     use prompt_store::PromptBuilder;
     use serde_json::json;
@@ -2177,6 +2570,75 @@ fn main() {{
         );
     }
 
+    #[gpui::test]
+    async fn test_compaction(cx: &mut TestAppContext) {
+        init_test_settings(cx);
+
+        let project = create_test_project(cx, json!({})).await;
+        let (_, _thread_store, thread, _context_store) =
+            setup_test_environment(cx, project.clone()).await;
+
+        let kept_message_id = thread.update(cx, |thread, cx| {
+            thread.insert_user_message("Rename `foo` to `bar`", vec![], None, cx);
+            thread.insert_message(
+                Role::Assistant,
+                vec![MessageSegment::Text("Renamed it in 3 files".into())],
+                cx,
+            );
+            thread.insert_user_message("Now add a test", vec![], None, cx);
+            let kept_message_id = thread.insert_message(
+                Role::Assistant,
+                vec![MessageSegment::Text("Added `test_bar`".into())],
+                cx,
+            );
+            thread.insert_user_message("Run it", vec![], None, cx);
+            kept_message_id
+        });
+
+        // Nothing is compacted while the thread fits comfortably in the context window.
+        let model = Arc::new(FakeLanguageModel::default());
+        thread.update(cx, |thread, cx| {
+            assert!(!thread.should_compact(model.as_ref(), cx));
+            thread.request_token_usage = TokenUsage {
+                input_tokens: model.max_token_count() as u32,
+                ..Default::default()
+            };
+            thread.send_to_model(model.clone(), RequestKind::Chat, cx);
+        });
+        cx.run_until_parked();
+
+        let compaction_request = model.pending_completions().pop().unwrap();
+        let transcript = compaction_request.messages[0].string_contents();
+        assert!(transcript.contains("Rename `foo` to `bar`"));
+        assert!(transcript.contains("Now add a test"));
+        assert!(!transcript.contains("Added `test_bar`"));
+
+        model.stream_last_completion_response("Renamed `foo` to `bar`.".into());
+        model.end_last_completion_stream();
+        cx.run_until_parked();
+
+        let compaction = thread.read_with(cx, |thread, _| thread.compaction().cloned().unwrap());
+        assert_eq!(compaction.first_kept_message_id, kept_message_id);
+        assert_eq!(compaction.summary.as_ref(), "Renamed `foo` to `bar`.");
+
+        // The thread is sent to the model with the summary in place of the older messages.
+        let request = model.pending_completions().pop().unwrap();
+        let contents = request
+            .messages
+            .iter()
+            .skip(1)
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>();
+        assert_eq!(contents.len(), 3);
+        assert!(contents[0].ends_with("Renamed `foo` to `bar`."));
+        assert_eq!(contents[1], "Added `test_bar`");
+        assert_eq!(contents[2], "Run it");
+
+        // Restoring a checkpoint from before the compaction discards it.
+        thread.update(cx, |thread, cx| thread.truncate(MessageId(1), cx));
+        thread.read_with(cx, |thread, _| assert!(thread.compaction().is_none()));
+    }
+
     #[gpui::test]
     async fn test_stale_buffer_notification(cx: &mut TestAppContext) {
         init_test_settings(cx);
@@ -2256,6 +2718,95 @@ fn main() {{
         );
     }
 
+    #[gpui::test]
+    async fn test_tool_permission_with_incomplete_subjects(cx: &mut TestAppContext) {
+        init_test_settings(cx);
+
+        let project = create_test_project(cx, json!({"code.rs": ""})).await;
+        let (_, _thread_store, thread, _context_store) =
+            setup_test_environment(cx, project.clone()).await;
+
+        let tool = IncompleteSubjectsTool;
+        let input = json!({"path": "../code.rs"});
+        let tool_permission = |cx: &mut TestAppContext| {
+            thread.read_with(cx, |thread, cx| thread.tool_permission(&tool, &input, cx))
+        };
+
+        // Without rules, the tool's own defaults apply.
+        assert_eq!(tool_permission(cx), None);
+
+        set_tool_permission_rules(
+            vec![ToolPermissionRule {
+                tool: "incomplete_subjects".into(),
+                pattern: None,
+                permission: ToolPermission::Allow,
+            }],
+            cx,
+        );
+        assert_eq!(tool_permission(cx), Some(ToolPermission::Ask));
+
+        set_tool_permission_rules(
+            vec![ToolPermissionRule {
+                tool: "*".into(),
+                pattern: None,
+                permission: ToolPermission::Deny,
+            }],
+            cx,
+        );
+        assert_eq!(tool_permission(cx), Some(ToolPermission::Deny));
+    }
+
+    struct IncompleteSubjectsTool;
+
+    impl Tool for IncompleteSubjectsTool {
+        fn name(&self) -> String {
+            "incomplete_subjects".into()
+        }
+
+        fn description(&self) -> String {
+            String::new()
+        }
+
+        fn icon(&self) -> ui::IconName {
+            ui::IconName::FileSearch
+        }
+
+        fn needs_confirmation(&self, _input: &serde_json::Value, _cx: &App) -> bool {
+            false
+        }
+
+        fn permission_subjects_are_incomplete(&self, _input: &serde_json::Value) -> bool {
+            true
+        }
+
+        fn ui_text(&self, _input: &serde_json::Value) -> String {
+            String::new()
+        }
+
+        fn run(
+            self: Arc<Self>,
+            _input: serde_json::Value,
+            _messages: &[LanguageModelRequestMessage],
+            _project: Entity<Project>,
+            _action_log: Entity<ActionLog>,
+            _cx: &mut App,
+        ) -> Task<Result<String>> {
+            Task::ready(Ok(String::new()))
+        }
+    }
+
+    fn set_tool_permission_rules(rules: Vec<ToolPermissionRule>, cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let mut settings = AssistantSettings::get_global(cx).clone();
+            let profile = settings
+                .profiles
+                .get_mut(&settings.default_profile)
+                .unwrap();
+            profile.tool_permissions = assistant_settings::ToolPermissions::new(rules);
+            AssistantSettings::override_global(settings, cx);
+        });
+    }
+
     fn init_test_settings(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
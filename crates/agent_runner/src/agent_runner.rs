@@ -0,0 +1,570 @@
+mod tool_policy;
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use agent::{RequestKind, Thread, ThreadEvent, ThreadStore};
+use anyhow::{Context as _, Result, anyhow};
+use assistant_settings::{AgentProfileId, AssistantSettings};
+use assistant_tool::ToolWorkingSet;
+use client::{Client, UserStore};
+use dap::DapRegistry;
+use fs::RealFs;
+use futures::channel::oneshot;
+use futures::future::join_all;
+use gpui::{App, AppContext as _, Entity, SemanticVersion, Task};
+use language::LanguageRegistry;
+use language_model::{
+    LanguageModel, LanguageModelId, LanguageModelProviderId, LanguageModelRegistry, StopReason,
+    TokenUsage,
+};
+use node_runtime::NodeRuntime;
+use project::Project;
+use prompt_store::PromptBuilder;
+use serde::Serialize;
+use settings::{Settings as _, SettingsStore};
+use util::ResultExt as _;
+
+pub use tool_policy::*;
+
+/// The parts of `workspace::AppState` needed to run agent threads, along with the prompt builder.
+pub struct AgentRunnerState {
+    pub languages: Arc<LanguageRegistry>,
+    pub client: Arc<Client>,
+    pub user_store: Entity<UserStore>,
+    pub fs: Arc<dyn fs::Fs>,
+    pub node_runtime: NodeRuntime,
+    pub prompt_builder: Arc<PromptBuilder>,
+}
+
+pub fn init(cx: &mut App) -> Arc<AgentRunnerState> {
+    release_channel::init(SemanticVersion::default(), cx);
+    gpui_tokio::init(cx);
+
+    let mut settings_store = SettingsStore::new(cx);
+    settings_store
+        .set_default_settings(settings::default_settings().as_ref(), cx)
+        .unwrap();
+    // Use the same models, profiles and tool permissions as the user does in Zed.
+    match std::fs::read_to_string(paths::settings_file()) {
+        Ok(user_settings) => {
+            settings_store
+                .set_user_settings(&user_settings, cx)
+                .with_context(|| format!("parsing {:?}", paths::settings_file()))
+                .log_err();
+        }
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => log::error!("reading {:?}: {error}", paths::settings_file()),
+    }
+    cx.set_global(settings_store);
+    client::init_settings(cx);
+    Project::init_settings(cx);
+
+    let client = Client::production(cx);
+    cx.set_http_client(client.http_client().clone());
+
+    let fs = Arc::new(RealFs::new(None, cx.background_executor().clone()));
+    let languages = Arc::new(LanguageRegistry::new(cx.background_executor().clone()));
+    let user_store = cx.new(|cx| UserStore::new(client.clone(), cx));
+
+    language::init(cx);
+    language_model::init(client.clone(), cx);
+    language_models::init(user_store.clone(), client.clone(), fs.clone(), cx);
+    assistant_tools::init(client.http_client().clone(), cx);
+    context_server::init(cx);
+    let stdout_is_a_pty = false;
+    let prompt_builder = PromptBuilder::load(fs.clone(), stdout_is_a_pty, cx);
+    agent::init_headless(cx);
+
+    select_default_model_from_settings(cx);
+    cx.subscribe(
+        &LanguageModelRegistry::global(cx),
+        |_, event: &language_model::Event, cx| match event {
+            language_model::Event::ProviderStateChanged
+            | language_model::Event::AddedProvider(_)
+            | language_model::Event::RemovedProvider(_) => {
+                select_default_model_from_settings(cx);
+            }
+            _ => {}
+        },
+    )
+    .detach();
+
+    Arc::new(AgentRunnerState {
+        languages,
+        client,
+        user_store,
+        fs,
+        node_runtime: NodeRuntime::unavailable(),
+        prompt_builder,
+    })
+}
+
+fn select_default_model_from_settings(cx: &mut App) {
+    let default_model = AssistantSettings::get_global(cx).default_model.clone();
+    LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+        registry.select_default_model(
+            &LanguageModelProviderId::from(default_model.provider),
+            &LanguageModelId::from(default_model.model),
+            cx,
+        );
+    });
+}
+
+/// Authenticates the provider of the model with the given ID, or of the default model configured
+/// in the settings if no ID is given, and returns the model.
+///
+/// The ID may be qualified with the ID of the model's provider, as in
+/// `anthropic/claude-3-7-sonnet-latest`. Otherwise, every provider is authenticated and searched
+/// for the model.
+pub fn find_model(model: Option<&str>, cx: &mut App) -> Task<Result<Arc<dyn LanguageModel>>> {
+    let model_registry = LanguageModelRegistry::read_global(cx);
+    let (providers, model_id) = match model {
+        Some(model) => {
+            let qualified = model.split_once('/').and_then(|(provider_id, model_id)| {
+                let provider_id = LanguageModelProviderId::from(provider_id.to_string());
+                Some((model_registry.provider(&provider_id)?, model_id))
+            });
+            match qualified {
+                Some((provider, model_id)) => (vec![provider], model_id.to_string()),
+                None => (model_registry.providers(), model.to_string()),
+            }
+        }
+        None => {
+            let default_model = &AssistantSettings::get_global(cx).default_model;
+            let provider_id = LanguageModelProviderId::from(default_model.provider.clone());
+            let Some(provider) = model_registry.provider(&provider_id) else {
+                return Task::ready(Err(anyhow!(
+                    "the provider of the default model, {provider_id}, is not available"
+                )));
+            };
+            (vec![provider], default_model.model.clone())
+        }
+    };
+
+    let authenticate = providers
+        .iter()
+        .map(|provider| provider.authenticate(cx))
+        .collect::<Vec<_>>();
+    cx.spawn(async move |cx| {
+        let results = join_all(authenticate).await;
+        // Providers that aren't configured fail to authenticate, which only matters when the
+        // model must come from a specific one.
+        if let [Err(error)] = results.as_slice() {
+            return Err(anyhow!("authenticating {}: {error}", providers[0].id()));
+        }
+
+        cx.update(|cx| {
+            providers
+                .iter()
+                .flat_map(|provider| provider.provided_models(cx))
+                .find(|model| model.id().0 == model_id.as_str())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "no language model named {model_id} is available. Available models: {}",
+                        LanguageModelRegistry::read_global(cx)
+                            .available_models(cx)
+                            .map(|model| format!("{}/{}", model.provider_id(), model.id().0))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+        })?
+    })
+}
+
+pub struct RunOptions {
+    pub project_path: PathBuf,
+    pub prompt: String,
+    pub profile: Option<AgentProfileId>,
+    pub policy: ToolPolicy,
+}
+
+/// The outcome of a headless run, printed as JSON.
+#[derive(Debug, Serialize)]
+pub struct RunOutput {
+    pub stop_reason: StopReason,
+    /// The thread, as produced by [`Thread::to_markdown`].
+    pub transcript: String,
+    /// A unified diff of the changes the agent made to the project.
+    pub diff: String,
+    pub token_usage: TokenUsage,
+}
+
+/// Opens the project at the given path and runs an agent thread in it until the model ends its
+/// turn.
+pub fn run(
+    options: RunOptions,
+    model: Arc<dyn LanguageModel>,
+    state: Arc<AgentRunnerState>,
+    cx: &mut App,
+) -> Task<Result<RunOutput>> {
+    let project = Project::local(
+        state.client.clone(),
+        state.node_runtime.clone(),
+        state.user_store.clone(),
+        state.languages.clone(),
+        Arc::new(DapRegistry::default()),
+        state.fs.clone(),
+        None,
+        cx,
+    );
+    let worktree = project.update(cx, |project, cx| {
+        project.create_worktree(options.project_path, true, cx)
+    });
+
+    cx.spawn(async move |cx| {
+        worktree.await?;
+        cx.update(|cx| {
+            run_in_project(
+                project,
+                options.prompt,
+                options.profile,
+                options.policy,
+                model,
+                state.prompt_builder.clone(),
+                cx,
+            )
+        })?
+        .await
+    })
+}
+
+/// Runs an agent thread in the given project until the model ends its turn, deciding which tools
+/// it may use with the given policy.
+pub fn run_in_project(
+    project: Entity<Project>,
+    prompt: String,
+    profile: Option<AgentProfileId>,
+    policy: ToolPolicy,
+    model: Arc<dyn LanguageModel>,
+    prompt_builder: Arc<PromptBuilder>,
+    cx: &mut App,
+) -> Task<Result<RunOutput>> {
+    if let Err(error) = policy.apply(profile, cx) {
+        return Task::ready(Err(error));
+    }
+
+    let tools = Arc::new(ToolWorkingSet::default());
+    let thread_store = ThreadStore::load(project, tools, prompt_builder, cx);
+    cx.spawn(async move |cx| {
+        let thread_store = thread_store.await;
+        let thread = thread_store.update(cx, |thread_store, cx| thread_store.create_thread(cx))?;
+
+        let (tx, rx) = oneshot::channel();
+        let mut tx = Some(tx);
+        let _subscription = cx.subscribe(&thread, move |thread, event: &ThreadEvent, cx| {
+            let result = match event {
+                ThreadEvent::ToolConfirmationNeeded => {
+                    thread.update(cx, |thread, cx| policy.resolve_confirmations(thread, cx));
+                    return;
+                }
+                ThreadEvent::Stopped(Ok(StopReason::ToolUse)) => return,
+                ThreadEvent::Stopped(Ok(stop_reason)) => Ok(*stop_reason),
+                ThreadEvent::Stopped(Err(error)) => Err(anyhow!(error.clone())),
+                ThreadEvent::ShowError(error) => Err(anyhow!(error.clone())),
+                _ => return,
+            };
+            if let Some(tx) = tx.take() {
+                tx.send(result).ok();
+            }
+        })?;
+
+        thread.update(cx, |thread, cx| {
+            thread.insert_user_message(prompt, Vec::new(), None, cx);
+            thread.send_to_model(model, RequestKind::Chat, cx);
+        })?;
+        let stop_reason = rx.await??;
+
+        thread.read_with(cx, |thread, cx| {
+            anyhow::Ok(RunOutput {
+                stop_reason,
+                transcript: thread.to_markdown(cx)?,
+                diff: changes_diff(thread, cx),
+                token_usage: thread.cumulative_token_usage(),
+            })
+        })?
+    })
+}
+
+/// Returns a unified diff of the buffers changed by the agent in the given thread.
+fn changes_diff(thread: &Thread, cx: &App) -> String {
+    let mut diff = String::new();
+    for (buffer, buffer_diff) in thread.action_log().read(cx).changed_buffers(cx) {
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file() else {
+            continue;
+        };
+        let path = file.path().to_string_lossy();
+        let buffer_diff = buffer_diff.read(cx);
+        let (old_path, old_text) = if buffer_diff.base_text_exists() {
+            (format!("a/{path}"), buffer_diff.base_text().text())
+        } else {
+            ("/dev/null".to_string(), String::new())
+        };
+        let (new_path, new_text) = if file.disk_state().exists() {
+            (format!("b/{path}"), buffer.text())
+        } else {
+            ("/dev/null".to_string(), String::new())
+        };
+
+        writeln!(diff, "--- {old_path}\n+++ {new_path}").ok();
+        diff.push_str(&language::unified_diff(&old_text, &new_text));
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assistant_settings::{LanguageModelSelection, ToolPermission, ToolPermissionRule};
+    use assistant_tool::{ActionLog, Tool, ToolRegistry};
+    use context_server::ContextServerSettings;
+    use gpui::TestAppContext;
+    use icons::IconName;
+    use language_model::{
+        LanguageModelCompletionEvent, LanguageModelRequestMessage, LanguageModelToolUse,
+        MessageContent, fake_provider::FakeLanguageModel,
+    };
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::Settings as _;
+    use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_run_in_project(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({"a.txt": "one\n"}))
+            .await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+
+        let model = Arc::new(FakeLanguageModel::default());
+        let policy = ToolPolicy {
+            rules: Vec::new(),
+            confirmation: ConfirmationPolicy::Deny,
+        };
+        let output = cx.update(|cx| {
+            run_in_project(
+                project,
+                "Say hello".into(),
+                Some(AgentProfileId("ask".into())),
+                policy,
+                model.clone(),
+                Arc::new(PromptBuilder::new(None).unwrap()),
+                cx,
+            )
+        });
+        cx.run_until_parked();
+
+        let request = model.pending_completions().pop().unwrap();
+        assert_eq!(
+            request.messages.last().unwrap().string_contents(),
+            "Say hello"
+        );
+        model.stream_last_completion_response("Hello!".into());
+        model.end_last_completion_stream();
+
+        let output = output.await.unwrap();
+        assert_eq!(output.stop_reason, StopReason::EndTurn);
+        assert!(output.transcript.contains("Say hello"));
+        assert!(output.transcript.contains("Hello!"));
+        assert_eq!(output.diff, "");
+        cx.update(|cx| {
+            assert_eq!(
+                AssistantSettings::get_global(cx).default_profile,
+                AgentProfileId("ask".into())
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_tool_round_trip(cx: &mut TestAppContext) {
+        init_test(cx);
+        let tool_runs = Arc::new(AtomicUsize::new(0));
+        cx.update(|cx| {
+            ToolRegistry::global(cx).register_tool(TestTool {
+                runs: tool_runs.clone(),
+            })
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({"a.txt": "one\n"}))
+            .await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+
+        let allow_rule = ToolPermissionRule {
+            tool: "test_tool".into(),
+            pattern: None,
+            permission: ToolPermission::Allow,
+        };
+        let deny_rule = ToolPermissionRule {
+            permission: ToolPermission::Deny,
+            ..allow_rule.clone()
+        };
+        let cases = [
+            (vec![allow_rule], ConfirmationPolicy::Deny, true),
+            (vec![deny_rule], ConfirmationPolicy::Allow, false),
+            (Vec::new(), ConfirmationPolicy::Allow, true),
+            (Vec::new(), ConfirmationPolicy::Deny, false),
+        ];
+        for (rules, confirmation, expect_run) in cases {
+            tool_runs.store(0, SeqCst);
+            let model = Arc::new(FakeLanguageModel::default());
+            let policy = ToolPolicy {
+                rules,
+                confirmation,
+            };
+            let output = cx.update(|cx| {
+                run_in_project(
+                    project.clone(),
+                    "Use the tool".into(),
+                    None,
+                    policy,
+                    model.clone(),
+                    Arc::new(PromptBuilder::new(None).unwrap()),
+                    cx,
+                )
+            });
+            cx.run_until_parked();
+
+            model.send_last_completion_event(LanguageModelCompletionEvent::ToolUse(
+                LanguageModelToolUse {
+                    id: "tool_use_1".into(),
+                    name: "test_tool".into(),
+                    input: json!({}),
+                },
+            ));
+            model.send_last_completion_event(LanguageModelCompletionEvent::Stop(
+                StopReason::ToolUse,
+            ));
+            model.end_last_completion_stream();
+            cx.run_until_parked();
+
+            // The tool results are sent back to the model the thread was started with.
+            let request = model.pending_completions().pop().unwrap();
+            let tool_result = request
+                .messages
+                .iter()
+                .flat_map(|message| &message.content)
+                .find_map(|content| match content {
+                    MessageContent::ToolResult(result) => Some(result.clone()),
+                    _ => None,
+                })
+                .unwrap();
+            assert_eq!(tool_runs.load(SeqCst), expect_run as usize);
+            assert_eq!(tool_result.is_error, !expect_run);
+            if expect_run {
+                assert_eq!(tool_result.content.as_ref(), "tool output");
+            }
+
+            model.stream_last_completion_response("Done".into());
+            model.end_last_completion_stream();
+            let output = output.await.unwrap();
+            assert_eq!(output.stop_reason, StopReason::EndTurn);
+            assert!(output.transcript.contains("Done"));
+        }
+    }
+
+    struct TestTool {
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl Tool for TestTool {
+        fn name(&self) -> String {
+            "test_tool".into()
+        }
+
+        fn description(&self) -> String {
+            "A tool for testing".into()
+        }
+
+        fn icon(&self) -> IconName {
+            IconName::Info
+        }
+
+        fn needs_confirmation(&self, _: &serde_json::Value, _: &App) -> bool {
+            true
+        }
+
+        fn ui_text(&self, _: &serde_json::Value) -> String {
+            "Test tool".into()
+        }
+
+        fn run(
+            self: Arc<Self>,
+            _input: serde_json::Value,
+            _messages: &[LanguageModelRequestMessage],
+            _project: Entity<Project>,
+            _action_log: Entity<ActionLog>,
+            _cx: &mut App,
+        ) -> Task<Result<String>> {
+            self.runs.fetch_add(1, SeqCst);
+            Task::ready(Ok("tool output".into()))
+        }
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            ContextServerSettings::register(cx);
+            assistant_tool::init(cx);
+            agent::init_headless(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_find_model(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            LanguageModelRegistry::test(cx);
+            let mut settings = AssistantSettings::get_global(cx).clone();
+            settings.default_model = LanguageModelSelection {
+                provider: "fake".into(),
+                model: "fake".into(),
+            };
+            AssistantSettings::override_global(settings, cx);
+        });
+
+        for model in [None, Some("fake"), Some("fake/fake")] {
+            let found = cx.update(|cx| find_model(model, cx)).await.unwrap();
+            assert_eq!(found.id().0, "fake", "{model:?}");
+            assert_eq!(found.provider_id().0, "fake", "{model:?}");
+        }
+        for model in ["missing", "fake/missing", "missing/fake"] {
+            let error = cx
+                .update(|cx| find_model(Some(model), cx))
+                .await
+                .unwrap_err();
+            assert!(
+                error.to_string().contains("Available models: fake/fake"),
+                "{model}: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tool_policy() {
+        let policy: ToolPolicy = serde_json_lenient::from_str(
+            r#"{
+                // Comments are allowed.
+                "rules": [
+                    { "tool": "terminal", "pattern": "cargo test*", "permission": "allow" }
+                ],
+                "confirmation": "allow"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(policy.rules.len(), 1);
+        assert_eq!(policy.confirmation, ConfirmationPolicy::Allow);
+
+        let policy: ToolPolicy = serde_json_lenient::from_str("{}").unwrap();
+        assert_eq!(policy.confirmation, ConfirmationPolicy::Deny);
+    }
+}
@@ -0,0 +1,113 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use agent_runner::{RunOptions, ToolPolicy};
+use anyhow::{Context as _, Result};
+use assistant_settings::AgentProfileId;
+use clap::Parser;
+use gpui::{App, Application, Task};
+use reqwest_client::ReqwestClient;
+
+/// Runs an agent thread in a project without a UI, printing the resulting diff, transcript and
+/// token usage as JSON.
+#[derive(Parser, Debug)]
+#[command(name = "agent_runner")]
+struct Args {
+    /// The directory of the project the agent works in.
+    #[arg(long, value_name = "DIR")]
+    project: PathBuf,
+    /// The message sent to the agent.
+    #[arg(
+        long,
+        conflicts_with = "prompt_file",
+        required_unless_present = "prompt_file"
+    )]
+    prompt: Option<String>,
+    /// A file containing the message sent to the agent.
+    #[arg(long, value_name = "PATH")]
+    prompt_file: Option<PathBuf>,
+    /// The language model to use, as `provider/model` or just the model's ID. Defaults to the
+    /// default model configured in the settings.
+    #[arg(long)]
+    model: Option<String>,
+    /// The ID of the agent profile to use. Defaults to the profile configured in the settings.
+    #[arg(long)]
+    profile: Option<String>,
+    /// A JSON file with the rules deciding which tools the agent may use. Without one, every tool
+    /// use that would ask for confirmation is denied.
+    #[arg(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+    /// Where to write the JSON output. Defaults to stdout.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+    let http_client = Arc::new(ReqwestClient::new());
+    let app = Application::headless().with_http_client(http_client);
+
+    app.run(move |cx| {
+        let state = agent_runner::init(cx);
+        let task = run(args, state, cx);
+        cx.spawn(async move |cx| {
+            let exit_code = match task.await {
+                Ok(()) => 0,
+                Err(error) => {
+                    eprintln!("error: {error:?}");
+                    1
+                }
+            };
+            cx.update(|cx| cx.quit()).ok();
+            std::process::exit(exit_code);
+        })
+        .detach();
+    });
+}
+
+fn run(args: Args, state: Arc<agent_runner::AgentRunnerState>, cx: &mut App) -> Task<Result<()>> {
+    let options = (|| {
+        let prompt = match (args.prompt, args.prompt_file.as_ref()) {
+            (Some(prompt), _) => prompt,
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .with_context(|| format!("reading prompt file {path:?}"))?,
+            (None, None) => anyhow::bail!("either --prompt or --prompt-file is required"),
+        };
+        let policy = match args.policy.as_ref() {
+            Some(path) => ToolPolicy::load(path)?,
+            None => ToolPolicy::default(),
+        };
+        let project_path = args
+            .project
+            .canonicalize()
+            .with_context(|| format!("opening project {:?}", args.project))?;
+        anyhow::Ok(RunOptions {
+            project_path,
+            prompt,
+            profile: args.profile.map(|profile| AgentProfileId(profile.into())),
+            policy,
+        })
+    })();
+    let options = match options {
+        Ok(options) => options,
+        Err(error) => return Task::ready(Err(error)),
+    };
+    let model = agent_runner::find_model(args.model.as_deref(), cx);
+    let output_path = args.output;
+
+    cx.spawn(async move |cx| {
+        let model = model.await?;
+        let output = cx
+            .update(|cx| agent_runner::run(options, model, state, cx))?
+            .await?;
+
+        let json = serde_json::to_string_pretty(&output)?;
+        match output_path {
+            Some(path) => std::fs::write(&path, json)
+                .with_context(|| format!("writing output to {path:?}"))?,
+            None => println!("{json}"),
+        }
+        Ok(())
+    })
+}
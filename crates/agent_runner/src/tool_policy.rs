@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use agent::Thread;
+use anyhow::{Context as _, Result};
+use assistant_settings::{AgentProfileId, AssistantSettings, ToolPermissionRule, ToolPermissions};
+use gpui::{App, Context};
+use serde::Deserialize;
+use settings::Settings as _;
+
+/// Decides which tool uses a headless run allows, as no one is around to confirm them.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ToolPolicy {
+    /// Rules deciding whether tool uses run, ask for confirmation or are denied, replacing the
+    /// `tool_permissions` of the profile used for the run.
+    #[serde(default)]
+    pub rules: Vec<ToolPermissionRule>,
+    /// What to do with tool uses that would ask for confirmation.
+    #[serde(default)]
+    pub confirmation: ConfirmationPolicy,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationPolicy {
+    /// Run the tool.
+    Allow,
+    /// Report to the model that the tool wasn't allowed to run.
+    #[default]
+    Deny,
+}
+
+impl ToolPolicy {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("reading tool policy {path:?}"))?;
+        serde_json_lenient::from_str(&contents)
+            .with_context(|| format!("parsing tool policy {path:?}"))
+    }
+
+    /// Makes the given profile, or the default one, the profile used by new threads, with the
+    /// rules of this policy as its tool permissions.
+    pub fn apply(&self, profile_id: Option<AgentProfileId>, cx: &mut App) -> Result<()> {
+        let mut settings = AssistantSettings::get_global(cx).clone();
+        let profile_id = profile_id.unwrap_or_else(|| settings.default_profile.clone());
+        let profile = settings
+            .profiles
+            .get_mut(&profile_id)
+            .with_context(|| format!("no agent profile with ID '{profile_id}'"))?;
+        profile.tool_permissions = ToolPermissions::new(self.rules.clone());
+        settings.default_profile = profile_id;
+        settings.always_allow_tool_actions = false;
+        AssistantSettings::override_global(settings, cx);
+        Ok(())
+    }
+
+    /// Allows or rejects the tool uses of the thread that are waiting for confirmation.
+    pub fn resolve_confirmations(&self, thread: &mut Thread, cx: &mut Context<Thread>) {
+        let tool_uses = thread
+            .tools_needing_confirmation()
+            .map(|tool_use| (tool_use.id.clone(), tool_use.name.clone()))
+            .collect::<Vec<_>>();
+        for (tool_use_id, tool_name) in tool_uses {
+            match self.confirmation {
+                ConfirmationPolicy::Allow => thread.allow_tool_use(&tool_use_id, cx),
+                ConfirmationPolicy::Deny => thread.reject_tool_use(
+                    tool_use_id,
+                    tool_name,
+                    "Permission to run tool action denied by the tool policy",
+                    cx,
+                ),
+            }
+        }
+    }
+}
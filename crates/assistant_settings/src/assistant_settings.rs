@@ -1,4 +1,5 @@
 mod agent_profile;
+mod tool_permissions;
 
 use std::sync::Arc;
 
@@ -17,6 +18,7 @@ use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
 
 pub use crate::agent_profile::*;
+pub use crate::tool_permissions::*;
 
 #[derive(Copy, Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -87,6 +89,7 @@ pub struct AssistantSettings {
     pub profiles: IndexMap<AgentProfileId, AgentProfile>,
     pub always_allow_tool_actions: bool,
     pub notify_when_agent_waiting: NotifyWhenAgentWaiting,
+    pub auto_compact_threshold: f32,
 }
 
 impl AssistantSettings {
@@ -109,6 +112,13 @@ impl AssistantSettings {
     pub fn set_thread_summary_model(&mut self, provider: String, model: String) {
         self.thread_summary_model = Some(LanguageModelSelection { provider, model });
     }
+
+    /// Returns what the tool permission rules of the default profile decide for a use of the
+    /// given tool, or `None` if no rule applies to it.
+    pub fn tool_permission(&self, tool_name: &str, subjects: &[String]) -> Option<ToolPermission> {
+        let profile = self.profiles.get(&self.default_profile)?;
+        profile.tool_permissions.permission(tool_name, subjects)
+    }
 }
 
 /// Assistant panel settings
@@ -209,6 +219,7 @@ impl AssistantSettingsContent {
                     profiles: None,
                     always_allow_tool_actions: None,
                     notify_when_agent_waiting: None,
+                    auto_compact_threshold: None,
                 },
                 VersionedAssistantSettingsContent::V2(ref settings) => settings.clone(),
             },
@@ -236,6 +247,7 @@ impl AssistantSettingsContent {
                 profiles: None,
                 always_allow_tool_actions: None,
                 notify_when_agent_waiting: None,
+                auto_compact_threshold: None,
             },
         }
     }
@@ -404,30 +416,35 @@ impl AssistantSettingsContent {
                 bail!("profile with ID '{profile_id}' already exists");
             }
 
-            profiles.insert(
-                profile_id,
-                AgentProfileContent {
-                    name: profile.name.into(),
-                    tools: profile.tools,
-                    enable_all_context_servers: Some(profile.enable_all_context_servers),
-                    context_servers: profile
-                        .context_servers
-                        .into_iter()
-                        .map(|(server_id, preset)| {
-                            (
-                                server_id,
-                                ContextServerPresetContent {
-                                    tools: preset.tools,
-                                },
-                            )
-                        })
-                        .collect(),
-                },
-            );
+            profiles.insert(profile_id, profile.into());
         }
 
         Ok(())
     }
+
+    /// Appends a tool permission rule to the given profile, adding the profile to the settings
+    /// file if it is only defined by the default settings.
+    pub fn add_tool_permission_rule(
+        &mut self,
+        profile_id: AgentProfileId,
+        profile: AgentProfile,
+        rule: ToolPermissionRule,
+    ) {
+        let AssistantSettingsContent::Versioned(boxed) = self else {
+            return;
+        };
+
+        if let VersionedAssistantSettingsContent::V2(ref mut settings) = **boxed {
+            let profile = settings
+                .profiles
+                .get_or_insert_default()
+                .entry(profile_id)
+                .or_insert_with(|| profile.into());
+            if !profile.tool_permissions.contains(&rule) {
+                profile.tool_permissions.push(rule);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
@@ -457,6 +474,7 @@ impl Default for VersionedAssistantSettingsContent {
             profiles: None,
             always_allow_tool_actions: None,
             notify_when_agent_waiting: None,
+            auto_compact_threshold: None,
         })
     }
 }
@@ -512,6 +530,11 @@ pub struct AssistantSettingsContentV2 {
     ///
     /// Default: "primary_screen"
     notify_when_agent_waiting: Option<NotifyWhenAgentWaiting>,
+    /// The fraction of the model's context window that an agent thread can use before its older
+    /// messages are replaced with a summary. Set to 0 to disable automatic compaction.
+    ///
+    /// Default: 0.8
+    auto_compact_threshold: Option<f32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
@@ -557,6 +580,32 @@ pub struct AgentProfileContent {
     pub enable_all_context_servers: Option<bool>,
     #[serde(default)]
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
+    /// Rules deciding which tool uses run without confirmation, need confirmation or are denied.
+    #[serde(default)]
+    pub tool_permissions: Vec<ToolPermissionRule>,
+}
+
+impl From<AgentProfile> for AgentProfileContent {
+    fn from(profile: AgentProfile) -> Self {
+        Self {
+            name: profile.name.into(),
+            tools: profile.tools,
+            enable_all_context_servers: Some(profile.enable_all_context_servers),
+            context_servers: profile
+                .context_servers
+                .into_iter()
+                .map(|(server_id, preset)| {
+                    (
+                        server_id,
+                        ContextServerPresetContent {
+                            tools: preset.tools,
+                        },
+                    )
+                })
+                .collect(),
+            tool_permissions: profile.tool_permissions.rules().to_vec(),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -674,6 +723,10 @@ impl Settings for AssistantSettings {
                 &mut settings.notify_when_agent_waiting,
                 value.notify_when_agent_waiting,
             );
+            merge(
+                &mut settings.auto_compact_threshold,
+                value.auto_compact_threshold,
+            );
             merge(&mut settings.default_profile, value.default_profile);
 
             if let Some(profiles) = value.profiles {
@@ -700,6 +753,7 @@ impl Settings for AssistantSettings {
                                         )
                                     })
                                     .collect(),
+                                tool_permissions: ToolPermissions::new(profile.tool_permissions),
                             },
                         )
                     }));
@@ -771,6 +825,7 @@ mod tests {
                             profiles: None,
                             always_allow_tool_actions: None,
                             notify_when_agent_waiting: None,
+                            auto_compact_threshold: None,
                         }),
                     ))
                 },
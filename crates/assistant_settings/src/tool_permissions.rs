@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use globset::{Glob, GlobMatcher};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// What happens when the agent wants to use a tool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolPermission {
+    /// Run the tool without asking for confirmation.
+    Allow,
+    /// Refuse to run the tool, reporting an error back to the model.
+    Deny,
+    /// Ask for confirmation before running the tool.
+    Ask,
+}
+
+/// A rule deciding whether an agent tool may run, based on its name and arguments.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ToolPermissionRule {
+    /// The name of the tool this rule applies to, or "*" for every tool.
+    pub tool: Arc<str>,
+    /// A glob matched against the tool's arguments: the command for the terminal tool, the paths
+    /// for tools that edit, create, move or delete files, and the URL for the fetch tool. When
+    /// omitted, the rule applies to every use of the tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    pub permission: ToolPermission,
+}
+
+/// The tool permission rules of a profile, with their patterns compiled once.
+#[derive(Clone, Debug, Default)]
+pub struct ToolPermissions {
+    rules: Vec<ToolPermissionRule>,
+    patterns: Vec<RulePattern>,
+}
+
+#[derive(Clone, Debug)]
+enum RulePattern {
+    Any,
+    Glob(GlobMatcher),
+    Invalid,
+}
+
+impl ToolPermissions {
+    pub fn new(rules: Vec<ToolPermissionRule>) -> Self {
+        let patterns = rules
+            .iter()
+            .map(|rule| match rule.pattern.as_ref() {
+                None => RulePattern::Any,
+                Some(pattern) => match Glob::new(pattern) {
+                    Ok(glob) => RulePattern::Glob(glob.compile_matcher()),
+                    Err(error) => {
+                        log::warn!("invalid tool permission pattern {pattern:?}: {error}");
+                        RulePattern::Invalid
+                    }
+                },
+            })
+            .collect();
+        Self { rules, patterns }
+    }
+
+    pub fn rules(&self) -> &[ToolPermissionRule] {
+        &self.rules
+    }
+
+    /// Decides whether a use of the given tool with the given subjects may run.
+    ///
+    /// A tool use is denied when any of its subjects matches a deny rule, and needs confirmation
+    /// when any of them matches an ask rule. It is only allowed when every subject matches an
+    /// allow rule, so that chaining an allowed command with another one still asks. Returns `None`
+    /// when the rules don't decide, in which case the tool's own defaults apply.
+    pub fn permission(&self, tool_name: &str, subjects: &[String]) -> Option<ToolPermission> {
+        let subjects = if subjects.is_empty() {
+            vec![None]
+        } else {
+            subjects
+                .iter()
+                .map(|subject| Some(subject.as_str()))
+                .collect()
+        };
+        let matches = |subject: Option<&str>, permission: ToolPermission| {
+            self.rules
+                .iter()
+                .zip(&self.patterns)
+                .any(|(rule, pattern)| {
+                    rule.permission == permission
+                        && Self::rule_matches(rule, pattern, tool_name, subject)
+                })
+        };
+
+        if subjects
+            .iter()
+            .any(|subject| matches(*subject, ToolPermission::Deny))
+        {
+            Some(ToolPermission::Deny)
+        } else if subjects
+            .iter()
+            .any(|subject| matches(*subject, ToolPermission::Ask))
+        {
+            Some(ToolPermission::Ask)
+        } else if subjects
+            .iter()
+            .all(|subject| matches(*subject, ToolPermission::Allow))
+        {
+            Some(ToolPermission::Allow)
+        } else {
+            None
+        }
+    }
+
+    fn rule_matches(
+        rule: &ToolPermissionRule,
+        pattern: &RulePattern,
+        tool_name: &str,
+        subject: Option<&str>,
+    ) -> bool {
+        if rule.tool.as_ref() != "*" && rule.tool.as_ref() != tool_name {
+            return false;
+        }
+        match (pattern, subject) {
+            (RulePattern::Any, _) => true,
+            (RulePattern::Glob(matcher), Some(subject)) => matcher.is_match(subject),
+            (RulePattern::Glob(_), None) | (RulePattern::Invalid, _) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(tool: &str, pattern: Option<&str>, permission: ToolPermission) -> ToolPermissionRule {
+        ToolPermissionRule {
+            tool: tool.into(),
+            pattern: pattern.map(ToString::to_string),
+            permission,
+        }
+    }
+
+    fn subjects(subjects: &[&str]) -> Vec<String> {
+        subjects.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_tool_permission() {
+        let rules = [
+            rule("terminal", Some("cargo *"), ToolPermission::Allow),
+            rule("terminal", Some("git status"), ToolPermission::Allow),
+            rule("terminal", Some("git push*"), ToolPermission::Ask),
+            rule("terminal", Some("rm *"), ToolPermission::Deny),
+            rule("find_replace_file", Some("src/**"), ToolPermission::Allow),
+            rule("*", Some("**/.env"), ToolPermission::Deny),
+            rule("fetch", Some("https://docs.rs/*"), ToolPermission::Allow),
+            rule("thinking", None, ToolPermission::Allow),
+        ];
+
+        let permissions = ToolPermissions::new(rules.to_vec());
+        let permission = |tool: &str, args: &[&str]| permissions.permission(tool, &subjects(args));
+
+        assert_eq!(
+            permission("terminal", &["cargo test -p editor"]),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(
+            permission("terminal", &["git status", "cargo check"]),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(
+            permission("terminal", &["git push origin main"]),
+            Some(ToolPermission::Ask)
+        );
+        assert_eq!(
+            permission("terminal", &["cargo build", "rm -rf target"]),
+            Some(ToolPermission::Deny)
+        );
+        // Every part of a chained command has to be allowed.
+        assert_eq!(permission("terminal", &["cargo build", "make"]), None);
+        assert_eq!(permission("terminal", &[]), None);
+
+        assert_eq!(
+            permission("find_replace_file", &["src/main.rs"]),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(
+            permission("find_replace_file", &["src/config/.env"]),
+            Some(ToolPermission::Deny)
+        );
+        assert_eq!(permission("find_replace_file", &["Cargo.toml"]), None);
+        assert_eq!(permission("delete_path", &["src/main.rs"]), None);
+
+        assert_eq!(
+            permission("fetch", &["https://docs.rs/gpui/latest"]),
+            Some(ToolPermission::Allow)
+        );
+        assert_eq!(permission("fetch", &["https://example.com"]), None);
+
+        assert_eq!(permission("thinking", &[]), Some(ToolPermission::Allow));
+        assert_eq!(permission("now", &[]), None);
+    }
+}
@@ -5,6 +5,7 @@ mod tool_working_set;
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -22,6 +23,47 @@ pub fn init(cx: &mut App) {
     ToolRegistry::default_global(cx);
 }
 
+/// Returns the subjects that tool permission rules are matched against for the given use of the
+/// tool, or `None` when rules can't tell what the tool use does, such as when a path escapes the
+/// directory it is relative to.
+pub fn tool_permission_subjects(tool: &dyn Tool, input: &serde_json::Value) -> Option<Vec<String>> {
+    if tool.permission_subjects_are_incomplete(input) {
+        return None;
+    }
+    let subjects = tool.permission_subjects(input);
+    if !tool.permission_subjects_are_paths() {
+        return Some(subjects);
+    }
+    subjects
+        .iter()
+        .map(|path| normalize_permission_path(path))
+        .collect()
+}
+
+/// Resolves the `.` and `..` components of a path without touching the file system.
+fn normalize_permission_path(path: &str) -> Option<String> {
+    let mut components = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                _ => return None,
+            },
+            component => components.push(component),
+        }
+    }
+    Some(
+        components
+            .into_iter()
+            .collect::<PathBuf>()
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum ToolSource {
     /// A native tool built-in to Zed.
@@ -50,6 +92,36 @@ pub trait Tool: 'static + Send + Sync {
     /// before having permission to run.
     fn needs_confirmation(&self, input: &serde_json::Value, cx: &App) -> bool;
 
+    /// Returns the arguments that tool permission rules are matched against, such as the
+    /// commands run by the terminal tool or the paths touched by file tools.
+    fn permission_subjects(&self, _input: &serde_json::Value) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns whether the permission subjects of this tool are paths, which are normalized
+    /// before rules are matched against them.
+    fn permission_subjects_are_paths(&self) -> bool {
+        false
+    }
+
+    /// Returns whether the permission subjects of the given input may not cover everything the
+    /// tool would do, such as a command substitution in a terminal command. Such tool uses are
+    /// never allowed by rules, only denied or confirmed.
+    fn permission_subjects_are_incomplete(&self, _input: &serde_json::Value) -> bool {
+        false
+    }
+
+    /// Returns the glob pattern stored in a tool permission rule when the user always allows
+    /// uses of this tool similar to one with the given subject.
+    fn permission_pattern(&self, subject: &str) -> String {
+        globset::escape(subject)
+    }
+
+    /// Returns the names and inputs of the tools this tool runs, whose permissions apply to it.
+    fn nested_tool_uses(&self, _input: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+        Vec::new()
+    }
+
     /// Returns the JSON schema that describes the tool's input.
     fn input_schema(&self, _: LanguageModelToolSchemaFormat) -> serde_json::Value {
         serde_json::Value::Object(serde_json::Map::default())
@@ -74,3 +146,23 @@ impl Debug for dyn Tool {
         f.debug_struct("Tool").field("name", &self.name()).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_normalize_permission_path() {
+        assert_eq!(
+            normalize_permission_path("src/main.rs").as_deref(),
+            Some("src/main.rs")
+        );
+        assert_eq!(
+            normalize_permission_path("./src/../config/./.env").as_deref(),
+            Some("config/.env")
+        );
+        assert_eq!(normalize_permission_path("src/../../.env"), None);
+        assert_eq!(normalize_permission_path("../outside.rs"), None);
+    }
+}
@@ -47,6 +47,16 @@ impl Tool for CopyPathTool {
         true
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<CopyPathToolInput>(input.clone())
+            .map(|input| vec![input.source_path, input.destination_path])
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_paths(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> String {
         include_str!("./copy_path_tool/description.md").into()
     }
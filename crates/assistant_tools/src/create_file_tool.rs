@@ -44,6 +44,16 @@ impl Tool for CreateFileTool {
         false
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<CreateFileToolInput>(input.clone())
+            .map(|input| vec![input.path])
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_paths(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> String {
         include_str!("./create_file_tool/description.md").into()
     }
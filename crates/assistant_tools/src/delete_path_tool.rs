@@ -37,6 +37,16 @@ impl Tool for DeletePathTool {
         true
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<DeletePathToolInput>(input.clone())
+            .map(|input| vec![input.path])
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_paths(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> String {
         include_str!("./delete_path_tool/description.md").into()
     }
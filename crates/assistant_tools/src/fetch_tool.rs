@@ -120,6 +120,27 @@ impl Tool for FetchTool {
         true
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<FetchToolInput>(input.clone())
+            .map(|input| {
+                if input.url.starts_with("https://") || input.url.starts_with("http://") {
+                    vec![input.url]
+                } else {
+                    vec![format!("https://{}", input.url)]
+                }
+            })
+            .unwrap_or_default()
+    }
+
+    fn permission_pattern(&self, url: &str) -> String {
+        // Allow any URL on the same origin.
+        let host_start = url.find("://").map_or(0, |ix| ix + 3);
+        let origin_end = url[host_start..]
+            .find(['/', '?', '#'])
+            .map_or(url.len(), |ix| host_start + ix);
+        format!("{}/*", globset::escape(&url[..origin_end]))
+    }
+
     fn description(&self) -> String {
         include_str!("./fetch_tool/description.md").to_string()
     }
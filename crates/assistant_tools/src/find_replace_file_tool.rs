@@ -143,6 +143,16 @@ impl Tool for FindReplaceFileTool {
         false
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<FindReplaceFileToolInput>(input.clone())
+            .map(|input| vec![input.path.to_string_lossy().into_owned()])
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_paths(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> String {
         include_str!("find_replace_tool/description.md").to_string()
     }
@@ -46,6 +46,16 @@ impl Tool for MovePathTool {
         true
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<MovePathToolInput>(input.clone())
+            .map(|input| vec![input.source_path, input.destination_path])
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_paths(&self) -> bool {
+        true
+    }
+
     fn description(&self) -> String {
         include_str!("./move_path_tool/description.md").into()
     }
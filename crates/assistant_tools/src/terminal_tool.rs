@@ -36,6 +36,33 @@ impl Tool for TerminalTool {
         true
     }
 
+    fn permission_subjects(&self, input: &serde_json::Value) -> Vec<String> {
+        serde_json::from_value::<TerminalToolInput>(input.clone())
+            .map(|input| split_commands(&input.command))
+            .unwrap_or_default()
+    }
+
+    fn permission_subjects_are_incomplete(&self, input: &serde_json::Value) -> bool {
+        serde_json::from_value::<TerminalToolInput>(input.clone()).map_or(true, |input| {
+            has_substitution_or_redirection(&input.command)
+        })
+    }
+
+    fn permission_pattern(&self, command: &str) -> String {
+        // Allow the same program and subcommand with any arguments, such as `cargo test*`.
+        let mut words = command.split_whitespace();
+        let mut prefix = words.next().unwrap_or_default().to_string();
+        if let Some(subcommand) = words.next().filter(|word| {
+            word.chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_')
+                && !word.starts_with('-')
+        }) {
+            prefix.push(' ');
+            prefix.push_str(subcommand);
+        }
+        format!("{}*", globset::escape(&prefix))
+    }
+
     fn description(&self) -> String {
         include_str!("./terminal_tool/description.md").to_string()
     }
@@ -260,6 +287,73 @@ fn output_block(output: &str) -> String {
     )
 }
 
+/// Splits a shell one-liner into the commands it chains together with `&&`, `||`, `;`, `|`, `&`
+/// or newlines, so that permission rules can be checked against each of them.
+fn split_commands(command: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = command.chars().peekable();
+    while let Some(char) = chars.next() {
+        match (char, quote) {
+            ('\\', _) => {
+                current.push(char);
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            ('\'' | '"', None) => {
+                quote = Some(char);
+                current.push(char);
+            }
+            (_, Some(open)) if char == open => {
+                quote = None;
+                current.push(char);
+            }
+            ('&' | '|', None) if chars.peek() == Some(&char) => {
+                chars.next();
+                commands.push(std::mem::take(&mut current));
+            }
+            // `>&` and `&>` redirect output rather than running a command in the background.
+            ('&', None) if current.ends_with('>') || chars.peek() == Some(&'>') => {
+                current.push(char);
+            }
+            (';' | '|' | '&' | '\n', None) => commands.push(std::mem::take(&mut current)),
+            _ => current.push(char),
+        }
+    }
+    commands.push(current);
+
+    commands
+        .into_iter()
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Returns whether a shell one-liner substitutes the output of other commands or redirects
+/// input or output, which permission rules can't match against.
+fn has_substitution_or_redirection(command: &str) -> bool {
+    let mut quote = None;
+    let mut chars = command.chars().peekable();
+    while let Some(char) = chars.next() {
+        match (char, quote) {
+            ('\\', Some('\'')) => {}
+            ('\\', _) => {
+                chars.next();
+            }
+            ('\'' | '"', None) => quote = Some(char),
+            (_, Some(open)) if char == open => quote = None,
+            // Substitutions are expanded inside double quotes too.
+            ('`', None | Some('"')) => return true,
+            ('$', None | Some('"')) if chars.peek() == Some(&'(') => return true,
+            ('<' | '>', None) => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
 #[cfg(test)]
 #[cfg(not(windows))]
 mod tests {
@@ -267,6 +361,41 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_split_commands() {
+        assert_eq!(
+            split_commands("cargo fmt && cargo test -p editor || echo 'failed; see log'"),
+            vec![
+                "cargo fmt",
+                "cargo test -p editor",
+                "echo 'failed; see log'"
+            ]
+        );
+        assert_eq!(
+            split_commands("ls -la | grep \"a|b\"; rm -rf target\ngit status"),
+            vec!["ls -la", "grep \"a|b\"", "rm -rf target", "git status"]
+        );
+        assert_eq!(split_commands("echo a\\;b &"), vec!["echo a\\;b"]);
+        assert_eq!(
+            split_commands("sleep 1 & rm -rf target"),
+            vec!["sleep 1", "rm -rf target"]
+        );
+        assert_eq!(
+            split_commands("cargo build 2>&1 | tail"),
+            vec!["cargo build 2>&1", "tail"]
+        );
+    }
+
+    #[test]
+    fn test_has_substitution_or_redirection() {
+        assert!(!has_substitution_or_redirection("cargo test -p editor"));
+        assert!(!has_substitution_or_redirection("echo '$(not run)' '>'"));
+        assert!(has_substitution_or_redirection("cargo test $(rm -rf ~)"));
+        assert!(has_substitution_or_redirection("echo \"`whoami`\""));
+        assert!(has_substitution_or_redirection("cargo build > ~/.bashrc"));
+        assert!(has_substitution_or_redirection("cat < /etc/passwd"));
+    }
+
     #[gpui::test(iterations = 10)]
     async fn test_run_command_simple(cx: &mut TestAppContext) {
         cx.executor().allow_parking();
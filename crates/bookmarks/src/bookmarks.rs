@@ -0,0 +1,258 @@
+use std::{path::Path, sync::Arc};
+
+use editor::{Editor, scroll::Autoscroll};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ParentElement,
+    Render, Styled, Task, WeakEntity, Window, actions, rems,
+};
+use language::{Buffer, Point};
+use picker::{Picker, PickerDelegate};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(bookmarks, [Toggle]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(BookmarksView::register).detach();
+}
+
+pub struct BookmarksView {
+    picker: Entity<Picker<BookmarksViewDelegate>>,
+}
+
+impl Focusable for BookmarksView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for BookmarksView {}
+impl ModalView for BookmarksView {}
+
+impl Render for BookmarksView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl BookmarksView {
+    fn register(
+        workspace: &mut Workspace,
+        _window: Option<&mut Window>,
+        _: &mut Context<Workspace>,
+    ) {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            let weak_workspace = cx.entity().downgrade();
+            let bookmarks = bookmark_entries(workspace, cx);
+            workspace.toggle_modal(window, cx, |window, cx| {
+                let delegate =
+                    BookmarksViewDelegate::new(cx.entity().downgrade(), weak_workspace, bookmarks);
+                let picker = cx.new(|cx| {
+                    Picker::uniform_list(delegate, window, cx).max_height(Some(vh(0.75, window)))
+                });
+                BookmarksView { picker }
+            });
+        });
+    }
+}
+
+struct BookmarkEntry {
+    buffer: Entity<Buffer>,
+    position: Point,
+}
+
+fn bookmark_entries(
+    workspace: &Workspace,
+    cx: &App,
+) -> (Vec<BookmarkEntry>, Vec<StringMatchCandidate>) {
+    let project = workspace.project().read(cx);
+    let bookmark_store = project.bookmark_store();
+    bookmark_store
+        .read(cx)
+        .all_bookmarks()
+        .enumerate()
+        .map(|(ix, (project_path, buffer, bookmark))| {
+            let snapshot = buffer.read(cx).snapshot();
+            let position = snapshot.summary_for_anchor::<Point>(&bookmark.position);
+            let title = match &bookmark.label {
+                Some(label) => label.to_string(),
+                None => snapshot
+                    .text_for_range(
+                        Point::new(position.row, 0)
+                            ..Point::new(position.row, snapshot.line_len(position.row)),
+                    )
+                    .collect::<String>()
+                    .trim()
+                    .to_string(),
+            };
+            let path = match project.worktree_for_id(project_path.worktree_id, cx) {
+                Some(worktree) => Path::new(worktree.read(cx).root_name())
+                    .join(&project_path.path)
+                    .to_string_lossy()
+                    .to_string(),
+                None => project_path.path.to_string_lossy().to_string(),
+            };
+            let candidate =
+                StringMatchCandidate::new(ix, &format!("{path}:{}  {title}", position.row + 1));
+            (
+                BookmarkEntry {
+                    buffer: buffer.clone(),
+                    position,
+                },
+                candidate,
+            )
+        })
+        .unzip()
+}
+
+struct BookmarksViewDelegate {
+    bookmarks_view: WeakEntity<BookmarksView>,
+    workspace: WeakEntity<Workspace>,
+    bookmarks: Vec<BookmarkEntry>,
+    candidates: Arc<[StringMatchCandidate]>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl BookmarksViewDelegate {
+    fn new(
+        bookmarks_view: WeakEntity<BookmarksView>,
+        workspace: WeakEntity<Workspace>,
+        (bookmarks, candidates): (Vec<BookmarkEntry>, Vec<StringMatchCandidate>),
+    ) -> Self {
+        Self {
+            bookmarks_view,
+            workspace,
+            bookmarks,
+            candidates: candidates.into(),
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for BookmarksViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search bookmarks...".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No bookmarks".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string.clone(),
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(bookmark) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|mat| self.bookmarks.get(mat.candidate_id))
+        else {
+            return;
+        };
+        let buffer = bookmark.buffer.clone();
+        let position = bookmark.position;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor =
+                    workspace.open_project_item::<Editor>(pane, buffer, true, true, window, cx);
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                        s.select_ranges([position..position]);
+                    });
+                });
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.bookmarks_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .start_slot(Icon::new(IconName::Book).color(Color::Accent))
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
@@ -1,21 +1,22 @@
 mod persistence;
 
 use std::{
-    cmp::{self, Reverse},
+    cmp::{self, Ordering},
     collections::HashMap,
     sync::Arc,
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
 use client::parse_zed_link;
 use command_palette_hooks::{
     CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
 };
+use db::frecency::Frecency;
 
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
     Action, App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable,
-    ParentElement, Render, Styled, Task, WeakEntity, Window,
+    ParentElement, Render, Styled, Task, WeakEntity, Window, actions,
 };
 use persistence::COMMAND_PALETTE_HISTORY;
 use picker::{Picker, PickerDelegate};
@@ -26,6 +27,8 @@ use util::ResultExt;
 use workspace::{ModalView, Workspace, WorkspaceSettings};
 use zed_actions::{OpenZedUrl, command_palette::Toggle};
 
+actions!(command_palette, [ClearHistory]);
+
 pub fn init(cx: &mut App) {
     client::init_settings(cx);
     command_palette_hooks::init(cx);
@@ -70,6 +73,10 @@ impl CommandPalette {
         workspace.register_action(|workspace, _: &Toggle, window, cx| {
             Self::toggle(workspace, "", window, cx)
         });
+        workspace.register_action(|_, _: &ClearHistory, _, cx| {
+            cx.background_spawn(async move { COMMAND_PALETTE_HISTORY.clear_history().await })
+                .detach_and_log_err(cx);
+        });
     }
 
     pub fn toggle(
@@ -243,14 +250,21 @@ impl CommandPaletteDelegate {
         }
     }
     ///
-    /// Hit count for each command in the palette.
+    /// Frecency boost for each command in the palette, from how often and how recently it was used.
     /// We only account for commands triggered directly via command palette and not by e.g. keystrokes because
     /// if a user already knows a keystroke for a command, they are unlikely to use a command palette to look for it.
-    fn hit_counts(&self) -> HashMap<String, u16> {
+    fn frecency_boosts(&self) -> HashMap<String, f64> {
         if let Ok(commands) = COMMAND_PALETTE_HISTORY.list_commands_used() {
+            let now = SystemTime::now();
             commands
                 .into_iter()
-                .map(|command| (command.command_name, command.invocations))
+                .map(|command| {
+                    let frecency = Frecency {
+                        count: command.invocations.into(),
+                        last_used: command.last_invoked.into(),
+                    };
+                    (command.command_name, frecency.boost(now))
+                })
                 .collect()
         } else {
             HashMap::new()
@@ -295,15 +309,17 @@ impl PickerDelegate for CommandPaletteDelegate {
         let (mut tx, mut rx) = postage::dispatch::channel(1);
         let task = cx.background_spawn({
             let mut commands = self.all_commands.clone();
-            let hit_counts = self.hit_counts();
+            let frecency_boosts = self.frecency_boosts();
             let executor = cx.background_executor().clone();
             let query = normalize_query(query.as_str());
             async move {
-                commands.sort_by_key(|action| {
-                    (
-                        Reverse(hit_counts.get(&action.name).cloned()),
-                        action.name.clone(),
-                    )
+                let boost =
+                    |command: &Command| frecency_boosts.get(&command.name).copied().unwrap_or(1.0);
+                commands.sort_by(|a, b| {
+                    boost(b)
+                        .partial_cmp(&boost(a))
+                        .unwrap_or(Ordering::Equal)
+                        .then_with(|| a.name.cmp(&b.name))
                 });
 
                 let candidates = commands
@@ -323,15 +339,20 @@ impl PickerDelegate for CommandPaletteDelegate {
                         })
                         .collect()
                 } else {
-                    fuzzy::match_strings(
+                    let mut matches = fuzzy::match_strings_with_query(
                         &candidates,
-                        &query,
+                        &fuzzy::Query::parse(&query),
                         true,
                         10000,
                         &Default::default(),
                         executor,
                     )
-                    .await
+                    .await;
+                    for string_match in &mut matches {
+                        string_match.score *= boost(&commands[string_match.candidate_id]);
+                    }
+                    matches.sort_by(|a, b| b.cmp(a));
+                    matches
                 };
 
                 tx.send((commands, matches)).await.log_err();
@@ -107,6 +107,12 @@ impl CommandPaletteDB {
             ORDER BY COUNT(1) DESC
         }
     }
+
+    query! {
+        pub async fn clear_history() -> Result<()> {
+            DELETE FROM command_invocations
+        }
+    }
 }
 
 #[cfg(test)]
@@ -207,4 +213,18 @@ mod tests {
         assert!(some_command.is_some());
         assert_eq!(some_command.expect("is some").invocations, 1000);
     }
+
+    #[gpui::test]
+    async fn test_clears_history() {
+        let db = CommandPaletteDB(db::open_test_db("test_clears_history").await);
+
+        db.write_command_invocation("editor: backspace", "")
+            .await
+            .unwrap();
+        assert_eq!(db.list_commands_used().unwrap().len(), 1);
+
+        db.clear_history().await.unwrap();
+        assert!(db.list_commands_used().unwrap().is_empty());
+        assert!(db.get_last_invoked("editor: backspace").unwrap().is_none());
+    }
 }
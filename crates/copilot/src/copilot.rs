@@ -1298,7 +1298,7 @@ mod tests {
             self.abs_path.clone()
         }
 
-        fn load(&self, _: &App) -> Task<Result<String>> {
+        fn load(&self, _: fs::Encoding, _: &App) -> Task<Result<String>> {
             unimplemented!()
         }
 
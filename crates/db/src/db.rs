@@ -1,3 +1,4 @@
+pub mod frecency;
 pub mod kvp;
 pub mod query;
 
@@ -12,12 +13,15 @@ pub use sqlez;
 pub use sqlez_macros;
 
 pub use release_channel::RELEASE_CHANNEL;
+use sqlez::connection::Connection;
 use sqlez::domain::Migrator;
+use sqlez::migrations::MigrationMismatch;
 use sqlez::thread_safe_connection::ThreadSafeConnection;
 use sqlez_macros::sql;
 use std::future::Future;
-use std::path::Path;
-use std::sync::{LazyLock, atomic::Ordering};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex, atomic::Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env, sync::atomic::AtomicBool};
 use util::{ResultExt, maybe};
 
@@ -41,9 +45,19 @@ pub static ZED_STATELESS: LazyLock<bool> =
 
 pub static ALL_FILE_DB_FAILED: LazyLock<AtomicBool> = LazyLock::new(|| AtomicBool::new(false));
 
+/// The backups of databases that were reset because their migrations didn't match, so that the
+/// user can be notified.
+static RESET_DB_BACKUPS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Serializes the recovery of databases whose migrations don't match, so that concurrent
+/// connections don't reset a database another one has just migrated.
+static DB_RECOVERY_LOCK: LazyLock<smol::lock::Mutex<()>> =
+    LazyLock::new(|| smol::lock::Mutex::new(()));
+
 /// Open or create a database at the given directory path.
-/// This will retry a couple times if there are failures. If opening fails once, the db directory
-/// is moved to a backup folder and a new one is created. If that fails, a shared in memory db is created.
+/// If the database was migrated by a version of Zed whose migrations don't match this one's, and
+/// its domains declare no compatible rewrite, it is backed up next to itself and the tables of the
+/// mismatched domains are dropped before migrating them again. If opening still fails, a shared in memory db is created.
 /// In either case, static variables are set so that the user can be notified.
 pub async fn open_db<M: Migrator + 'static>(db_dir: &Path, scope: &str) -> ThreadSafeConnection<M> {
     if *ZED_STATELESS {
@@ -75,12 +89,103 @@ pub async fn open_db<M: Migrator + 'static>(db_dir: &Path, scope: &str) -> Threa
 
 async fn open_main_db<M: Migrator>(db_path: &Path) -> Option<ThreadSafeConnection<M>> {
     log::info!("Opening main db");
+    match build_main_db(db_path).await {
+        Ok(connection) => return Some(connection),
+        Err(error) if error.downcast_ref::<MigrationMismatch>().is_some() => {
+            log::error!("{error:?}");
+        }
+        Err(error) => {
+            log::error!("{error:?}");
+            return None;
+        }
+    }
+
+    // The database was migrated by a different version of Zed, for example one on another
+    // release channel. Back it up and reset the tables of the domains whose migrations don't
+    // match, so that their migrations can run from scratch.
+    let _recovery = DB_RECOVERY_LOCK.lock().await;
+    let mut backup_path = None;
+    let mut reset_domains = Vec::new();
+    loop {
+        let error = match build_main_db(db_path).await {
+            Ok(connection) => {
+                // Another connection may already have recovered the database.
+                if let Some(backup_path) = backup_path {
+                    RESET_DB_BACKUPS.lock().unwrap().push(backup_path);
+                }
+                return Some(connection);
+            }
+            Err(error) => error,
+        };
+        let Some(mismatch) = error.downcast_ref::<MigrationMismatch>() else {
+            log::error!("{error:?}");
+            return None;
+        };
+        if reset_domains.contains(&mismatch.domain) {
+            log::error!("{error:?}");
+            return None;
+        }
+
+        let connection = Connection::open_file(db_path.to_string_lossy().as_ref());
+        if !connection.persistent() {
+            log::error!("Could not open {db_path:?}");
+            return None;
+        }
+        // Dropping the tables of a domain deletes the rows of other domains that reference them.
+        connection
+            .exec(CONNECTION_INITIALIZE_QUERY)
+            .and_then(|mut initialize| initialize())
+            .context("Could not initialize the db connection")
+            .log_err()?;
+        if backup_path.is_none() {
+            let path = backup_main_db(&connection, db_path).log_err()?;
+            log::info!("Backed up the db to {path:?}");
+            backup_path = Some(path);
+        }
+        connection
+            .reset_domain(mismatch.domain)
+            .with_context(|| format!("Could not reset the {} tables of the db", mismatch.domain))
+            .log_err()?;
+        log::info!("Reset the {} tables of the db", mismatch.domain);
+        reset_domains.push(mismatch.domain);
+    }
+}
+
+async fn build_main_db<M: Migrator>(db_path: &Path) -> anyhow::Result<ThreadSafeConnection<M>> {
     ThreadSafeConnection::<M>::builder(db_path.to_string_lossy().as_ref(), true)
         .with_db_initialization_query(DB_INITIALIZE_QUERY)
         .with_connection_initialize_query(CONNECTION_INITIALIZE_QUERY)
         .build()
         .await
-        .log_err()
+}
+
+/// Copies the database to a new backup file next to it, returning the path of the backup.
+fn backup_main_db(connection: &Connection, db_path: &Path) -> anyhow::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let backup_path = (0..)
+        .map(|ix| {
+            let suffix = if ix == 0 {
+                String::new()
+            } else {
+                format!("-{ix}")
+            };
+            db_path.with_file_name(format!("db-backup-{timestamp}{suffix}.sqlite"))
+        })
+        .find(|path| !path.exists())
+        .unwrap();
+    connection
+        .backup_main_to(&backup_path)
+        .context("Could not back up the db")?;
+    Ok(backup_path)
+}
+
+/// Returns the backups of the databases that were reset since the last call, because their
+/// migrations didn't match this version of Zed.
+pub fn take_reset_db_backups() -> Vec<PathBuf> {
+    std::mem::take(&mut *RESET_DB_BACKUPS.lock().unwrap())
 }
 
 async fn open_fallback_db<M: Migrator>() -> ThreadSafeConnection<M> {
@@ -109,10 +214,11 @@ pub async fn open_test_db<M: Migrator>(db_name: &str) -> ThreadSafeConnection<M>
         .unwrap()
 }
 
-/// Implements a basic DB wrapper for a given domain
+/// Implements a basic DB wrapper for a given domain. Migrations that changed in a compatible way
+/// after being released can be declared with `compatible_rewrites: &[...]` after the migrations.
 #[macro_export]
 macro_rules! define_connection {
-    (pub static ref $id:ident: $t:ident<()> = $migrations:expr; $($global:ident)?) => {
+    (pub static ref $id:ident: $t:ident<()> = $migrations:expr $(, compatible_rewrites: $rewrites:expr)?; $($global:ident)?) => {
         pub struct $t($crate::sqlez::thread_safe_connection::ThreadSafeConnection<$t>);
 
         impl ::std::ops::Deref for $t {
@@ -131,6 +237,12 @@ macro_rules! define_connection {
             fn migrations() -> &'static [&'static str] {
                 $migrations
             }
+
+            $(
+                fn compatible_rewrites() -> &'static [$crate::sqlez::migrations::MigrationRewrite] {
+                    $rewrites
+                }
+            )?
         }
 
         #[cfg(any(test, feature = "test-support"))]
@@ -149,7 +261,7 @@ macro_rules! define_connection {
             $t($crate::smol::block_on($crate::open_db(db_dir, scope)))
         });
     };
-    (pub static ref $id:ident: $t:ident<$($d:ty),+> = $migrations:expr; $($global:ident)?) => {
+    (pub static ref $id:ident: $t:ident<$($d:ty),+> = $migrations:expr $(, compatible_rewrites: $rewrites:expr)?; $($global:ident)?) => {
         pub struct $t($crate::sqlez::thread_safe_connection::ThreadSafeConnection<( $($d),+, $t )>);
 
         impl ::std::ops::Deref for $t {
@@ -168,6 +280,12 @@ macro_rules! define_connection {
             fn migrations() -> &'static [&'static str] {
                 $migrations
             }
+
+            $(
+                fn compatible_rewrites() -> &'static [$crate::sqlez::migrations::MigrationRewrite] {
+                    $rewrites
+                }
+            )?
         }
 
         #[cfg(any(test, feature = "test-support"))]
@@ -200,8 +318,11 @@ where
 mod tests {
     use std::thread;
 
+    use sqlez::bindable::{Column, StaticColumnCount};
+    use sqlez::connection::Connection;
     use sqlez::domain::Domain;
-    use sqlez_macros::sql;
+    use sqlez::migrations::MigrationRewrite;
+    use sqlez_macros::{Bind, Column, sql};
 
     use crate::open_db;
 
@@ -249,7 +370,24 @@ mod tests {
             }
 
             fn migrations() -> &'static [&'static str] {
-                &[sql!(CREATE TABLE test(value);)]
+                &[sql!(CREATE TABLE test(value INTEGER PRIMARY KEY);)]
+            }
+        }
+
+        enum OtherDB {}
+
+        impl Domain for OtherDB {
+            fn name() -> &'static str {
+                "other_db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(
+                    CREATE TABLE other(value);
+                    CREATE TABLE other_tests(
+                        test_value INTEGER REFERENCES test(value) ON DELETE CASCADE
+                    );
+                )]
             }
         }
 
@@ -276,6 +414,25 @@ mod tests {
             )
             .await;
             assert!(corrupt_db.persistent());
+            corrupt_db
+                .write(|connection| connection.exec(sql!(INSERT INTO test(value) VALUES (1);))?())
+                .await
+                .unwrap();
+
+            let other_db = open_db::<OtherDB>(
+                tempdir.path(),
+                &release_channel::ReleaseChannel::Dev.dev_name(),
+            )
+            .await;
+            other_db
+                .write(|connection| {
+                    connection.exec(sql!(
+                        INSERT INTO other(value) VALUES (1);
+                        INSERT INTO other_tests(test_value) VALUES (1);
+                    ))?()
+                })
+                .await
+                .unwrap();
         }
 
         let good_db = open_db::<GoodDB>(
@@ -283,11 +440,115 @@ mod tests {
             &release_channel::ReleaseChannel::Dev.dev_name(),
         )
         .await;
+        assert!(good_db.persistent());
         assert!(
             good_db.select_row::<usize>("SELECT * FROM test2").unwrap()()
                 .unwrap()
                 .is_none()
         );
+
+        // The tables of other domains are kept, without the rows referencing the dropped tables
+        assert_eq!(
+            good_db.select_row::<usize>("SELECT * FROM other").unwrap()().unwrap(),
+            Some(1)
+        );
+        assert!(
+            good_db
+                .select_row::<usize>("SELECT * FROM other_tests")
+                .unwrap()()
+            .unwrap()
+            .is_none()
+        );
+
+        // The mismatched database was backed up before being reset
+        let db_dir = tempdir.path().join(format!(
+            "0-{}",
+            release_channel::ReleaseChannel::Dev.dev_name()
+        ));
+        let backup_path = std::fs::read_dir(&db_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .starts_with("db-backup-")
+            })
+            .unwrap();
+        let backup = sqlez::connection::Connection::open_file(&backup_path.to_string_lossy());
+        assert!(
+            backup.select_row::<usize>("SELECT * FROM test").unwrap()()
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    /// Test that a DB whose migration changed compatibly is migrated in place
+    #[gpui::test]
+    async fn test_db_compatible_rewrite(cx: &mut gpui::TestAppContext) {
+        cx.executor().allow_parking();
+
+        enum OldDB {}
+
+        impl Domain for OldDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(value);)]
+            }
+        }
+
+        enum NewDB {}
+
+        impl Domain for NewDB {
+            fn name() -> &'static str {
+                "db_tests"
+            }
+
+            fn migrations() -> &'static [&'static str] {
+                &[sql!(CREATE TABLE test(renamed_value);)]
+            }
+
+            fn compatible_rewrites() -> &'static [MigrationRewrite] {
+                &[MigrationRewrite {
+                    step: 0,
+                    stored: sql!(CREATE TABLE test(value);),
+                    rewrite: "ALTER TABLE test RENAME COLUMN value TO renamed_value",
+                }]
+            }
+        }
+
+        let tempdir = tempfile::Builder::new()
+            .prefix("DbTests")
+            .tempdir()
+            .unwrap();
+        {
+            let old_db = open_db::<OldDB>(
+                tempdir.path(),
+                &release_channel::ReleaseChannel::Dev.dev_name(),
+            )
+            .await;
+            old_db
+                .write(|connection| connection.exec(sql!(INSERT INTO test(value) VALUES (1);))?())
+                .await
+                .unwrap();
+        }
+
+        let new_db = open_db::<NewDB>(
+            tempdir.path(),
+            &release_channel::ReleaseChannel::Dev.dev_name(),
+        )
+        .await;
+        assert!(new_db.persistent());
+        assert_eq!(
+            new_db
+                .select_row::<usize>("SELECT renamed_value FROM test")
+                .unwrap()()
+            .unwrap(),
+            Some(1)
+        );
     }
 
     /// Test that DB exists but corrupted (causing recreate)
@@ -356,4 +617,170 @@ mod tests {
             assert!(guard.join().is_ok());
         }
     }
+
+    #[derive(Bind, Column, Debug, Clone, Copy, PartialEq)]
+    enum Dock {
+        Left,
+        #[sqlez(rename = "bottom")]
+        Bottom,
+    }
+
+    #[derive(Bind, Column, Debug, Clone, Copy, PartialEq)]
+    #[sqlez(repr = "int")]
+    enum Mode {
+        Insert = 1,
+        Normal = 7,
+    }
+
+    #[derive(Bind, Column, Debug, PartialEq)]
+    struct Item {
+        name: String,
+        position: (i64, Option<i64>),
+        dock: Option<Dock>,
+        mode: Mode,
+        #[sqlez(skip)]
+        cached: Option<String>,
+        active: bool,
+    }
+
+    #[derive(Bind, Column, Debug, PartialEq)]
+    struct Wrapper(i64, Option<Item>);
+
+    fn connection(name: &str) -> Connection {
+        let connection = Connection::open_memory(Some(name));
+        connection
+            .exec(
+                "CREATE TABLE items (
+                    name TEXT,
+                    row INTEGER,
+                    column INTEGER,
+                    dock TEXT,
+                    mode INTEGER,
+                    active INTEGER
+                );",
+            )
+            .unwrap()()
+        .unwrap();
+        connection
+    }
+
+    #[test]
+    fn test_column_counts() {
+        assert_eq!(Dock::COLUMN_COUNT, 1);
+        assert_eq!(Item::COLUMN_COUNT, 6);
+        assert_eq!(Wrapper::COLUMN_COUNT, 7);
+        assert_eq!(<Option<Item>>::COLUMN_COUNT, 6);
+        assert_eq!(Item::CHECKED_COLUMN_COUNT, Some(6));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let connection = connection("test_round_trip");
+        let items = [
+            Item {
+                name: "a".into(),
+                position: (1, Some(2)),
+                dock: Some(Dock::Bottom),
+                mode: Mode::Normal,
+                cached: Some("ignored".into()),
+                active: true,
+            },
+            Item {
+                name: "b".into(),
+                position: (3, None),
+                dock: None,
+                mode: Mode::Insert,
+                cached: None,
+                active: false,
+            },
+        ];
+        let mut insert = connection
+            .exec_bound::<Item>("INSERT INTO items VALUES (?, ?, ?, ?, ?, ?)")
+            .unwrap();
+        for item in items {
+            insert(item).unwrap();
+        }
+
+        let stored = connection
+            .select::<(String, Option<String>, i64)>("SELECT name, dock, mode FROM items")
+            .unwrap()()
+        .unwrap();
+        assert_eq!(
+            stored,
+            [
+                ("a".to_string(), Some("bottom".to_string()), 7),
+                ("b".to_string(), None, 1)
+            ]
+        );
+
+        let items = connection
+            .select::<Item>("SELECT * FROM items ORDER BY name")
+            .unwrap()()
+        .unwrap();
+        assert_eq!(
+            items,
+            [
+                Item {
+                    name: "a".into(),
+                    position: (1, Some(2)),
+                    dock: Some(Dock::Bottom),
+                    mode: Mode::Normal,
+                    cached: None,
+                    active: true,
+                },
+                Item {
+                    name: "b".into(),
+                    position: (3, None),
+                    dock: None,
+                    mode: Mode::Insert,
+                    cached: None,
+                    active: false,
+                },
+            ]
+        );
+
+        let wrapper = connection
+            .select_row::<Wrapper>("SELECT rowid, * FROM items WHERE name = 'b'")
+            .unwrap()()
+        .unwrap();
+        assert_eq!(wrapper.map(|wrapper| wrapper.0), Some(2));
+    }
+
+    #[test]
+    fn test_mismatched_select_fails() {
+        let connection = connection("test_mismatched_select_fails");
+        connection
+            .exec("INSERT INTO items VALUES ('a', 1, 2, 'Left', 1, 1)")
+            .unwrap()()
+        .unwrap();
+
+        assert!(
+            connection
+                .select_row::<Item>("SELECT name, row, column, dock, mode FROM items")
+                .unwrap()()
+            .is_err()
+        );
+        assert!(
+            connection
+                .select_row::<Item>("SELECT *, rowid FROM items")
+                .unwrap()()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_unknown_variant_fails() {
+        let connection = connection("test_unknown_variant_fails");
+        connection
+            .exec("INSERT INTO items VALUES ('a', 1, 2, 'Right', 1, 1)")
+            .unwrap()()
+        .unwrap();
+
+        assert!(
+            connection
+                .select_row::<Item>("SELECT * FROM items")
+                .unwrap()()
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use sqlez_macros::sql;
+
+use crate::{define_connection, query};
+
+/// How long it takes for the uses of an item to count half as much.
+const HALF_LIFE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How often and how recently an item was used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frecency {
+    pub count: u32,
+    pub last_used: SystemTime,
+}
+
+impl Frecency {
+    /// The number of uses, decayed by the time since the last one.
+    pub fn score(&self, now: SystemTime) -> f64 {
+        let age = now.duration_since(self.last_used).unwrap_or_default();
+        self.count as f64 * 0.5_f64.powf(age.as_secs_f64() / HALF_LIFE.as_secs_f64())
+    }
+
+    /// A factor to multiply match scores by. It grows slowly with the score, so that frequently
+    /// used items are preferred without hiding much better matches.
+    pub fn boost(&self, now: SystemTime) -> f64 {
+        1.0 + self.score(now).ln_1p() / 2.0
+    }
+}
+
+define_connection!(pub static ref FRECENCY_STORE: FrecencyStore<()> =
+    &[sql!(
+        CREATE TABLE IF NOT EXISTS frecency(
+            scope TEXT NOT NULL,
+            key TEXT NOT NULL,
+            count INTEGER NOT NULL,
+            last_used INTEGER NOT NULL,
+            PRIMARY KEY(scope, key)
+        ) STRICT;
+    )];
+);
+
+impl FrecencyStore {
+    /// Returns the frecency of every item used in a scope, by key.
+    pub fn load(&self, scope: &str) -> Result<HashMap<String, Frecency>> {
+        Ok(self
+            .entries(scope)?
+            .into_iter()
+            .map(|(key, count, last_used)| {
+                let last_used = UNIX_EPOCH + Duration::from_secs(last_used.max(0) as u64);
+                (key, Frecency { count, last_used })
+            })
+            .collect())
+    }
+
+    query! {
+        fn entries(scope: &str) -> Result<Vec<(String, u32, i64)>> {
+            SELECT key, count, last_used FROM frecency WHERE scope = (?)
+        }
+    }
+
+    query! {
+        pub async fn record_use(scope: String, key: String) -> Result<()> {
+            INSERT INTO frecency(scope, key, count, last_used) VALUES (?1, ?2, 1, unixepoch())
+            ON CONFLICT(scope, key) DO UPDATE SET count = count + 1, last_used = unixepoch()
+        }
+    }
+
+    query! {
+        pub async fn clear(scope: String) -> Result<()> {
+            DELETE FROM frecency WHERE scope = (?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_frecency_store() {
+        let db = FrecencyStore(crate::open_test_db("test_frecency_store").await);
+        assert!(db.load("one").unwrap().is_empty());
+
+        db.record_use("one".into(), "a".into()).await.unwrap();
+        db.record_use("one".into(), "a".into()).await.unwrap();
+        db.record_use("one".into(), "b".into()).await.unwrap();
+        db.record_use("two".into(), "a".into()).await.unwrap();
+
+        let one = db.load("one").unwrap();
+        assert_eq!(one.len(), 2);
+        assert_eq!(one["a"].count, 2);
+        assert_eq!(one["b"].count, 1);
+        assert_eq!(db.load("two").unwrap()["a"].count, 1);
+
+        db.clear("one".into()).await.unwrap();
+        assert!(db.load("one").unwrap().is_empty());
+        assert_eq!(db.load("two").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_frecency_score_decays() {
+        let now = SystemTime::now();
+        let frecency = Frecency {
+            count: 4,
+            last_used: now,
+        };
+        assert_eq!(frecency.score(now), 4.0);
+        assert_eq!(frecency.score(now + HALF_LIFE), 2.0);
+        assert_eq!(frecency.score(now + HALF_LIFE * 2), 1.0);
+        // Uses in the future, from clock changes, aren't boosted.
+        assert_eq!(frecency.score(now - HALF_LIFE), 4.0);
+
+        let unused = Frecency {
+            count: 0,
+            last_used: now,
+        };
+        assert_eq!(unused.boost(now), 1.0);
+        assert!(frecency.boost(now) > frecency.boost(now + HALF_LIFE));
+    }
+}
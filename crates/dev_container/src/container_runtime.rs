@@ -0,0 +1,419 @@
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use std::{collections::BTreeMap, path::PathBuf};
+
+/// A container found by its labels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContainerInfo {
+    pub id: String,
+    pub running: bool,
+}
+
+/// An image to build from a Dockerfile.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageBuild {
+    pub tag: String,
+    pub dockerfile: PathBuf,
+    pub context: PathBuf,
+    pub args: BTreeMap<String, String>,
+    pub target: Option<String>,
+}
+
+/// How to create a container. The container runs a command that keeps it alive, so that
+/// commands can be run in it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContainerSpec {
+    pub image: String,
+    pub labels: BTreeMap<String, String>,
+    /// Mounts in the `--mount` syntax.
+    pub mounts: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// Container ports to publish on the same port of the local machine.
+    pub published_ports: Vec<u16>,
+    pub user: Option<String>,
+    /// Extra arguments for the runtime's command that creates the container.
+    pub run_args: Vec<String>,
+}
+
+/// Creates and runs containers, such as Docker or Podman.
+#[async_trait]
+pub trait ContainerRuntime: Send + Sync {
+    /// Finds a container, running or not, that has all of the given labels.
+    async fn find_container(
+        &self,
+        labels: &BTreeMap<String, String>,
+    ) -> Result<Option<ContainerInfo>>;
+
+    async fn build_image(&self, build: &ImageBuild) -> Result<()>;
+
+    /// Creates a container, pulling its image if needed, and returns its ID.
+    async fn create_container(&self, spec: &ContainerSpec) -> Result<String>;
+
+    async fn start_container(&self, container_id: &str) -> Result<()>;
+
+    /// Stops and deletes a container.
+    async fn remove_container(&self, container_id: &str) -> Result<()>;
+
+    /// Runs a program in a running container and returns its output.
+    async fn exec(
+        &self,
+        container_id: &str,
+        user: Option<&str>,
+        working_dir: Option<&str>,
+        command: &[String],
+    ) -> Result<String>;
+
+    /// The command that runs a program in a running container with its stdin attached. The
+    /// program and its arguments are appended to it.
+    fn exec_command(&self, container_id: &str, user: Option<&str>) -> Vec<String>;
+
+    /// The command that starts a container, which does nothing if it's already running.
+    fn start_command(&self, container_id: &str) -> Vec<String>;
+}
+
+/// Runs containers with the Docker CLI, or a CLI that's compatible with it, such as Podman's.
+pub struct DockerRuntime {
+    command: String,
+}
+
+impl DockerRuntime {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    async fn run(&self, args: &[String]) -> Result<String> {
+        log::debug!("{} {}", self.command, args.join(" "));
+        let output = util::command::new_smol_command(&self.command)
+            .args(args)
+            .output()
+            .await
+            .with_context(|| format!("failed to run {}", self.command))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(anyhow!(
+                "{} {} failed: {}",
+                self.command,
+                args.first().map_or("", |arg| arg.as_str()),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    }
+}
+
+/// Keeps the container running until it's stopped, like the dev container CLI does.
+const KEEP_ALIVE_SCRIPT: &str =
+    "echo Container started; trap \"exit 0\" 15; while sleep 1000 & wait $!; do :; done";
+
+#[async_trait]
+impl ContainerRuntime for DockerRuntime {
+    async fn find_container(
+        &self,
+        labels: &BTreeMap<String, String>,
+    ) -> Result<Option<ContainerInfo>> {
+        let mut args = vec!["ps".to_string(), "--all".to_string()];
+        for (key, value) in labels {
+            args.push("--filter".to_string());
+            args.push(format!("label={key}={value}"));
+        }
+        args.push("--format".to_string());
+        args.push("{{.ID}}\t{{.State}}".to_string());
+
+        let output = self.run(&args).await?;
+        Ok(output.lines().find_map(|line| {
+            let (id, state) = line.split_once('\t')?;
+            Some(ContainerInfo {
+                id: id.to_string(),
+                running: state == "running",
+            })
+        }))
+    }
+
+    async fn build_image(&self, build: &ImageBuild) -> Result<()> {
+        let mut args = vec![
+            "build".to_string(),
+            "--file".to_string(),
+            build.dockerfile.to_string_lossy().into(),
+            "--tag".to_string(),
+            build.tag.clone(),
+        ];
+        if let Some(target) = &build.target {
+            args.push("--target".to_string());
+            args.push(target.clone());
+        }
+        for (key, value) in &build.args {
+            args.push("--build-arg".to_string());
+            args.push(format!("{key}={value}"));
+        }
+        args.push(build.context.to_string_lossy().into());
+        self.run(&args).await?;
+        Ok(())
+    }
+
+    async fn create_container(&self, spec: &ContainerSpec) -> Result<String> {
+        let mut args = vec!["create".to_string()];
+        for (key, value) in &spec.labels {
+            args.push("--label".to_string());
+            args.push(format!("{key}={value}"));
+        }
+        for mount in &spec.mounts {
+            args.push("--mount".to_string());
+            args.push(mount.clone());
+        }
+        for (key, value) in &spec.env {
+            args.push("--env".to_string());
+            args.push(format!("{key}={value}"));
+        }
+        for port in &spec.published_ports {
+            args.push("--publish".to_string());
+            args.push(format!("127.0.0.1:{port}:{port}"));
+        }
+        if let Some(user) = &spec.user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+        }
+        args.extend(spec.run_args.iter().cloned());
+        args.extend([
+            "--entrypoint".to_string(),
+            "/bin/sh".to_string(),
+            spec.image.clone(),
+            "-c".to_string(),
+            KEEP_ALIVE_SCRIPT.to_string(),
+        ]);
+
+        let output = self.run(&args).await?;
+        let id = output.trim();
+        if id.is_empty() {
+            return Err(anyhow!(
+                "{} create did not print a container ID",
+                self.command
+            ));
+        }
+        Ok(id.to_string())
+    }
+
+    async fn start_container(&self, container_id: &str) -> Result<()> {
+        self.run(&["start".to_string(), container_id.to_string()])
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_container(&self, container_id: &str) -> Result<()> {
+        self.run(&[
+            "rm".to_string(),
+            "--force".to_string(),
+            container_id.to_string(),
+        ])
+        .await?;
+        Ok(())
+    }
+
+    async fn exec(
+        &self,
+        container_id: &str,
+        user: Option<&str>,
+        working_dir: Option<&str>,
+        command: &[String],
+    ) -> Result<String> {
+        let mut args = vec!["exec".to_string()];
+        if let Some(user) = user {
+            args.push("--user".to_string());
+            args.push(user.to_string());
+        }
+        if let Some(working_dir) = working_dir {
+            args.push("--workdir".to_string());
+            args.push(working_dir.to_string());
+        }
+        args.push(container_id.to_string());
+        args.extend(command.iter().cloned());
+        self.run(&args).await
+    }
+
+    fn exec_command(&self, container_id: &str, user: Option<&str>) -> Vec<String> {
+        let mut command = vec![
+            self.command.clone(),
+            "exec".to_string(),
+            "--interactive".to_string(),
+        ];
+        if let Some(user) = user {
+            command.push("--user".to_string());
+            command.push(user.to_string());
+        }
+        command.push(container_id.to_string());
+        command
+    }
+
+    fn start_command(&self, container_id: &str) -> Vec<String> {
+        vec![
+            self.command.clone(),
+            "start".to_string(),
+            container_id.to_string(),
+        ]
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+pub use fake::*;
+
+#[cfg(any(test, feature = "test-support"))]
+mod fake {
+    use super::*;
+    use parking_lot::Mutex;
+
+    /// A container of a [`FakeContainerRuntime`].
+    #[derive(Clone, Debug)]
+    pub struct FakeContainer {
+        pub id: String,
+        pub spec: ContainerSpec,
+        pub running: bool,
+        /// The commands that were run in the container, with their working directories.
+        pub executed: Vec<(Option<String>, Vec<String>)>,
+    }
+
+    /// A [`ContainerRuntime`] that keeps its images and containers in memory.
+    #[derive(Default)]
+    pub struct FakeContainerRuntime {
+        state: Mutex<FakeContainerRuntimeState>,
+    }
+
+    #[derive(Default)]
+    struct FakeContainerRuntimeState {
+        built_images: Vec<ImageBuild>,
+        containers: Vec<FakeContainer>,
+        next_container_id: usize,
+        exec_error: Option<String>,
+    }
+
+    impl FakeContainerRuntime {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn built_images(&self) -> Vec<ImageBuild> {
+            self.state.lock().built_images.clone()
+        }
+
+        pub fn containers(&self) -> Vec<FakeContainer> {
+            self.state.lock().containers.clone()
+        }
+
+        pub fn stop_container(&self, container_id: &str) {
+            let mut state = self.state.lock();
+            if let Some(container) = state
+                .containers
+                .iter_mut()
+                .find(|container| container.id == container_id)
+            {
+                container.running = false;
+            }
+        }
+
+        /// Makes the commands run in containers fail with the given error, if any.
+        pub fn set_exec_error(&self, error: Option<&str>) {
+            self.state.lock().exec_error = error.map(str::to_string);
+        }
+    }
+
+    #[async_trait]
+    impl ContainerRuntime for FakeContainerRuntime {
+        async fn find_container(
+            &self,
+            labels: &BTreeMap<String, String>,
+        ) -> Result<Option<ContainerInfo>> {
+            let state = self.state.lock();
+            Ok(state
+                .containers
+                .iter()
+                .find(|container| {
+                    labels
+                        .iter()
+                        .all(|(key, value)| container.spec.labels.get(key) == Some(value))
+                })
+                .map(|container| ContainerInfo {
+                    id: container.id.clone(),
+                    running: container.running,
+                }))
+        }
+
+        async fn build_image(&self, build: &ImageBuild) -> Result<()> {
+            self.state.lock().built_images.push(build.clone());
+            Ok(())
+        }
+
+        async fn create_container(&self, spec: &ContainerSpec) -> Result<String> {
+            let mut state = self.state.lock();
+            state.next_container_id += 1;
+            let id = format!("container-{}", state.next_container_id);
+            state.containers.push(FakeContainer {
+                id: id.clone(),
+                spec: spec.clone(),
+                running: false,
+                executed: Vec::new(),
+            });
+            Ok(id)
+        }
+
+        async fn start_container(&self, container_id: &str) -> Result<()> {
+            let mut state = self.state.lock();
+            let container = state
+                .containers
+                .iter_mut()
+                .find(|container| container.id == container_id)
+                .with_context(|| format!("no such container: {container_id}"))?;
+            container.running = true;
+            Ok(())
+        }
+
+        async fn remove_container(&self, container_id: &str) -> Result<()> {
+            let mut state = self.state.lock();
+            let ix = state
+                .containers
+                .iter()
+                .position(|container| container.id == container_id)
+                .with_context(|| format!("no such container: {container_id}"))?;
+            state.containers.remove(ix);
+            Ok(())
+        }
+
+        async fn exec(
+            &self,
+            container_id: &str,
+            _user: Option<&str>,
+            working_dir: Option<&str>,
+            command: &[String],
+        ) -> Result<String> {
+            let mut state = self.state.lock();
+            let container = state
+                .containers
+                .iter_mut()
+                .find(|container| container.id == container_id && container.running)
+                .with_context(|| format!("container is not running: {container_id}"))?;
+            container
+                .executed
+                .push((working_dir.map(str::to_string), command.to_vec()));
+            match &state.exec_error {
+                Some(error) => Err(anyhow!("{error}")),
+                None => Ok(String::new()),
+            }
+        }
+
+        fn exec_command(&self, container_id: &str, user: Option<&str>) -> Vec<String> {
+            let mut command = vec!["fake-container-runtime".to_string(), "exec".to_string()];
+            if let Some(user) = user {
+                command.push(format!("--user={user}"));
+            }
+            command.push(container_id.to_string());
+            command
+        }
+
+        fn start_command(&self, container_id: &str) -> Vec<String> {
+            vec![
+                "fake-container-runtime".to_string(),
+                "start".to_string(),
+                container_id.to_string(),
+            ]
+        }
+    }
+}
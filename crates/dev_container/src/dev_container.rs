@@ -0,0 +1,605 @@
+mod container_runtime;
+mod dev_container_json;
+mod dev_container_settings;
+
+use anyhow::{Context as _, Result};
+use extension_host::ExtensionStore;
+use fs::Fs;
+use gpui::{App, Context, Window, actions};
+use recent_projects::open_ssh_project;
+use remote::SshConnectionOptions;
+use settings::Settings;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use util::ResultExt as _;
+use workspace::{
+    OpenOptions, Toast, Workspace,
+    notifications::{DetachAndPromptErr, NotificationId},
+};
+
+pub use container_runtime::*;
+pub use dev_container_json::*;
+pub use dev_container_settings::DevContainerSettings;
+
+actions!(dev_container, [ReopenInContainer]);
+
+/// Where a dev container's configuration can be, relative to the folder it's for.
+const CONFIG_PATHS: [&str; 2] = [".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+pub fn init(cx: &mut App) {
+    DevContainerSettings::register(cx);
+    cx.observe_new(register).detach();
+}
+
+fn register(workspace: &mut Workspace, _: Option<&mut Window>, _: &mut Context<Workspace>) {
+    workspace.register_action(reopen_in_container);
+}
+
+/// The configuration of a dev container for a local folder.
+#[derive(Clone, Debug)]
+pub struct DevContainer {
+    pub config: DevContainerConfig,
+    pub config_path: PathBuf,
+    pub local_folder: PathBuf,
+}
+
+/// A dev container that is running.
+#[derive(Clone, Debug)]
+pub struct RunningDevContainer {
+    pub container_id: String,
+    /// Where the local folder is in the container.
+    pub workspace_folder: String,
+    /// Connects to the container by running a shell in it.
+    pub connection_options: SshConnectionOptions,
+}
+
+impl DevContainer {
+    /// Loads the dev container configuration of a local folder, if it has one.
+    pub async fn load(fs: &dyn Fs, local_folder: &Path) -> Result<Option<Self>> {
+        for config_path in CONFIG_PATHS {
+            let config_path = local_folder.join(config_path);
+            if !fs.is_file(&config_path).await {
+                continue;
+            }
+            let content = fs.load(&config_path).await?;
+            let config = DevContainerConfig::parse(&content)
+                .with_context(|| format!("failed to parse {}", config_path.display()))?;
+            return Ok(Some(Self {
+                config,
+                config_path,
+                local_folder: local_folder.to_path_buf(),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Starts the container, creating it on first use, and returns how to connect to it.
+    pub async fn start(&self, runtime: &dyn ContainerRuntime) -> Result<RunningDevContainer> {
+        let workspace_folder = self.workspace_folder();
+        let variables = Variables {
+            local_workspace_folder: &self.local_folder,
+            container_workspace_folder: &workspace_folder,
+        };
+        let labels = self.labels();
+
+        let container_id = match runtime.find_container(&labels).await? {
+            Some(container) => {
+                if !container.running {
+                    runtime.start_container(&container.id).await?;
+                }
+                container.id
+            }
+            None => {
+                let image = match self.image_build() {
+                    Some(build) => {
+                        runtime
+                            .build_image(&build)
+                            .await
+                            .context("failed to build the dev container's image")?;
+                        build.tag
+                    }
+                    None => self
+                        .config
+                        .image
+                        .clone()
+                        .context("the dev container has no image or Dockerfile")?,
+                };
+                let spec = self.container_spec(image, labels, &variables);
+                let container_id = runtime.create_container(&spec).await?;
+                let created = async {
+                    runtime.start_container(&container_id).await?;
+                    self.run_post_create_command(runtime, &container_id, &variables)
+                        .await
+                }
+                .await;
+                // A container is only reused once it's set up, so a failed setup is retried
+                // with a new container the next time.
+                if let Err(error) = created {
+                    runtime.remove_container(&container_id).await.log_err();
+                    return Err(error);
+                }
+                container_id
+            }
+        };
+
+        // The command is stored with the project, so it starts the container first, in case it
+        // was stopped by the time the project is reopened.
+        let start_command = shlex::try_join(
+            runtime
+                .start_command(&container_id)
+                .iter()
+                .map(String::as_str),
+        )
+        .context("quoting the command that starts the container")?;
+        let mut shell_command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("{start_command} >/dev/null && exec \"$@\""),
+            "sh".to_string(),
+        ];
+        shell_command.extend(runtime.exec_command(&container_id, self.remote_user()));
+        let remote_env = self.remote_env(&variables);
+        if remote_env.is_empty() {
+            shell_command.push("sh".to_string());
+        } else {
+            // Scripts are run by appending `-c <script>`, which become the arguments of the
+            // shell that's started after the environment is set up.
+            shell_command.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                format!("{}exec sh \"$@\"", remote_env_script(&remote_env)),
+                "sh".to_string(),
+            ]);
+        }
+
+        Ok(RunningDevContainer {
+            connection_options: SshConnectionOptions {
+                host: container_id.chars().take(12).collect(),
+                nickname: self.config.name.clone(),
+                shell_command: Some(shell_command),
+                ..Default::default()
+            },
+            container_id,
+            workspace_folder,
+        })
+    }
+
+    fn workspace_folder(&self) -> String {
+        match &self.config.workspace_folder {
+            Some(workspace_folder) => Variables {
+                local_workspace_folder: &self.local_folder,
+                container_workspace_folder: "",
+            }
+            .substitute(workspace_folder),
+            None => format!("/workspaces/{}", self.folder_name()),
+        }
+    }
+
+    fn folder_name(&self) -> String {
+        self.local_folder
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn remote_user(&self) -> Option<&str> {
+        self.config
+            .remote_user
+            .as_deref()
+            .or(self.config.container_user.as_deref())
+    }
+
+    /// Identifies the container of this configuration, using the same labels as the dev
+    /// container CLI.
+    fn labels(&self) -> BTreeMap<String, String> {
+        BTreeMap::from_iter([
+            (
+                "devcontainer.local_folder".to_string(),
+                self.local_folder.to_string_lossy().into_owned(),
+            ),
+            (
+                "devcontainer.config_file".to_string(),
+                self.config_path.to_string_lossy().into_owned(),
+            ),
+        ])
+    }
+
+    fn image_build(&self) -> Option<ImageBuild> {
+        let config_dir = self.config_path.parent()?;
+        let build = self.config.build.clone().unwrap_or_default();
+        let dockerfile = build
+            .dockerfile
+            .or_else(|| self.config.docker_file.clone())?;
+        let context = build
+            .context
+            .or_else(|| self.config.context.clone())
+            .unwrap_or_else(|| ".".to_string());
+
+        // Images are tagged per folder, so that folders with the same name don't share one.
+        let path_hash = format!(
+            "{:x}",
+            Sha256::digest(self.local_folder.to_string_lossy().as_bytes())
+        );
+        let tag = format!(
+            "zed-dev-container-{}-{}",
+            self.folder_name()
+                .to_lowercase()
+                .replace(|c: char| !c.is_ascii_alphanumeric(), "-"),
+            &path_hash[..12]
+        );
+        Some(ImageBuild {
+            tag,
+            dockerfile: config_dir.join(dockerfile),
+            context: config_dir.join(context),
+            args: build.args,
+            target: build.target,
+        })
+    }
+
+    fn container_spec(
+        &self,
+        image: String,
+        labels: BTreeMap<String, String>,
+        variables: &Variables,
+    ) -> ContainerSpec {
+        let workspace_mount = match &self.config.workspace_mount {
+            Some(workspace_mount) => variables.substitute(workspace_mount),
+            None => format!(
+                "type=bind,source={},target={}",
+                self.local_folder.display(),
+                variables.container_workspace_folder
+            ),
+        };
+        let mounts = std::iter::once(workspace_mount)
+            .chain(
+                self.config
+                    .mounts
+                    .iter()
+                    .map(|mount| variables.substitute(&mount.to_mount_arg())),
+            )
+            .collect();
+
+        ContainerSpec {
+            image,
+            labels,
+            mounts,
+            env: self
+                .config
+                .container_env
+                .iter()
+                .map(|(key, value)| (key.clone(), variables.substitute(value)))
+                .collect(),
+            published_ports: self
+                .config
+                .forward_ports
+                .iter()
+                .filter_map(|port| port.container_port())
+                .collect(),
+            user: self.config.container_user.clone(),
+            run_args: self
+                .config
+                .run_args
+                .iter()
+                .map(|arg| variables.substitute(arg))
+                .collect(),
+        }
+    }
+
+    fn remote_env(&self, variables: &Variables) -> BTreeMap<String, Option<String>> {
+        self.config
+            .remote_env
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.clone(),
+                    value.as_ref().map(|value| variables.substitute(value)),
+                )
+            })
+            .collect()
+    }
+
+    async fn run_post_create_command(
+        &self,
+        runtime: &dyn ContainerRuntime,
+        container_id: &str,
+        variables: &Variables<'_>,
+    ) -> Result<()> {
+        let Some(post_create_command) = &self.config.post_create_command else {
+            return Ok(());
+        };
+        let env_script = remote_env_script(&self.remote_env(variables));
+        for script in post_create_command
+            .scripts()
+            .context("reading postCreateCommand")?
+        {
+            runtime
+                .exec(
+                    container_id,
+                    self.remote_user(),
+                    Some(variables.container_workspace_folder),
+                    &[
+                        "sh".to_string(),
+                        "-c".to_string(),
+                        format!("{env_script}{script}"),
+                    ],
+                )
+                .await
+                .with_context(|| format!("postCreateCommand failed: {script}"))?;
+        }
+        Ok(())
+    }
+}
+
+fn reopen_in_container(
+    workspace: &mut Workspace,
+    _: &ReopenInContainer,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let local_folder = project
+        .visible_worktrees(cx)
+        .next()
+        .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+    let Some(local_folder) = local_folder.filter(|_| project.is_local()) else {
+        return;
+    };
+    let Some(window_handle) = window.window_handle().downcast::<Workspace>() else {
+        return;
+    };
+
+    let app_state = workspace.app_state().clone();
+    let runtime = DockerRuntime::new(
+        DevContainerSettings::get_global(cx)
+            .container_runtime
+            .clone(),
+    );
+    let toast_id = NotificationId::unique::<ReopenInContainer>();
+    workspace.show_toast(Toast::new(toast_id.clone(), "Starting dev container…"), cx);
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let started = async {
+            let dev_container = DevContainer::load(app_state.fs.as_ref(), &local_folder)
+                .await?
+                .with_context(|| {
+                    format!(
+                        "{} has no .devcontainer/devcontainer.json",
+                        local_folder.display()
+                    )
+                })?;
+            let running = dev_container.start(&runtime).await?;
+            anyhow::Ok((dev_container, running))
+        }
+        .await;
+        workspace
+            .update(cx, |workspace, cx| workspace.dismiss_toast(&toast_id, cx))
+            .ok();
+        let (dev_container, running) = started?;
+
+        // Extensions are installed locally, and synced to the container once it's connected.
+        cx.update(|_, cx| {
+            ExtensionStore::global(cx).update(cx, |store, cx| {
+                for extension_id in dev_container.config.zed_extensions() {
+                    if !store.installed_extensions().contains_key(&extension_id) {
+                        store.install_latest_extension(extension_id, cx);
+                    }
+                }
+            })
+        })?;
+
+        open_ssh_project(
+            running.connection_options,
+            vec![PathBuf::from(running.workspace_folder)],
+            app_state,
+            OpenOptions {
+                replace_window: Some(window_handle),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await
+    })
+    .detach_and_prompt_err("Failed to open dev container", window, cx, |_, _, _| None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_start_dev_container(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/home/me/project",
+            json!({
+                ".devcontainer": {
+                    "devcontainer.json": r#"{
+                        "name": "Project",
+                        "build": { "dockerfile": "Dockerfile", "context": ".." },
+                        "mounts": ["type=volume,source=${localWorkspaceFolderBasename}-cache,target=/cache"],
+                        "postCreateCommand": "make setup",
+                        "forwardPorts": [3000],
+                        "remoteEnv": { "PATH": "${containerEnv:PATH}:${containerWorkspaceFolder}/bin" },
+                        "remoteUser": "dev",
+                    }"#,
+                    "Dockerfile": "FROM debian",
+                },
+                "src": {},
+            }),
+        )
+        .await;
+
+        let dev_container = DevContainer::load(fs.as_ref(), Path::new("/home/me/project"))
+            .await
+            .unwrap()
+            .unwrap();
+        let runtime = FakeContainerRuntime::new();
+        let running = dev_container.start(&runtime).await.unwrap();
+
+        assert_eq!(running.workspace_folder, "/workspaces/project");
+        assert_eq!(
+            running.connection_options.nickname.as_deref(),
+            Some("Project")
+        );
+        assert_eq!(
+            running.connection_options.shell_command.unwrap(),
+            [
+                "sh",
+                "-c",
+                "fake-container-runtime start container-1 >/dev/null && exec \"$@\"",
+                "sh",
+                "fake-container-runtime",
+                "exec",
+                "--user=dev",
+                "container-1",
+                "sh",
+                "-c",
+                "export PATH=\"${PATH}:/workspaces/project/bin\"; exec sh \"$@\"",
+                "sh",
+            ]
+        );
+
+        let built_images = runtime.built_images();
+        assert_eq!(built_images.len(), 1);
+        assert_eq!(
+            built_images[0].dockerfile,
+            Path::new("/home/me/project/.devcontainer/Dockerfile")
+        );
+        assert_eq!(
+            built_images[0].context,
+            Path::new("/home/me/project/.devcontainer/..")
+        );
+
+        let containers = runtime.containers();
+        assert_eq!(containers.len(), 1);
+        let container = &containers[0];
+        assert!(container.running);
+        assert_eq!(container.spec.image, built_images[0].tag);
+        assert_eq!(
+            container.spec.mounts,
+            [
+                "type=bind,source=/home/me/project,target=/workspaces/project",
+                "type=volume,source=project-cache,target=/cache",
+            ]
+        );
+        assert_eq!(container.spec.published_ports, [3000]);
+        assert_eq!(
+            container.executed,
+            [(
+                Some("/workspaces/project".to_string()),
+                vec![
+                    "sh".to_string(),
+                    "-c".to_string(),
+                    "export PATH=\"${PATH}:/workspaces/project/bin\"; make setup".to_string(),
+                ]
+            )]
+        );
+
+        // The container is reused, and restarted if it was stopped, without running
+        // `postCreateCommand` again.
+        runtime.stop_container(&running.container_id);
+        let restarted = dev_container.start(&runtime).await.unwrap();
+        assert_eq!(restarted.container_id, running.container_id);
+        let containers = runtime.containers();
+        assert_eq!(containers.len(), 1);
+        assert!(containers[0].running);
+        assert_eq!(containers[0].executed.len(), 1);
+        assert_eq!(runtime.built_images().len(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_dev_container_from_image(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".devcontainer.json": r#"{
+                    "image": "mcr.microsoft.com/devcontainers/rust:1",
+                    "workspaceFolder": "/src",
+                    "workspaceMount": "type=bind,source=${localWorkspaceFolder},target=/src",
+                }"#,
+            }),
+        )
+        .await;
+
+        let dev_container = DevContainer::load(fs.as_ref(), Path::new("/project"))
+            .await
+            .unwrap()
+            .unwrap();
+        let runtime = FakeContainerRuntime::new();
+        let running = dev_container.start(&runtime).await.unwrap();
+
+        assert_eq!(running.workspace_folder, "/src");
+        assert_eq!(
+            running.connection_options.shell_command.unwrap(),
+            [
+                "sh",
+                "-c",
+                "fake-container-runtime start container-1 >/dev/null && exec \"$@\"",
+                "sh",
+                "fake-container-runtime",
+                "exec",
+                "container-1",
+                "sh",
+            ]
+        );
+        assert!(runtime.built_images().is_empty());
+        let containers = runtime.containers();
+        assert_eq!(
+            containers[0].spec.image,
+            "mcr.microsoft.com/devcontainers/rust:1"
+        );
+        assert_eq!(
+            containers[0].spec.mounts,
+            ["type=bind,source=/project,target=/src"]
+        );
+        assert!(containers[0].executed.is_empty());
+
+        assert!(
+            DevContainer::load(fs.as_ref(), Path::new("/other"))
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[gpui::test]
+    async fn test_failed_post_create_command(cx: &mut TestAppContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/project",
+            json!({
+                ".devcontainer.json": r#"{
+                    "image": "debian",
+                    "postCreateCommand": "make setup",
+                }"#,
+            }),
+        )
+        .await;
+
+        let dev_container = DevContainer::load(fs.as_ref(), Path::new("/project"))
+            .await
+            .unwrap()
+            .unwrap();
+        let runtime = FakeContainerRuntime::new();
+        runtime.set_exec_error(Some("make: *** No rule to make target 'setup'"));
+        let error = dev_container.start(&runtime).await.unwrap_err();
+        assert_eq!(error.to_string(), "postCreateCommand failed: make setup");
+        assert!(runtime.containers().is_empty());
+
+        // The next start sets up a new container rather than reusing the one that failed.
+        runtime.set_exec_error(None);
+        let running = dev_container.start(&runtime).await.unwrap();
+        assert_eq!(running.container_id, "container-2");
+        let containers = runtime.containers();
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].executed.len(), 1);
+    }
+}
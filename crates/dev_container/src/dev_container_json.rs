@@ -0,0 +1,468 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+/// The parts of a `devcontainer.json` that Zed supports.
+///
+/// See <https://containers.dev/implementors/json_reference/>.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerConfig {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The image to create the container from, unless the configuration has a [`BuildConfig`].
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub build: Option<BuildConfig>,
+    /// The deprecated top-level form of `build.dockerfile`.
+    #[serde(default, rename = "dockerFile")]
+    pub docker_file: Option<String>,
+    /// The deprecated top-level form of `build.context`.
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub mounts: Vec<Mount>,
+    /// Overrides how the local folder is mounted into the container.
+    #[serde(default)]
+    pub workspace_mount: Option<String>,
+    /// Where the local folder is opened in the container, `/workspaces/<folder name>` by default.
+    #[serde(default)]
+    pub workspace_folder: Option<String>,
+    /// Runs once in the container, after it's created.
+    #[serde(default)]
+    pub post_create_command: Option<LifecycleCommand>,
+    #[serde(default)]
+    pub forward_ports: Vec<ForwardPort>,
+    /// Environment variables of the container itself.
+    #[serde(default)]
+    pub container_env: BTreeMap<String, String>,
+    /// Environment variables of the processes Zed runs in the container. A `null` value unsets
+    /// the variable.
+    #[serde(default)]
+    pub remote_env: BTreeMap<String, Option<String>>,
+    #[serde(default)]
+    pub remote_user: Option<String>,
+    #[serde(default)]
+    pub container_user: Option<String>,
+    /// Extra arguments to pass when creating the container.
+    #[serde(default)]
+    pub run_args: Vec<String>,
+    #[serde(default)]
+    pub customizations: Customizations,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildConfig {
+    /// The Dockerfile to build, relative to the `devcontainer.json`.
+    #[serde(default)]
+    pub dockerfile: Option<String>,
+    /// The build context, relative to the `devcontainer.json`.
+    #[serde(default)]
+    pub context: Option<String>,
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Mount {
+    /// A mount in the `--mount` syntax, such as `type=volume,source=cache,target=/cache`.
+    Raw(String),
+    Object {
+        #[serde(default)]
+        source: Option<String>,
+        target: String,
+        #[serde(rename = "type")]
+        kind: String,
+    },
+}
+
+impl Mount {
+    /// The mount in the `--mount` syntax.
+    pub fn to_mount_arg(&self) -> String {
+        match self {
+            Mount::Raw(mount) => mount.clone(),
+            Mount::Object {
+                source: Some(source),
+                target,
+                kind,
+            } => format!("type={kind},source={source},target={target}"),
+            Mount::Object {
+                source: None,
+                target,
+                kind,
+            } => format!("type={kind},target={target}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum LifecycleCommand {
+    /// A script run by a shell.
+    Script(String),
+    /// A program and its arguments, run without a shell.
+    Args(Vec<String>),
+    /// Named commands that are all run.
+    Named(BTreeMap<String, LifecycleCommand>),
+}
+
+impl LifecycleCommand {
+    /// The shell scripts to run for this command, in order. Fails if an argument can't be
+    /// quoted for the shell, such as one containing a NUL byte.
+    pub fn scripts(&self) -> Result<Vec<String>> {
+        match self {
+            LifecycleCommand::Script(script) => Ok(vec![script.clone()]),
+            LifecycleCommand::Args(args) => {
+                if args.is_empty() {
+                    Ok(Vec::new())
+                } else {
+                    Ok(vec![shlex::try_join(args.iter().map(String::as_str))?])
+                }
+            }
+            LifecycleCommand::Named(commands) => {
+                let mut scripts = Vec::new();
+                for command in commands.values() {
+                    scripts.extend(command.scripts()?);
+                }
+                Ok(scripts)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum ForwardPort {
+    Port(u16),
+    /// A port on another host, such as `db:5432`.
+    HostPort(String),
+}
+
+impl ForwardPort {
+    /// The port to publish on the local machine, if the port is on the container itself.
+    pub fn container_port(&self) -> Option<u16> {
+        match self {
+            ForwardPort::Port(port) => Some(*port),
+            ForwardPort::HostPort(host_port) => {
+                let (host, port) = host_port.rsplit_once(':')?;
+                if host == "localhost" || host == "127.0.0.1" {
+                    port.parse().ok()
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct Customizations {
+    #[serde(default)]
+    pub zed: ExtensionCustomizations,
+    #[serde(default)]
+    pub vscode: ExtensionCustomizations,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct ExtensionCustomizations {
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// VS Code extensions whose features are provided by a Zed extension.
+const VSCODE_EXTENSIONS: &[(&str, &str)] = &[
+    ("astro-build.astro-vscode", "astro"),
+    ("bmewburn.vscode-intelephense-client", "php"),
+    ("dart-code.dart-code", "dart"),
+    ("elixir-lsp.elixir-ls", "elixir"),
+    ("erlang-ls.erlang-ls", "erlang"),
+    ("fwcd.kotlin", "kotlin"),
+    ("gleam.gleam", "gleam"),
+    ("graphql.vscode-graphql", "graphql"),
+    ("haskell.haskell", "haskell"),
+    ("hashicorp.terraform", "terraform"),
+    ("jakebecker.elixir-ls", "elixir"),
+    ("julialang.language-julia", "julia"),
+    ("mathiasfrohlich.kotlin", "kotlin"),
+    ("ms-azuretools.vscode-docker", "dockerfile"),
+    ("ms-dotnettools.csharp", "csharp"),
+    ("ms-vscode.makefile-tools", "make"),
+    ("ms-vscode.powershell", "powershell"),
+    ("ocamllabs.ocaml-platform", "ocaml"),
+    ("prisma.prisma", "prisma"),
+    ("redhat.java", "java"),
+    ("scalameta.metals", "scala"),
+    ("shopify.ruby-lsp", "ruby"),
+    ("svelte.svelte-vscode", "svelte"),
+    ("sswg.swift-lang", "swift"),
+    ("tamasfe.even-better-toml", "toml"),
+    ("vue.volar", "vue"),
+    ("xdebug.php-debug", "php"),
+    ("ziglang.vscode-zig", "zig"),
+    ("zxh404.vscode-proto3", "proto"),
+];
+
+impl DevContainerConfig {
+    pub fn parse(content: &str) -> Result<Self> {
+        settings::parse_json_with_comments(content)
+    }
+
+    /// The Zed extensions the container wants, from its Zed customizations and the VS Code
+    /// extensions that have a Zed equivalent.
+    pub fn zed_extensions(&self) -> Vec<Arc<str>> {
+        let mut extensions = Vec::<Arc<str>>::new();
+        let vscode_extensions = self
+            .customizations
+            .vscode
+            .extensions
+            .iter()
+            .filter_map(|id| {
+                let id = id.to_lowercase();
+                VSCODE_EXTENSIONS
+                    .iter()
+                    .find(|(vscode_id, _)| *vscode_id == id)
+                    .map(|(_, zed_id)| zed_id.to_string())
+            });
+        for id in self
+            .customizations
+            .zed
+            .extensions
+            .iter()
+            .cloned()
+            .chain(vscode_extensions)
+        {
+            if !extensions.iter().any(|extension| extension.as_ref() == id) {
+                extensions.push(id.into());
+            }
+        }
+        extensions
+    }
+}
+
+/// Replaces the `${...}` variables in `devcontainer.json` values that are known on the local
+/// machine. `${containerEnv:...}` variables are left for the container's shell.
+pub(crate) struct Variables<'a> {
+    pub local_workspace_folder: &'a Path,
+    pub container_workspace_folder: &'a str,
+}
+
+impl Variables<'_> {
+    pub fn substitute(&self, value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            result.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let variable = &rest[start + 2..start + len];
+            match self.resolve(variable) {
+                Some(resolved) => result.push_str(&resolved),
+                None => result.push_str(&rest[start..=start + len]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
+
+    fn resolve(&self, variable: &str) -> Option<String> {
+        match variable {
+            "localWorkspaceFolder" => Some(self.local_workspace_folder.to_string_lossy().into()),
+            "localWorkspaceFolderBasename" => Some(
+                self.local_workspace_folder
+                    .file_name()?
+                    .to_string_lossy()
+                    .into(),
+            ),
+            "containerWorkspaceFolder" => Some(self.container_workspace_folder.to_string()),
+            "containerWorkspaceFolderBasename" => Some(
+                Path::new(self.container_workspace_folder)
+                    .file_name()?
+                    .to_string_lossy()
+                    .into(),
+            ),
+            _ => {
+                let local_env = variable.strip_prefix("localEnv:")?;
+                let (name, default) = match local_env.split_once(':') {
+                    Some((name, default)) => (name, default),
+                    None => (local_env, ""),
+                };
+                Some(std::env::var(name).unwrap_or_else(|_| default.to_string()))
+            }
+        }
+    }
+}
+
+/// A script that applies the `remoteEnv` of a container to the shell that runs it, in which
+/// `${containerEnv:NAME}` refers to the container's variable.
+pub(crate) fn remote_env_script(remote_env: &BTreeMap<String, Option<String>>) -> String {
+    let mut script = String::new();
+    for (name, value) in remote_env {
+        let is_valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid_name {
+            log::warn!("ignoring invalid remoteEnv variable name {name:?}");
+            continue;
+        }
+        match value {
+            Some(value) => {
+                script.push_str(&format!("export {name}=\"{}\"; ", shell_value(value)));
+            }
+            None => script.push_str(&format!("unset {name}; ")),
+        }
+    }
+    script
+}
+
+/// Escapes a value for a double-quoted shell string, turning `${containerEnv:NAME}` and
+/// `${containerEnv:NAME:default}` into parameter expansions.
+fn shell_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(c) = rest.chars().next() {
+        if let Some(variable) = rest.strip_prefix("${containerEnv:") {
+            if let Some(end) = variable.find('}') {
+                match variable[..end].split_once(':') {
+                    Some((name, default)) => {
+                        result.push_str(&format!("${{{name}:-{}}}", shell_value(default)))
+                    }
+                    None => result.push_str(&format!("${{{}}}", &variable[..end])),
+                }
+                rest = &variable[end + 1..];
+                continue;
+            }
+        }
+        if matches!(c, '\\' | '"' | '$' | '`') {
+            result.push('\\');
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_dev_container_json() {
+        let config = DevContainerConfig::parse(
+            r#"{
+                // Comments and trailing commas are allowed.
+                "name": "Rust",
+                "build": { "dockerfile": "Dockerfile", "args": { "VARIANT": "bookworm" } },
+                "mounts": [
+                    "type=volume,source=cargo,target=/usr/local/cargo",
+                    { "source": "${localEnv:HOME}/.ssh", "target": "/root/.ssh", "type": "bind" },
+                ],
+                "postCreateCommand": { "deps": ["cargo", "fetch"], "hooks": "git config core.hooksPath .hooks" },
+                "forwardPorts": [3000, "localhost:8080", "db:5432"],
+                "remoteEnv": { "PATH": "${containerEnv:PATH}:/extra", "PAGER": null },
+                "customizations": {
+                    "zed": { "extensions": ["toml"] },
+                    "vscode": { "extensions": ["tamasfe.even-better-toml", "Vue.volar", "unknown.extension"] },
+                },
+                "unsupportedProperty": true,
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.name.as_deref(), Some("Rust"));
+        assert_eq!(
+            config.build.as_ref().unwrap().args.get("VARIANT").unwrap(),
+            "bookworm"
+        );
+        assert_eq!(
+            config
+                .mounts
+                .iter()
+                .map(|mount| mount.to_mount_arg())
+                .collect::<Vec<_>>(),
+            [
+                "type=volume,source=cargo,target=/usr/local/cargo",
+                "type=bind,source=${localEnv:HOME}/.ssh,target=/root/.ssh",
+            ]
+        );
+        assert_eq!(
+            config
+                .post_create_command
+                .as_ref()
+                .unwrap()
+                .scripts()
+                .unwrap(),
+            ["cargo fetch", "git config core.hooksPath .hooks"]
+        );
+        assert!(
+            LifecycleCommand::Args(vec!["echo".into(), "nul\0".into()])
+                .scripts()
+                .is_err()
+        );
+        assert_eq!(
+            config
+                .forward_ports
+                .iter()
+                .filter_map(|port| port.container_port())
+                .collect::<Vec<_>>(),
+            [3000, 8080]
+        );
+        assert_eq!(
+            config
+                .zed_extensions()
+                .iter()
+                .map(|id| id.as_ref())
+                .collect::<Vec<_>>(),
+            ["toml", "vue"]
+        );
+    }
+
+    #[test]
+    fn test_substitute_variables() {
+        let variables = Variables {
+            local_workspace_folder: Path::new("/home/me/project"),
+            container_workspace_folder: "/workspaces/project",
+        };
+        assert_eq!(
+            variables.substitute(
+                "source=${localWorkspaceFolder},target=${containerWorkspaceFolder}/${localWorkspaceFolderBasename}"
+            ),
+            "source=/home/me/project,target=/workspaces/project/project"
+        );
+        assert_eq!(
+            variables.substitute("${localEnv:ZED_DEV_CONTAINER_UNSET_VARIABLE:fallback}"),
+            "fallback"
+        );
+        assert_eq!(
+            variables.substitute("${containerEnv:PATH}:${unterminated"),
+            "${containerEnv:PATH}:${unterminated"
+        );
+    }
+
+    #[test]
+    fn test_remote_env_script() {
+        let remote_env = BTreeMap::from_iter([
+            (
+                "PATH".to_string(),
+                Some("${containerEnv:PATH}:/extra".to_string()),
+            ),
+            (
+                "GREETING".to_string(),
+                Some("say \"hi\" for $5 ${containerEnv:NAME:you}".to_string()),
+            ),
+            ("PAGER".to_string(), None),
+            ("NOT VALID".to_string(), Some("value".to_string())),
+        ]);
+        assert_eq!(
+            remote_env_script(&remote_env),
+            "export GREETING=\"say \\\"hi\\\" for \\$5 ${NAME:-you}\"; unset PAGER; export PATH=\"${PATH}:/extra\"; "
+        );
+    }
+}
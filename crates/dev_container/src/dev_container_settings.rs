@@ -0,0 +1,45 @@
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The settings for dev containers.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DevContainerSettings {
+    /// The command of the container runtime to create and run dev containers with. It must
+    /// accept the same arguments as `docker`, like `podman` does.
+    ///
+    /// Default: "docker"
+    #[serde(default = "default_container_runtime")]
+    pub container_runtime: String,
+}
+
+fn default_container_runtime() -> String {
+    "docker".to_string()
+}
+
+impl Default for DevContainerSettings {
+    fn default() -> Self {
+        Self {
+            container_runtime: default_container_runtime(),
+        }
+    }
+}
+
+impl Settings for DevContainerSettings {
+    const KEY: Option<&'static str> = Some("dev_container");
+
+    type FileContent = Self;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut App,
+    ) -> Result<Self, anyhow::Error> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
@@ -193,6 +193,14 @@ pub struct SpawnNearestTask {
     pub reveal: task::RevealStrategy,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ToggleBookmark {
+    /// An optional label shown for the bookmark in the bookmarks picker.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize, Default)]
 pub enum UuidVersion {
     #[default]
@@ -227,6 +235,7 @@ impl_actions!(
         SelectUpByLines,
         SpawnNearestTask,
         ShowCompletions,
+        ToggleBookmark,
         ToggleCodeActions,
         ToggleComments,
         FoldAtLevel,
@@ -246,6 +255,7 @@ actions!(
         Backspace,
         Cancel,
         CancelLanguageServerWork,
+        ClearBookmarks,
         ConfirmRename,
         ConfirmCompletionInsert,
         ConfirmCompletionReplace,
@@ -277,6 +287,7 @@ actions!(
         DeleteToEndOfLine,
         DeleteToNextSubwordEnd,
         DeleteToPreviousSubwordStart,
+        DiffClipboardWithSelection,
         DisplayCursorNames,
         DuplicateLineDown,
         DuplicateLineUp,
@@ -298,6 +309,8 @@ actions!(
         GoToDeclarationSplit,
         GoToDefinition,
         GoToDefinitionSplit,
+        GoToNextBookmark,
+        GoToPreviousBookmark,
         GoToDiagnostic,
         GoToHunk,
         GoToPreviousHunk,
@@ -34,10 +34,13 @@ mod mouse_context_menu;
 pub mod movement;
 mod persistence;
 mod proposed_changes_editor;
+mod rainbow_brackets;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod sticky_scroll;
 pub mod tasks;
+mod text_diff_view;
 
 #[cfg(test)]
 mod editor_tests;
@@ -120,10 +123,12 @@ use mouse_context_menu::MouseContextMenu;
 use persistence::DB;
 use project::{
     ProjectPath,
+    bookmark_store::{BookmarkDirection, BookmarkStore},
     debugger::breakpoint_store::{
         BreakpointEditAction, BreakpointState, BreakpointStore, BreakpointStoreEvent,
     },
 };
+use rainbow_brackets::RainbowBracketsState;
 
 pub use git::blame::BlameRenderer;
 pub use proposed_changes_editor::{
@@ -132,6 +137,7 @@ pub use proposed_changes_editor::{
 use smallvec::smallvec;
 use std::{cell::OnceCell, iter::Peekable};
 use task::{ResolvedTask, RunnableTag, TaskTemplate, TaskVariables};
+pub use text_diff_view::TextDiffView;
 
 pub use lsp::CompletionContext;
 use lsp::{
@@ -821,6 +827,8 @@ pub struct Editor {
     tasks: BTreeMap<(BufferId, BufferRow), RunnableTasks>,
     tasks_update_task: Option<Task<()>>,
     breakpoint_store: Option<Entity<BreakpointStore>>,
+    bookmark_store: Option<Entity<BookmarkStore>>,
+    rainbow_brackets: RainbowBracketsState,
     /// Allow's a user to create a breakpoint by selecting this indicator
     /// It should be None while a user is not hovering over the gutter
     /// Otherwise it represents the point that the breakpoint will be shown
@@ -1442,6 +1450,10 @@ impl Editor {
             (EditorMode::Full { .. }, Some(project)) => Some(project.read(cx).breakpoint_store()),
             _ => None,
         };
+        let bookmark_store = match (mode, project.as_ref()) {
+            (EditorMode::Full { .. }, Some(project)) => Some(project.read(cx).bookmark_store()),
+            _ => None,
+        };
 
         let mut code_action_providers = Vec::new();
         let mut load_uncommitted_diff = None;
@@ -1585,6 +1597,8 @@ impl Editor {
             tasks: Default::default(),
 
             breakpoint_store,
+            bookmark_store,
+            rainbow_brackets: RainbowBracketsState::default(),
             gutter_breakpoint_indicator: (None, None),
             _subscriptions: vec![
                 cx.observe(&buffer, Self::on_buffer_changed),
@@ -1631,6 +1645,11 @@ impl Editor {
                     cx.notify();
                 }));
         }
+        if let Some(bookmarks) = this.bookmark_store.as_ref() {
+            this._subscriptions.push(cx.observe(bookmarks, |_, _, cx| {
+                cx.notify();
+            }));
+        }
         this.tasks_update_task = Some(this.refresh_runnables(window, cx));
         this._subscriptions.extend(project_subscriptions);
 
@@ -9091,6 +9110,208 @@ impl Editor {
         self.breakpoint_store.clone()
     }
 
+    fn active_bookmarks(
+        &self,
+        range: Range<DisplayRow>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> HashMap<DisplayRow, Anchor> {
+        let mut bookmark_display_points = HashMap::default();
+        let Some(bookmark_store) = self.bookmark_store.clone() else {
+            return bookmark_display_points;
+        };
+
+        let snapshot = self.snapshot(window, cx);
+        let multi_buffer_snapshot = &snapshot.display_snapshot.buffer_snapshot;
+        let range = snapshot.display_point_to_point(DisplayPoint::new(range.start, 0), Bias::Left)
+            ..snapshot.display_point_to_point(DisplayPoint::new(range.end, 0), Bias::Right);
+
+        for (buffer_snapshot, range, excerpt_id) in
+            multi_buffer_snapshot.range_to_buffer_ranges(range)
+        {
+            let Some(buffer) = self.buffer.read(cx).buffer(buffer_snapshot.remote_id()) else {
+                continue;
+            };
+            for bookmark in bookmark_store.read(cx).bookmarks_for_buffer(&buffer, cx) {
+                let offset = buffer_snapshot.summary_for_anchor::<usize>(&bookmark.position);
+                if offset < range.start || offset > range.end {
+                    continue;
+                }
+                let multi_buffer_anchor =
+                    Anchor::in_buffer(excerpt_id, buffer_snapshot.remote_id(), bookmark.position);
+                let position = multi_buffer_anchor
+                    .to_point(&multi_buffer_snapshot)
+                    .to_display_point(&snapshot);
+                bookmark_display_points.insert(position.row(), multi_buffer_anchor);
+            }
+        }
+
+        bookmark_display_points
+    }
+
+    fn render_bookmark(
+        &self,
+        position: Anchor,
+        row: DisplayRow,
+        cx: &mut Context<Self>,
+    ) -> IconButton {
+        IconButton::new(("bookmark_indicator", row.0 as usize), ui::IconName::Book)
+            .icon_size(IconSize::XSmall)
+            .size(ui::ButtonSize::None)
+            .icon_color(Color::Accent)
+            .style(ButtonStyle::Transparent)
+            .on_click(cx.listener(move |editor, _, window, cx| {
+                window.focus(&editor.focus_handle(cx));
+                editor.toggle_bookmark_at_anchor(position, None, cx);
+            }))
+            .tooltip(Tooltip::text("Remove Bookmark"))
+    }
+
+    pub fn toggle_bookmark(
+        &mut self,
+        action: &ToggleBookmark,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let label = action.name.clone().map(SharedString::from);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut toggled_rows = HashSet::default();
+        for selection in self.selections.all::<Point>(cx) {
+            let head = snapshot.anchor_before(selection.head());
+            if toggled_rows.insert((head.buffer_id, selection.head().row)) {
+                self.toggle_bookmark_at_anchor(head, label.clone(), cx);
+            }
+        }
+    }
+
+    pub fn toggle_bookmark_at_anchor(
+        &mut self,
+        position: Anchor,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(bookmark_store) = &self.bookmark_store else {
+            return;
+        };
+        let Some(buffer) = position
+            .buffer_id
+            .and_then(|buffer_id| self.buffer.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+        bookmark_store.update(cx, |bookmark_store, cx| {
+            bookmark_store.toggle_bookmark(buffer, position.text_anchor, label, cx);
+        });
+    }
+
+    pub fn clear_bookmarks(
+        &mut self,
+        _: &ClearBookmarks,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(bookmark_store) = self.bookmark_store.as_ref() {
+            bookmark_store.update(cx, |bookmark_store, cx| bookmark_store.clear_bookmarks(cx));
+        }
+    }
+
+    pub fn go_to_next_bookmark(
+        &mut self,
+        _: &GoToNextBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_adjacent_bookmark(BookmarkDirection::Next, window, cx);
+    }
+
+    pub fn go_to_previous_bookmark(
+        &mut self,
+        _: &GoToPreviousBookmark,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_adjacent_bookmark(BookmarkDirection::Previous, window, cx);
+    }
+
+    fn go_to_adjacent_bookmark(
+        &mut self,
+        direction: BookmarkDirection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(bookmark_store) = self.bookmark_store.clone() else {
+            return;
+        };
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let Some((buffer, point)) = head.buffer_id.and_then(|buffer_id| {
+            let buffer = self.buffer.read(cx).buffer(buffer_id)?;
+            let point = buffer
+                .read(cx)
+                .summary_for_anchor::<Point>(&head.text_anchor);
+            Some((buffer, point))
+        }) else {
+            return;
+        };
+        let Some((target_buffer, bookmark)) = bookmark_store
+            .read(cx)
+            .adjacent_bookmark(&buffer, point.row, direction, cx)
+        else {
+            return;
+        };
+
+        let target_buffer_id = target_buffer.read(cx).remote_id();
+        if let Some(excerpt_id) = snapshot
+            .excerpts()
+            .find(|(_, buffer_snapshot, range)| {
+                buffer_snapshot.remote_id() == target_buffer_id
+                    && range
+                        .context
+                        .start
+                        .cmp(&bookmark.position, buffer_snapshot)
+                        .is_le()
+                    && range
+                        .context
+                        .end
+                        .cmp(&bookmark.position, buffer_snapshot)
+                        .is_ge()
+            })
+            .map(|(excerpt_id, _, _)| excerpt_id)
+        {
+            let anchor = Anchor::in_buffer(excerpt_id, target_buffer_id, bookmark.position);
+            self.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                s.select_anchor_ranges([anchor..anchor]);
+            });
+            return;
+        }
+
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let position = bookmark.position;
+        window.defer(cx, move |window, cx| {
+            workspace.update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Self>(
+                    pane,
+                    target_buffer.clone(),
+                    true,
+                    true,
+                    window,
+                    cx,
+                );
+                editor.update(cx, |editor, cx| {
+                    let point = target_buffer
+                        .read(cx)
+                        .summary_for_anchor::<Point>(&position);
+                    editor.change_selections(Some(Autoscroll::center()), window, cx, |s| {
+                        s.select_ranges([point..point]);
+                    });
+                });
+            });
+        });
+    }
+
     pub fn prepare_restore_change(
         &self,
         revert_changes: &mut HashMap<BufferId, Vec<(Range<text::Anchor>, Rope)>>,
@@ -17201,6 +17422,7 @@ impl Editor {
                         }
                     }
                 }
+                self.refresh_rainbow_brackets(cx);
                 cx.emit(EditorEvent::BufferEdited);
                 cx.emit(SearchEvent::MatchesInvalidated);
                 if *singleton_buffer_edited {
@@ -17287,10 +17509,12 @@ impl Editor {
             }
             multi_buffer::Event::ExcerptsExpanded { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                self.refresh_rainbow_brackets(cx);
                 cx.emit(EditorEvent::ExcerptsExpanded { ids: ids.clone() })
             }
             multi_buffer::Event::Reparsed(buffer_id) => {
                 self.tasks_update_task = Some(self.refresh_runnables(window, cx));
+                self.refresh_rainbow_brackets(cx);
                 jsx_tag_auto_close::refresh_enabled_in_any_buffer(self, multibuffer, cx);
 
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
@@ -17300,6 +17524,7 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, window, cx);
+                self.refresh_rainbow_brackets(cx);
                 jsx_tag_auto_close::refresh_enabled_in_any_buffer(self, multibuffer, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
@@ -17342,6 +17567,9 @@ impl Editor {
             cx,
         );
 
+        self.rainbow_brackets.invalidate();
+        self.refresh_rainbow_brackets(cx);
+
         let old_cursor_shape = self.cursor_shape;
 
         {
@@ -17442,6 +17670,49 @@ impl Editor {
         });
     }
 
+    fn diff_clipboard_with_selection(
+        &mut self,
+        _: &DiffClipboardWithSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            cx.propagate();
+            return;
+        };
+        let Some(clipboard_text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+
+        let selection = self.selections.newest::<usize>(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+        let buffer_ranges = multi_buffer_snapshot.range_to_buffer_ranges(selection.range());
+        let [(buffer_snapshot, range, _)] = buffer_ranges.as_slice() else {
+            return;
+        };
+        let Some(buffer) = multi_buffer.buffer(buffer_snapshot.remote_id()) else {
+            return;
+        };
+        let range = text_diff_view::comparison_range(buffer_snapshot, range.clone());
+
+        let title = format!(
+            "Clipboard ↔ {}",
+            text_diff_view::describe_range(&buffer, &range, cx)
+        );
+        let clipboard_buffer = cx.new(|cx| Buffer::local(clipboard_text, cx));
+        let text_diff_view = cx
+            .new(|cx| TextDiffView::new(clipboard_buffer, buffer, Some(range), title, window, cx));
+
+        window.defer(cx, move |window, cx| {
+            workspace.update(cx, |workspace, cx| {
+                workspace.active_pane().update(cx, |pane, cx| {
+                    pane.add_item(Box::new(text_diff_view), true, true, None, window, cx);
+                });
+            });
+        });
+    }
+
     pub fn open_excerpts_in_split(
         &mut self,
         _: &OpenExcerptsSplit,
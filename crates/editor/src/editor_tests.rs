@@ -15047,6 +15047,87 @@ async fn test_toggle_diff_expand_in_multi_buffer(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_text_diff_view(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let old_buffer = cx.new(|cx| Buffer::local("one\ntwo\nthree\n", cx));
+    let new_buffer = cx.new(|cx| Buffer::local("one\n2\nthree\n", cx));
+    let (view, cx) = cx.add_window_view(|window, cx| {
+        TextDiffView::new(
+            old_buffer.clone(),
+            new_buffer.clone(),
+            None,
+            "Diff",
+            window,
+            cx,
+        )
+    });
+    let hunk_rows = |cx: &mut VisualTestContext| {
+        cx.executor()
+            .advance_clock(text_diff_view::DIFF_DEBOUNCE * 2);
+        cx.run_until_parked();
+        view.update(cx, |view, cx| {
+            let snapshot = new_buffer.read(cx).text_snapshot();
+            view.diff()
+                .read(cx)
+                .hunks(&snapshot, cx)
+                .map(|hunk| hunk.range.start.row..hunk.range.end.row)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(hunk_rows(cx), [1..2]);
+
+    // Editing either side updates the diff.
+    old_buffer.update(cx, |buffer, cx| buffer.edit([(4..7, "2")], None, cx));
+    assert!(hunk_rows(cx).is_empty());
+    new_buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "ONE")], None, cx));
+    assert_eq!(hunk_rows(cx), [0..1]);
+}
+
+#[gpui::test]
+async fn test_text_diff_view_for_range(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let clipboard_buffer = cx.new(|cx| Buffer::local("B\nC\n", cx));
+    let buffer = cx.new(|cx| Buffer::local("a\nb\nc\nd\n", cx));
+    let range = buffer.read_with(cx, |buffer, _| {
+        text_diff_view::comparison_range(&buffer.snapshot(), 2..6)
+    });
+    let (view, cx) = cx.add_window_view(|window, cx| {
+        TextDiffView::new(
+            clipboard_buffer.clone(),
+            buffer.clone(),
+            Some(range),
+            "Diff",
+            window,
+            cx,
+        )
+    });
+    let hunk_rows = |cx: &mut VisualTestContext| {
+        cx.executor()
+            .advance_clock(text_diff_view::DIFF_DEBOUNCE * 2);
+        cx.run_until_parked();
+        view.update(cx, |view, cx| {
+            let snapshot = buffer.read(cx).text_snapshot();
+            view.diff()
+                .read(cx)
+                .hunks(&snapshot, cx)
+                .map(|hunk| hunk.range.start.row..hunk.range.end.row)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(hunk_rows(cx), [1..3]);
+
+    // Text outside of the compared range is never reported as changed.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..1, "x")], None, cx));
+    assert_eq!(hunk_rows(cx), [1..3]);
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(2..3, "B"), (4..5, "C")], None, cx)
+    });
+    assert!(hunk_rows(cx).is_empty());
+}
+
 #[gpui::test]
 async fn test_expand_diff_hunk_at_excerpt_boundary(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
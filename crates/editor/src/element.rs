@@ -24,7 +24,8 @@ use crate::{
     inlay_hint_settings,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition},
-    scroll::scroll_amount::ScrollAmount,
+    scroll::{Autoscroll, scroll_amount::ScrollAmount},
+    sticky_scroll::StickyScrollHeader,
 };
 use buffer_diff::{DiffHunkStatus, DiffHunkStatusKind};
 use client::ParticipantIndex;
@@ -33,15 +34,15 @@ use feature_flags::{Debugger, FeatureFlagAppExt};
 use file_icons::FileIcons;
 use git::{Oid, blame::BlameEntry, status::FileStatus};
 use gpui::{
-    Action, Along, AnyElement, App, AvailableSpace, Axis as ScrollbarAxis, BorderStyle, Bounds,
-    ClickEvent, ContentMask, Context, Corner, Corners, CursorStyle, DispatchPhase, Edges, Element,
-    ElementInputHandler, Entity, Focusable as _, FontId, GlobalElementId, Hitbox, Hsla,
-    InteractiveElement, IntoElement, Keystroke, Length, ModifiersChangedEvent, MouseButton,
-    MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad, ParentElement, Pixels, ScrollDelta,
-    ScrollWheelEvent, ShapedLine, SharedString, Size, StatefulInteractiveElement, Style, Styled,
-    TextRun, TextStyleRefinement, WeakEntity, Window, anchored, deferred, div, fill,
-    linear_color_stop, linear_gradient, outline, point, px, quad, relative, size, solid_background,
-    transparent_black,
+    AccessibilityProperties, AccessibilityRole, Action, Along, AnyElement, App, AvailableSpace,
+    Axis as ScrollbarAxis, BorderStyle, Bounds, ClickEvent, ContentMask, Context, Corner, Corners,
+    CursorStyle, DispatchPhase, Edges, Element, ElementInputHandler, Entity, Focusable as _,
+    FontId, GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Keystroke, Length,
+    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad,
+    ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size,
+    StatefulInteractiveElement, Style, Styled, StyledText, TextRun, TextStyleRefinement,
+    WeakEntity, Window, anchored, deferred, div, fill, linear_color_stop, linear_gradient, outline,
+    point, px, quad, relative, size, solid_background, transparent_black,
 };
 use itertools::Itertools;
 use language::language_settings::{
@@ -409,6 +410,7 @@ impl EditorElement {
         register_action(editor, window, Editor::open_excerpts);
         register_action(editor, window, Editor::open_excerpts_in_split);
         register_action(editor, window, Editor::open_proposed_changes_editor);
+        register_action(editor, window, Editor::diff_clipboard_with_selection);
         register_action(editor, window, Editor::toggle_soft_wrap);
         register_action(editor, window, Editor::toggle_tab_bar);
         register_action(editor, window, Editor::toggle_line_numbers);
@@ -539,6 +541,10 @@ impl EditorElement {
         register_action(editor, window, Editor::insert_uuid_v4);
         register_action(editor, window, Editor::insert_uuid_v7);
         register_action(editor, window, Editor::open_selections_in_multibuffer);
+        register_action(editor, window, Editor::toggle_bookmark);
+        register_action(editor, window, Editor::go_to_next_bookmark);
+        register_action(editor, window, Editor::go_to_previous_bookmark);
+        register_action(editor, window, Editor::clear_bookmarks);
         if cx.has_flag::<Debugger>() {
             register_action(editor, window, Editor::toggle_breakpoint);
             register_action(editor, window, Editor::edit_log_breakpoint);
@@ -2069,6 +2075,65 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_bookmarks(
+        &self,
+        line_height: Pixels,
+        range: Range<DisplayRow>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        display_hunks: &[(DisplayDiffHunk, Option<Hitbox>)],
+        snapshot: &EditorSnapshot,
+        bookmarks: HashMap<DisplayRow, Anchor>,
+        row_infos: &[RowInfo],
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            bookmarks
+                .into_iter()
+                .filter_map(|(display_row, anchor)| {
+                    if range.start > display_row || range.end < display_row {
+                        return None;
+                    }
+
+                    if row_infos
+                        .get((display_row.0.saturating_sub(range.start.0)) as usize)
+                        .is_some_and(|row_info| {
+                            row_info.expand_info.is_some()
+                                || row_info
+                                    .diff_status
+                                    .is_some_and(|status| status.is_deleted())
+                        })
+                    {
+                        return None;
+                    }
+
+                    let row =
+                        MultiBufferRow(DisplayPoint::new(display_row, 0).to_point(&snapshot).row);
+                    if snapshot.is_line_folded(row) {
+                        return None;
+                    }
+
+                    let button = editor.render_bookmark(anchor, display_row, cx);
+                    let button = prepaint_gutter_button(
+                        button,
+                        display_row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        display_hunks,
+                        window,
+                        cx,
+                    );
+                    Some(button)
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_run_indicators(
         &self,
@@ -3317,6 +3382,99 @@ impl EditorElement {
         header
     }
 
+    fn layout_sticky_scroll_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        scroll_position: gpui::Point<f32>,
+        has_sticky_buffer_header: bool,
+        hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        em_width: Pixels,
+        line_height: Pixels,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<AnyElement> {
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled || !matches!(snapshot.mode, EditorMode::Full { .. }) {
+            return None;
+        }
+
+        // Keep the pinned headers below the multibuffer's own sticky file header.
+        let reserved_rows = if has_sticky_buffer_header {
+            FILE_HEADER_HEIGHT
+        } else {
+            0
+        };
+        let headers = snapshot.sticky_scroll_headers(
+            scroll_position.y + reserved_rows as f32,
+            settings.max_depth,
+            Some(cx.theme().syntax()),
+        );
+        if headers.is_empty() {
+            return None;
+        }
+
+        let colors = cx.theme().colors();
+        let background = colors.editor_background;
+        let hover_background = colors.editor_active_line_background;
+        let border_color = colors.border_variant;
+        let text_x = content_origin.x - hitbox.origin.x - scroll_position.x * em_width;
+
+        let mut element = v_flex()
+            .id("sticky-scroll-headers")
+            .occlude()
+            .w(hitbox.size.width)
+            .bg(background)
+            .border_b_1()
+            .border_color(border_color)
+            .children(headers.into_iter().enumerate().map(|(ix, header)| {
+                let StickyScrollHeader {
+                    item,
+                    indent_column,
+                } = header;
+                let jump_anchor = item.range.start;
+                h_flex()
+                    .id(("sticky-scroll-header", ix))
+                    .w_full()
+                    .h(line_height)
+                    .pl(text_x + indent_column as f32 * em_width)
+                    .overflow_x_hidden()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(hover_background))
+                    .child(
+                        StyledText::new(item.text)
+                            .with_default_highlights(&self.style.text, item.highlight_ranges),
+                    )
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_click(window.listener_for(&self.editor, {
+                        move |editor, _: &ClickEvent, window, cx| {
+                            // Put the definition exactly where its header was pinned.
+                            editor.change_selections(
+                                Some(Autoscroll::top_relative(ix)),
+                                window,
+                                cx,
+                                |selections| {
+                                    selections.select_anchor_ranges([jump_anchor..jump_anchor])
+                                },
+                            );
+                        }
+                    }))
+            }))
+            .into_any_element();
+
+        let origin = point(
+            hitbox.origin.x,
+            hitbox.origin.y + reserved_rows as f32 * line_height,
+        );
+        let size = size(
+            AvailableSpace::Definite(hitbox.size.width),
+            AvailableSpace::MinContent,
+        );
+        element.prepaint_as_root(origin, size, window, cx);
+
+        Some(element)
+    }
+
     fn layout_cursor_popovers(
         &self,
         line_height: Pixels,
@@ -4677,6 +4835,10 @@ impl EditorElement {
                 }
             });
 
+            for bookmark in layout.bookmarks.iter_mut() {
+                bookmark.paint(window, cx);
+            }
+
             for breakpoint in layout.breakpoints.iter_mut() {
                 breakpoint.paint(window, cx);
             }
@@ -7088,6 +7250,18 @@ impl Element for EditorElement {
                         })
                     });
 
+                    let sticky_scroll_headers = self.layout_sticky_scroll_headers(
+                        &snapshot,
+                        scroll_position,
+                        sticky_buffer_header.is_some(),
+                        &hitbox,
+                        content_origin,
+                        em_width,
+                        line_height,
+                        window,
+                        cx,
+                    );
+
                     let start_buffer_row =
                         MultiBufferRow(start_anchor.to_point(&snapshot.buffer_snapshot).row);
                     let end_buffer_row =
@@ -7408,7 +7582,51 @@ impl Element for EditorElement {
                     let show_breakpoints = snapshot
                         .show_breakpoints
                         .unwrap_or(gutter_settings.breakpoints);
-                    let breakpoints = if cx.has_flag::<Debugger>() && show_breakpoints {
+                    let show_breakpoints = cx.has_flag::<Debugger>() && show_breakpoints;
+
+                    // Bookmarks share the indicator slot with breakpoints, runnables and the
+                    // code actions indicator, all of which take precedence over them.
+                    let mut bookmark_rows = self.editor.update(cx, |editor, cx| {
+                        editor.active_bookmarks(start_row..end_row, window, cx)
+                    });
+                    bookmark_rows.retain(|display_row, anchor| {
+                        if show_breakpoints && breakpoint_rows.contains_key(display_row) {
+                            return false;
+                        }
+                        if code_actions_indicator.is_some()
+                            && newest_selection_head.is_some_and(|head| head.row() == *display_row)
+                        {
+                            return false;
+                        }
+                        let has_test_indicator = gutter_settings.runnables
+                            && anchor.buffer_id.is_some_and(|buffer_id| {
+                                let row = anchor.to_point(&snapshot.buffer_snapshot).row;
+                                let buffer_row = snapshot
+                                    .buffer_snapshot
+                                    .buffer_line_for_row(MultiBufferRow(row))
+                                    .map_or(row, |(_, range)| range.start.row);
+                                self.editor
+                                    .read(cx)
+                                    .tasks
+                                    .contains_key(&(buffer_id, buffer_row))
+                            });
+                        !has_test_indicator
+                    });
+                    let bookmarks = self.layout_bookmarks(
+                        line_height,
+                        start_row..end_row,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &display_hunks,
+                        &snapshot,
+                        bookmark_rows,
+                        &row_infos,
+                        window,
+                        cx,
+                    );
+
+                    let breakpoints = if show_breakpoints {
                         self.layout_breakpoints(
                             line_height,
                             start_row..end_row,
@@ -7581,12 +7799,14 @@ impl Element for EditorElement {
                         mouse_context_menu,
                         test_indicators,
                         breakpoints,
+                        bookmarks,
                         code_actions_indicator,
                         crease_toggles,
                         crease_trailers,
                         tab_invisible,
                         space_invisible,
                         sticky_buffer_header,
+                        sticky_scroll_headers,
                         expand_toggles,
                     }
                 })
@@ -7623,46 +7843,93 @@ impl Element for EditorElement {
             ..Default::default()
         };
         let rem_size = self.rem_size(cx);
-        window.with_rem_size(rem_size, |window| {
-            window.with_text_style(Some(text_style), |window| {
-                window.with_content_mask(Some(ContentMask { bounds }), |window| {
-                    self.paint_mouse_listeners(layout, window, cx);
-                    self.paint_background(layout, window, cx);
-                    self.paint_indent_guides(layout, window, cx);
+        let accessibility = if window.accessibility_enabled() {
+            self.accessibility_properties(layout, cx)
+        } else {
+            AccessibilityProperties::default()
+        };
+        let focused = focus_handle.is_focused(window);
+        window.with_accessibility_node(None, accessibility, bounds, focused, |window| {
+            window.with_rem_size(rem_size, |window| {
+                window.with_text_style(Some(text_style), |window| {
+                    window.with_content_mask(Some(ContentMask { bounds }), |window| {
+                        self.paint_mouse_listeners(layout, window, cx);
+                        self.paint_background(layout, window, cx);
+                        self.paint_indent_guides(layout, window, cx);
+
+                        if layout.gutter_hitbox.size.width > Pixels::ZERO {
+                            self.paint_blamed_display_rows(layout, window, cx);
+                            self.paint_line_numbers(layout, window, cx);
+                        }
 
-                    if layout.gutter_hitbox.size.width > Pixels::ZERO {
-                        self.paint_blamed_display_rows(layout, window, cx);
-                        self.paint_line_numbers(layout, window, cx);
-                    }
+                        self.paint_text(layout, window, cx);
 
-                    self.paint_text(layout, window, cx);
+                        if layout.gutter_hitbox.size.width > Pixels::ZERO {
+                            self.paint_gutter_highlights(layout, window, cx);
+                            self.paint_gutter_indicators(layout, window, cx);
+                        }
 
-                    if layout.gutter_hitbox.size.width > Pixels::ZERO {
-                        self.paint_gutter_highlights(layout, window, cx);
-                        self.paint_gutter_indicators(layout, window, cx);
-                    }
+                        if !layout.blocks.is_empty() {
+                            window.with_element_namespace("blocks", |window| {
+                                self.paint_blocks(layout, window, cx);
+                            });
+                        }
+
+                        if let Some(mut sticky_scroll_headers) = layout.sticky_scroll_headers.take()
+                        {
+                            sticky_scroll_headers.paint(window, cx);
+                        }
 
-                    if !layout.blocks.is_empty() {
                         window.with_element_namespace("blocks", |window| {
-                            self.paint_blocks(layout, window, cx);
+                            if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
+                                sticky_header.paint(window, cx)
+                            }
                         });
-                    }
 
-                    window.with_element_namespace("blocks", |window| {
-                        if let Some(mut sticky_header) = layout.sticky_buffer_header.take() {
-                            sticky_header.paint(window, cx)
-                        }
+                        self.paint_scrollbars(layout, window, cx);
+                        self.paint_inline_completion_popover(layout, window, cx);
+                        self.paint_mouse_context_menu(layout, window, cx);
                     });
-
-                    self.paint_scrollbars(layout, window, cx);
-                    self.paint_inline_completion_popover(layout, window, cx);
-                    self.paint_mouse_context_menu(layout, window, cx);
-                });
+                })
             })
         })
     }
 }
 
+impl EditorElement {
+    /// Describes the editor to assistive technologies, which read the visible text as its value.
+    fn accessibility_properties(&self, layout: &EditorLayout, cx: &App) -> AccessibilityProperties {
+        let snapshot = &layout.position_map.snapshot;
+        let role = if snapshot.mode.is_full() {
+            AccessibilityRole::MultilineTextInput
+        } else {
+            AccessibilityRole::TextInput
+        };
+        let rows = layout.visible_display_row_range.clone();
+        let line_count = rows.end.0.saturating_sub(rows.start.0) as usize;
+        let mut text = String::new();
+        let mut lines = 0;
+        'chunks: for chunk in snapshot.text_chunks(rows.start) {
+            for line in chunk.split_inclusive('\n') {
+                text.push_str(line);
+                if line.ends_with('\n') {
+                    lines += 1;
+                    if lines >= line_count {
+                        text.pop();
+                        break 'chunks;
+                    }
+                }
+            }
+        }
+
+        let mut properties = AccessibilityProperties::new(role).value(text);
+        if let Some(placeholder) = self.editor.read(cx).placeholder_text() {
+            properties = properties.name(placeholder.to_string());
+        }
+        properties
+    }
+}
+
 pub(super) fn gutter_bounds(
     editor_bounds: Bounds<Pixels>,
     gutter_dimensions: GutterDimensions,
@@ -7755,6 +8022,7 @@ pub struct EditorLayout {
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
     breakpoints: Vec<AnyElement>,
+    bookmarks: Vec<AnyElement>,
     crease_toggles: Vec<Option<AnyElement>>,
     expand_toggles: Vec<Option<(AnyElement, gpui::Point<Pixels>)>>,
     diff_hunk_controls: Vec<AnyElement>,
@@ -7764,6 +8032,7 @@ pub struct EditorLayout {
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
     sticky_buffer_header: Option<AnyElement>,
+    sticky_scroll_headers: Option<AnyElement>,
 }
 
 impl EditorLayout {
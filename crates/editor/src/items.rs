@@ -1317,6 +1317,20 @@ fn clip_ranges<'a>(
         .collect()
 }
 
+/// Returns the replacement for a structural search match, which must lie within a single
+/// excerpt for its syntax tree to be matched again.
+fn structural_replacement_for(
+    query: &SearchQuery,
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+) -> Option<String> {
+    let buffer_ranges = snapshot.range_to_buffer_ranges(range.clone());
+    let [(buffer, range, _)] = buffer_ranges.as_slice() else {
+        return None;
+    };
+    query.structural_replacement_for(buffer, range.clone())
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 impl Editor {
@@ -1516,6 +1530,14 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            if let Some(replacement) = structural_replacement_for(query, &text, identifier) {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement))], cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1543,6 +1565,13 @@ impl SearchableItem for Editor {
         let mut last_point: Option<Point> = None;
 
         for m in matches {
+            if query.is_structural() {
+                if let Some(replacement) = structural_replacement_for(query, &text, m) {
+                    edits.push((m.clone(), Arc::from(replacement)));
+                }
+                continue;
+            }
+
             let point = m.start.to_point(&text);
             let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
 
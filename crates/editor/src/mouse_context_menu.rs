@@ -1,8 +1,8 @@
 use crate::{
-    Copy, CopyAndTrim, CopyPermalinkToLine, Cut, DebuggerEvaluateSelectedText, DisplayPoint,
-    DisplaySnapshot, Editor, FindAllReferences, GoToDeclaration, GoToDefinition,
-    GoToImplementation, GoToTypeDefinition, Paste, Rename, RevealInFileManager, SelectMode,
-    ToDisplayPoint, ToggleCodeActions,
+    Copy, CopyAndTrim, CopyPermalinkToLine, Cut, DebuggerEvaluateSelectedText,
+    DiffClipboardWithSelection, DisplayPoint, DisplaySnapshot, Editor, FindAllReferences,
+    GoToDeclaration, GoToDefinition, GoToImplementation, GoToTypeDefinition, Paste, Rename,
+    RevealInFileManager, SelectMode, ToDisplayPoint, ToggleCodeActions,
     actions::{Format, FormatSelections},
     selections_collection::SelectionsCollection,
 };
@@ -204,6 +204,12 @@ pub fn deploy_context_menu(
                 .action("Copy", Box::new(Copy))
                 .action("Copy and trim", Box::new(CopyAndTrim))
                 .action("Paste", Box::new(Paste))
+                .when(has_selections, |builder| {
+                    builder.action(
+                        "Diff Clipboard with Selection",
+                        Box::new(DiffClipboardWithSelection),
+                    )
+                })
                 .separator()
                 .map(|builder| {
                     let reveal_in_finder_label = if cfg!(target_os = "macos") {
@@ -19,9 +19,7 @@ pub(crate) struct SerializedEditor {
 }
 
 impl StaticColumnCount for SerializedEditor {
-    fn column_count() -> usize {
-        6
-    }
+    const COLUMN_COUNT: usize = 6;
 }
 
 impl Bind for SerializedEditor {
@@ -0,0 +1,259 @@
+use std::{any::TypeId, cmp::Reverse, ops::Range, time::Duration};
+
+use gpui::{Context, HighlightStyle, Hsla, Task};
+use language::{BufferSnapshot, language_settings::language_settings};
+use multi_buffer::{MultiBufferSnapshot, ToPoint as _};
+use text::{Bias, Point};
+use theme::ActiveTheme as _;
+
+use crate::{Anchor, Editor};
+
+/// The number of accent colors brackets cycle through before repeating.
+const MAX_COLORS: usize = 8;
+
+const RAINBOW_BRACKETS_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// A text highlight key for every color, as text highlights are keyed by type.
+enum RainbowBracket<const COLOR: usize> {}
+
+const RAINBOW_BRACKET_HIGHLIGHTS: [fn() -> TypeId; MAX_COLORS] = [
+    TypeId::of::<RainbowBracket<0>>,
+    TypeId::of::<RainbowBracket<1>>,
+    TypeId::of::<RainbowBracket<2>>,
+    TypeId::of::<RainbowBracket<3>>,
+    TypeId::of::<RainbowBracket<4>>,
+    TypeId::of::<RainbowBracket<5>>,
+    TypeId::of::<RainbowBracket<6>>,
+    TypeId::of::<RainbowBracket<7>>,
+];
+
+/// Tracks what the bracket colors were last computed for, so that they are only recomputed when
+/// the visible text, its syntax tree or the settings change.
+#[derive(Default)]
+pub(crate) struct RainbowBracketsState {
+    computed_for: Option<(Range<usize>, usize, usize)>,
+    refresh_task: Option<Task<()>>,
+    has_highlights: bool,
+}
+
+impl RainbowBracketsState {
+    pub(crate) fn invalidate(&mut self) {
+        self.computed_for = None;
+    }
+}
+
+impl Editor {
+    /// Colors the brackets around the visible rows by their nesting depth, using the theme's
+    /// accent colors. The brackets are matched on a background thread once the editor has
+    /// stopped changing for a moment.
+    pub(crate) fn refresh_rainbow_brackets(&mut self, cx: &mut Context<Self>) {
+        if !self.mode.is_full() {
+            return;
+        }
+
+        let colorize_brackets = self.buffer.read(cx).all_buffers().iter().any(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                .colorize_brackets
+        });
+        if !colorize_brackets {
+            self.rainbow_brackets.computed_for = None;
+            self.rainbow_brackets.refresh_task = None;
+            if self.rainbow_brackets.has_highlights {
+                self.set_rainbow_bracket_highlights(Vec::new(), &[], cx);
+            }
+            return;
+        }
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let visible_range = self.rainbow_brackets_range(&snapshot);
+        let computed_for = Some((
+            visible_range.clone(),
+            snapshot.edit_count(),
+            snapshot.non_text_state_update_count(),
+        ));
+        if self.rainbow_brackets.computed_for == computed_for {
+            return;
+        }
+        self.rainbow_brackets.computed_for = computed_for;
+
+        let accents = cx.theme().accents().0.clone();
+        let color_count = accents.len().min(MAX_COLORS);
+        let buffer_ranges = snapshot
+            .range_to_buffer_ranges(visible_range)
+            .into_iter()
+            .filter(|(buffer, _, _)| {
+                language_settings(buffer.language().map(|l| l.name()), buffer.file(), cx)
+                    .colorize_brackets
+            })
+            .map(|(buffer, range, excerpt_id)| (buffer.clone(), range, excerpt_id))
+            .collect::<Vec<_>>();
+
+        self.rainbow_brackets.refresh_task = Some(cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(RAINBOW_BRACKETS_DEBOUNCE)
+                .await;
+            let ranges_by_color = cx
+                .background_spawn(async move {
+                    let mut ranges_by_color = vec![Vec::new(); color_count];
+                    if color_count == 0 {
+                        return ranges_by_color;
+                    }
+                    for (buffer, range, excerpt_id) in buffer_ranges {
+                        for (bracket_range, depth) in bracket_depths(&buffer, range) {
+                            let start = snapshot.anchor_in_excerpt(
+                                excerpt_id,
+                                buffer.anchor_after(bracket_range.start),
+                            );
+                            let end = snapshot.anchor_in_excerpt(
+                                excerpt_id,
+                                buffer.anchor_before(bracket_range.end),
+                            );
+                            if let Some((start, end)) = start.zip(end) {
+                                ranges_by_color[depth % color_count].push(start..end);
+                            }
+                        }
+                    }
+                    ranges_by_color
+                })
+                .await;
+
+            editor
+                .update(cx, |editor, cx| {
+                    editor.set_rainbow_bracket_highlights(ranges_by_color, &accents, cx)
+                })
+                .ok();
+        }));
+    }
+
+    fn set_rainbow_bracket_highlights(
+        &mut self,
+        mut ranges_by_color: Vec<Vec<Range<Anchor>>>,
+        accents: &[Hsla],
+        cx: &mut Context<Self>,
+    ) {
+        self.rainbow_brackets.has_highlights =
+            ranges_by_color.iter().any(|ranges| !ranges.is_empty());
+        self.display_map.update(cx, |display_map, _| {
+            for (ix, highlight) in RAINBOW_BRACKET_HIGHLIGHTS.iter().enumerate() {
+                match ranges_by_color.get_mut(ix) {
+                    Some(ranges) if !ranges.is_empty() => display_map.highlight_text(
+                        highlight(),
+                        std::mem::take(ranges),
+                        HighlightStyle::color(accents[ix]),
+                    ),
+                    _ => {
+                        display_map.clear_highlights(highlight());
+                    }
+                }
+            }
+        });
+        cx.notify();
+    }
+
+    /// The visible rows, along with a screen's worth of rows above and below them so that
+    /// scrolling doesn't reveal uncolored brackets.
+    fn rainbow_brackets_range(&self, snapshot: &MultiBufferSnapshot) -> Range<usize> {
+        let visible_lines = self.visible_line_count().unwrap_or(0.).ceil() as u32;
+        let top = self.scroll_manager.anchor().anchor.to_point(snapshot);
+        let start = Point::new(top.row.saturating_sub(visible_lines), 0);
+        let end = snapshot.clip_point(Point::new(top.row + 2 * visible_lines, 0), Bias::Left);
+        snapshot.point_to_offset(start)..snapshot.point_to_offset(end)
+    }
+}
+
+/// Returns the ranges of the brackets intersecting the given range, along with the number of
+/// bracket pairs enclosing them.
+fn bracket_depths(buffer: &BufferSnapshot, range: Range<usize>) -> Vec<(Range<usize>, usize)> {
+    // The bracket queries return every pair intersecting the range, including the ones that
+    // enclose it, so the depth of each pair is the number of pairs it is nested in.
+    let mut pairs = buffer
+        .bracket_ranges(range.clone())
+        .filter(|pair| is_colorable_bracket(buffer, &pair.open_range))
+        .map(|pair| (pair.open_range, pair.close_range))
+        .collect::<Vec<_>>();
+    pairs.sort_unstable_by_key(|(open, close)| (open.start, Reverse(close.end)));
+    pairs.dedup();
+
+    let mut brackets = Vec::new();
+    let mut enclosing_ends = Vec::new();
+    for (open, close) in pairs {
+        while enclosing_ends.last().is_some_and(|end| *end <= open.start) {
+            enclosing_ends.pop();
+        }
+        let depth = enclosing_ends.len();
+        enclosing_ends.push(close.end);
+
+        for bracket in [open, close] {
+            if bracket.start < range.end && bracket.end > range.start {
+                brackets.push((bracket, depth));
+            }
+        }
+    }
+    brackets.sort_unstable_by_key(|(bracket, _)| bracket.start);
+    brackets
+}
+
+/// Quotes and other pairs matched by the bracket queries aren't colored.
+fn is_colorable_bracket(buffer: &BufferSnapshot, range: &Range<usize>) -> bool {
+    range.len() == 1
+        && buffer
+            .chars_at(range.start)
+            .next()
+            .is_some_and(|char| matches!(char, '(' | '[' | '{' | '<'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{App, AppContext as _};
+    use indoc::indoc;
+    use language::{Buffer, Language, LanguageConfig};
+    use std::sync::Arc;
+
+    #[gpui::test]
+    fn test_bracket_depths(cx: &mut App) {
+        let language = Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+        .with_brackets_query(indoc! {r#"
+            ("(" @open ")" @close)
+            ("[" @open "]" @close)
+            ("{" @open "}" @close)
+            ("\"" @open "\"" @close)
+        "#})
+        .unwrap();
+        let text = indoc! {r#"
+            fn main() {
+                let a = [(1, 2)];
+                println!("{}", a[0].0);
+            }
+        "#};
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+        let snapshot = buffer.read(cx).snapshot();
+
+        let depths = |range: Range<usize>| {
+            bracket_depths(&snapshot, range)
+                .into_iter()
+                .map(|(range, depth)| (&text[range.clone()], range.start, depth))
+                .collect::<Vec<_>>()
+        };
+
+        let second_line = text.find("let").unwrap()..text.find("println").unwrap();
+        let open_square = text.find('[').unwrap();
+        assert_eq!(
+            depths(second_line),
+            vec![
+                ("[", open_square, 1),
+                ("(", open_square + 1, 2),
+                (")", open_square + 6, 2),
+                ("]", open_square + 7, 1),
+            ]
+        );
+
+        // Brackets that open above the range are still counted.
+        let last_line = text.rfind('}').unwrap()..text.len();
+        assert_eq!(depths(last_line.clone()), vec![("}", last_line.start, 0)]);
+    }
+}
@@ -387,7 +387,8 @@ impl Editor {
             cx.spawn_in(window, async move |editor, cx| {
                 editor
                     .update(cx, |editor, cx| {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                        editor.refresh_rainbow_brackets(cx);
                     })
                     .ok()
             })
@@ -488,6 +489,7 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        self.refresh_rainbow_brackets(cx);
     }
 
     pub fn scroll_position(&self, cx: &mut Context<Self>) -> gpui::Point<f32> {
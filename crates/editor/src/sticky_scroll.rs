@@ -0,0 +1,175 @@
+use language::OutlineItem;
+use multi_buffer::Anchor;
+use theme::SyntaxTheme;
+
+use crate::{DisplayPoint, DisplayRow, EditorSnapshot, display_map::ToDisplayPoint};
+
+/// An outline item enclosing the top of the viewport whose first line has scrolled
+/// out of view, and whose header is therefore pinned to the top of the editor.
+#[derive(Clone, Debug)]
+pub struct StickyScrollHeader {
+    pub item: OutlineItem<Anchor>,
+    /// The display column the item starts at, used to align the pinned header
+    /// with the indentation of the line it stands in for.
+    pub indent_column: u32,
+}
+
+impl EditorSnapshot {
+    /// Returns the headers to pin when the editor is scrolled to `scroll_top`,
+    /// ordered from the outermost to the innermost enclosing item.
+    pub fn sticky_scroll_headers(
+        &self,
+        scroll_top: f32,
+        max_depth: usize,
+        theme: Option<&SyntaxTheme>,
+    ) -> Vec<StickyScrollHeader> {
+        let top_row = scroll_top.max(0.) as u32;
+        let mut headers = Vec::new();
+        // Every pinned header covers another row of the viewport, which can change
+        // which items enclose the first row that is still visible below them.
+        for _ in 0..=max_depth {
+            let pinned_rows = headers.len();
+            headers = self.sticky_scroll_headers_at(top_row, pinned_rows as u32, max_depth, theme);
+            if headers.len() <= pinned_rows {
+                break;
+            }
+        }
+        headers
+    }
+
+    fn sticky_scroll_headers_at(
+        &self,
+        top_row: u32,
+        pinned_rows: u32,
+        max_depth: usize,
+        theme: Option<&SyntaxTheme>,
+    ) -> Vec<StickyScrollHeader> {
+        if max_depth == 0 {
+            return Vec::new();
+        }
+
+        let first_visible_row = DisplayRow(top_row + pinned_rows).min(self.max_point().row());
+        let first_visible_point =
+            DisplayPoint::new(first_visible_row, 0).to_point(&self.display_snapshot);
+        let Some((_, items)) = self
+            .buffer_snapshot
+            .symbols_containing(first_visible_point, theme)
+        else {
+            return Vec::new();
+        };
+
+        items
+            .into_iter()
+            .filter(|item| {
+                item.range
+                    .end
+                    .to_display_point(&self.display_snapshot)
+                    .row()
+                    >= first_visible_row
+            })
+            .enumerate()
+            .take(max_depth)
+            .map_while(|(ix, item)| {
+                let start = item.range.start.to_display_point(&self.display_snapshot);
+                // Only pin headers whose own line is hidden behind the slot they would occupy.
+                if start.row().0 < top_row + ix as u32 {
+                    Some(StickyScrollHeader {
+                        item,
+                        indent_column: start.column(),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_test_context::EditorTestContext};
+    use indoc::indoc;
+    use language::{Language, LanguageConfig, LanguageMatcher};
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_sticky_scroll_headers(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+
+        let mut cx = EditorTestContext::new(cx).await;
+        let language = Arc::new(
+            Language::new(
+                LanguageConfig {
+                    name: "Rust".into(),
+                    matcher: LanguageMatcher {
+                        path_suffixes: vec!["rs".to_string()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                Some(tree_sitter_rust::LANGUAGE.into()),
+            )
+            .with_outline_query(indoc! {r#"
+                (impl_item
+                    "impl" @context
+                    type: (_) @name) @item
+                (function_item
+                    "fn" @context
+                    name: (_) @name) @item
+            "#})
+            .unwrap(),
+        );
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+        cx.set_state(indoc! {"
+            ˇimpl Foo {
+                fn one() {
+                    let a = 1;
+                    let b = 2;
+                    let c = 3;
+                    let d = 4;
+                }
+
+                fn two() {
+                    let e = 5;
+                }
+            }
+        "});
+        cx.run_until_parked();
+
+        let headers = |scroll_top: f32, max_depth: usize, cx: &mut EditorTestContext| {
+            cx.update_editor(|editor, window, cx| {
+                editor
+                    .snapshot(window, cx)
+                    .sticky_scroll_headers(scroll_top, max_depth, None)
+                    .into_iter()
+                    .map(|header| (header.item.text, header.indent_column))
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // Nothing is pinned while the enclosing headers are still on screen.
+        assert_eq!(headers(0., 5, &mut cx), Vec::new());
+
+        // Scrolling past `impl Foo` pins it, which in turn hides `fn one()`.
+        assert_eq!(
+            headers(1., 5, &mut cx),
+            vec![("impl Foo".to_string(), 0), ("fn one".to_string(), 4)]
+        );
+        assert_eq!(
+            headers(3., 5, &mut cx),
+            vec![("impl Foo".to_string(), 0), ("fn one".to_string(), 4)]
+        );
+
+        // The depth limit keeps only the outermost headers.
+        assert_eq!(headers(3., 1, &mut cx), vec![("impl Foo".to_string(), 0)]);
+        assert_eq!(headers(3., 0, &mut cx), Vec::new());
+
+        // Once a function ends, only its parent stays pinned.
+        assert_eq!(headers(6., 5, &mut cx), vec![("impl Foo".to_string(), 0)]);
+        assert_eq!(
+            headers(8., 5, &mut cx),
+            vec![("impl Foo".to_string(), 0), ("fn two".to_string(), 4)]
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{Editor, EditorEvent};
+use buffer_diff::BufferDiff;
+use gpui::{App, Entity, EventEmitter, Focusable, Render, Subscription, Task};
+use language::{Buffer, BufferEvent, Point};
+use multi_buffer::{ExcerptRange, MultiBuffer};
+use project::Project;
+use std::{any::TypeId, ops::Range, time::Duration};
+use text::{Rope, ToOffset, ToPoint};
+use ui::prelude::*;
+use workspace::{Item, Workspace, item::ItemEvent, searchable::SearchableItemHandle};
+
+pub(crate) const DIFF_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Shows the changes between two texts that aren't related through version control, such as two
+/// files in the project, or the clipboard and a selection.
+///
+/// The new side is shown in an editable editor, and the diff is kept up to date as either side is
+/// edited.
+pub struct TextDiffView {
+    editor: Entity<Editor>,
+    diff: Entity<BufferDiff>,
+    old_buffer: Entity<Buffer>,
+    new_buffer: Entity<Buffer>,
+    /// The part of the new buffer compared against the old buffer's text. The rest of the new
+    /// buffer is considered unchanged.
+    new_range: Range<text::Anchor>,
+    title: SharedString,
+    update_diff_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TextDiffView {
+    /// Opens a view of the changes made to `old_buffer` to turn it into `new_buffer`.
+    pub fn open_for_buffers(
+        old_buffer: Entity<Buffer>,
+        new_buffer: Entity<Buffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let title = format!(
+            "{} ↔ {}",
+            buffer_title(&old_buffer, cx),
+            buffer_title(&new_buffer, cx)
+        );
+        let view = cx.new(|cx| Self::new(old_buffer, new_buffer, None, title, window, cx));
+        workspace.active_pane().update(cx, |pane, cx| {
+            pane.add_item(Box::new(view.clone()), true, true, None, window, cx)
+        });
+        view
+    }
+
+    pub(crate) fn new(
+        old_buffer: Entity<Buffer>,
+        new_buffer: Entity<Buffer>,
+        new_range: Option<Range<text::Anchor>>,
+        title: impl Into<SharedString>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let multibuffer = cx.new(|cx| match &new_range {
+            Some(new_range) => {
+                let buffer = new_buffer.read(cx);
+                let start = new_range.start.to_point(buffer);
+                let end = new_range.end.to_point(buffer);
+                let context =
+                    Point::new(start.row, 0)..Point::new(end.row, buffer.line_len(end.row));
+                let mut multibuffer = MultiBuffer::new(buffer.capability());
+                multibuffer.push_excerpts(new_buffer.clone(), [ExcerptRange::new(context)], cx);
+                multibuffer
+            }
+            None => MultiBuffer::singleton(new_buffer.clone(), cx),
+        });
+        let diff = cx.new(|cx| BufferDiff::new(&new_buffer.read(cx).text_snapshot(), cx));
+        multibuffer.update(cx, |multibuffer, cx| multibuffer.add_diff(diff.clone(), cx));
+        let editor = cx.new(|cx| {
+            // The editor is given no project, so that the diff between the two texts isn't
+            // replaced with the one against the git index.
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+        let subscriptions = [&old_buffer, &new_buffer]
+            .into_iter()
+            .map(|buffer| {
+                cx.subscribe(buffer, |this, _, event, cx| {
+                    if let BufferEvent::Edited = event {
+                        this.schedule_diff_update(cx);
+                    }
+                })
+            })
+            .collect();
+
+        let mut this = Self {
+            editor,
+            diff,
+            old_buffer,
+            new_range: new_range.unwrap_or(text::Anchor::MIN..text::Anchor::MAX),
+            new_buffer,
+            title: title.into(),
+            update_diff_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.update_diff(cx);
+        this
+    }
+
+    fn schedule_diff_update(&mut self, cx: &mut Context<Self>) {
+        self.update_diff_task = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(DIFF_DEBOUNCE).await;
+            this.update(cx, |this, cx| this.update_diff(cx)).ok();
+        });
+    }
+
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let new_buffer = self.new_buffer.read(cx);
+        let snapshot = new_buffer.text_snapshot();
+        let new_range =
+            self.new_range.start.to_offset(&snapshot)..self.new_range.end.to_offset(&snapshot);
+
+        // The base text is the new buffer with the compared range replaced by the old text, so
+        // that only the compared range shows up as changed.
+        let mut base_text = Rope::new();
+        base_text.append(snapshot.as_rope().slice(0..new_range.start));
+        base_text.append(self.old_buffer.read(cx).as_rope().clone());
+        base_text.append(snapshot.as_rope().slice(new_range.end..snapshot.len()));
+
+        let language = new_buffer.language().cloned();
+        let language_registry = new_buffer.language_registry();
+        let base_snapshot =
+            Buffer::build_snapshot(base_text, language, language_registry.clone(), cx);
+        let diff = self.diff.clone();
+        self.update_diff_task = cx.spawn(async move |_, cx| {
+            let base_snapshot = base_snapshot.await;
+            let Ok(diff_updated) = diff.update(cx, |diff, cx| {
+                diff.set_base_text(base_snapshot, language_registry, snapshot, cx)
+            }) else {
+                return;
+            };
+            diff_updated.await.ok();
+        });
+    }
+
+    #[cfg(test)]
+    pub(crate) fn diff(&self) -> &Entity<BufferDiff> {
+        &self.diff
+    }
+}
+
+fn buffer_title(buffer: &Entity<Buffer>, cx: &App) -> String {
+    buffer
+        .read(cx)
+        .file()
+        .map(|file| file.file_name(cx).to_string_lossy().into_owned())
+        .unwrap_or_else(|| "untitled".to_string())
+}
+
+/// Returns the range of the selection in the buffer, or the whole buffer when the selection is
+/// empty.
+pub(crate) fn comparison_range(
+    buffer: &language::BufferSnapshot,
+    selection: Range<usize>,
+) -> Range<text::Anchor> {
+    if selection.is_empty() {
+        text::Anchor::MIN..text::Anchor::MAX
+    } else {
+        buffer.anchor_before(selection.start)..buffer.anchor_after(selection.end)
+    }
+}
+
+/// A human readable description of the compared part of the buffer, such as `main.rs:3-7`.
+pub(crate) fn describe_range(
+    buffer: &Entity<Buffer>,
+    range: &Range<text::Anchor>,
+    cx: &App,
+) -> String {
+    let file_name = buffer_title(buffer, cx);
+    if *range == (text::Anchor::MIN..text::Anchor::MAX) {
+        return file_name;
+    }
+    let buffer = buffer.read(cx);
+    let start = range.start.to_point(buffer);
+    let end = range.end.to_point(buffer);
+    if start.row == end.row || end == Point::new(start.row + 1, 0) {
+        format!("{file_name}:{}", start.row + 1)
+    } else {
+        format!("{file_name}:{}-{}", start.row + 1, end.row + 1)
+    }
+}
+
+impl Render for TextDiffView {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .size_full()
+            .key_context("TextDiffView")
+            .child(self.editor.clone())
+    }
+}
+
+impl Focusable for TextDiffView {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<EditorEvent> for TextDiffView {}
+
+impl Item for TextDiffView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::Diff).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _window: &Window, _cx: &App) -> Option<SharedString> {
+        Some(self.title.clone())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Text Diff Opened")
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::added_to_workspace(editor, workspace, window, cx)
+        });
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn std::any::Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| Item::navigate(editor, data, window, cx))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: workspace::ItemNavHistory,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            Item::set_nav_history(editor, nav_history, window, cx)
+        });
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.new_buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.new_buffer.read(cx).file().is_some()
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<gpui::Result<()>> {
+        self.editor.update(cx, |editor, cx| {
+            Item::save(editor, format, project, window, cx)
+        })
+    }
+}
@@ -11,6 +11,7 @@ use futures::future::join_all;
 pub use open_path_prompt::OpenPathDelegate;
 
 use collections::HashMap;
+use db::frecency::FRECENCY_STORE;
 use editor::Editor;
 use file_finder_settings::{FileFinderSettings, FileFinderWidth};
 use file_icons::FileIcons;
@@ -34,6 +35,7 @@ use std::{
         Arc,
         atomic::{self, AtomicBool},
     },
+    time::SystemTime,
 };
 use text::Point;
 use ui::{
@@ -46,7 +48,7 @@ use workspace::{
     notifications::NotifyResultExt, pane,
 };
 
-actions!(file_finder, [SelectPrevious, ToggleMenu]);
+actions!(file_finder, [SelectPrevious, ToggleMenu, ClearHistory]);
 
 impl ModalView for FileFinder {
     fn on_before_dismiss(
@@ -99,6 +101,12 @@ impl FileFinder {
                 });
             },
         );
+        workspace.register_action(|workspace, _: &ClearHistory, _, cx| {
+            if let Some(scope) = frecency_scope(workspace) {
+                cx.background_spawn(FRECENCY_STORE.clear(scope))
+                    .detach_and_log_err(cx);
+            }
+        });
     }
 
     fn open(
@@ -145,8 +153,46 @@ impl FileFinder {
                 }
             })
             .collect::<Vec<_>>();
+
+        let frecency_scope = frecency_scope(workspace);
+        let worktree_paths = project
+            .visible_worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (worktree.id(), worktree.abs_path())
+            })
+            .collect::<Vec<_>>();
+        let frecency: Task<HashMap<ProjectPath, f64>> = cx.background_spawn({
+            let frecency_scope = frecency_scope.clone();
+            async move {
+                let Some(scope) = frecency_scope else {
+                    return HashMap::default();
+                };
+                let now = SystemTime::now();
+                FRECENCY_STORE
+                    .load(&scope)
+                    .log_err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|(abs_path, frecency)| {
+                        let abs_path = Path::new(&abs_path);
+                        let project_path =
+                            worktree_paths
+                                .iter()
+                                .find_map(|(worktree_id, worktree_path)| {
+                                    Some(ProjectPath {
+                                        worktree_id: *worktree_id,
+                                        path: abs_path.strip_prefix(worktree_path).ok()?.into(),
+                                    })
+                                })?;
+                        Some((project_path, frecency.boost(now)))
+                    })
+                    .collect()
+            }
+        });
         cx.spawn_in(window, async move |workspace, cx| {
             let history_items = join_all(history_items).await.into_iter().flatten();
+            let frecency = Arc::new(frecency.await);
 
             workspace
                 .update_in(cx, |workspace, window, cx| {
@@ -160,6 +206,8 @@ impl FileFinder {
                             currently_opened_path,
                             history_items.collect(),
                             separate_history,
+                            frecency_scope,
+                            frecency,
                             window,
                             cx,
                         );
@@ -268,6 +316,7 @@ impl FileFinder {
             let delegate = &mut picker.delegate;
             if let Some(workspace) = delegate.workspace.upgrade() {
                 if let Some(m) = delegate.matches.get(delegate.selected_index()) {
+                    delegate.record_open(m, cx);
                     let path = match &m {
                         Match::History { path, .. } => {
                             let worktree_id = path.project.worktree_id;
@@ -348,6 +397,8 @@ pub struct FileFinderDelegate {
     cancel_flag: Arc<AtomicBool>,
     history_items: Vec<FoundPath>,
     separate_history: bool,
+    frecency_scope: Option<String>,
+    frecency: Arc<HashMap<ProjectPath, f64>>,
     first_update: bool,
     popover_menu_handle: PopoverMenuHandle<ContextMenu>,
     focus_handle: FocusHandle,
@@ -389,6 +440,8 @@ impl PartialOrd for ProjectPanelOrdMatch {
 #[derive(Debug, Default)]
 struct Matches {
     separate_history: bool,
+    /// How much to boost the scores of frequently and recently opened files.
+    frecency: Arc<HashMap<ProjectPath, f64>>,
     matches: Vec<Match>,
 }
 
@@ -409,6 +462,16 @@ impl Match {
         }
     }
 
+    fn project_path(&self) -> ProjectPath {
+        match self {
+            Match::History { path, .. } => path.project.clone(),
+            Match::Search(panel_match) => ProjectPath {
+                worktree_id: WorktreeId::from_usize(panel_match.0.worktree_id),
+                path: panel_match.0.path.clone(),
+            },
+        }
+    }
+
     fn panel_match(&self) -> Option<&ProjectPanelOrdMatch> {
         match self {
             Match::History { panel_match, .. } => panel_match.as_ref(),
@@ -448,7 +511,14 @@ impl Matches {
             self.matches.binary_search_by(|m| {
                 // `reverse()` since if cmp_matches(a, b) == Ordering::Greater, then a is better than b.
                 // And we want the better entries go first.
-                Self::cmp_matches(self.separate_history, currently_opened, &m, &entry).reverse()
+                Self::cmp_matches(
+                    self.separate_history,
+                    &self.frecency,
+                    currently_opened,
+                    &m,
+                    &entry,
+                )
+                .reverse()
             })
         }
     }
@@ -515,6 +585,7 @@ impl Matches {
     /// If a < b, then a is a worse match, aligning with the `ProjectPanelOrdMatch` ordering.
     fn cmp_matches(
         separate_history: bool,
+        frecency: &HashMap<ProjectPath, f64>,
         currently_opened: Option<&FoundPath>,
         a: &Match,
         b: &Match,
@@ -567,7 +638,13 @@ impl Matches {
             _ => {} // Both are filename matches or both are path matches
         }
 
-        a_panel_match.cmp(b_panel_match)
+        let boost = |m: &Match| frecency.get(&m.project_path()).copied().unwrap_or(1.0);
+        let a_score = a_panel_match.0.score * boost(a);
+        let b_score = b_panel_match.0.score * boost(b);
+        a_score
+            .partial_cmp(&b_score)
+            .unwrap_or(cmp::Ordering::Equal)
+            .then_with(|| a_panel_match.cmp(b_panel_match))
     }
 
     /// Determines if the match occurred within the filename rather than in the path
@@ -642,10 +719,10 @@ fn matching_history_items<'a>(
     for (worktree, candidates) in history_items_by_worktrees {
         let max_results = candidates.len() + 1;
         matching_history_paths.extend(
-            fuzzy::match_fixed_path_set(
+            fuzzy::match_fixed_path_set_with_query(
                 candidates,
                 worktree.to_usize(),
-                query.path_query(),
+                &fuzzy::Query::parse(query.path_query()),
                 false,
                 max_results,
             )
@@ -685,6 +762,13 @@ impl FoundPath {
 
 const MAX_RECENT_SELECTIONS: usize = 20;
 
+/// The scope of the files opened from the file finder in the workspace's project, in the frecency
+/// store. Workspaces that aren't persisted don't keep the files they opened.
+fn frecency_scope(workspace: &Workspace) -> Option<String> {
+    let database_id = workspace.database_id()?;
+    Some(format!("file_finder-{}", i64::from(database_id)))
+}
+
 pub enum Event {
     Selected(ProjectPath),
     Dismissed,
@@ -714,6 +798,8 @@ impl FileFinderDelegate {
         currently_opened_path: Option<FoundPath>,
         history_items: Vec<FoundPath>,
         separate_history: bool,
+        frecency_scope: Option<String>,
+        frecency: Arc<HashMap<ProjectPath, f64>>,
         window: &mut Window,
         cx: &mut Context<FileFinder>,
     ) -> Self {
@@ -733,12 +819,36 @@ impl FileFinderDelegate {
             cancel_flag: Arc::new(AtomicBool::new(false)),
             history_items,
             separate_history,
+            frecency_scope,
+            frecency,
             first_update: true,
             popover_menu_handle: PopoverMenuHandle::default(),
             focus_handle: cx.focus_handle(),
         }
     }
 
+    /// Records that the file of a match was opened, to rank it higher in later searches.
+    fn record_open(&self, m: &Match, cx: &App) {
+        let Some(scope) = self.frecency_scope.clone() else {
+            return;
+        };
+        let project_path = m.project_path();
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(project_path.worktree_id, cx)
+        else {
+            return;
+        };
+        let abs_path = worktree.read(cx).abs_path().join(&project_path.path);
+        cx.background_spawn(async move {
+            FRECENCY_STORE
+                .record_use(scope, abs_path.to_string_lossy().into_owned())
+                .await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn subscribe_to_updates(
         project: &Entity<Project>,
         window: &mut Window,
@@ -793,9 +903,9 @@ impl FileFinderDelegate {
         self.cancel_flag = Arc::new(AtomicBool::new(false));
         let cancel_flag = self.cancel_flag.clone();
         cx.spawn_in(window, async move |picker, cx| {
-            let matches = fuzzy::match_path_sets(
+            let matches = fuzzy::match_path_sets_with_query(
                 candidate_sets.as_slice(),
-                query.path_query(),
+                &fuzzy::Query::parse(query.path_query()),
                 relative_to,
                 false,
                 100,
@@ -1169,7 +1279,7 @@ impl PickerDelegate for FileFinderDelegate {
         window: &mut Window,
         cx: &mut Context<Picker<Self>>,
     ) -> Task<()> {
-        let raw_query = raw_query.replace(' ', "");
+        // Spaces separate the terms of extended fuzzy queries, so only the outer ones are trimmed.
         let raw_query = raw_query.trim();
         if raw_query.is_empty() {
             // if there was no query before, and we already have some (history) matches
@@ -1182,6 +1292,7 @@ impl PickerDelegate for FileFinderDelegate {
                 self.latest_search_query = None;
                 self.matches = Matches {
                     separate_history: self.separate_history,
+                    frecency: self.frecency.clone(),
                     ..Matches::default()
                 };
                 self.matches.push_new_matches(
@@ -1232,6 +1343,7 @@ impl PickerDelegate for FileFinderDelegate {
         cx: &mut Context<Picker<FileFinderDelegate>>,
     ) {
         if let Some(m) = self.matches.get(self.selected_index()) {
+            self.record_open(m, cx);
             if let Some(workspace) = self.workspace.upgrade() {
                 let open_task = workspace.update(cx, |workspace, cx| {
                     let split_or_open =
@@ -174,6 +174,45 @@ fn test_custom_project_search_ordering_in_file_finder() {
     );
 }
 
+#[test]
+fn test_frecency_boosts_matches() {
+    let search_match = |path: &str, score: f64| {
+        Match::Search(ProjectPanelOrdMatch(PathMatch {
+            score,
+            positions: Vec::new(),
+            worktree_id: 0,
+            path: Arc::from(Path::new(path)),
+            path_prefix: Arc::default(),
+            distance_to_relative_ancestor: 0,
+            is_dir: false,
+        }))
+    };
+    let better_match = search_match("a.rs", 1.0);
+    let frecent_match = search_match("b.rs", 0.8);
+
+    let no_frecency = HashMap::default();
+    assert_eq!(
+        Matches::cmp_matches(false, &no_frecency, None, &better_match, &frecent_match),
+        cmp::Ordering::Greater
+    );
+
+    let frecency = HashMap::from_iter([(frecent_match.project_path(), 1.5)]);
+    assert_eq!(
+        Matches::cmp_matches(false, &frecency, None, &better_match, &frecent_match),
+        cmp::Ordering::Less
+    );
+    assert_eq!(
+        Matches::cmp_matches(
+            false,
+            &frecency,
+            None,
+            &search_match("a.rs", 2.0),
+            &frecent_match
+        ),
+        cmp::Ordering::Greater
+    );
+}
+
 #[gpui::test]
 async fn test_matching_paths(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
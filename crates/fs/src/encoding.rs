@@ -0,0 +1,325 @@
+use anyhow::{Result, anyhow};
+use std::{borrow::Cow, fmt};
+
+/// How many bytes at the start of a file are inspected to guess its encoding.
+const DETECTION_LIMIT: usize = 64 * 1024;
+
+/// The character encoding of a file on disk, which is decoded to UTF-8 when the file is loaded
+/// and restored when it is saved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    /// Whether the file starts with a byte order mark.
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.encoding == encoding_rs::UTF_8 && self.bom {
+            write!(f, "{} with BOM", self.name())
+        } else if self.is_utf16() && !self.bom {
+            write!(f, "{} without BOM", self.name())
+        } else {
+            f.write_str(self.name())
+        }
+    }
+}
+
+impl Encoding {
+    pub const UTF8: Self = Self {
+        encoding: encoding_rs::UTF_8,
+        bom: false,
+    };
+
+    /// Returns the encoding with the given WHATWG label, such as `"shift_jis"` or `"latin1"`.
+    ///
+    /// UTF-16 files are written with a byte order mark, other files without one.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let encoding = encoding_rs::Encoding::for_label_no_replacement(name.trim().as_bytes())?;
+        let mut this = Self {
+            encoding,
+            bom: false,
+        };
+        this.bom = this.is_utf16();
+        Some(this)
+    }
+
+    /// Returns the encoding with the given WHATWG label, and whether files start with a byte
+    /// order mark.
+    pub fn from_name_and_bom(name: &str, bom: bool) -> Option<Self> {
+        Some(Self {
+            bom,
+            ..Self::from_name(name)?
+        })
+    }
+
+    /// The encodings that can be chosen to reopen or save a file.
+    pub fn all() -> Vec<Self> {
+        let mut encodings = vec![
+            Self::UTF8,
+            Self {
+                encoding: encoding_rs::UTF_8,
+                bom: true,
+            },
+        ];
+        encodings.extend(
+            [
+                encoding_rs::UTF_16LE,
+                encoding_rs::UTF_16BE,
+                encoding_rs::WINDOWS_1252,
+                encoding_rs::ISO_8859_2,
+                encoding_rs::ISO_8859_3,
+                encoding_rs::ISO_8859_4,
+                encoding_rs::ISO_8859_5,
+                encoding_rs::ISO_8859_6,
+                encoding_rs::ISO_8859_7,
+                encoding_rs::ISO_8859_8,
+                encoding_rs::ISO_8859_10,
+                encoding_rs::ISO_8859_13,
+                encoding_rs::ISO_8859_14,
+                encoding_rs::ISO_8859_15,
+                encoding_rs::ISO_8859_16,
+                encoding_rs::WINDOWS_1250,
+                encoding_rs::WINDOWS_1251,
+                encoding_rs::WINDOWS_1253,
+                encoding_rs::WINDOWS_1254,
+                encoding_rs::WINDOWS_1255,
+                encoding_rs::WINDOWS_1256,
+                encoding_rs::WINDOWS_1257,
+                encoding_rs::WINDOWS_1258,
+                encoding_rs::WINDOWS_874,
+                encoding_rs::KOI8_R,
+                encoding_rs::KOI8_U,
+                encoding_rs::IBM866,
+                encoding_rs::MACINTOSH,
+                encoding_rs::X_MAC_CYRILLIC,
+                encoding_rs::SHIFT_JIS,
+                encoding_rs::EUC_JP,
+                encoding_rs::ISO_2022_JP,
+                encoding_rs::GBK,
+                encoding_rs::GB18030,
+                encoding_rs::BIG5,
+                encoding_rs::EUC_KR,
+            ]
+            .into_iter()
+            .map(|encoding| Self::from_name(encoding.name()).unwrap()),
+        );
+        encodings
+    }
+
+    /// The WHATWG name of the encoding, such as `"UTF-8"` or `"Shift_JIS"`.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    fn is_utf16(&self) -> bool {
+        self.encoding == encoding_rs::UTF_16LE || self.encoding == encoding_rs::UTF_16BE
+    }
+
+    /// Guesses the encoding of a file's contents.
+    ///
+    /// A byte order mark takes precedence. Otherwise, contents where every other byte is zero
+    /// are assumed to be UTF-16, contents that are valid UTF-8 are assumed to be UTF-8, and the
+    /// encoding of anything else is guessed from the frequency of its bytes.
+    /// The returned encoding is always able to decode `bytes`. Fails when the contents contain
+    /// NUL bytes without being UTF-16 or UTF-8, as they are most likely binary.
+    pub fn detect(bytes: &[u8]) -> Result<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Ok(Self {
+                encoding,
+                bom: true,
+            });
+        }
+        let sample = &bytes[..bytes.len().min(DETECTION_LIMIT)];
+        let encoding = if let Some(encoding) = detect_utf16(sample) {
+            encoding
+        } else if std::str::from_utf8(bytes).is_ok() {
+            return Ok(Self::UTF8);
+        } else if bytes.contains(&0) {
+            return Err(anyhow!(
+                "file contains NUL bytes and can't be decoded as text"
+            ));
+        } else {
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(sample, sample.len() == bytes.len());
+            detector.guess(None, false)
+        };
+        let this = Self {
+            encoding,
+            bom: false,
+        };
+        if this.decode(bytes).is_ok() {
+            Ok(this)
+        } else {
+            // Every byte sequence is valid windows-1252, so the file can always be opened and
+            // saved without losing any of its contents.
+            Ok(Self {
+                encoding: encoding_rs::WINDOWS_1252,
+                bom: false,
+            })
+        }
+    }
+
+    /// Decodes a file's contents, skipping the byte order mark if the encoding has one.
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Result<Cow<'a, str>> {
+        let bytes = if self.bom {
+            bytes.strip_prefix(self.bom_bytes()).unwrap_or(bytes)
+        } else {
+            bytes
+        };
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .ok_or_else(|| anyhow!("file is not valid {}", self.name()))
+    }
+
+    /// Encodes text to be written to a file, preceded by a byte order mark if the encoding has
+    /// one. Fails if the text contains characters that the encoding can't represent.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let bom = if self.bom { self.bom_bytes() } else { &[] };
+        let encoded: Cow<[u8]> = if self.encoding == encoding_rs::UTF_16LE {
+            Cow::Owned(text.encode_utf16().flat_map(u16::to_le_bytes).collect())
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            Cow::Owned(text.encode_utf16().flat_map(u16::to_be_bytes).collect())
+        } else {
+            let (encoded, _, had_unmappable_characters) = self.encoding.encode(text);
+            if had_unmappable_characters {
+                let character = text
+                    .chars()
+                    .find(|character| {
+                        let mut buffer = [0; 4];
+                        self.encoding.encode(character.encode_utf8(&mut buffer)).2
+                    })
+                    .unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Err(anyhow!(
+                    "{character:?} can't be represented in {}",
+                    self.name()
+                ));
+            }
+            encoded
+        };
+
+        if bom.is_empty() {
+            Ok(encoded)
+        } else {
+            let mut bytes = Vec::with_capacity(bom.len() + encoded.len());
+            bytes.extend_from_slice(bom);
+            bytes.extend_from_slice(&encoded);
+            Ok(Cow::Owned(bytes))
+        }
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == encoding_rs::UTF_8 {
+            b"\xEF\xBB\xBF"
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            b"\xFF\xFE"
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            b"\xFE\xFF"
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Recognizes UTF-16 without a byte order mark from mostly ASCII text, where every other byte is
+/// zero.
+fn detect_utf16(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    if bytes.len() < 4 || bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units = bytes.len() / 2;
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in bytes.chunks_exact(2) {
+        even_zeros += (pair[0] == 0) as usize;
+        odd_zeros += (pair[1] == 0) as usize;
+    }
+    if odd_zeros * 10 >= units * 7 && even_zeros == 0 {
+        Some(encoding_rs::UTF_16LE)
+    } else if even_zeros * 10 >= units * 7 && odd_zeros == 0 {
+        Some(encoding_rs::UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Encoding::detect(b"plain ascii").unwrap(), Encoding::UTF8);
+        assert_eq!(
+            Encoding::detect("héllo wörld".as_bytes()).unwrap(),
+            Encoding::UTF8
+        );
+        assert_eq!(Encoding::detect(b"").unwrap(), Encoding::UTF8);
+
+        let with_bom = Encoding::detect(b"\xEF\xBB\xBFfn main() {}").unwrap();
+        assert_eq!(
+            with_bom,
+            Encoding::from_name_and_bom("utf-8", true).unwrap()
+        );
+        assert_eq!(with_bom.to_string(), "UTF-8 with BOM");
+
+        let utf16 = Encoding::detect(b"\xFF\xFEa\0b\0").unwrap();
+        assert_eq!(utf16, Encoding::from_name("utf-16le").unwrap());
+        assert_eq!(utf16.decode(b"\xFF\xFEa\0b\0").unwrap(), "ab");
+        assert_eq!(
+            Encoding::detect(b"\0h\0e\0l\0l\0o").unwrap(),
+            Encoding::from_name_and_bom("utf-16be", false).unwrap()
+        );
+
+        let (shift_jis, _, _) = encoding_rs::SHIFT_JIS.encode("日本語のテキストファイルです。");
+        assert_eq!(Encoding::detect(&shift_jis).unwrap().name(), "Shift_JIS");
+
+        let latin1 = b"Caf\xE9 cr\xE8me br\xFBl\xE9e, s'il vous pla\xEEt";
+        let detected = Encoding::detect(latin1).unwrap();
+        assert_eq!(detected.name(), "windows-1252");
+        assert_eq!(
+            detected.decode(latin1).unwrap(),
+            "Café crème brûlée, s'il vous plaît"
+        );
+
+        // Valid UTF-8 may contain NUL bytes, but other contents with them are binary.
+        assert_eq!(
+            Encoding::detect(b"text\0with a NUL").unwrap(),
+            Encoding::UTF8
+        );
+        assert!(Encoding::detect(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for (encoding, bytes) in [
+            ("utf-8", b"\xEF\xBB\xBFa\nb".as_slice()),
+            ("utf-16le", b"\xFF\xFEa\0\n\0b\0"),
+            ("utf-16be", b"\xFE\xFF\0a\0\n\0b"),
+        ] {
+            let encoding = Encoding::detect(bytes).unwrap();
+            assert!(encoding.has_bom(), "{encoding}");
+            let text = encoding.decode(bytes).unwrap();
+            assert_eq!(text, "a\nb");
+            assert_eq!(encoding.encode(&text).unwrap(), bytes);
+        }
+
+        let shift_jis = Encoding::from_name("sjis").unwrap();
+        let bytes = shift_jis.encode("こんにちは").unwrap().into_owned();
+        assert_eq!(shift_jis.decode(&bytes).unwrap(), "こんにちは");
+        assert!(Encoding::UTF8.decode(&bytes).is_err());
+
+        let latin1 = Encoding::from_name("latin1").unwrap();
+        assert_eq!(latin1.encode("café").unwrap(), b"caf\xE9".as_slice());
+        assert!(latin1.encode("日本").is_err());
+    }
+}
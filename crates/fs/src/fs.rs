@@ -1,3 +1,4 @@
+mod encoding;
 #[cfg(target_os = "macos")]
 mod mac_watcher;
 
@@ -14,6 +15,8 @@ use gpui::ReadGlobal as _;
 use std::borrow::Cow;
 use util::command::new_std_command;
 
+pub use encoding::Encoding;
+
 #[cfg(unix)]
 use std::os::fd::{AsFd, AsRawFd};
 
@@ -105,12 +108,41 @@ pub trait Fs: Send + Sync {
     }
     async fn open_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>>;
     async fn open_sync(&self, path: &Path) -> Result<Box<dyn io::Read + Send + Sync>>;
+    /// Opens a file for reading from any offset, without loading it into memory first.
+    async fn open_seekable(&self, path: &Path) -> Result<Box<dyn SeekableFile>>;
     async fn load(&self, path: &Path) -> Result<String> {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads a file and decodes it with the given encoding, or with the encoding detected from
+    /// its contents.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = self.load_bytes(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&bytes).with_context(|| format!("decoding {path:?}"))?,
+        };
+        let text = encoding
+            .decode(&bytes)
+            .with_context(|| format!("decoding {path:?}"))?;
+        Ok((text.into_owned(), encoding))
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+        self.save_with_encoding(path, text, line_ending, Encoding::UTF8)
+            .await
+    }
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -246,6 +278,10 @@ pub struct RealFs {
     executor: BackgroundExecutor,
 }
 
+pub trait SeekableFile: io::Read + io::Seek + Send + Sync {}
+
+impl<T: io::Read + io::Seek + Send + Sync> SeekableFile for T {}
+
 pub trait FileHandle: Send + Sync + std::fmt::Debug {
     fn current_path(&self, fs: &Arc<dyn Fs>) -> Result<PathBuf>;
 }
@@ -509,6 +545,10 @@ impl Fs for RealFs {
         Ok(Box::new(std::fs::File::open(path)?))
     }
 
+    async fn open_seekable(&self, path: &Path) -> Result<Box<dyn SeekableFile>> {
+        Ok(Box::new(std::fs::File::open(path)?))
+    }
+
     async fn open_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>> {
         Ok(Arc::new(std::fs::File::open(path)?))
     }
@@ -553,15 +593,33 @@ impl Fs for RealFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
+        // Encode before touching the file, so that text the encoding can't represent doesn't
+        // truncate it.
+        let encoded = if encoding == Encoding::UTF8 {
+            None
+        } else {
+            let text = chunks(text, line_ending).collect::<String>();
+            Some(encoding.encode(&text)?.into_owned())
+        };
         let buffer_size = text.summary().len.min(10 * 1024);
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
+        if let Some(encoded) = encoded {
+            writer.write_all(&encoded).await?;
+        } else {
+            for chunk in chunks(text, line_ending) {
+                writer.write_all(chunk.as_bytes()).await?;
+            }
         }
         writer.flush().await?;
         Ok(())
@@ -1980,6 +2038,11 @@ impl Fs for FakeFs {
         Ok(Box::new(io::Cursor::new(bytes)))
     }
 
+    async fn open_seekable(&self, path: &Path) -> Result<Box<dyn SeekableFile>> {
+        let bytes = self.load_internal(path).await?;
+        Ok(Box::new(io::Cursor::new(bytes)))
+    }
+
     async fn open_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>> {
         self.simulate_random_delay().await;
         let state = self.state.lock();
@@ -2009,14 +2072,21 @@ impl Fs for FakeFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save_with_encoding(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
         let content = chunks(text, line_ending).collect::<String>();
+        let content = encoding.encode(&content)?.into_owned();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, content.into_bytes(), false)?;
+        self.write_file_internal(path, content, false)?;
         Ok(())
     }
 
@@ -2769,4 +2839,66 @@ mod tests {
             "B"
         );
     }
+
+    #[gpui::test]
+    async fn test_load_and_save_with_encoding(executor: BackgroundExecutor) {
+        let fs = FakeFs::new(executor.clone());
+        let path = Path::new(path!("/root/latin1.txt"));
+        fs.insert_file(path, b"caf\xE9\r\nna\xEFve".to_vec()).await;
+
+        let (text, encoding) = fs.load_with_encoding(path, None).await.unwrap();
+        assert_eq!(text, "café\r\nnaïve");
+        assert_eq!(encoding.name(), "windows-1252");
+
+        fs.save_with_encoding(
+            path,
+            &Rope::from("crème\nbrûlée"),
+            LineEnding::Windows,
+            encoding,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            fs.read_file_sync(path).unwrap(),
+            b"cr\xE8me\r\nbr\xFBl\xE9e"
+        );
+
+        // Text that the encoding can't represent leaves the file untouched.
+        assert!(
+            fs.save_with_encoding(path, &Rope::from("日本"), LineEnding::Unix, encoding)
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            fs.read_file_sync(path).unwrap(),
+            b"cr\xE8me\r\nbr\xFBl\xE9e"
+        );
+
+        let utf16 = Encoding::from_name("utf-16le").unwrap();
+        fs.save_with_encoding(path, &Rope::from("日本"), LineEnding::Unix, utf16)
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.read_file_sync(path).unwrap(),
+            b"\xFF\xFE\xE5\x65\x2C\x67"
+        );
+        assert_eq!(
+            fs.load_with_encoding(path, None).await.unwrap(),
+            ("日本".to_string(), utf16)
+        );
+        assert!(fs.load(path).await.is_err());
+
+        // NUL bytes in UTF-8 files don't make them binary.
+        let path = Path::new(path!("/root/nul.txt"));
+        fs.insert_file(path, "a\0é".as_bytes().to_vec()).await;
+        assert_eq!(
+            fs.load_with_encoding(path, None).await.unwrap(),
+            ("a\0é".to_string(), Encoding::UTF8)
+        );
+
+        let path = Path::new(path!("/root/binary.bin"));
+        fs.insert_file(path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec())
+            .await;
+        assert!(fs.load_with_encoding(path, None).await.is_err());
+    }
 }
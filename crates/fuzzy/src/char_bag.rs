@@ -8,6 +8,11 @@ impl CharBag {
         self.0 & other.0 == other.0
     }
 
+    /// Returns a bag with the characters that are in either bag.
+    pub(crate) fn union(self, other: CharBag) -> CharBag {
+        CharBag(self.0 | other.0)
+    }
+
     fn insert(&mut self, c: char) {
         let c = c.to_ascii_lowercase();
         if c.is_ascii_lowercase() {
@@ -1,10 +1,13 @@
 mod char_bag;
 mod matcher;
 mod paths;
+mod query;
 mod strings;
 
 pub use char_bag::CharBag;
 pub use paths::{
-    PathMatch, PathMatchCandidate, PathMatchCandidateSet, match_fixed_path_set, match_path_sets,
+    PathMatch, PathMatchCandidate, PathMatchCandidateSet, match_fixed_path_set,
+    match_fixed_path_set_with_query, match_path_sets, match_path_sets_with_query,
 };
-pub use strings::{StringMatch, StringMatchCandidate, match_strings};
+pub use query::Query;
+pub use strings::{StringMatch, StringMatchCandidate, match_strings, match_strings_with_query};
@@ -3,7 +3,10 @@ use std::{
     sync::atomic::{self, AtomicBool},
 };
 
-use crate::CharBag;
+use crate::{
+    CharBag,
+    query::{Query, Term, TermKind},
+};
 
 const BASE_DISTANCE_PENALTY: f64 = 0.6;
 const ADDITIONAL_DISTANCE_PENALTY: f64 = 0.05;
@@ -12,9 +15,24 @@ const MIN_DISTANCE_PENALTY: f64 = 0.2;
 // TODO:
 // Use `Path` instead of `&str` for paths.
 pub struct Matcher<'a> {
+    groups: Vec<Vec<TermMatcher<'a>>>,
+    query_char_bag: CharBag,
+    smart_case: bool,
+    match_positions: Vec<usize>,
+    full_chars: Vec<char>,
+}
+
+/// Matches a single term of a [`Query`], keeping the positions of its last match.
+struct TermMatcher<'a> {
+    term: &'a Term,
+    fuzzy: FuzzyMatcher<'a>,
+    positions: Vec<usize>,
+}
+
+/// Scores fuzzy matches of a single pattern.
+struct FuzzyMatcher<'a> {
     query: &'a [char],
     lowercase_query: &'a [char],
-    query_char_bag: CharBag,
     smart_case: bool,
     min_score: f64,
     match_positions: Vec<usize>,
@@ -29,27 +47,38 @@ pub trait MatchCandidate {
 }
 
 impl<'a> Matcher<'a> {
-    pub fn new(
-        query: &'a [char],
-        lowercase_query: &'a [char],
-        query_char_bag: CharBag,
-        smart_case: bool,
-    ) -> Self {
+    pub fn new(query: &'a Query, smart_case: bool) -> Self {
         Self {
-            query,
-            lowercase_query,
-            query_char_bag,
-            min_score: 0.0,
-            last_positions: vec![0; lowercase_query.len()],
-            match_positions: vec![0; query.len()],
-            score_matrix: Vec::new(),
-            best_position_matrix: Vec::new(),
+            groups: query
+                .groups
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|term| TermMatcher {
+                            term,
+                            fuzzy: FuzzyMatcher::new(
+                                &term.chars,
+                                &term.lowercase_chars,
+                                smart_case,
+                            ),
+                            positions: Vec::new(),
+                        })
+                        .collect()
+                })
+                .collect(),
+            query_char_bag: query.char_bag(),
             smart_case,
+            match_positions: Vec::new(),
+            full_chars: Vec::new(),
         }
     }
 
     /// Filter and score fuzzy match candidates. Results are returned unsorted, in the same order as
     /// the input candidates.
+    ///
+    /// The score of a candidate is the product of the scores of the terms it matched, and its
+    /// positions are the union of their positions.
     pub fn match_candidates<C, R, F, T>(
         &mut self,
         prefix: &[char],
@@ -66,7 +95,7 @@ impl<'a> Matcher<'a> {
         let mut candidate_chars = Vec::new();
         let mut lowercase_candidate_chars = Vec::new();
 
-        for candidate in candidates {
+        'candidates: for candidate in candidates {
             if !candidate.borrow().has_chars(self.query_char_bag) {
                 continue;
             }
@@ -81,23 +110,37 @@ impl<'a> Matcher<'a> {
                 candidate_chars.push(c);
                 lowercase_candidate_chars.append(&mut c.to_lowercase().collect::<Vec<_>>());
             }
+            self.full_chars.clear();
+            self.full_chars.extend_from_slice(prefix);
+            self.full_chars.extend_from_slice(&candidate_chars);
+
+            let mut score = 1.0;
+            self.match_positions.clear();
+            for group in &mut self.groups {
+                let mut best_match: Option<(f64, usize)> = None;
+                for (term_ix, term_matcher) in group.iter_mut().enumerate() {
+                    let term_score = term_matcher.match_candidate(
+                        &candidate_chars,
+                        &lowercase_candidate_chars,
+                        prefix,
+                        lowercase_prefix,
+                        &self.full_chars,
+                        self.smart_case,
+                    );
+                    if term_score > best_match.map_or(0.0, |(score, _)| score) {
+                        best_match = Some((term_score, term_ix));
+                    }
+                }
 
-            if !self.find_last_positions(lowercase_prefix, &lowercase_candidate_chars) {
-                continue;
+                let Some((term_score, term_ix)) = best_match else {
+                    continue 'candidates;
+                };
+                score *= term_score;
+                self.match_positions
+                    .extend_from_slice(&group[term_ix].positions);
             }
-
-            let matrix_len = self.query.len() * (prefix.len() + candidate_chars.len());
-            self.score_matrix.clear();
-            self.score_matrix.resize(matrix_len, None);
-            self.best_position_matrix.clear();
-            self.best_position_matrix.resize(matrix_len, 0);
-
-            let score = self.score_match(
-                &candidate_chars,
-                &lowercase_candidate_chars,
-                prefix,
-                lowercase_prefix,
-            );
+            self.match_positions.sort_unstable();
+            self.match_positions.dedup();
 
             if score > 0.0 {
                 results.push(build_match(
@@ -108,6 +151,114 @@ impl<'a> Matcher<'a> {
             }
         }
     }
+}
+
+impl TermMatcher<'_> {
+    /// Scores the candidate against the term, returning zero if it doesn't match.
+    fn match_candidate(
+        &mut self,
+        candidate_chars: &[char],
+        lowercase_candidate_chars: &[char],
+        prefix: &[char],
+        lowercase_prefix: &[char],
+        full_chars: &[char],
+        smart_case: bool,
+    ) -> f64 {
+        self.positions.clear();
+        if self.term.kind == TermKind::Fuzzy {
+            let score = self.fuzzy.match_candidate(
+                candidate_chars,
+                lowercase_candidate_chars,
+                prefix,
+                lowercase_prefix,
+            );
+            self.positions
+                .extend_from_slice(&self.fuzzy.match_positions);
+            return score;
+        }
+
+        let case_sensitive = smart_case && self.term.chars.iter().any(|c| c.is_uppercase());
+        let start = find_exact(self.term, full_chars, case_sensitive);
+        if self.term.negated {
+            return if start.is_some() { 0.0 } else { 1.0 };
+        }
+        let Some(start) = start else {
+            return 0.0;
+        };
+
+        let mut byte_ix = full_chars[..start]
+            .iter()
+            .map(|c| c.len_utf8())
+            .sum::<usize>();
+        for c in &full_chars[start..start + self.term.chars.len()] {
+            self.positions.push(byte_ix);
+            byte_ix += c.len_utf8();
+        }
+        self.term.chars.len() as f64 / full_chars.len() as f64
+    }
+}
+
+/// Finds the char index where an exact, prefix or suffix term matches the candidate.
+fn find_exact(term: &Term, candidate: &[char], case_sensitive: bool) -> Option<usize> {
+    let len = term.chars.len();
+    if len > candidate.len() {
+        return None;
+    }
+    let matches_at = |start: usize| {
+        candidate[start..start + len]
+            .iter()
+            .zip(&term.chars)
+            .all(|(a, b)| a == b || (!case_sensitive && a.to_lowercase().eq(b.to_lowercase())))
+    };
+    match term.kind {
+        TermKind::Prefix => matches_at(0).then_some(0),
+        TermKind::Suffix => matches_at(candidate.len() - len).then_some(candidate.len() - len),
+        TermKind::Exact | TermKind::Fuzzy => {
+            (0..=candidate.len() - len).find(|&start| matches_at(start))
+        }
+    }
+}
+
+impl<'a> FuzzyMatcher<'a> {
+    fn new(query: &'a [char], lowercase_query: &'a [char], smart_case: bool) -> Self {
+        Self {
+            query,
+            lowercase_query,
+            min_score: 0.0,
+            last_positions: vec![0; lowercase_query.len()],
+            match_positions: vec![0; query.len()],
+            score_matrix: Vec::new(),
+            best_position_matrix: Vec::new(),
+            smart_case,
+        }
+    }
+
+    /// Scores the candidate against the pattern, storing the byte offsets of the matched
+    /// characters in `match_positions`. Returns zero if the candidate doesn't match.
+    fn match_candidate(
+        &mut self,
+        candidate_chars: &[char],
+        lowercase_candidate_chars: &[char],
+        prefix: &[char],
+        lowercase_prefix: &[char],
+    ) -> f64 {
+        if !self.find_last_positions(lowercase_prefix, lowercase_candidate_chars) {
+            return 0.0;
+        }
+
+        let matrix_len = self.query.len() * (prefix.len() + candidate_chars.len());
+        self.score_matrix.clear();
+        self.score_matrix.resize(matrix_len, None);
+        self.best_position_matrix.clear();
+        self.best_position_matrix.resize(matrix_len, 0);
+
+        self.score_match(
+            candidate_chars,
+            lowercase_candidate_chars,
+            prefix,
+            lowercase_prefix,
+        )
+    }
 
     fn find_last_positions(
         &mut self,
@@ -320,18 +471,18 @@ mod tests {
     #[test]
     fn test_get_last_positions() {
         let mut query: &[char] = &['d', 'c'];
-        let mut matcher = Matcher::new(query, query, query.into(), false);
+        let mut matcher = FuzzyMatcher::new(query, query, false);
         let result = matcher.find_last_positions(&['a', 'b', 'c'], &['b', 'd', 'e', 'f']);
         assert!(!result);
 
         query = &['c', 'd'];
-        let mut matcher = Matcher::new(query, query, query.into(), false);
+        let mut matcher = FuzzyMatcher::new(query, query, false);
         let result = matcher.find_last_positions(&['a', 'b', 'c'], &['b', 'd', 'e', 'f']);
         assert!(result);
         assert_eq!(matcher.last_positions, vec![2, 4]);
 
         query = &['z', '/', 'z', 'f'];
-        let mut matcher = Matcher::new(query, query, query.into(), false);
+        let mut matcher = FuzzyMatcher::new(query, query, false);
         let result = matcher.find_last_positions(&['z', 'e', 'd', '/'], &['z', 'e', 'd', '/', 'f']);
         assert!(result);
         assert_eq!(matcher.last_positions, vec![0, 3, 4, 8]);
@@ -471,14 +622,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_match_extended_query() {
+        let paths = vec![
+            "src/main.rs",
+            "src/lib.rs",
+            "tests/main.rs",
+            "README.md",
+            "src/main.go",
+        ];
+
+        assert_eq!(
+            match_single_path_query("'main", false, &paths),
+            vec![
+                ("src/main.rs", vec![4, 5, 6, 7]),
+                ("src/main.go", vec![4, 5, 6, 7]),
+                ("tests/main.rs", vec![6, 7, 8, 9]),
+            ]
+        );
+        assert_eq!(
+            match_single_path_query("^src rs$", false, &paths),
+            vec![
+                ("src/lib.rs", vec![0, 1, 2, 8, 9]),
+                ("src/main.rs", vec![0, 1, 2, 9, 10]),
+            ]
+        );
+        assert_eq!(
+            match_single_path_query("main !tests", false, &paths),
+            vec![
+                ("src/main.rs", vec![4, 5, 6, 7]),
+                ("src/main.go", vec![4, 5, 6, 7]),
+            ]
+        );
+        assert_eq!(
+            match_single_path_query("go$ | md$", false, &paths),
+            vec![("README.md", vec![7, 8]), ("src/main.go", vec![9, 10])]
+        );
+        assert_eq!(
+            match_single_path_query("^SRC", true, &paths),
+            Vec::<(&str, Vec<usize>)>::new()
+        );
+    }
+
     fn match_single_path_query<'a>(
         query: &str,
         smart_case: bool,
         paths: &[&'a str],
     ) -> Vec<(&'a str, Vec<usize>)> {
-        let lowercase_query = query.to_lowercase().chars().collect::<Vec<_>>();
-        let query = query.chars().collect::<Vec<_>>();
-        let query_chars = CharBag::from(&lowercase_query[..]);
+        let query = Query::parse(query);
 
         let path_arcs: Vec<Arc<Path>> = paths
             .iter()
@@ -495,7 +686,7 @@ mod tests {
             });
         }
 
-        let mut matcher = Matcher::new(&query, &lowercase_query, query_chars, smart_case);
+        let mut matcher = Matcher::new(&query, smart_case);
 
         let cancel_flag = AtomicBool::new(false);
         let mut results = Vec::new();
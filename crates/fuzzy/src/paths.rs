@@ -12,6 +12,7 @@ use std::{
 use crate::{
     CharBag,
     matcher::{MatchCandidate, Matcher},
+    query::Query,
 };
 
 #[derive(Clone, Debug)]
@@ -91,11 +92,25 @@ pub fn match_fixed_path_set(
     smart_case: bool,
     max_results: usize,
 ) -> Vec<PathMatch> {
-    let lowercase_query = query.to_lowercase().chars().collect::<Vec<_>>();
-    let query = query.chars().collect::<Vec<_>>();
-    let query_char_bag = CharBag::from(&lowercase_query[..]);
+    match_fixed_path_set_with_query(
+        candidates,
+        worktree_id,
+        &Query::fuzzy(query),
+        smart_case,
+        max_results,
+    )
+}
 
-    let mut matcher = Matcher::new(&query, &lowercase_query, query_char_bag, smart_case);
+/// Like [`match_fixed_path_set`], but matches a parsed [`Query`], such as one in the extended
+/// syntax.
+pub fn match_fixed_path_set_with_query(
+    candidates: Vec<PathMatchCandidate>,
+    worktree_id: usize,
+    query: &Query,
+    smart_case: bool,
+    max_results: usize,
+) -> Vec<PathMatch> {
+    let mut matcher = Matcher::new(query, smart_case);
 
     let mut results = Vec::new();
     matcher.match_candidates(
@@ -126,19 +141,34 @@ pub async fn match_path_sets<'a, Set: PathMatchCandidateSet<'a>>(
     max_results: usize,
     cancel_flag: &AtomicBool,
     executor: BackgroundExecutor,
+) -> Vec<PathMatch> {
+    match_path_sets_with_query(
+        candidate_sets,
+        &Query::fuzzy(query),
+        relative_to,
+        smart_case,
+        max_results,
+        cancel_flag,
+        executor,
+    )
+    .await
+}
+
+/// Like [`match_path_sets`], but matches a parsed [`Query`], such as one in the extended syntax.
+pub async fn match_path_sets_with_query<'a, Set: PathMatchCandidateSet<'a>>(
+    candidate_sets: &'a [Set],
+    query: &Query,
+    relative_to: Option<Arc<Path>>,
+    smart_case: bool,
+    max_results: usize,
+    cancel_flag: &AtomicBool,
+    executor: BackgroundExecutor,
 ) -> Vec<PathMatch> {
     let path_count: usize = candidate_sets.iter().map(|s| s.len()).sum();
     if path_count == 0 {
         return Vec::new();
     }
 
-    let lowercase_query = query.to_lowercase().chars().collect::<Vec<_>>();
-    let query = query.chars().collect::<Vec<_>>();
-
-    let lowercase_query = &lowercase_query;
-    let query = &query;
-    let query_char_bag = CharBag::from(&lowercase_query[..]);
-
     let num_cpus = executor.num_cpus().min(path_count);
     let segment_size = path_count.div_ceil(num_cpus);
     let mut segment_results = (0..num_cpus)
@@ -152,8 +182,7 @@ pub async fn match_path_sets<'a, Set: PathMatchCandidateSet<'a>>(
                 scope.spawn(async move {
                     let segment_start = segment_idx * segment_size;
                     let segment_end = segment_start + segment_size;
-                    let mut matcher =
-                        Matcher::new(query, lowercase_query, query_char_bag, smart_case);
+                    let mut matcher = Matcher::new(query, smart_case);
 
                     let mut tree_start = 0;
                     for candidate_set in candidate_sets {
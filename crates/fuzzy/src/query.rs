@@ -0,0 +1,225 @@
+use crate::CharBag;
+
+/// A query in fzf's extended search syntax.
+///
+/// The query is split into terms at unescaped spaces, and a candidate must match every term.
+/// Terms separated by `|` form a group that matches when any of them does. Each term is
+/// matched fuzzily unless it is written as:
+///
+/// - `'text`, which matches `text` exactly,
+/// - `^text`, which matches candidates starting with `text`,
+/// - `text$`, which matches candidates ending with `text`,
+/// - `!text`, `!^text` or `!text$`, which match candidates that don't match the rest of the
+///   term exactly.
+///
+/// A term that is empty without its operators, such as `^` or `!`, is matched fuzzily as
+/// written. Spaces can be escaped with a backslash to include them in a term.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Query {
+    pub(crate) groups: Vec<Vec<Term>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Term {
+    pub kind: TermKind,
+    pub negated: bool,
+    pub chars: Vec<char>,
+    pub lowercase_chars: Vec<char>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TermKind {
+    Fuzzy,
+    Exact,
+    Prefix,
+    Suffix,
+}
+
+impl Query {
+    /// Parses a query as a single fuzzy pattern, keeping any spaces and operators in it.
+    pub fn fuzzy(query: &str) -> Self {
+        if query.is_empty() {
+            return Self::default();
+        }
+        Self {
+            groups: vec![vec![Term::new(TermKind::Fuzzy, false, query)]],
+        }
+    }
+
+    /// Parses a query in the extended syntax.
+    pub fn parse(query: &str) -> Self {
+        let mut groups: Vec<Vec<Term>> = Vec::new();
+        let mut continues_group = false;
+        for token in tokenize(query) {
+            if token == "|" {
+                continues_group = !groups.is_empty();
+                continue;
+            }
+
+            let term = Term::parse(&token);
+            match groups.last_mut() {
+                Some(group) if continues_group => group.push(term),
+                _ => groups.push(vec![term]),
+            }
+            continues_group = false;
+        }
+        Self { groups }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// The characters that every matching candidate contains.
+    pub fn char_bag(&self) -> CharBag {
+        self.groups
+            .iter()
+            .filter_map(|group| match group.as_slice() {
+                [term] if !term.negated => Some(CharBag::from(term.lowercase_chars.as_slice())),
+                _ => None,
+            })
+            .fold(CharBag::default(), CharBag::union)
+    }
+}
+
+impl Term {
+    fn parse(token: &str) -> Self {
+        let (negated, text) = match token.strip_prefix('!') {
+            Some(text) => (true, text),
+            None => (false, token),
+        };
+        let (kind, text) = if let Some(text) = text.strip_prefix('\'') {
+            (TermKind::Exact, text)
+        } else if let Some(text) = text.strip_prefix('^') {
+            (TermKind::Prefix, text)
+        } else if let Some(text) = text.strip_suffix('$') {
+            (TermKind::Suffix, text)
+        } else if negated {
+            (TermKind::Exact, text)
+        } else {
+            (TermKind::Fuzzy, text)
+        };
+
+        if text.is_empty() {
+            Self::new(TermKind::Fuzzy, false, token)
+        } else {
+            Self::new(kind, negated, text)
+        }
+    }
+
+    fn new(kind: TermKind, negated: bool, text: &str) -> Self {
+        Self {
+            kind,
+            negated,
+            chars: text.chars().collect(),
+            lowercase_chars: text.to_lowercase().chars().collect(),
+        }
+    }
+}
+
+/// Splits a query at spaces that aren't escaped with a backslash.
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek() == Some(&' ') {
+            token.push(' ');
+            chars.next();
+        } else if c == ' ' {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn describe(query: &str) -> Vec<Vec<(TermKind, bool, String)>> {
+        Query::parse(query)
+            .groups
+            .into_iter()
+            .map(|group| {
+                group
+                    .into_iter()
+                    .map(|term| (term.kind, term.negated, term.chars.into_iter().collect()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        use TermKind::*;
+
+        assert_eq!(describe("abc"), [[(Fuzzy, false, "abc".into())]]);
+        assert_eq!(
+            describe("  'abc ^src rs$  "),
+            [
+                [(Exact, false, "abc".into())],
+                [(Prefix, false, "src".into())],
+                [(Suffix, false, "rs".into())],
+            ]
+        );
+        assert_eq!(
+            describe("!test !^target !.md$"),
+            [
+                [(Exact, true, "test".into())],
+                [(Prefix, true, "target".into())],
+                [(Suffix, true, ".md".into())],
+            ]
+        );
+        assert_eq!(
+            describe("^core go$ | rb$ | py$"),
+            vec![
+                vec![(Prefix, false, "core".into())],
+                vec![
+                    (Suffix, false, "go".into()),
+                    (Suffix, false, "rb".into()),
+                    (Suffix, false, "py".into()),
+                ],
+            ]
+        );
+        assert_eq!(
+            describe("| a | ! ^ $ '"),
+            vec![
+                vec![(Fuzzy, false, "a".into()), (Fuzzy, false, "!".into())],
+                vec![(Fuzzy, false, "^".into())],
+                vec![(Fuzzy, false, "$".into())],
+                vec![(Fuzzy, false, "'".into())],
+            ]
+        );
+        assert_eq!(
+            describe(r"'hello\ world"),
+            [[(Exact, false, "hello world".into())]]
+        );
+        assert!(Query::parse("   ").is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_query() {
+        assert_eq!(
+            Query::fuzzy("^a b|").groups,
+            [[Term::new(TermKind::Fuzzy, false, "^a b|")]]
+        );
+        assert!(Query::fuzzy("").is_empty());
+        assert!(!Query::fuzzy(" ").is_empty());
+    }
+
+    #[test]
+    fn test_query_char_bag() {
+        assert_eq!(Query::parse("ab").char_bag(), CharBag::from("ab"));
+        assert_eq!(Query::parse("ab 'ba").char_bag(), CharBag::from("ab"));
+        assert_eq!(Query::parse("ab !cd").char_bag(), CharBag::from("ab"));
+        assert_eq!(Query::parse("ab | cd").char_bag(), CharBag::default());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     CharBag,
     matcher::{MatchCandidate, Matcher},
+    query::Query,
 };
 use gpui::BackgroundExecutor;
 use std::{
@@ -121,6 +122,29 @@ pub async fn match_strings<T>(
     cancel_flag: &AtomicBool,
     executor: BackgroundExecutor,
 ) -> Vec<StringMatch>
+where
+    T: Borrow<StringMatchCandidate> + Sync,
+{
+    match_strings_with_query(
+        candidates,
+        &Query::fuzzy(query),
+        smart_case,
+        max_results,
+        cancel_flag,
+        executor,
+    )
+    .await
+}
+
+/// Like [`match_strings`], but matches a parsed [`Query`], such as one in the extended syntax.
+pub async fn match_strings_with_query<T>(
+    candidates: &[T],
+    query: &Query,
+    smart_case: bool,
+    max_results: usize,
+    cancel_flag: &AtomicBool,
+    executor: BackgroundExecutor,
+) -> Vec<StringMatch>
 where
     T: Borrow<StringMatchCandidate> + Sync,
 {
@@ -140,13 +164,6 @@ where
             .collect();
     }
 
-    let lowercase_query = query.to_lowercase().chars().collect::<Vec<_>>();
-    let query = query.chars().collect::<Vec<_>>();
-
-    let lowercase_query = &lowercase_query;
-    let query = &query;
-    let query_char_bag = CharBag::from(&lowercase_query[..]);
-
     let num_cpus = executor.num_cpus().min(candidates.len());
     let segment_size = candidates.len().div_ceil(num_cpus);
     let mut segment_results = (0..num_cpus)
@@ -160,8 +177,7 @@ where
                 scope.spawn(async move {
                     let segment_start = cmp::min(segment_idx * segment_size, candidates.len());
                     let segment_end = cmp::min(segment_start + segment_size, candidates.len());
-                    let mut matcher =
-                        Matcher::new(query, lowercase_query, query_char_bag, smart_case);
+                    let mut matcher = Matcher::new(query, smart_case);
 
                     matcher.match_candidates(
                         &[],
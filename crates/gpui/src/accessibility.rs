@@ -0,0 +1,508 @@
+//! GPUI builds an accessibility tree while painting each frame, so that assistive technologies
+//! such as screen readers can present the contents of a window.
+//!
+//! Elements opt into the tree by annotating themselves with an [`AccessibilityRole`] and
+//! related properties, for example with [`InteractiveElement::role`](crate::InteractiveElement::role).
+//! Text elements contribute [`AccessibilityRole::StaticText`] nodes automatically. The tree is only
+//! built while the platform reports that an assistive technology is observing the window.
+
+use crate::{Bounds, GlobalElementId, Pixels, SharedString};
+use collections::{FxHashMap, FxHasher};
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// The role of an element in the accessibility tree, which tells assistive technologies
+/// how to present it and how users can interact with it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AccessibilityRole {
+    /// The root of a window's accessibility tree.
+    Window,
+    /// A container for related elements without any other semantics.
+    #[default]
+    Group,
+    /// A distinct region of a window, such as a panel or an editor pane.
+    Pane,
+    /// A set of controls, such as buttons, presented together.
+    Toolbar,
+    /// A window or panel that asks for the user's attention, such as a modal.
+    Dialog,
+    /// An element that performs an action when clicked.
+    Button,
+    /// A control that can be checked or unchecked.
+    CheckBox,
+    /// A control that toggles a setting on or off.
+    Switch,
+    /// An element that navigates somewhere when clicked.
+    Link,
+    /// A run of text that isn't editable.
+    StaticText,
+    /// A heading for a section of content.
+    Heading,
+    /// An image or icon.
+    Image,
+    /// An editable single-line text field.
+    TextInput,
+    /// An editable multi-line text area, such as a code editor.
+    MultilineTextInput,
+    /// A list of items.
+    List,
+    /// An item within a [`AccessibilityRole::List`].
+    ListItem,
+    /// A list from which the user can select items, such as the matches in a picker.
+    ListBox,
+    /// A selectable item within a [`AccessibilityRole::ListBox`].
+    ListBoxOption,
+    /// A hierarchical list of items, such as a file tree.
+    Tree,
+    /// An item within a [`AccessibilityRole::Tree`], which can be expanded if it has children.
+    TreeItem,
+    /// A set of tabs.
+    TabList,
+    /// A tab within a [`AccessibilityRole::TabList`].
+    Tab,
+    /// A menu of commands.
+    Menu,
+    /// A command within a [`AccessibilityRole::Menu`].
+    MenuItem,
+    /// A region whose contents can be scrolled.
+    ScrollView,
+    /// A region that presents status information.
+    Status,
+    /// A popup containing information about another element.
+    Tooltip,
+}
+
+impl AccessibilityRole {
+    /// Whether nodes with this role take their name from the text they contain when they
+    /// don't have an explicit name.
+    fn is_named_from_contents(self) -> bool {
+        matches!(
+            self,
+            Self::Button
+                | Self::CheckBox
+                | Self::Switch
+                | Self::Link
+                | Self::Heading
+                | Self::ListItem
+                | Self::ListBoxOption
+                | Self::TreeItem
+                | Self::Tab
+                | Self::MenuItem
+                | Self::Tooltip
+        )
+    }
+}
+
+/// The accessibility annotations for an element.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccessibilityProperties {
+    /// The role of the element.
+    pub role: AccessibilityRole,
+    /// The name that assistive technologies announce for the element. Elements with roles
+    /// such as buttons and list items are named after the text they contain when this is unset.
+    pub name: Option<SharedString>,
+    /// The current value of the element, such as the contents of a text field.
+    pub value: Option<SharedString>,
+    /// Whether the element is checked, for check boxes and switches.
+    pub checked: Option<bool>,
+    /// Whether the element is selected, for options, tabs and tree items.
+    pub selected: Option<bool>,
+    /// Whether the element is expanded, for tree items and other disclosures.
+    pub expanded: Option<bool>,
+    /// Whether the element is disabled.
+    pub disabled: bool,
+}
+
+impl AccessibilityProperties {
+    /// Creates properties for an element with the given role.
+    pub fn new(role: AccessibilityRole) -> Self {
+        Self {
+            role,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the name of the element.
+    pub fn name(mut self, name: impl Into<SharedString>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the value of the element.
+    pub fn value(mut self, value: impl Into<SharedString>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+}
+
+/// The identifier of a node in an [`AccessibilityTree`], which is stable across frames for
+/// elements with an [`ElementId`](crate::ElementId).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AccessibilityNodeId(pub u64);
+
+/// A node in an [`AccessibilityTree`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessibilityNode {
+    /// The identifier of this node.
+    pub id: AccessibilityNodeId,
+    /// The annotations of the element this node was built from.
+    pub properties: AccessibilityProperties,
+    /// Whether this node's element has keyboard focus.
+    pub focused: bool,
+    /// The bounds of this node's element, relative to the window.
+    pub bounds: Bounds<Pixels>,
+    /// The children of this node, in paint order.
+    pub children: Vec<AccessibilityNodeId>,
+}
+
+impl AccessibilityNode {
+    /// The role of this node.
+    pub fn role(&self) -> AccessibilityRole {
+        self.properties.role
+    }
+
+    /// The name of this node, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.properties.name.as_deref()
+    }
+
+    /// The value of this node, if it has one.
+    pub fn value(&self) -> Option<&str> {
+        self.properties.value.as_deref()
+    }
+}
+
+/// The accessibility tree of a window, rooted at an [`AccessibilityRole::Window`] node.
+#[derive(Clone, Debug)]
+pub struct AccessibilityTree {
+    /// The nodes in depth-first order, starting with the root.
+    nodes: Vec<AccessibilityNode>,
+    indices: FxHashMap<AccessibilityNodeId, usize>,
+    focus: AccessibilityNodeId,
+}
+
+/// An entry recorded while painting, from which the [`AccessibilityTree`] is built.
+#[derive(Clone, Debug)]
+pub(crate) enum AccessibilityRecord {
+    Push {
+        key: Option<u64>,
+        properties: AccessibilityProperties,
+        bounds: Bounds<Pixels>,
+        focused: bool,
+    },
+    Pop,
+}
+
+impl AccessibilityRecord {
+    pub(crate) fn push(
+        id: Option<&GlobalElementId>,
+        properties: AccessibilityProperties,
+        bounds: Bounds<Pixels>,
+        focused: bool,
+    ) -> Self {
+        Self::Push {
+            key: id.map(|id| {
+                let mut hasher = FxHasher::default();
+                id.hash(&mut hasher);
+                hasher.finish()
+            }),
+            properties,
+            bounds,
+            focused,
+        }
+    }
+}
+
+impl AccessibilityTree {
+    pub(crate) const ROOT_ID: AccessibilityNodeId = AccessibilityNodeId(0);
+
+    pub(crate) fn build(records: &[AccessibilityRecord], window_bounds: Bounds<Pixels>) -> Self {
+        let mut tree = Self {
+            nodes: vec![AccessibilityNode {
+                id: Self::ROOT_ID,
+                properties: AccessibilityProperties::new(AccessibilityRole::Window),
+                focused: false,
+                bounds: window_bounds,
+                children: Vec::new(),
+            }],
+            indices: FxHashMap::default(),
+            focus: Self::ROOT_ID,
+        };
+        tree.indices.insert(Self::ROOT_ID, 0);
+
+        let mut stack = vec![0];
+        for record in records {
+            match record {
+                AccessibilityRecord::Push {
+                    key,
+                    properties,
+                    bounds,
+                    focused,
+                } => {
+                    let parent_ix = *stack.last().unwrap();
+                    let parent = &tree.nodes[parent_ix];
+                    let id = match key {
+                        Some(key) if !tree.indices.contains_key(&AccessibilityNodeId(*key)) => {
+                            AccessibilityNodeId(*key)
+                        }
+                        // Elements without an id are identified by their position in the parent,
+                        // as are elements that were painted more than once.
+                        _ => {
+                            let mut hasher = FxHasher::default();
+                            (parent.id.0, parent.children.len(), key).hash(&mut hasher);
+                            AccessibilityNodeId(hasher.finish())
+                        }
+                    };
+                    if *focused {
+                        tree.focus = id;
+                    }
+
+                    let ix = tree.nodes.len();
+                    tree.nodes[parent_ix].children.push(id);
+                    tree.nodes.push(AccessibilityNode {
+                        id,
+                        properties: properties.clone(),
+                        focused: *focused,
+                        bounds: *bounds,
+                        children: Vec::new(),
+                    });
+                    tree.indices.insert(id, ix);
+                    stack.push(ix);
+                }
+                AccessibilityRecord::Pop => {
+                    if stack.len() > 1 {
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        // Nodes are in depth-first order, so visiting them in reverse names each node
+        // after its descendants have been named.
+        for ix in (0..tree.nodes.len()).rev() {
+            let node = &tree.nodes[ix];
+            if node.properties.name.is_some() || !node.role().is_named_from_contents() {
+                continue;
+            }
+            let mut name = String::new();
+            tree.collect_text(node, &mut name);
+            if !name.is_empty() {
+                tree.nodes[ix].properties.name = Some(name.into());
+            }
+        }
+
+        tree
+    }
+
+    fn collect_text(&self, node: &AccessibilityNode, text: &mut String) {
+        for child in self.children(node) {
+            if child.role() == AccessibilityRole::StaticText {
+                if let Some(name) = child.name() {
+                    if !text.is_empty() {
+                        text.push(' ');
+                    }
+                    text.push_str(name);
+                }
+            } else {
+                self.collect_text(child, text);
+            }
+        }
+    }
+
+    /// The root node of the tree, which represents the window.
+    pub fn root(&self) -> &AccessibilityNode {
+        &self.nodes[0]
+    }
+
+    /// The node with keyboard focus, or the root if no annotated element is focused.
+    pub fn focus(&self) -> &AccessibilityNode {
+        self.node(self.focus).unwrap_or_else(|| self.root())
+    }
+
+    /// Returns the node with the given id.
+    pub fn node(&self, id: AccessibilityNodeId) -> Option<&AccessibilityNode> {
+        self.indices.get(&id).map(|ix| &self.nodes[*ix])
+    }
+
+    /// Returns the children of the given node.
+    pub fn children<'a>(
+        &'a self,
+        node: &'a AccessibilityNode,
+    ) -> impl Iterator<Item = &'a AccessibilityNode> + 'a {
+        node.children.iter().filter_map(|id| self.node(*id))
+    }
+
+    /// Returns every node in the tree, in depth-first order.
+    pub fn nodes(&self) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes.iter()
+    }
+
+    /// Returns the first node, in depth-first order, with the given role and name.
+    pub fn find(&self, role: AccessibilityRole, name: &str) -> Option<&AccessibilityNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.role() == role && node.name() == Some(name))
+    }
+
+    /// Returns every node with the given role, in depth-first order.
+    pub fn find_all(&self, role: AccessibilityRole) -> impl Iterator<Item = &AccessibilityNode> {
+        self.nodes.iter().filter(move |node| node.role() == role)
+    }
+
+    fn fmt_node(
+        &self,
+        node: &AccessibilityNode,
+        depth: usize,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{:indent$}{:?}", "", node.role(), indent = depth * 2)?;
+        if let Some(name) = node.name() {
+            write!(f, " {name:?}")?;
+        }
+        if let Some(value) = node.value() {
+            write!(f, " value={value:?}")?;
+        }
+        let properties = &node.properties;
+        for (flag, value) in [
+            ("checked", properties.checked),
+            ("selected", properties.selected),
+            ("expanded", properties.expanded),
+        ] {
+            if let Some(value) = value {
+                write!(f, " {flag}={value}")?;
+            }
+        }
+        if properties.disabled {
+            write!(f, " disabled")?;
+        }
+        if node.focused {
+            write!(f, " focused")?;
+        }
+        writeln!(f)?;
+        for child in self.children(node) {
+            self.fmt_node(child, depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the tree as an indented outline with one node per line, which is convenient
+/// for assertions in tests.
+impl fmt::Display for AccessibilityTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_node(self.root(), 0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        self as gpui, AccessibilityRole, Context, FocusHandle, InteractiveElement, IntoElement,
+        ParentElement, Render, Styled, StyledText, TestAppContext, Window, div, px, size,
+        uniform_list,
+    };
+
+    struct TestView {
+        focus_handle: FocusHandle,
+        selected_ix: usize,
+    }
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+            div()
+                .size_full()
+                .child(StyledText::new("Files").with_accessibility_role(AccessibilityRole::Heading))
+                .child(
+                    div()
+                        .role(AccessibilityRole::Toolbar)
+                        .child(
+                            div()
+                                .id("save")
+                                .role(AccessibilityRole::Button)
+                                .child("Save"),
+                        )
+                        .child(
+                            div()
+                                .role(AccessibilityRole::Button)
+                                .accessible_name("Close")
+                                .accessible_disabled(true),
+                        ),
+                )
+                .child(
+                    uniform_list(cx.entity(), "matches", 2, |this, range, _, _| {
+                        range
+                            .map(|ix| {
+                                div()
+                                    .id(ix)
+                                    .h(px(20.))
+                                    .role(AccessibilityRole::ListBoxOption)
+                                    .accessible_selected(ix == this.selected_ix)
+                                    .child(format!("Item {ix}"))
+                            })
+                            .collect()
+                    })
+                    .track_focus(&self.focus_handle)
+                    .role(AccessibilityRole::ListBox)
+                    .accessible_name("Matches")
+                    .h(px(100.)),
+                )
+        }
+    }
+
+    #[gpui::test]
+    fn test_accessibility_tree(cx: &mut TestAppContext) {
+        let (view, cx) = cx.add_window_view(|_, cx| TestView {
+            focus_handle: cx.focus_handle(),
+            selected_ix: 1,
+        });
+        cx.simulate_resize(size(px(200.), px(200.)));
+
+        let tree = cx.accessibility_tree();
+        assert_eq!(
+            tree.to_string(),
+            concat!(
+                "Window\n",
+                "  Heading \"Files\"\n",
+                "  Toolbar\n",
+                "    Button \"Save\"\n",
+                "      StaticText \"Save\"\n",
+                "    Button \"Close\" disabled\n",
+                "  ListBox \"Matches\"\n",
+                "    ListBoxOption \"Item 0\" selected=false\n",
+                "      StaticText \"Item 0\"\n",
+                "    ListBoxOption \"Item 1\" selected=true\n",
+                "      StaticText \"Item 1\"\n",
+            )
+        );
+        assert_eq!(tree.focus().id, tree.root().id);
+        let save_id = tree.find(AccessibilityRole::Button, "Save").unwrap().id;
+        let option = tree
+            .find(AccessibilityRole::ListBoxOption, "Item 1")
+            .unwrap();
+        assert_eq!(option.bounds.size.height, px(20.));
+
+        cx.update(|window, cx| {
+            view.update(cx, |view, cx| {
+                view.selected_ix = 0;
+                window.focus(&view.focus_handle);
+                cx.notify();
+            })
+        });
+        let tree = cx.accessibility_tree();
+        let list = tree.focus();
+        assert_eq!(list.role(), AccessibilityRole::ListBox);
+        assert_eq!(
+            tree.children(list)
+                .map(|option| option.properties.selected)
+                .collect::<Vec<_>>(),
+            [Some(true), Some(false)]
+        );
+        // Elements with an id keep their node id across frames.
+        assert_eq!(
+            tree.find(AccessibilityRole::Button, "Save").unwrap().id,
+            save_id
+        );
+    }
+}
@@ -1,15 +1,18 @@
 use crate::{
-    Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp, AvailableSpace,
-    BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, DrawPhase, Drawable, Element,
-    Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke, Modifiers,
-    ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels,
-    Platform, Point, Render, Result, Size, Task, TestDispatcher, TestPlatform,
-    TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window, WindowBounds,
-    WindowHandle, WindowOptions,
+    AccessibilityTree, Action, AnyView, AnyWindowHandle, App, AppCell, AppContext, AsyncApp,
+    AvailableSpace, BackgroundExecutor, BorrowAppContext, Bounds, ClipboardItem, DrawPhase,
+    Drawable, Element, Empty, EventEmitter, ForegroundExecutor, Global, InputEvent, Keystroke,
+    Modifiers, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+    Pixels, Platform, Point, Render, Result, Size, SnapshotTolerance, Task, TestDispatcher,
+    TestPlatform, TestScreenCaptureSource, TestWindow, TextSystem, VisualContext, Window,
+    WindowBounds, WindowHandle, WindowOptions, compare_images, rasterize_scene,
 };
 use anyhow::{anyhow, bail};
 use futures::{Stream, StreamExt, channel::oneshot};
-use std::{cell::RefCell, future::Future, ops::Deref, rc::Rc, sync::Arc, time::Duration};
+use image::RgbaImage;
+use std::{
+    cell::RefCell, future::Future, ops::Deref, path::Path, rc::Rc, sync::Arc, time::Duration,
+};
 
 /// A TestAppContext is provided to tests created with `#[gpui::test]`, it provides
 /// an implementation of `Context` with additional methods that are useful in tests.
@@ -443,6 +446,67 @@ impl TestAppContext {
             .clone()
     }
 
+    /// Draws the given window with accessibility enabled and returns its accessibility tree,
+    /// as an assistive technology such as a screen reader would see it.
+    pub fn accessibility_tree(&mut self, window: AnyWindowHandle) -> AccessibilityTree {
+        let test_window = self.test_window(window);
+        test_window.0.lock().accessibility_enabled = true;
+        self.update_window(window, |_, window, cx| window.draw(cx))
+            .unwrap();
+        let tree = test_window.0.lock().accessibility_tree.clone();
+        tree.expect("drawing the window should have built an accessibility tree")
+    }
+
+    /// Draws the given window and rasterizes it in software, returning an image of its contents
+    /// in device pixels. This doesn't need a GPU, so it can be used for visual snapshot tests.
+    pub fn capture_window(&mut self, window: AnyWindowHandle) -> RgbaImage {
+        let atlas = self.test_window(window).0.lock().sprite_atlas.clone();
+        self.update_window(window, |_, window, cx| {
+            window.draw(cx);
+            let size = window
+                .viewport_size()
+                .to_device_pixels(window.scale_factor());
+            rasterize_scene(&window.rendered_frame.scene, size, &atlas)
+        })
+        .unwrap()
+    }
+
+    /// Captures the given window and compares it against the PNG snapshot at `path`.
+    ///
+    /// When the images differ, the captured image is written next to the snapshot with an
+    /// `.actual.png` extension. Run the test with `UPDATE_SNAPSHOTS=1` to record the captured
+    /// image as the new snapshot instead.
+    #[track_caller]
+    pub fn assert_window_snapshot(
+        &mut self,
+        window: AnyWindowHandle,
+        path: impl AsRef<Path>,
+        tolerance: SnapshotTolerance,
+    ) {
+        let path = path.as_ref();
+        let actual = self.capture_window(window);
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).unwrap();
+            }
+            actual.save(path).unwrap();
+            return;
+        }
+
+        let result = match image::open(path) {
+            Ok(expected) => compare_images(&expected.into_rgba8(), &actual, tolerance),
+            Err(error) => Err(format!("couldn't load snapshot: {error}")),
+        };
+        if let Err(message) = result {
+            let actual_path = path.with_extension("actual.png");
+            actual.save(&actual_path).unwrap();
+            panic!(
+                "window doesn't match snapshot {path:?}: {message}\n\
+                captured image saved to {actual_path:?}; rerun with UPDATE_SNAPSHOTS=1 to accept it"
+            );
+        }
+    }
+
     /// Returns a stream of notifications whenever the Entity is updated.
     pub fn notifications<T: 'static>(
         &mut self,
@@ -779,6 +843,23 @@ impl VisualTestContext {
         self.simulate_window_resize(self.window, size)
     }
 
+    /// Draws the window and rasterizes it in software. See [TestAppContext::capture_window].
+    pub fn capture_image(&mut self) -> RgbaImage {
+        self.cx.capture_window(self.window)
+    }
+
+    /// Compares the window against a PNG snapshot. See [TestAppContext::assert_window_snapshot].
+    #[track_caller]
+    pub fn assert_snapshot(&mut self, path: impl AsRef<Path>, tolerance: SnapshotTolerance) {
+        self.cx.assert_window_snapshot(self.window, path, tolerance)
+    }
+
+    /// Draws the window with accessibility enabled and returns its accessibility tree. See
+    /// [TestAppContext::accessibility_tree].
+    pub fn accessibility_tree(&mut self) -> AccessibilityTree {
+        self.cx.accessibility_tree(self.window)
+    }
+
     /// debug_bounds returns the bounds of the element with the given selector.
     pub fn debug_bounds(&mut self, selector: &'static str) -> Option<Bounds<Pixels>> {
         self.update(|window, _| window.rendered_frame.debug_bounds.get(selector).copied())
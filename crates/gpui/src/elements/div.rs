@@ -16,12 +16,12 @@
 //! constructed by combining these two systems into an all-in-one element.
 
 use crate::{
-    Action, AnyDrag, AnyElement, AnyTooltip, AnyView, App, Bounds, ClickEvent, DispatchPhase,
-    Element, ElementId, Entity, FocusHandle, Global, GlobalElementId, Hitbox, HitboxId,
-    IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent, LayoutId, ModifiersChangedEvent,
-    MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, ParentElement, Pixels, Point,
-    Render, ScrollWheelEvent, SharedString, Size, Style, StyleRefinement, Styled, Task, TooltipId,
-    Visibility, Window, point, px, size,
+    AccessibilityProperties, AccessibilityRole, Action, AnyDrag, AnyElement, AnyTooltip, AnyView,
+    App, Bounds, ClickEvent, DispatchPhase, Element, ElementId, Entity, FocusHandle, Global,
+    GlobalElementId, Hitbox, HitboxId, IntoElement, IsZero, KeyContext, KeyDownEvent, KeyUpEvent,
+    LayoutId, ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
+    ParentElement, Pixels, Point, Render, ScrollWheelEvent, SharedString, Size, Style,
+    StyleRefinement, Styled, Task, TooltipId, Visibility, Window, point, px, size,
 };
 use collections::HashMap;
 use refineable::Refineable;
@@ -639,6 +639,51 @@ pub trait InteractiveElement: Sized {
         self
     }
 
+    /// Set the role of this element in the window's accessibility tree, which tells
+    /// assistive technologies such as screen readers how to present it
+    fn role(mut self, role: AccessibilityRole) -> Self {
+        self.interactivity().accessibility_mut().role = role;
+        self
+    }
+
+    /// Set the name that assistive technologies announce for this element. Elements with roles
+    /// such as buttons and list items are named after the text they contain by default
+    fn accessible_name(mut self, name: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().name = Some(name.into());
+        self
+    }
+
+    /// Set the value that assistive technologies announce for this element, such as the
+    /// contents of a text field
+    fn accessible_value(mut self, value: impl Into<SharedString>) -> Self {
+        self.interactivity().accessibility_mut().value = Some(value.into());
+        self
+    }
+
+    /// Mark this element as checked or unchecked for assistive technologies
+    fn accessible_checked(mut self, checked: bool) -> Self {
+        self.interactivity().accessibility_mut().checked = Some(checked);
+        self
+    }
+
+    /// Mark this element as selected or unselected for assistive technologies
+    fn accessible_selected(mut self, selected: bool) -> Self {
+        self.interactivity().accessibility_mut().selected = Some(selected);
+        self
+    }
+
+    /// Mark this element as expanded or collapsed for assistive technologies
+    fn accessible_expanded(mut self, expanded: bool) -> Self {
+        self.interactivity().accessibility_mut().expanded = Some(expanded);
+        self
+    }
+
+    /// Mark this element as disabled for assistive technologies
+    fn accessible_disabled(mut self, disabled: bool) -> Self {
+        self.interactivity().accessibility_mut().disabled = disabled;
+        self
+    }
+
     /// Bind the given callback to the mouse down event for any button, during the capture phase
     /// the fluent API equivalent to [`Interactivity::capture_any_mouse_down`]
     ///
@@ -1363,6 +1408,7 @@ pub struct Interactivity {
     pub(crate) hover_listener: Option<Box<dyn Fn(&bool, &mut Window, &mut App)>>,
     pub(crate) tooltip_builder: Option<TooltipBuilder>,
     pub(crate) occlude_mouse: bool,
+    pub(crate) accessibility: Option<Box<AccessibilityProperties>>,
 
     #[cfg(debug_assertions)]
     pub(crate) location: Option<core::panic::Location<'static>>,
@@ -1372,6 +1418,12 @@ pub struct Interactivity {
 }
 
 impl Interactivity {
+    /// The accessibility annotations of this element, which are added to the window's
+    /// accessibility tree when any are set.
+    pub fn accessibility_mut(&mut self) -> &mut AccessibilityProperties {
+        self.accessibility.get_or_insert_with(Default::default)
+    }
+
     /// Layout this element according to this interactivity state's configured styles
     pub fn request_layout(
         &mut self,
@@ -1639,7 +1691,26 @@ impl Interactivity {
                                     }
 
                                     self.paint_keyboard_listeners(window, cx);
-                                    f(&style, window, cx);
+                                    match self
+                                        .accessibility
+                                        .as_deref()
+                                        .filter(|_| window.accessibility_enabled())
+                                    {
+                                        Some(properties) => {
+                                            let focused = self
+                                                .tracked_focus_handle
+                                                .as_ref()
+                                                .is_some_and(|handle| handle.is_focused(window));
+                                            window.with_accessibility_node(
+                                                global_id,
+                                                properties.clone(),
+                                                bounds,
+                                                focused,
+                                                |window| f(&style, window, cx),
+                                            );
+                                        }
+                                        None => f(&style, window, cx),
+                                    }
 
                                     if hitbox.is_some() {
                                         if let Some(group) = self.group.as_ref() {
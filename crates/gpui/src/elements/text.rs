@@ -1,8 +1,9 @@
 use crate::{
-    ActiveTooltip, AnyView, App, Bounds, DispatchPhase, Element, ElementId, GlobalElementId,
-    HighlightStyle, Hitbox, IntoElement, LayoutId, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, SharedString, Size, TextOverflow, TextRun, TextStyle, TooltipId, WhiteSpace,
-    Window, WrappedLine, WrappedLineLayout, register_tooltip_mouse_handlers, set_tooltip_on_window,
+    AccessibilityProperties, AccessibilityRole, ActiveTooltip, AnyView, App, Bounds, DispatchPhase,
+    Element, ElementId, GlobalElementId, HighlightStyle, Hitbox, IntoElement, LayoutId,
+    MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point, SharedString, Size, TextOverflow,
+    TextRun, TextStyle, TooltipId, WhiteSpace, Window, WrappedLine, WrappedLineLayout,
+    register_tooltip_mouse_handlers, set_tooltip_on_window,
 };
 use anyhow::anyhow;
 use smallvec::SmallVec;
@@ -54,7 +55,7 @@ impl Element for &'static str {
         window: &mut Window,
         cx: &mut App,
     ) {
-        text_layout.paint(self, window, cx)
+        text_layout.paint(self, AccessibilityRole::StaticText, window, cx)
     }
 }
 
@@ -115,7 +116,7 @@ impl Element for SharedString {
         window: &mut Window,
         cx: &mut App,
     ) {
-        text_layout.paint(self.as_ref(), window, cx)
+        text_layout.paint(self.as_ref(), AccessibilityRole::StaticText, window, cx)
     }
 }
 
@@ -137,6 +138,7 @@ pub struct StyledText {
     runs: Option<Vec<TextRun>>,
     delayed_highlights: Option<Vec<(Range<usize>, HighlightStyle)>>,
     layout: TextLayout,
+    accessibility_role: AccessibilityRole,
 }
 
 impl StyledText {
@@ -147,6 +149,7 @@ impl StyledText {
             runs: None,
             delayed_highlights: None,
             layout: TextLayout::default(),
+            accessibility_role: AccessibilityRole::StaticText,
         }
     }
 
@@ -215,6 +218,13 @@ impl StyledText {
         self.runs = Some(runs);
         self
     }
+
+    /// Set the role of this text in the window's accessibility tree, such as
+    /// [`AccessibilityRole::Heading`]. Defaults to [`AccessibilityRole::StaticText`].
+    pub fn with_accessibility_role(mut self, role: AccessibilityRole) -> Self {
+        self.accessibility_role = role;
+        self
+    }
 }
 
 impl Element for StyledText {
@@ -261,7 +271,8 @@ impl Element for StyledText {
         window: &mut Window,
         cx: &mut App,
     ) {
-        self.layout.paint(&self.text, window, cx)
+        self.layout
+            .paint(&self.text, self.accessibility_role, window, cx)
     }
 }
 
@@ -402,7 +413,13 @@ impl TextLayout {
         element_state.bounds = Some(bounds);
     }
 
-    fn paint(&self, text: &str, window: &mut Window, cx: &mut App) {
+    fn paint(
+        &self,
+        text: &str,
+        accessibility_role: AccessibilityRole,
+        window: &mut Window,
+        cx: &mut App,
+    ) {
         let element_state = self.0.borrow();
         let element_state = element_state
             .as_ref()
@@ -413,6 +430,16 @@ impl TextLayout {
             .ok_or_else(|| anyhow!("prepaint has not been performed on {:?}", text))
             .unwrap();
 
+        if window.accessibility_enabled() && !text.trim().is_empty() {
+            window.with_accessibility_node(
+                None,
+                AccessibilityProperties::new(accessibility_role).name(text.to_string()),
+                bounds,
+                false,
+                |_| {},
+            );
+        }
+
         let line_height = element_state.line_height;
         let mut line_origin = bounds.origin;
         let text_style = window.text_style();
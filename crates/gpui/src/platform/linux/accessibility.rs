@@ -0,0 +1,193 @@
+use crate::{AccessibilityNode, AccessibilityRole, AccessibilityTree, Bounds, Pixels};
+use accesskit::{
+    ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, Node, NodeId, Rect, Role,
+    Toggled, Tree, TreeUpdate,
+};
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering::SeqCst},
+};
+
+/// Exposes the accessibility tree of a window to assistive technologies over AT-SPI.
+pub(crate) struct AccessibilityAdapter {
+    adapter: accesskit_unix::Adapter,
+    state: Arc<AdapterState>,
+    title: Option<String>,
+}
+
+#[derive(Default)]
+struct AdapterState {
+    active: AtomicBool,
+    /// Set when an assistive technology starts observing the window, until the window has drawn
+    /// a frame to build its accessibility tree.
+    needs_tree: AtomicBool,
+}
+
+/// Receives requests from AccessKit, which calls it on its own thread.
+struct Handler(Arc<AdapterState>);
+
+impl ActivationHandler for Handler {
+    fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+        self.0.active.store(true, SeqCst);
+        self.0.needs_tree.store(true, SeqCst);
+        // The tree is only built while painting, so it is provided by the next frame.
+        None
+    }
+}
+
+impl DeactivationHandler for Handler {
+    fn deactivate_accessibility(&mut self) {
+        self.0.active.store(false, SeqCst);
+    }
+}
+
+impl ActionHandler for Handler {
+    fn do_action(&mut self, request: ActionRequest) {
+        // The tree doesn't advertise any actions, so assistive technologies shouldn't request any.
+        log::debug!("ignoring accessibility action request: {request:?}");
+    }
+}
+
+impl AccessibilityAdapter {
+    pub fn new() -> Self {
+        let state = Arc::new(AdapterState::default());
+        let adapter = accesskit_unix::Adapter::new(
+            Handler(state.clone()),
+            Handler(state.clone()),
+            Handler(state.clone()),
+        );
+        Self {
+            adapter,
+            state,
+            title: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.active.load(SeqCst)
+    }
+
+    /// Returns whether the window has to draw a frame for an assistive technology that started
+    /// observing it, even if nothing in the window changed.
+    pub fn take_needs_tree(&self) -> bool {
+        self.state.needs_tree.swap(false, SeqCst)
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.title = Some(title.to_string());
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.adapter.update_window_focus_state(focused);
+    }
+
+    /// Updates the position of the window on screen, which is only known on X11.
+    pub fn set_window_bounds(&mut self, bounds: Bounds<Pixels>, scale_factor: f32) {
+        let bounds = to_rect(bounds, scale_factor);
+        self.adapter.set_root_window_bounds(bounds, bounds);
+    }
+
+    pub fn update(&mut self, tree: &AccessibilityTree, scale_factor: f32) {
+        let title = self.title.as_deref();
+        self.adapter
+            .update_if_active(|| tree_update(tree, title, scale_factor));
+    }
+}
+
+fn tree_update(tree: &AccessibilityTree, title: Option<&str>, scale_factor: f32) -> TreeUpdate {
+    let nodes = tree
+        .nodes()
+        .map(|node| {
+            let mut accesskit_node = to_node(node, scale_factor);
+            if node.id == tree.root().id {
+                if let Some(title) = title {
+                    accesskit_node.set_label(title);
+                }
+            }
+            (NodeId(node.id.0), accesskit_node)
+        })
+        .collect();
+    TreeUpdate {
+        nodes,
+        tree: Some(Tree {
+            root: NodeId(tree.root().id.0),
+            toolkit_name: Some("GPUI".to_string()),
+            toolkit_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        }),
+        focus: NodeId(tree.focus().id.0),
+    }
+}
+
+fn to_node(node: &AccessibilityNode, scale_factor: f32) -> Node {
+    let properties = &node.properties;
+    let mut accesskit_node = Node::new(to_role(properties.role));
+    accesskit_node.set_bounds(to_rect(node.bounds, scale_factor));
+    accesskit_node.set_children(
+        node.children
+            .iter()
+            .map(|child| NodeId(child.0))
+            .collect::<Vec<_>>(),
+    );
+    if let Some(name) = &properties.name {
+        accesskit_node.set_label(name.as_ref());
+    }
+    if let Some(value) = &properties.value {
+        accesskit_node.set_value(value.as_ref());
+    }
+    if let Some(checked) = properties.checked {
+        accesskit_node.set_toggled(Toggled::from(checked));
+    }
+    if let Some(selected) = properties.selected {
+        accesskit_node.set_selected(selected);
+    }
+    if let Some(expanded) = properties.expanded {
+        accesskit_node.set_expanded(expanded);
+    }
+    if properties.disabled {
+        accesskit_node.set_disabled();
+    }
+    accesskit_node
+}
+
+fn to_role(role: AccessibilityRole) -> Role {
+    match role {
+        AccessibilityRole::Window => Role::Window,
+        AccessibilityRole::Group => Role::Group,
+        AccessibilityRole::Pane => Role::Pane,
+        AccessibilityRole::Toolbar => Role::Toolbar,
+        AccessibilityRole::Dialog => Role::Dialog,
+        AccessibilityRole::Button => Role::Button,
+        AccessibilityRole::CheckBox => Role::CheckBox,
+        AccessibilityRole::Switch => Role::Switch,
+        AccessibilityRole::Link => Role::Link,
+        AccessibilityRole::StaticText => Role::Label,
+        AccessibilityRole::Heading => Role::Heading,
+        AccessibilityRole::Image => Role::Image,
+        AccessibilityRole::TextInput => Role::TextInput,
+        AccessibilityRole::MultilineTextInput => Role::MultilineTextInput,
+        AccessibilityRole::List => Role::List,
+        AccessibilityRole::ListItem => Role::ListItem,
+        AccessibilityRole::ListBox => Role::ListBox,
+        AccessibilityRole::ListBoxOption => Role::ListBoxOption,
+        AccessibilityRole::Tree => Role::Tree,
+        AccessibilityRole::TreeItem => Role::TreeItem,
+        AccessibilityRole::TabList => Role::TabList,
+        AccessibilityRole::Tab => Role::Tab,
+        AccessibilityRole::Menu => Role::Menu,
+        AccessibilityRole::MenuItem => Role::MenuItem,
+        AccessibilityRole::ScrollView => Role::ScrollView,
+        AccessibilityRole::Status => Role::Status,
+        AccessibilityRole::Tooltip => Role::Tooltip,
+    }
+}
+
+fn to_rect(bounds: Bounds<Pixels>, scale_factor: f32) -> Rect {
+    let bounds = bounds.scale(scale_factor);
+    let bottom_right = bounds.bottom_right();
+    Rect {
+        x0: bounds.origin.x.0 as f64,
+        y0: bounds.origin.y.0 as f64,
+        x1: bottom_right.x.0 as f64,
+        y1: bottom_right.y.0 as f64,
+    }
+}
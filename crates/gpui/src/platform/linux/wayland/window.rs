@@ -28,11 +28,11 @@ use crate::platform::{
 };
 use crate::scene::Scene;
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, Globals, GpuSpecs, Modifiers, Output, Pixels,
-    PlatformDisplay, PlatformInput, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
-    ScaledPixels, Size, Tiling, WaylandClientStatePtr, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations, WindowParams, px,
-    size,
+    AccessibilityAdapter, AccessibilityTree, AnyWindowHandle, Bounds, Decorations, Globals,
+    GpuSpecs, Modifiers, Output, Pixels, PlatformDisplay, PlatformInput, Point, PromptLevel,
+    RequestFrameOptions, ResizeEdge, ScaledPixels, Size, Tiling, WaylandClientStatePtr,
+    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
+    WindowParams, px, size,
 };
 
 #[derive(Default)]
@@ -107,6 +107,7 @@ pub struct WaylandWindowState {
     in_progress_window_controls: Option<WindowControls>,
     window_controls: WindowControls,
     inset: Option<Pixels>,
+    accessibility: AccessibilityAdapter,
 }
 
 #[derive(Clone)]
@@ -181,6 +182,7 @@ impl WaylandWindowState {
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             inset: None,
+            accessibility: AccessibilityAdapter::new(),
         })
     }
 
@@ -332,11 +334,15 @@ impl WaylandWindowStatePtr {
     pub fn frame(&self) {
         let mut state = self.state.borrow_mut();
         state.surface.frame(&state.globals.qh, state.surface.id());
+        let force_render = state.accessibility.take_needs_tree();
         drop(state);
 
         let mut cb = self.callbacks.borrow_mut();
         if let Some(fun) = cb.request_frame.as_mut() {
-            fun(Default::default());
+            fun(RequestFrameOptions {
+                force_render,
+                ..Default::default()
+            });
         }
     }
 
@@ -712,7 +718,10 @@ impl WaylandWindowStatePtr {
     }
 
     pub fn set_focused(&self, focus: bool) {
-        self.state.borrow_mut().active = focus;
+        let mut state = self.state.borrow_mut();
+        state.active = focus;
+        state.accessibility.set_focused(focus);
+        drop(state);
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -893,7 +902,9 @@ impl PlatformWindow for WaylandWindow {
     }
 
     fn set_title(&mut self, title: &str) {
-        self.borrow().toplevel.set_title(title.to_string());
+        let mut state = self.borrow_mut();
+        state.toplevel.set_title(title.to_string());
+        state.accessibility.set_title(title);
     }
 
     fn set_app_id(&mut self, app_id: &str) {
@@ -1050,6 +1061,16 @@ impl PlatformWindow for WaylandWindow {
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.borrow().renderer.gpu_specs().into()
     }
+
+    fn accessibility_enabled(&self) -> bool {
+        self.borrow().accessibility.is_active()
+    }
+
+    fn update_accessibility_tree(&self, tree: AccessibilityTree) {
+        let mut state = self.borrow_mut();
+        let scale = state.scale;
+        state.accessibility.update(&tree, scale);
+    }
 }
 
 fn update_window(mut state: RefMut<WaylandWindowState>) {
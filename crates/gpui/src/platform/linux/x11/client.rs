@@ -551,6 +551,7 @@ impl X11Client {
                 if let Some(window) = self.get_window(window) {
                     window.refresh(RequestFrameOptions {
                         require_presentation: true,
+                        force_render: false,
                     });
                 }
             }
@@ -2,11 +2,11 @@ use anyhow::{Context as _, anyhow};
 
 use crate::platform::blade::{BladeContext, BladeRenderer, BladeSurfaceConfig};
 use crate::{
-    AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size, Tiling,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowDecorations, WindowKind,
-    WindowParams, X11ClientStatePtr, px, size,
+    AccessibilityAdapter, AccessibilityTree, AnyWindowHandle, Bounds, Decorations, DevicePixels,
+    ForegroundExecutor, GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptLevel, RequestFrameOptions, ResizeEdge,
+    ScaledPixels, Scene, Size, Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowDecorations, WindowKind, WindowParams, X11ClientStatePtr, px, size,
 };
 
 use blade_graphics as gpu;
@@ -269,6 +269,7 @@ pub struct X11WindowState {
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    accessibility: AccessibilityAdapter,
 }
 
 impl X11WindowState {
@@ -632,6 +633,7 @@ impl X11WindowState {
                 edge_constraints: None,
                 counter_id: sync_request_counter,
                 last_sync_counter: None,
+                accessibility: AccessibilityAdapter::new(),
             })
         });
 
@@ -947,7 +949,8 @@ impl X11WindowStatePtr {
         }
     }
 
-    pub fn refresh(&self, request_frame_options: RequestFrameOptions) {
+    pub fn refresh(&self, mut request_frame_options: RequestFrameOptions) {
+        request_frame_options.force_render |= self.state.borrow().accessibility.take_needs_tree();
         let mut cb = self.callbacks.borrow_mut();
         if let Some(ref mut fun) = cb.request_frame {
             fun(request_frame_options);
@@ -1079,6 +1082,7 @@ impl X11WindowStatePtr {
     }
 
     pub fn set_active(&self, focus: bool) {
+        self.state.borrow_mut().accessibility.set_focused(focus);
         if let Some(ref mut fun) = self.callbacks.borrow_mut().active_status_change {
             fun(focus);
         }
@@ -1293,6 +1297,7 @@ impl PlatformWindow for X11Window {
         )
         .log_err();
         self.flush().log_err();
+        self.0.state.borrow_mut().accessibility.set_title(title);
     }
 
     fn set_app_id(&mut self, app_id: &str) {
@@ -1611,4 +1616,15 @@ impl PlatformWindow for X11Window {
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
+
+    fn accessibility_enabled(&self) -> bool {
+        self.0.state.borrow().accessibility.is_active()
+    }
+
+    fn update_accessibility_tree(&self, tree: AccessibilityTree) {
+        let mut state = self.0.state.borrow_mut();
+        let (bounds, scale_factor) = (state.bounds, state.scale_factor);
+        state.accessibility.set_window_bounds(bounds, scale_factor);
+        state.accessibility.update(&tree, scale_factor);
+    }
 }
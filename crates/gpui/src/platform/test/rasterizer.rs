@@ -0,0 +1,956 @@
+//! A software rasterizer for [`Scene`]s, which lets tests assert on the pixels of a window
+//! without a GPU. It follows the blade shaders closely enough for regression tests, with a few
+//! simplifications: dashed borders are drawn solid, and surfaces aren't drawn at all.
+
+use crate::{
+    Background, BackgroundTag, Bounds, ColorSpace, ContentMask, Corners, DevicePixels, Hsla,
+    MonochromeSprite, Path, PolychromeSprite, PrimitiveBatch, Quad, Rgba, ScaledPixels, Scene,
+    Shadow, Size, TestAtlas, Underline,
+};
+use image::RgbaImage;
+use std::{f32::consts::PI, sync::Arc};
+
+const ANTIALIAS_THRESHOLD: f32 = 0.5;
+const GRAYSCALE_FACTORS: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+/// Rasterizes the scene into an image of the given size, in device pixels.
+pub(crate) fn rasterize_scene(
+    scene: &Scene,
+    size: Size<DevicePixels>,
+    atlas: &TestAtlas,
+) -> RgbaImage {
+    let mut canvas = Canvas::new(size.width.0.max(0) as u32, size.height.0.max(0) as u32);
+    for batch in scene.batches() {
+        match batch {
+            PrimitiveBatch::Shadows(shadows) => {
+                for shadow in shadows {
+                    canvas.draw_shadow(shadow);
+                }
+            }
+            PrimitiveBatch::Quads(quads) => {
+                for quad in quads {
+                    canvas.draw_quad(quad);
+                }
+            }
+            PrimitiveBatch::Paths(paths) => {
+                for path in paths {
+                    canvas.draw_path(path);
+                }
+            }
+            PrimitiveBatch::Underlines(underlines) => {
+                for underline in underlines {
+                    canvas.draw_underline(underline);
+                }
+            }
+            PrimitiveBatch::MonochromeSprites { sprites, .. } => {
+                for sprite in sprites {
+                    canvas.draw_monochrome_sprite(sprite, atlas);
+                }
+            }
+            PrimitiveBatch::PolychromeSprites { sprites, .. } => {
+                for sprite in sprites {
+                    canvas.draw_polychrome_sprite(sprite, atlas);
+                }
+            }
+            PrimitiveBatch::Surfaces(_) => {}
+        }
+    }
+    canvas.into_image()
+}
+
+/// How much a captured image may differ from a stored snapshot before the comparison fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotTolerance {
+    /// Pixels whose channels all differ by at most this much are considered equal.
+    pub max_channel_difference: u8,
+    /// The number of pixels that may differ by more than `max_channel_difference`.
+    pub max_differing_pixels: usize,
+}
+
+impl Default for SnapshotTolerance {
+    fn default() -> Self {
+        Self {
+            max_channel_difference: 2,
+            max_differing_pixels: 0,
+        }
+    }
+}
+
+/// Compares two images, returning a description of the difference if they aren't equal within
+/// the given tolerance.
+pub(crate) fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    tolerance: SnapshotTolerance,
+) -> Result<(), String> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "expected a {}x{} image, but got {}x{}",
+            expected.width(),
+            expected.height(),
+            actual.width(),
+            actual.height()
+        ));
+    }
+
+    let mut differing_pixels = 0;
+    let mut first_difference = None;
+    for ((x, y, expected), actual) in expected.enumerate_pixels().zip(actual.pixels()) {
+        let differs = expected
+            .0
+            .iter()
+            .zip(actual.0.iter())
+            .any(|(a, b)| a.abs_diff(*b) > tolerance.max_channel_difference);
+        if differs {
+            differing_pixels += 1;
+            first_difference.get_or_insert((x, y, *expected, *actual));
+        }
+    }
+
+    match first_difference {
+        Some((x, y, expected, actual)) if differing_pixels > tolerance.max_differing_pixels => {
+            Err(format!(
+                "{differing_pixels} pixels differ, first at ({x}, {y}): expected {:?}, got {:?}",
+                expected.0, actual.0
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// An axis-aligned rectangle, in device pixels.
+#[derive(Clone, Copy, Debug)]
+struct Rect {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+}
+
+impl Rect {
+    fn new(bounds: &Bounds<ScaledPixels>) -> Self {
+        Self {
+            x: bounds.origin.x.0,
+            y: bounds.origin.y.0,
+            width: bounds.size.width.0,
+            height: bounds.size.height.0,
+        }
+    }
+
+    fn dilate(self, amount: f32) -> Self {
+        Self {
+            x: self.x - amount,
+            y: self.y - amount,
+            width: self.width + 2. * amount,
+            height: self.height + 2. * amount,
+        }
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+    }
+
+    fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        Rect {
+            x,
+            y,
+            width: ((self.x + self.width).min(other.x + other.width) - x).max(0.),
+            height: ((self.y + self.height).min(other.y + other.height) - y).max(0.),
+        }
+    }
+
+    fn half_size(&self) -> [f32; 2] {
+        [self.width / 2., self.height / 2.]
+    }
+
+    fn center(&self) -> [f32; 2] {
+        [self.x + self.width / 2., self.y + self.height / 2.]
+    }
+}
+
+struct CornerRadii([f32; 4]);
+
+impl CornerRadii {
+    fn new(corners: &Corners<ScaledPixels>) -> Self {
+        Self([
+            corners.top_left.0,
+            corners.top_right.0,
+            corners.bottom_right.0,
+            corners.bottom_left.0,
+        ])
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.iter().all(|radius| *radius == 0.)
+    }
+
+    /// Selects the corner radius based on the quadrant of the point.
+    fn pick(&self, center_to_point: [f32; 2]) -> f32 {
+        let [top_left, top_right, bottom_right, bottom_left] = self.0;
+        match (center_to_point[0] < 0., center_to_point[1] < 0.) {
+            (true, true) => top_left,
+            (true, false) => bottom_left,
+            (false, true) => top_right,
+            (false, false) => bottom_right,
+        }
+    }
+}
+
+/// Pixels in linear color space with premultiplied alpha, as produced by blending straight-alpha
+/// colors with `src * a + dst * (1 - a)`.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![[0.; 4]; width as usize * height as usize],
+        }
+    }
+
+    fn into_image(self) -> RgbaImage {
+        let mut image = RgbaImage::new(self.width, self.height);
+        for (pixel, [r, g, b, a]) in image.pixels_mut().zip(self.pixels) {
+            if a <= 0. {
+                continue;
+            }
+            let channel = |value: f32| (linear_to_srgb(value / a) * 255.).round() as u8;
+            pixel.0 = [
+                channel(r),
+                channel(g),
+                channel(b),
+                (a.clamp(0., 1.) * 255.).round() as u8,
+            ];
+        }
+        image
+    }
+
+    /// Calls `shade` with the center of each pixel within the given rectangle, and blends the
+    /// returned straight-alpha color, scaled by the returned alpha factor, onto the canvas.
+    fn fill(&mut self, rect: Rect, mut shade: impl FnMut(f32, f32) -> Option<([f32; 4], f32)>) {
+        let min_x = rect.x.floor().max(0.) as u32;
+        let min_y = rect.y.floor().max(0.) as u32;
+        let max_x = ((rect.x + rect.width).ceil().max(0.) as u32).min(self.width);
+        let max_y = ((rect.y + rect.height).ceil().max(0.) as u32).min(self.height);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+                if !rect.contains(center_x, center_y) {
+                    continue;
+                }
+                if let Some((color, alpha_factor)) = shade(center_x, center_y) {
+                    self.blend(x, y, color, alpha_factor);
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: [f32; 4], alpha_factor: f32) {
+        let alpha = (color[3] * alpha_factor).clamp(0., 1.);
+        if alpha <= 0. {
+            return;
+        }
+        let pixel = &mut self.pixels[(y * self.width + x) as usize];
+        for channel in 0..3 {
+            pixel[channel] = color[channel] * alpha + pixel[channel] * (1. - alpha);
+        }
+        pixel[3] = alpha + pixel[3] * (1. - alpha);
+    }
+
+    fn draw_quad(&mut self, quad: &Quad) {
+        let bounds = Rect::new(&quad.bounds);
+        let clip = bounds.intersect(&content_mask_rect(&quad.content_mask));
+        let corner_radii = CornerRadii::new(&quad.corner_radii);
+        let border_widths = [
+            quad.border_widths.top.0,
+            quad.border_widths.right.0,
+            quad.border_widths.bottom.0,
+            quad.border_widths.left.0,
+        ];
+        let border_color = hsla_to_linear(quad.border_color);
+        let unbordered = border_widths.iter().all(|width| *width == 0.);
+        let unrounded = corner_radii.is_zero();
+
+        self.fill(clip, |x, y| {
+            let background = background_color(&quad.background, [x, y], &bounds);
+            if unbordered && unrounded {
+                return Some((background, 1.));
+            }
+
+            let half_size = bounds.half_size();
+            let center = bounds.center();
+            let center_to_point = [x - center[0], y - center[1]];
+            let corner_radius = corner_radii.pick(center_to_point);
+            let [top, right, bottom, left] = border_widths;
+            let border = [
+                if center_to_point[0] < 0. { left } else { right },
+                if center_to_point[1] < 0. { top } else { bottom },
+            ];
+            // Zero-width borders are reduced so that no antialiasing pixels are drawn for them.
+            let reduced_border = border.map(|width| {
+                if width == 0. {
+                    -ANTIALIAS_THRESHOLD
+                } else {
+                    width
+                }
+            });
+            let corner_to_point = [
+                center_to_point[0].abs() - half_size[0],
+                center_to_point[1].abs() - half_size[1],
+            ];
+            let corner_center_to_point = corner_to_point.map(|distance| distance + corner_radius);
+            let straight_border_inner_corner_to_point = [
+                corner_to_point[0] + reduced_border[0],
+                corner_to_point[1] + reduced_border[1],
+            ];
+
+            let outer_sdf = quad_sdf_impl(corner_center_to_point, corner_radius);
+            let inner_sdf = if corner_center_to_point[0] <= 0. || corner_center_to_point[1] <= 0. {
+                -straight_border_inner_corner_to_point[0]
+                    .max(straight_border_inner_corner_to_point[1])
+            } else if straight_border_inner_corner_to_point[0] > 0.
+                || straight_border_inner_corner_to_point[1] > 0.
+            {
+                -1.
+            } else if reduced_border[0] == reduced_border[1] {
+                -(outer_sdf + reduced_border[0])
+            } else {
+                let ellipse_radii = reduced_border.map(|width| (corner_radius - width).max(0.));
+                quarter_ellipse_sdf(corner_center_to_point, ellipse_radii)
+            };
+            let border_sdf = inner_sdf.max(outer_sdf);
+
+            let mut color = background;
+            if border_sdf < ANTIALIAS_THRESHOLD {
+                let blended_border = over(background, border_color);
+                color = mix(
+                    background,
+                    blended_border,
+                    saturate(ANTIALIAS_THRESHOLD - inner_sdf),
+                );
+            }
+            Some((color, saturate(ANTIALIAS_THRESHOLD - outer_sdf)))
+        });
+    }
+
+    fn draw_shadow(&mut self, shadow: &Shadow) {
+        let blur_radius = shadow.blur_radius.0;
+        let bounds = Rect::new(&shadow.bounds);
+        let clip = bounds
+            .dilate(3. * blur_radius)
+            .intersect(&content_mask_rect(&shadow.content_mask));
+        let corner_radii = CornerRadii::new(&shadow.corner_radii);
+        let color = hsla_to_linear(shadow.color);
+
+        self.fill(clip, |x, y| {
+            let half_size = bounds.half_size();
+            let center = bounds.center();
+            let center_to_point = [x - center[0], y - center[1]];
+            let corner_radius = corner_radii.pick(center_to_point);
+            if blur_radius <= 0. {
+                let corner_to_point = [
+                    center_to_point[0].abs() - half_size[0] + corner_radius,
+                    center_to_point[1].abs() - half_size[1] + corner_radius,
+                ];
+                let distance = quad_sdf_impl(corner_to_point, corner_radius);
+                return Some((color, saturate(ANTIALIAS_THRESHOLD - distance)));
+            }
+
+            // The signal is only non-zero in a limited range, so don't waste samples.
+            let low = center_to_point[1] - half_size[1];
+            let high = center_to_point[1] + half_size[1];
+            let start = (-3. * blur_radius).clamp(low, high);
+            let end = (3. * blur_radius).clamp(low, high);
+
+            let step = (end - start) / 4.;
+            let mut sample_y = start + step * 0.5;
+            let mut alpha = 0.;
+            for _ in 0..4 {
+                let blur = blur_along_x(
+                    center_to_point[0],
+                    center_to_point[1] - sample_y,
+                    blur_radius,
+                    corner_radius,
+                    half_size,
+                );
+                alpha += blur * gaussian(sample_y, blur_radius) * step;
+                sample_y += step;
+            }
+            Some((color, alpha))
+        });
+    }
+
+    fn draw_path(&mut self, path: &Path<ScaledPixels>) {
+        let clipped = Rect::new(&path.bounds).intersect(&content_mask_rect(&path.content_mask));
+        let origin_x = clipped.x.floor();
+        let origin_y = clipped.y.floor();
+        let tile = Rect {
+            x: origin_x,
+            y: origin_y,
+            width: (clipped.x + clipped.width).ceil() - origin_x,
+            height: (clipped.y + clipped.height).ceil() - origin_y,
+        };
+        if tile.width <= 0. || tile.height <= 0. {
+            return;
+        }
+
+        // Accumulate the coverage of every triangle, like the additively-blended path texture.
+        let tile_width = tile.width as usize;
+        let mut coverage = vec![0f32; tile_width * tile.height as usize];
+        for triangle in path.vertices.chunks_exact(3) {
+            let xy = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].xy_position;
+                [position.x.0, position.y.0]
+            });
+            let st = [0, 1, 2].map(|ix| {
+                let position = triangle[ix].st_position;
+                [position.x, position.y]
+            });
+            rasterize_triangle(xy, st, tile, |column, row, value| {
+                coverage[row * tile_width + column] += value;
+            });
+        }
+
+        let bounds = Rect::new(&path.bounds);
+        self.fill(tile, |x, y| {
+            let column = (x - tile.x) as usize;
+            let row = (y - tile.y) as usize;
+            let sample = coverage[row * tile_width + column];
+            // Overlapping triangles alternate between filled and unfilled.
+            let mask = 1. - (1. - sample % 2.).abs();
+            Some((background_color(&path.color, [x, y], &bounds), mask))
+        });
+    }
+
+    fn draw_underline(&mut self, underline: &Underline) {
+        let bounds = Rect::new(&underline.bounds);
+        let clip = bounds.intersect(&content_mask_rect(&underline.content_mask));
+        let color = hsla_to_linear(underline.color);
+        let thickness = underline.thickness.0;
+
+        self.fill(clip, |x, y| {
+            if !underline.wavy {
+                return Some((color, color[3]));
+            }
+
+            let half_thickness = thickness * 0.5;
+            let st_x = (x - bounds.x) / bounds.height;
+            let st_y = (y - bounds.y) / bounds.height - 0.5;
+            let frequency = PI * 3. * thickness / 3.;
+            let amplitude = 1. / (4. * thickness);
+            let sine = (st_x * frequency).sin() * amplitude;
+            let d_sine = (st_x * frequency).cos() * amplitude * frequency;
+            let distance = (st_y - sine) / (1. + d_sine * d_sine).sqrt();
+            let distance_in_pixels = distance * bounds.height;
+            let distance_from_top_border = distance_in_pixels - half_thickness;
+            let distance_from_bottom_border = distance_in_pixels + half_thickness;
+            let alpha = saturate(
+                ANTIALIAS_THRESHOLD - (-distance_from_bottom_border).max(distance_from_top_border),
+            );
+            Some((color, alpha * color[3]))
+        });
+    }
+
+    fn draw_monochrome_sprite(&mut self, sprite: &MonochromeSprite, atlas: &TestAtlas) {
+        let Some(texture) = atlas.tile_texture(&sprite.tile) else {
+            return;
+        };
+        let bounds = Rect::new(&sprite.bounds);
+        let clip = content_mask_rect(&sprite.content_mask);
+        let color = hsla_to_linear(sprite.color);
+
+        // Sprites can be transformed, so walk the pixels covered by the transformed bounds and
+        // map them back into the untransformed sprite.
+        let matrix = sprite.transformation.rotation_scale;
+        let translation = sprite.transformation.translation;
+        let transform = |x: f32, y: f32| {
+            [
+                matrix[0][0] * x + matrix[0][1] * y + translation[0],
+                matrix[1][0] * x + matrix[1][1] * y + translation[1],
+            ]
+        };
+        let determinant = matrix[0][0] * matrix[1][1] - matrix[0][1] * matrix[1][0];
+        if determinant == 0. {
+            return;
+        }
+        let inverse = |x: f32, y: f32| {
+            let x = x - translation[0];
+            let y = y - translation[1];
+            [
+                (matrix[1][1] * x - matrix[0][1] * y) / determinant,
+                (matrix[0][0] * y - matrix[1][0] * x) / determinant,
+            ]
+        };
+
+        let corners = [
+            transform(bounds.x, bounds.y),
+            transform(bounds.x + bounds.width, bounds.y),
+            transform(bounds.x, bounds.y + bounds.height),
+            transform(bounds.x + bounds.width, bounds.y + bounds.height),
+        ];
+        let min_x = corners
+            .iter()
+            .map(|corner| corner[0])
+            .fold(f32::MAX, f32::min);
+        let min_y = corners
+            .iter()
+            .map(|corner| corner[1])
+            .fold(f32::MAX, f32::min);
+        let max_x = corners
+            .iter()
+            .map(|corner| corner[0])
+            .fold(f32::MIN, f32::max);
+        let max_y = corners
+            .iter()
+            .map(|corner| corner[1])
+            .fold(f32::MIN, f32::max);
+        let transformed_bounds = Rect {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x,
+            height: max_y - min_y,
+        };
+
+        self.fill(transformed_bounds, |x, y| {
+            let [x, y] = inverse(x, y);
+            if !bounds.contains(x, y) || !clip.contains(x, y) {
+                return None;
+            }
+            let sample = texture.sample(&bounds, x, y)[0];
+            Some((color, sample))
+        });
+    }
+
+    fn draw_polychrome_sprite(&mut self, sprite: &PolychromeSprite, atlas: &TestAtlas) {
+        let Some(texture) = atlas.tile_texture(&sprite.tile) else {
+            return;
+        };
+        let bounds = Rect::new(&sprite.bounds);
+        let clip = bounds.intersect(&content_mask_rect(&sprite.content_mask));
+        let corner_radii = CornerRadii::new(&sprite.corner_radii);
+
+        self.fill(clip, |x, y| {
+            // Polychrome tiles are stored as BGRA in sRGB space.
+            let [b, g, r, a] = texture.sample(&bounds, x, y);
+            let mut color = [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a];
+            if sprite.grayscale {
+                let grayscale = color[0] * GRAYSCALE_FACTORS[0]
+                    + color[1] * GRAYSCALE_FACTORS[1]
+                    + color[2] * GRAYSCALE_FACTORS[2];
+                color = [grayscale, grayscale, grayscale, a];
+            }
+
+            let center = bounds.center();
+            let half_size = bounds.half_size();
+            let center_to_point = [x - center[0], y - center[1]];
+            let corner_radius = corner_radii.pick(center_to_point);
+            let corner_center_to_point = [
+                center_to_point[0].abs() - half_size[0] + corner_radius,
+                center_to_point[1].abs() - half_size[1] + corner_radius,
+            ];
+            let distance = quad_sdf_impl(corner_center_to_point, corner_radius);
+            Some((
+                color,
+                sprite.opacity * saturate(ANTIALIAS_THRESHOLD - distance),
+            ))
+        });
+    }
+}
+
+/// The contents of a single atlas tile, as stored by the [`TestAtlas`].
+pub(crate) struct TileTexture {
+    pub(crate) size: Size<DevicePixels>,
+    pub(crate) bytes: Arc<[u8]>,
+}
+
+impl TileTexture {
+    fn bytes_per_pixel(&self) -> usize {
+        let pixels = (self.size.width.0 * self.size.height.0).max(1) as usize;
+        (self.bytes.len() / pixels).max(1)
+    }
+
+    /// Bilinearly samples the texel under the given point of the sprite's bounds, returning its
+    /// channels normalized to `0.0..=1.0`.
+    fn sample(&self, bounds: &Rect, x: f32, y: f32) -> [f32; 4] {
+        let width = self.size.width.0;
+        let height = self.size.height.0;
+        let u = (x - bounds.x) / bounds.width * width as f32 - 0.5;
+        let v = (y - bounds.y) / bounds.height * height as f32 - 0.5;
+        let (u0, v0) = (u.floor(), v.floor());
+        let (fu, fv) = (u - u0, v - v0);
+
+        let mut result = [0.; 4];
+        for (dx, dy, weight) in [
+            (0, 0, (1. - fu) * (1. - fv)),
+            (1, 0, fu * (1. - fv)),
+            (0, 1, (1. - fu) * fv),
+            (1, 1, fu * fv),
+        ] {
+            let column = (u0 as i32 + dx).clamp(0, width - 1);
+            let row = (v0 as i32 + dy).clamp(0, height - 1);
+            let texel = self.texel(column as usize, row as usize);
+            for (channel, value) in result.iter_mut().zip(texel) {
+                *channel += value * weight;
+            }
+        }
+        result
+    }
+
+    fn texel(&self, column: usize, row: usize) -> [f32; 4] {
+        let bytes_per_pixel = self.bytes_per_pixel();
+        let offset = (row * self.size.width.0 as usize + column) * bytes_per_pixel;
+        let mut texel = [0.; 4];
+        for (channel, byte) in texel
+            .iter_mut()
+            .zip(self.bytes.iter().skip(offset).take(bytes_per_pixel))
+        {
+            *channel = *byte as f32 / 255.;
+        }
+        texel
+    }
+}
+
+/// Rasterizes a path triangle into a tile, calling `coverage` with the tile-relative column and
+/// row of each pixel whose center lies within the triangle.
+fn rasterize_triangle(
+    xy: [[f32; 2]; 3],
+    st: [[f32; 2]; 3],
+    tile: Rect,
+    mut coverage: impl FnMut(usize, usize, f32),
+) {
+    let edge = |a: [f32; 2], b: [f32; 2], x: f32, y: f32| {
+        (b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])
+    };
+    let area = edge(xy[0], xy[1], xy[2][0], xy[2][1]);
+    if area == 0. {
+        return;
+    }
+    // Use a consistent winding so that the top-left fill rule below applies.
+    let (xy, st) = if area < 0. {
+        ([xy[0], xy[2], xy[1]], [st[0], st[2], st[1]])
+    } else {
+        (xy, st)
+    };
+    let area = area.abs();
+
+    // The texture coordinates vary linearly over the triangle, so their screen-space
+    // derivatives are constant.
+    let ds = st_gradient(xy, [st[0][0], st[1][0], st[2][0]], area);
+    let dt = st_gradient(xy, [st[0][1], st[1][1], st[2][1]], area);
+
+    let edges = [(xy[1], xy[2]), (xy[2], xy[0]), (xy[0], xy[1])];
+    let is_top_left = |(a, b): ([f32; 2], [f32; 2])| {
+        let dy = b[1] - a[1];
+        dy < 0. || (dy == 0. && b[0] > a[0])
+    };
+
+    let min_x = xy.iter().map(|p| p[0]).fold(f32::MAX, f32::min).max(tile.x);
+    let min_y = xy.iter().map(|p| p[1]).fold(f32::MAX, f32::min).max(tile.y);
+    let max_x = xy
+        .iter()
+        .map(|p| p[0])
+        .fold(f32::MIN, f32::max)
+        .min(tile.x + tile.width);
+    let max_y = xy
+        .iter()
+        .map(|p| p[1])
+        .fold(f32::MIN, f32::max)
+        .min(tile.y + tile.height);
+    if min_x >= max_x || min_y >= max_y {
+        return;
+    }
+
+    let first_column = (min_x - tile.x).floor().max(0.) as usize;
+    let first_row = (min_y - tile.y).floor().max(0.) as usize;
+    let last_column = ((max_x - tile.x).ceil() as usize).min(tile.width as usize);
+    let last_row = ((max_y - tile.y).ceil() as usize).min(tile.height as usize);
+    for row in first_row..last_row {
+        for column in first_column..last_column {
+            let x = tile.x + column as f32 + 0.5;
+            let y = tile.y + row as f32 + 0.5;
+            let weights = edges.map(|(a, b)| edge(a, b, x, y));
+            let inside = weights
+                .iter()
+                .zip(edges)
+                .all(|(weight, edge)| *weight > 0. || (*weight == 0. && is_top_left(edge)));
+            if !inside {
+                continue;
+            }
+
+            let s = (weights[0] * st[0][0] + weights[1] * st[1][0] + weights[2] * st[2][0]) / area;
+            let t = (weights[0] * st[0][1] + weights[1] * st[1][1] + weights[2] * st[2][1]) / area;
+            let gradient = [2. * s * ds[0] - dt[0], 2. * s * ds[1] - dt[1]];
+            let gradient_length = gradient[0].hypot(gradient[1]);
+            let f = s * s - t;
+            let value = if gradient_length == 0. {
+                if f <= 0. { 1. } else { 0. }
+            } else {
+                saturate(0.5 - f / gradient_length)
+            };
+            coverage(column, row, value);
+        }
+    }
+}
+
+/// Returns the screen-space gradient of a value that varies linearly across a triangle.
+fn st_gradient(xy: [[f32; 2]; 3], values: [f32; 3], area: f32) -> [f32; 2] {
+    let [a, b, c] = xy;
+    let [va, vb, vc] = values;
+    [
+        (va * (b[1] - c[1]) + vb * (c[1] - a[1]) + vc * (a[1] - b[1])) / area,
+        (va * (c[0] - b[0]) + vb * (a[0] - c[0]) + vc * (b[0] - a[0])) / area,
+    ]
+}
+
+fn content_mask_rect(content_mask: &ContentMask<ScaledPixels>) -> Rect {
+    Rect::new(&content_mask.bounds)
+}
+
+fn background_color(background: &Background, point: [f32; 2], bounds: &Rect) -> [f32; 4] {
+    match background.tag {
+        BackgroundTag::Solid => hsla_to_linear(background.solid),
+        BackgroundTag::LinearGradient => {
+            // Subtract 90 degrees to match the CSS gradient angle.
+            let angle = background.gradient_angle_or_pattern_height;
+            let radians = (angle % 360. - 90.) * PI / 180.;
+            let mut direction = [radians.cos(), radians.sin()];
+            if bounds.width > bounds.height {
+                direction[1] *= bounds.height / bounds.width;
+            } else {
+                direction[0] *= bounds.width / bounds.height;
+            }
+
+            let half_size = bounds.half_size();
+            let center = bounds.center();
+            let center_to_point = [point[0] - center[0], point[1] - center[1]];
+            let mut t = (center_to_point[0] * direction[0] + center_to_point[1] * direction[1])
+                / direction[0].hypot(direction[1]);
+            if direction[0].abs() > direction[1].abs() {
+                t = (t + half_size[0]) / bounds.width;
+            } else {
+                t = (t + half_size[1]) / bounds.height;
+            }
+
+            let [stop0, stop1] = background.colors;
+            t = ((t - stop0.percentage) / (stop1.percentage - stop0.percentage)).clamp(0., 1.);
+            let color0 = hsla_to_linear(stop0.color);
+            let color1 = hsla_to_linear(stop1.color);
+            match background.color_space {
+                ColorSpace::Srgb => {
+                    srgba_to_linear(mix(linear_to_srgba(color0), linear_to_srgba(color1), t))
+                }
+                ColorSpace::Oklab => oklab_to_linear_srgb(mix(
+                    linear_srgb_to_oklab(color0),
+                    linear_srgb_to_oklab(color1),
+                    t,
+                )),
+            }
+        }
+        BackgroundTag::PatternSlash => {
+            let height = background.gradient_angle_or_pattern_height;
+            let pattern_width = (height / 65535.) / 255.;
+            let pattern_interval = (height % 65535.) / 255.;
+            let pattern_height = pattern_width + pattern_interval;
+            let stripe_angle = PI / 4.;
+            let pattern_period = pattern_height * stripe_angle.sin();
+            let relative_x = point[0] - bounds.x;
+            let relative_y = point[1] - bounds.y;
+            let rotated_x = stripe_angle.cos() * relative_x + stripe_angle.sin() * relative_y;
+            let pattern = rotated_x % pattern_period;
+            let distance = pattern.min(pattern_period - pattern)
+                - pattern_period * (pattern_width / pattern_height) / 2.;
+            let mut color = hsla_to_linear(background.solid);
+            color[3] *= saturate(0.5 - distance);
+            color
+        }
+    }
+}
+
+fn quad_sdf_impl(corner_center_to_point: [f32; 2], corner_radius: f32) -> f32 {
+    let [x, y] = corner_center_to_point;
+    if corner_radius == 0. {
+        x.max(y)
+    } else {
+        let signed_distance_to_inset_quad = x.max(0.).hypot(y.max(0.)) + x.max(y).min(0.);
+        signed_distance_to_inset_quad - corner_radius
+    }
+}
+
+/// Approximates the distance to a quarter ellipse, negative outside and positive inside.
+fn quarter_ellipse_sdf(point: [f32; 2], radii: [f32; 2]) -> f32 {
+    let unit_circle_sdf = (point[0] / radii[0]).hypot(point[1] / radii[1]) - 1.;
+    unit_circle_sdf * (radii[0] + radii[1]) * -0.5
+}
+
+fn gaussian(x: f32, sigma: f32) -> f32 {
+    (-(x * x) / (2. * sigma * sigma)).exp() / ((2. * PI).sqrt() * sigma)
+}
+
+/// Approximates the error function, needed for the gaussian integral.
+fn erf(value: f32) -> f32 {
+    let a = value.abs();
+    let r1 = 1. + (0.278393 + (0.230389 + (0.000972 + 0.078108 * a) * a) * a) * a;
+    let r2 = r1 * r1;
+    value.signum() - value.signum() / (r2 * r2)
+}
+
+fn blur_along_x(x: f32, y: f32, sigma: f32, corner: f32, half_size: [f32; 2]) -> f32 {
+    let delta = (half_size[1] - corner - y.abs()).min(0.);
+    let curved = half_size[0] - corner + (corner * corner - delta * delta).max(0.).sqrt();
+    let scale = 0.5f32.sqrt() / sigma;
+    let low = 0.5 + 0.5 * erf((x - curved) * scale);
+    let high = 0.5 + 0.5 * erf((x + curved) * scale);
+    high - low
+}
+
+fn saturate(value: f32) -> f32 {
+    value.clamp(0., 1.)
+}
+
+fn mix(a: [f32; 4], b: [f32; 4], t: f32) -> [f32; 4] {
+    [0, 1, 2, 3].map(|ix| a[ix] + (b[ix] - a[ix]) * t)
+}
+
+fn over(below: [f32; 4], above: [f32; 4]) -> [f32; 4] {
+    let alpha = above[3] + below[3] * (1. - above[3]);
+    if alpha <= 0. {
+        return [0.; 4];
+    }
+    let channel =
+        |ix: usize| (above[ix] * above[3] + below[ix] * below[3] * (1. - above[3])) / alpha;
+    [channel(0), channel(1), channel(2), alpha]
+}
+
+fn hsla_to_linear(color: Hsla) -> [f32; 4] {
+    let Rgba { r, g, b, a } = color.into();
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value < 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.clamp(0., 1.);
+    if value < 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1. / 2.4) - 0.055
+    }
+}
+
+fn srgba_to_linear([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+}
+
+fn linear_to_srgba([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    [linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a]
+}
+
+fn linear_srgb_to_oklab([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        a,
+    ]
+}
+
+fn oklab_to_linear_srgb([l, a_, b_, alpha]: [f32; 4]) -> [f32; 4] {
+    let l_ = l + 0.3963377774 * a_ + 0.2158037573 * b_;
+    let m_ = l - 0.1055613458 * a_ - 0.0638541728 * b_;
+    let s_ = l - 0.0894841775 * a_ - 1.2914855480 * b_;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        alpha,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        self as gpui, Context, IntoElement, ParentElement, Render, Styled, TestAppContext, Window,
+        div, px, rgb, size,
+    };
+
+    struct TestView;
+
+    impl Render for TestView {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div().size_full().bg(rgb(0xff0000)).child(
+                div()
+                    .absolute()
+                    .top(px(10.))
+                    .left(px(10.))
+                    .size(px(20.))
+                    .rounded(px(10.))
+                    .bg(rgb(0x0000ff)),
+            )
+        }
+    }
+
+    #[gpui::test]
+    fn test_capture_window(cx: &mut TestAppContext) {
+        let (_, cx) = cx.add_window_view(|_, _| TestView);
+        cx.simulate_resize(size(px(40.), px(30.)));
+
+        let image = cx.capture_image();
+        // The test window has a scale factor of 2.
+        assert_eq!(image.dimensions(), (80, 60));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(40, 40).0, [0, 0, 255, 255]);
+        // The corners of the circle are outside of it.
+        assert_eq!(image.get_pixel(21, 21).0, [255, 0, 0, 255]);
+        let edge = image.get_pixel(25, 26).0;
+        assert!(
+            edge[0] > 0 && edge[2] > 0,
+            "edge isn't antialiased: {edge:?}"
+        );
+    }
+
+    #[test]
+    fn test_compare_images() {
+        let expected = RgbaImage::from_pixel(4, 4, image::Rgba([10, 20, 30, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(1, 2, image::Rgba([12, 20, 30, 255]));
+        assert_eq!(
+            compare_images(&expected, &actual, SnapshotTolerance::default()),
+            Ok(())
+        );
+
+        actual.put_pixel(3, 3, image::Rgba([10, 40, 30, 255]));
+        assert!(compare_images(&expected, &actual, SnapshotTolerance::default()).is_err());
+        assert_eq!(
+            compare_images(
+                &expected,
+                &actual,
+                SnapshotTolerance {
+                    max_differing_pixels: 1,
+                    ..Default::default()
+                }
+            ),
+            Ok(())
+        );
+
+        let smaller = RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 255]));
+        assert!(compare_images(&expected, &smaller, SnapshotTolerance::default()).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
-    AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DispatchEventResult, GpuSpecs,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, RequestFrameOptions, ScaledPixels, Size, TestPlatform, TileId, WindowAppearance,
-    WindowBackgroundAppearance, WindowBounds, WindowParams,
+    AccessibilityTree, AnyWindowHandle, AtlasKey, AtlasTextureId, AtlasTile, Bounds, DevicePixels,
+    DispatchEventResult, GpuSpecs, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, RequestFrameOptions, ScaledPixels, Size,
+    TestPlatform, TileId, TileTexture, WindowAppearance, WindowBackgroundAppearance, WindowBounds,
+    WindowParams,
 };
 use collections::HashMap;
 use parking_lot::Mutex;
@@ -19,7 +20,7 @@ pub(crate) struct TestWindowState {
     pub(crate) title: Option<String>,
     pub(crate) edited: bool,
     platform: Weak<TestPlatform>,
-    sprite_atlas: Arc<dyn PlatformAtlas>,
+    pub(crate) sprite_atlas: Arc<TestAtlas>,
     pub(crate) should_close_handler: Option<Box<dyn FnMut() -> bool>>,
     input_callback: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
     active_status_change_callback: Option<Box<dyn FnMut(bool)>>,
@@ -28,6 +29,8 @@ pub(crate) struct TestWindowState {
     moved_callback: Option<Box<dyn FnMut()>>,
     input_handler: Option<PlatformInputHandler>,
     is_fullscreen: bool,
+    pub(crate) accessibility_enabled: bool,
+    pub(crate) accessibility_tree: Option<AccessibilityTree>,
 }
 
 #[derive(Clone)]
@@ -72,6 +75,8 @@ impl TestWindow {
             moved_callback: None,
             input_handler: None,
             is_fullscreen: false,
+            accessibility_enabled: false,
+            accessibility_tree: None,
         })))
     }
 
@@ -262,6 +267,14 @@ impl PlatformWindow for TestWindow {
         self.0.lock().sprite_atlas.clone()
     }
 
+    fn accessibility_enabled(&self) -> bool {
+        self.0.lock().accessibility_enabled
+    }
+
+    fn update_accessibility_tree(&self, tree: AccessibilityTree) {
+        self.0.lock().accessibility_tree = Some(tree);
+    }
+
     fn as_test(&mut self) -> Option<&mut TestWindow> {
         Some(self)
     }
@@ -289,6 +302,8 @@ impl PlatformWindow for TestWindow {
 pub(crate) struct TestAtlasState {
     next_id: u32,
     tiles: HashMap<AtlasKey, AtlasTile>,
+    /// The contents of each tile, keyed by texture index, so that scenes can be rasterized.
+    textures: HashMap<u32, (Size<DevicePixels>, Arc<[u8]>)>,
 }
 
 pub(crate) struct TestAtlas(Mutex<TestAtlasState>);
@@ -298,8 +313,18 @@ impl TestAtlas {
         TestAtlas(Mutex::new(TestAtlasState {
             next_id: 0,
             tiles: HashMap::default(),
+            textures: HashMap::default(),
         }))
     }
+
+    pub(crate) fn tile_texture(&self, tile: &AtlasTile) -> Option<TileTexture> {
+        let state = self.0.lock();
+        let (size, bytes) = state.textures.get(&tile.texture_id.index)?;
+        Some(TileTexture {
+            size: *size,
+            bytes: bytes.clone(),
+        })
+    }
 }
 
 impl PlatformAtlas for TestAtlas {
@@ -316,7 +341,7 @@ impl PlatformAtlas for TestAtlas {
         }
         drop(state);
 
-        let Some((size, _)) = build()? else {
+        let Some((size, bytes)) = build()? else {
             return Ok(None);
         };
 
@@ -331,7 +356,7 @@ impl PlatformAtlas for TestAtlas {
             crate::AtlasTile {
                 texture_id: AtlasTextureId {
                     index: texture_id,
-                    kind: crate::AtlasTextureKind::Path,
+                    kind: key.texture_kind(),
                 },
                 tile_id: TileId(tile_id),
                 padding: 0,
@@ -342,11 +367,15 @@ impl PlatformAtlas for TestAtlas {
             },
         );
 
+        state.textures.insert(texture_id, (size, bytes.into()));
+
         Ok(Some(state.tiles[key].clone()))
     }
 
     fn remove(&self, key: &AtlasKey) {
         let mut state = self.0.lock();
-        state.tiles.remove(key);
+        if let Some(tile) = state.tiles.remove(key) {
+            state.textures.remove(&tile.texture_id.index);
+        }
     }
 }
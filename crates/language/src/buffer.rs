@@ -22,7 +22,7 @@ use async_watch as watch;
 use clock::Lamport;
 pub use clock::ReplicaId;
 use collections::HashMap;
-use fs::MTime;
+use fs::{Encoding, MTime};
 use futures::channel::oneshot;
 use gpui::{
     App, AppContext as _, Context, Entity, EventEmitter, HighlightStyle, SharedString, StyledText,
@@ -101,6 +101,9 @@ pub struct Buffer {
     /// The version vector when this buffer was last loaded from
     /// or saved to disk.
     saved_version: clock::Global,
+    /// The encoding of the file on disk, which the buffer is
+    /// converted back to when it is saved.
+    encoding: Encoding,
     preview_version: clock::Global,
     transaction_depth: usize,
     was_dirty_before_starting_transaction: Option<bool>,
@@ -381,8 +384,8 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file
     fn abs_path(&self, cx: &App) -> PathBuf;
 
-    /// Loads the file contents from disk and returns them as a UTF-8 encoded string.
-    fn load(&self, cx: &App) -> Task<Result<String>>;
+    /// Loads the file contents from disk, decoding them from the given encoding.
+    fn load(&self, encoding: Encoding, cx: &App) -> Task<Result<String>>;
 
     /// Loads the file's contents from disk.
     fn load_bytes(&self, cx: &App) -> Task<Result<Vec<u8>>>;
@@ -823,6 +826,11 @@ impl Buffer {
         ));
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
+        this.encoding = message
+            .encoding
+            .as_ref()
+            .map(proto::deserialize_encoding)
+            .unwrap_or_default();
         Ok(this)
     }
 
@@ -835,6 +843,7 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            encoding: Some(proto::serialize_encoding(self.encoding)),
         }
     }
 
@@ -912,6 +921,7 @@ impl Buffer {
         Self {
             saved_mtime,
             saved_version: buffer.version(),
+            encoding: Encoding::UTF8,
             preview_version: buffer.version(),
             reload_task: None,
             transaction_depth: 0,
@@ -1207,6 +1217,16 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The encoding of the buffer's file on disk.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Sets the encoding that the buffer is written in when it is saved.
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        self.encoding = encoding;
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut Context<Self>) {
         self.non_text_state_update_count += 1;
@@ -1260,12 +1280,21 @@ impl Buffer {
 
     /// Reloads the contents of the buffer from disk.
     pub fn reload(&mut self, cx: &Context<Self>) -> oneshot::Receiver<Option<Transaction>> {
+        self.reload_with_encoding(self.encoding, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them from the given encoding.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Encoding,
+        cx: &Context<Self>,
+    ) -> oneshot::Receiver<Option<Transaction>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(async move |this, cx| {
             let Some((new_mtime, new_text)) = this.update(cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.disk_state().mtime(), file.load(cx)))
+                Some((file.disk_state().mtime(), file.load(encoding, cx)))
             })?
             else {
                 return Ok(());
@@ -1277,6 +1306,9 @@ impl Buffer {
                 .await;
             this.update(cx, |this, cx| {
                 if this.version() == diff.base_version {
+                    // The buffer only switches to the new encoding when its contents are replaced
+                    // with the ones decoded from it.
+                    this.encoding = encoding;
                     this.finalize_last_transaction();
                     this.apply_diff(diff, cx);
                     tx.send(this.finalize_last_transaction().cloned()).ok();
@@ -3823,6 +3855,76 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns the ranges of prose that should be spell checked, such as comments and strings, as
+    /// matched by the spellcheck query of each language in the buffer. Nodes captured as
+    /// `@spellcheck.ignore` (e.g. inline code in Markdown) are cut out of those ranges.
+    ///
+    /// Buffers without a grammar are treated as prose in their entirety.
+    pub fn spellcheck_ranges<T: ToOffset>(&self, range: Range<T>) -> Vec<Range<usize>> {
+        let offset_range = range.start.to_offset(self)..range.end.to_offset(self);
+        if self
+            .language
+            .as_ref()
+            .is_none_or(|language| language.grammar().is_none())
+        {
+            return vec![offset_range];
+        }
+
+        let mut syntax_matches = self.syntax.matches(offset_range, self, |grammar| {
+            grammar
+                .spellcheck_config
+                .as_ref()
+                .map(|config| &config.query)
+        });
+        let configs = syntax_matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.spellcheck_config.as_ref())
+            .collect::<Vec<_>>();
+
+        let mut included_ranges = Vec::new();
+        let mut ignored_ranges = Vec::new();
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(config) = configs[mat.grammar_index] {
+                for capture in mat.captures {
+                    if capture.index == config.spellcheck_capture_ix {
+                        included_ranges.push(capture.node.byte_range());
+                    } else if Some(capture.index) == config.ignore_capture_ix {
+                        ignored_ranges.push(capture.node.byte_range());
+                    }
+                }
+            }
+            syntax_matches.advance();
+        }
+        included_ranges.sort_unstable_by_key(|range| (range.start, Reverse(range.end)));
+        ignored_ranges.sort_unstable_by_key(|range| range.start);
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for range in included_ranges {
+            // Skip ranges nested in one that was already included.
+            if ranges.last().is_some_and(|last| last.end >= range.end) {
+                continue;
+            }
+            let mut start = range.start.max(ranges.last().map_or(0, |last| last.end));
+            for ignored in &ignored_ranges {
+                if ignored.end <= start {
+                    continue;
+                }
+                if ignored.start >= range.end {
+                    break;
+                }
+                if ignored.start > start {
+                    ranges.push(start..ignored.start);
+                }
+                start = start.max(ignored.end);
+            }
+            if start < range.end {
+                ranges.push(start..range.end);
+            }
+        }
+        ranges
+    }
+
     pub fn injections_intersecting_range<T: ToOffset>(
         &self,
         range: Range<T>,
@@ -4629,7 +4731,7 @@ impl LocalFile for TestFile {
             .join(self.path.as_ref())
     }
 
-    fn load(&self, _cx: &App) -> Task<Result<String>> {
+    fn load(&self, _: Encoding, _cx: &App) -> Task<Result<String>> {
         unimplemented!()
     }
 
@@ -969,6 +969,37 @@ fn test_text_objects(cx: &mut App) {
     )
 }
 
+#[gpui::test]
+fn test_spellcheck_ranges(cx: &mut App) {
+    let language = rust_lang()
+        .with_spellcheck_query(
+            r#"
+            (block_comment) @spellcheck
+            (string_literal) @spellcheck
+            (escape_sequence) @spellcheck.ignore
+            "#,
+        )
+        .unwrap();
+    let text = indoc! {r#"
+        /* Say helo */
+        fn say() -> &'static str { "helo\nwrold" }
+    "#};
+
+    let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    let ranges = snapshot
+        .spellcheck_ranges(0..text.len())
+        .into_iter()
+        .map(|range| &text[range])
+        .collect::<Vec<_>>();
+    assert_eq!(ranges, &["/* Say helo */", "\"helo", "wrold\""]);
+
+    // Buffers without a grammar are checked as a whole.
+    let buffer = cx.new(|cx| Buffer::local(text, cx));
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(snapshot.spellcheck_ranges(0..text.len()), &[0..text.len()]);
+}
+
 #[gpui::test]
 fn test_enclosing_bracket_ranges(cx: &mut App) {
     let mut assert = |selection_text, range_markers| {
@@ -14,6 +14,7 @@ pub mod language_settings;
 mod manifest;
 mod outline;
 pub mod proto;
+mod structural_pattern;
 mod syntax_map;
 mod task_context;
 mod text_diff;
@@ -80,6 +81,7 @@ pub use language_registry::{
 };
 pub use lsp::{LanguageServerId, LanguageServerName};
 pub use outline::*;
+pub use structural_pattern::{StructuralMatch, StructuralPattern};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer, ToTreeSitterPoint, TreeSitterOptions};
 pub use text::{AnchorRangeExt, LineEnding};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
@@ -1020,6 +1022,7 @@ pub struct Grammar {
     pub(crate) highlights_query: Option<Query>,
     pub(crate) brackets_config: Option<BracketsConfig>,
     pub(crate) redactions_config: Option<RedactionConfig>,
+    pub(crate) spellcheck_config: Option<SpellcheckConfig>,
     pub(crate) runnable_config: Option<RunnableConfig>,
     pub(crate) indents_config: Option<IndentConfig>,
     pub outline_config: Option<OutlineConfig>,
@@ -1109,6 +1112,12 @@ struct RedactionConfig {
     pub redaction_capture_ix: u32,
 }
 
+struct SpellcheckConfig {
+    pub query: Query,
+    pub spellcheck_capture_ix: u32,
+    pub ignore_capture_ix: Option<u32>,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 enum RunnableCapture {
     Named(SharedString),
@@ -1180,6 +1189,7 @@ impl Language {
                     injection_config: None,
                     override_config: None,
                     redactions_config: None,
+                    spellcheck_config: None,
                     runnable_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").ok(),
                     ts_language,
@@ -1247,6 +1257,11 @@ impl Language {
                 .with_runnable_query(query.as_ref())
                 .context("Error loading runnables query")?;
         }
+        if let Some(query) = queries.spellcheck {
+            self = self
+                .with_spellcheck_query(query.as_ref())
+                .context("Error loading spellcheck query")?;
+        }
         if let Some(query) = queries.text_objects {
             self = self
                 .with_text_object_query(query.as_ref())
@@ -1622,6 +1637,33 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_spellcheck_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut spellcheck_capture_ix = None;
+        let mut ignore_capture_ix = None;
+        get_capture_indices(
+            &query,
+            &mut [
+                ("spellcheck", &mut spellcheck_capture_ix),
+                ("spellcheck.ignore", &mut ignore_capture_ix),
+            ],
+        );
+
+        if let Some(spellcheck_capture_ix) = spellcheck_capture_ix {
+            grammar.spellcheck_config = Some(SpellcheckConfig {
+                query,
+                spellcheck_capture_ix,
+                ignore_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -196,6 +196,7 @@ pub const QUERY_FILENAME_PREFIXES: &[(
     ("overrides", |q| &mut q.overrides),
     ("redactions", |q| &mut q.redactions),
     ("runnables", |q| &mut q.runnables),
+    ("spellcheck", |q| &mut q.spellcheck),
     ("textobjects", |q| &mut q.text_objects),
 ];
 
@@ -211,6 +212,7 @@ pub struct LanguageQueries {
     pub overrides: Option<Cow<'static, str>>,
     pub redactions: Option<Cow<'static, str>>,
     pub runnables: Option<Cow<'static, str>>,
+    pub spellcheck: Option<Cow<'static, str>>,
     pub text_objects: Option<Cow<'static, str>>,
 }
 
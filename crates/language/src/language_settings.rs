@@ -124,6 +124,8 @@ pub struct LanguageSettings {
     pub edit_predictions_disabled_in: Vec<String>,
     /// Whether to show tabs and spaces in the editor.
     pub show_whitespaces: ShowWhitespaceSetting,
+    /// Whether to color bracket pairs by their nesting depth.
+    pub colorize_brackets: bool,
     /// Whether to start a new line with a comment when a previous line is a comment as well.
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
@@ -490,6 +492,12 @@ pub struct LanguageSettingsContent {
     /// Whether to show tabs and spaces in the editor.
     #[serde(default)]
     pub show_whitespaces: Option<ShowWhitespaceSetting>,
+    /// Whether to color bracket pairs by their nesting depth, cycling through
+    /// the accent colors of the theme.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub colorize_brackets: Option<bool>,
     /// Whether to start a new line with a comment when a previous line is a comment as well.
     ///
     /// Default: true
@@ -1438,6 +1446,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.edit_predictions_disabled_in.clone(),
     );
     merge(&mut settings.show_whitespaces, src.show_whitespaces);
+    merge(&mut settings.colorize_brackets, src.colorize_brackets);
     merge(
         &mut settings.extend_comment_on_newline,
         src.extend_comment_on_newline,
@@ -27,6 +27,20 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`fs::Encoding`] from the RPC representation, falling back to UTF-8 for
+/// encodings that aren't known.
+pub fn deserialize_encoding(message: &proto::Encoding) -> fs::Encoding {
+    fs::Encoding::from_name_and_bom(&message.name, message.bom).unwrap_or_default()
+}
+
+/// Serializes a [`fs::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: fs::Encoding) -> proto::Encoding {
+    proto::Encoding {
+        name: encoding.name().to_string(),
+        bom: encoding.has_bom(),
+    }
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
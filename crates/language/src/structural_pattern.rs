@@ -0,0 +1,605 @@
+use crate::{BufferSnapshot, Grammar, GrammarId, with_parser};
+use anyhow::{Result, bail};
+use collections::HashMap;
+use parking_lot::Mutex;
+use std::{fmt, fmt::Write as _, ops::Range, sync::Arc};
+use tree_sitter::{Node, Query};
+
+/// What metavariables are replaced with before a pattern is parsed as code.
+const METAVARIABLE_PREFIX: &str = "zed_metavariable_";
+
+/// The name of the capture that spans a whole match.
+const MATCH_CAPTURE: &str = "match";
+
+/// A pattern that matches syntax trees, in one of two forms:
+///
+/// - A tree-sitter query, such as `(call_expression function: (identifier) @name)`. A match
+///   spans its `@match` capture, or all of its captures if it has none.
+/// - A code snippet with metavariables, such as `foo($A, $B)`. The snippet is parsed in the
+///   language it's run against, and matches code with the same syntax tree, where each
+///   metavariable matches any single node. A metavariable that's used more than once matches
+///   nodes with the same text.
+///
+/// Captures and metavariables can be referred to in replacement templates as `$NAME`.
+pub struct StructuralPattern {
+    source: Arc<str>,
+    /// The pattern compiled for each grammar it has been run against, or `None` if it isn't
+    /// valid in that grammar.
+    queries: Mutex<HashMap<GrammarId, Option<Arc<Query>>>>,
+}
+
+/// A match of a [`StructuralPattern`] in a buffer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    /// The ranges of the match's captures, by name.
+    pub captures: Vec<(Arc<str>, Range<usize>)>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StructuralPattern")
+            .field(&self.source)
+            .finish()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: impl Into<Arc<str>>) -> Self {
+        Self {
+            source: source.into(),
+            queries: Mutex::default(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the pattern is a code snippet, rather than a tree-sitter query.
+    pub fn is_snippet(&self) -> bool {
+        !self.source.trim_start().starts_with(['(', '[', ';'])
+    }
+
+    /// Checks that the pattern is well-formed, independently of the languages it's run against:
+    /// its parentheses, brackets and braces must be balanced and its strings terminated, and
+    /// the captures of a tree-sitter query must be named.
+    pub fn validate(&self) -> Result<()> {
+        if self.source.trim().is_empty() {
+            bail!("the pattern is empty");
+        }
+        let is_query = !self.is_snippet();
+        let mut open_delimiters = Vec::<(char, usize)>::new();
+        let mut chars = self.source.char_indices().peekable();
+        while let Some((ix, c)) = chars.next() {
+            match c {
+                '\'' | '`' if is_query => {}
+                '"' | '\'' | '`' => {
+                    // In snippets, quotes that aren't closed on the same line aren't strings,
+                    // and neither are quotes followed by a word, like Rust's lifetimes.
+                    let rest = &self.source[ix + 1..];
+                    let word_len = rest
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    if c == '\'' && word_len > 0 && !rest[word_len..].starts_with('\'') {
+                        continue;
+                    }
+                    let line = rest.split('\n').next().unwrap_or_default();
+                    match string_len(line, c) {
+                        Some(len) => {
+                            let end = ix + 1 + len;
+                            while chars.next_if(|(ix, _)| *ix <= end).is_some() {}
+                        }
+                        None if is_query => bail!("unterminated string at offset {ix}"),
+                        None => {}
+                    }
+                }
+                ';' if is_query => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+                '@' if is_query => {
+                    if !chars
+                        .peek()
+                        .is_some_and(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+                    {
+                        bail!("missing capture name at offset {ix}");
+                    }
+                }
+                '(' | '[' | '{' => open_delimiters.push((c, ix)),
+                ')' | ']' | '}' => {
+                    let expected = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    match open_delimiters.pop() {
+                        Some((open, _)) if open == expected => {}
+                        _ => bail!("unmatched `{c}` at offset {ix}"),
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some((open, ix)) = open_delimiters.pop() {
+            bail!("unclosed `{open}` at offset {ix}");
+        }
+        Ok(())
+    }
+
+    /// The longest word that every match of the pattern contains, which can be used to skip
+    /// files without parsing them.
+    pub fn required_word(&self) -> Option<&str> {
+        if !self.is_snippet() {
+            return None;
+        }
+        let mut longest = None::<&str>;
+        let mut rest = &*self.source;
+        while let Some(start) = rest.find(|c: char| c.is_alphanumeric() || c == '_' || c == '$') {
+            rest = &rest[start..];
+            let is_metavariable = rest.starts_with('$');
+            let word_start = if is_metavariable { 1 } else { 0 };
+            let len = rest[word_start..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .map_or(rest.len(), |len| word_start + len);
+            let word = &rest[word_start..len];
+            if !is_metavariable && longest.is_none_or(|longest| word.len() > longest.len()) {
+                longest = Some(word);
+            }
+            rest = &rest[len.max(1)..];
+        }
+        longest
+    }
+
+    /// Finds the matches of the pattern in the given range of a buffer, in every language
+    /// layer that it's valid in.
+    pub fn matches(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut queries = Vec::<(GrammarId, Arc<Query>)>::new();
+        for layer in buffer
+            .syntax
+            .layers_for_range(range.clone(), &buffer.text, true)
+        {
+            let Some(grammar) = layer.language.grammar() else {
+                continue;
+            };
+            if queries.iter().any(|(id, _)| *id == grammar.id()) {
+                continue;
+            }
+            if let Some(query) = self.query_for_grammar(grammar) {
+                queries.push((grammar.id(), query));
+            }
+        }
+        if queries.is_empty() {
+            return Vec::new();
+        }
+
+        let query_for_grammar = |grammar: &Grammar| {
+            queries
+                .iter()
+                .find(|(id, _)| *id == grammar.id())
+                .map(|(_, query)| query.as_ref())
+        };
+        let mut syntax_matches = buffer
+            .syntax
+            .matches_with_queries(range, buffer, |grammar| query_for_grammar(grammar));
+        let grammars = syntax_matches.grammars().to_vec();
+
+        let mut matches = Vec::new();
+        while let Some(mat) = syntax_matches.peek() {
+            if let Some(query) = query_for_grammar(grammars[mat.grammar_index]) {
+                let capture_names = query.capture_names();
+                let mut match_range = None::<Range<usize>>;
+                let mut union_range = None::<Range<usize>>;
+                let mut captures = Vec::new();
+                for capture in mat.captures {
+                    let name = capture_names[capture.index as usize];
+                    let range = capture.node.byte_range();
+                    if name == MATCH_CAPTURE {
+                        match_range = Some(range.clone());
+                    }
+                    union_range = Some(match union_range {
+                        Some(union) => union.start.min(range.start)..union.end.max(range.end),
+                        None => range.clone(),
+                    });
+                    if !name.starts_with('_') {
+                        captures.push((Arc::from(name), range));
+                    }
+                }
+                if let Some(range) = match_range.or(union_range) {
+                    matches.push(StructuralMatch { range, captures });
+                }
+            }
+            syntax_matches.advance();
+        }
+        matches.sort_by_key(|mat| (mat.range.start, mat.range.end));
+        matches.dedup_by(|a, b| a.range == b.range);
+        matches
+    }
+
+    fn query_for_grammar(&self, grammar: &Grammar) -> Option<Arc<Query>> {
+        self.queries
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| {
+                if !self.is_snippet() {
+                    if let Ok(query) = Query::new(&grammar.ts_language, &self.source) {
+                        return Some(Arc::new(query));
+                    }
+                }
+                let query_source = snippet_query(&self.source, grammar)?;
+                match Query::new(&grammar.ts_language, &query_source) {
+                    Ok(query) => Some(Arc::new(query)),
+                    Err(error) => {
+                        log::debug!("invalid query {query_source:?} for pattern: {error}");
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+}
+
+impl StructuralMatch {
+    /// Expands a replacement template, where `$NAME` or `${NAME}` is replaced with the text of
+    /// the capture or metavariable with that name, and `$$` with `$`.
+    pub fn expand_template(&self, template: &str, buffer: &BufferSnapshot) -> String {
+        let mut result = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(ix) = rest.find('$') {
+            result.push_str(&rest[..ix]);
+            rest = &rest[ix + 1..];
+            if let Some(after) = rest.strip_prefix('$') {
+                result.push('$');
+                rest = after;
+                continue;
+            }
+
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let len = rest
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..len], &rest[len..])
+                }
+            };
+            match self
+                .captures
+                .iter()
+                .find(|(capture_name, _)| capture_name.as_ref() == name)
+            {
+                Some((_, range)) if !name.is_empty() => {
+                    result.extend(buffer.text_for_range(range.clone()));
+                    rest = after;
+                }
+                _ => result.push('$'),
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+}
+
+/// Compiles a code snippet with metavariables into a tree-sitter query for a grammar, by
+/// parsing it and turning its syntax tree into a pattern.
+fn snippet_query(snippet: &str, grammar: &Grammar) -> Option<String> {
+    let mut source = String::with_capacity(snippet.len());
+    let mut rest = snippet.trim();
+    while let Some(ix) = rest.find('$') {
+        source.push_str(&rest[..ix]);
+        rest = &rest[ix + 1..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        if len == 0 || rest.starts_with(|c: char| c.is_ascii_digit()) {
+            source.push('$');
+            continue;
+        }
+        source.push_str(METAVARIABLE_PREFIX);
+        source.push_str(&rest[..len]);
+        rest = &rest[len..];
+    }
+    source.push_str(rest);
+
+    let tree = with_parser(|parser| {
+        parser.set_language(&grammar.ts_language).ok()?;
+        parser.parse(&source, None)
+    })?;
+
+    // The snippet is the innermost node that spans all of it, since it's usually parsed inside
+    // of nodes like a source file or a statement.
+    let mut node = tree.root_node();
+    'descend: loop {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.byte_range() == (0..source.len()) {
+                node = child;
+                continue 'descend;
+            }
+        }
+        break;
+    }
+    if node.has_error() || node.byte_range() != (0..source.len()) {
+        return None;
+    }
+
+    let mut builder = SnippetQueryBuilder {
+        source: &source,
+        pattern: String::new(),
+        predicates: String::new(),
+        metavariables: Vec::new(),
+        leaf_count: 0,
+    };
+    builder.push_node(node);
+    Some(format!(
+        "({} @{MATCH_CAPTURE}{})",
+        builder.pattern, builder.predicates
+    ))
+}
+
+struct SnippetQueryBuilder<'a> {
+    source: &'a str,
+    pattern: String,
+    predicates: String,
+    metavariables: Vec<&'a str>,
+    leaf_count: usize,
+}
+
+impl<'a> SnippetQueryBuilder<'a> {
+    fn push_node(&mut self, node: Node) {
+        let text = &self.source[node.byte_range()];
+        if let Some(name) = text.strip_prefix(METAVARIABLE_PREFIX) {
+            if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                // Later uses of a metavariable get their own captures, which must have the
+                // same text as the first one, unless it starts with `_` like `$_`.
+                let uses = self
+                    .metavariables
+                    .iter()
+                    .filter(|metavariable| **metavariable == name)
+                    .count();
+                if uses == 0 || name.starts_with('_') {
+                    write!(self.pattern, "(_) @{name}").unwrap();
+                } else {
+                    write!(self.pattern, "(_) @__{name}_{uses}").unwrap();
+                    write!(self.predicates, " (#eq? @{name} @__{name}_{uses})").unwrap();
+                }
+                self.metavariables.push(name);
+                return;
+            }
+        }
+
+        if node.named_child_count() == 0 {
+            let capture = format!("__leaf_{}", self.leaf_count);
+            self.leaf_count += 1;
+            write!(self.pattern, "({}) @{capture}", node.kind()).unwrap();
+            write!(self.predicates, " (#eq? @{capture} {})", query_string(text)).unwrap();
+            return;
+        }
+
+        // Named children are anchored to each other and to the ends of their parent, so that
+        // they only match nodes with the same children. Anonymous children are only matched
+        // when they're in a field, like the operator of a binary expression.
+        write!(self.pattern, "({}", node.kind()).unwrap();
+        let mut cursor = node.walk();
+        for (ix, child) in node.children(&mut cursor).enumerate() {
+            if child.is_extra() {
+                continue;
+            }
+            let field_name = node.field_name_for_child(ix as u32);
+            if !child.is_named() {
+                if let Some(field_name) = field_name {
+                    write!(
+                        self.pattern,
+                        " {field_name}: {}",
+                        query_string(child.kind())
+                    )
+                    .unwrap();
+                }
+                continue;
+            }
+            self.pattern.push_str(" .");
+            if let Some(field_name) = field_name {
+                write!(self.pattern, " {field_name}:").unwrap();
+            }
+            self.pattern.push(' ');
+            self.push_node(child);
+        }
+        self.pattern.push_str(" .)");
+    }
+}
+
+/// Returns the length of a string's contents up to its closing quote, skipping escaped
+/// characters, if it's closed.
+fn string_len(text: &str, quote: char) -> Option<usize> {
+    let mut chars = text.char_indices();
+    while let Some((ix, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return Some(ix);
+        }
+    }
+    None
+}
+
+fn query_string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Buffer, Language, LanguageConfig, LanguageMatcher};
+    use gpui::{AppContext as _, TestAppContext};
+    use pretty_assertions::assert_eq;
+
+    fn rust_lang() -> Language {
+        Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Some(tree_sitter_rust::LANGUAGE.into()),
+        )
+    }
+
+    fn matched_text(
+        pattern: &str,
+        buffer: &BufferSnapshot,
+    ) -> Vec<(String, Vec<(String, String)>)> {
+        StructuralPattern::new(pattern)
+            .matches(buffer, 0..buffer.len())
+            .into_iter()
+            .map(|mat| {
+                (
+                    buffer.text_for_range(mat.range).collect(),
+                    mat.captures
+                        .into_iter()
+                        .filter(|(name, _)| name.as_ref() != MATCH_CAPTURE)
+                        .map(|(name, range)| {
+                            (name.to_string(), buffer.text_for_range(range).collect())
+                        })
+                        .collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[gpui::test]
+    fn test_structural_pattern_matches(cx: &mut TestAppContext) {
+        let text = r#"
+            fn main() {
+                foo(1, bar(2));
+                foo(x);
+                foo(a, /* b */ b, c);
+                let y = x.unwrap() + z.unwrap();
+                let q = a - a;
+                let r = a + a;
+            }
+        "#;
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx));
+        cx.executor().run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let owned = |captures: &[(&str, &str)]| {
+            captures
+                .iter()
+                .map(|(name, text)| (name.to_string(), text.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            matched_text("foo($A, $B)", &snapshot),
+            [(
+                "foo(1, bar(2))".to_string(),
+                owned(&[("A", "1"), ("B", "bar(2)")])
+            )]
+        );
+        assert_eq!(
+            matched_text("$X.unwrap()", &snapshot),
+            [
+                ("x.unwrap()".to_string(), owned(&[("X", "x")])),
+                ("z.unwrap()".to_string(), owned(&[("X", "z")])),
+            ]
+        );
+        assert_eq!(
+            matched_text("$A + $A", &snapshot),
+            [("a + a".to_string(), owned(&[("A", "a")]))]
+        );
+        assert_eq!(
+            matched_text(
+                "(call_expression function: (identifier) @name (#eq? @name \"bar\"))",
+                &snapshot
+            ),
+            [("bar".to_string(), owned(&[("name", "bar")]))]
+        );
+        assert_eq!(
+            matched_text(
+                "((call_expression function: (identifier) @name) @match (#eq? @name \"bar\"))",
+                &snapshot
+            ),
+            [("bar(2)".to_string(), owned(&[("name", "bar")]))]
+        );
+        assert!(matched_text("foo(", &snapshot).is_empty());
+    }
+
+    #[gpui::test]
+    fn test_structural_pattern_replacement(cx: &mut TestAppContext) {
+        let text = "fn f() { let total = add(price, tax); }";
+        let buffer = cx.new(|cx| Buffer::local(text, cx).with_language(Arc::new(rust_lang()), cx));
+        cx.executor().run_until_parked();
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+
+        let matches = StructuralPattern::new("add($A, $B)").matches(&snapshot, 0..snapshot.len());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0].expand_template("${A}_plus($B) costs $$ $UNKNOWN", &snapshot),
+            "price_plus(tax) costs $ $UNKNOWN"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        for pattern in [
+            "foo($A, $B)",
+            "println!(\"(\", $A)",
+            "fn $F<'a>(x: &'a str) { $$$ }",
+            "(call_expression function: (identifier) @name (#eq? @name \")\"))",
+            "; a comment (\n[(identifier) (string)] @match",
+        ] {
+            assert!(
+                StructuralPattern::new(pattern).validate().is_ok(),
+                "{pattern:?} should be valid"
+            );
+        }
+
+        for (pattern, error) in [
+            ("  ", "the pattern is empty"),
+            ("foo(", "unclosed `(` at offset 3"),
+            ("foo($A]", "unmatched `]` at offset 6"),
+            ("(identifier) @", "missing capture name at offset 13"),
+            (
+                "((identifier) @name (#eq? @name \"x))",
+                "unterminated string at offset 32",
+            ),
+        ] {
+            assert_eq!(
+                StructuralPattern::new(pattern)
+                    .validate()
+                    .unwrap_err()
+                    .to_string(),
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_required_word() {
+        assert_eq!(
+            StructuralPattern::new("$A.unwrap_or_default($B)").required_word(),
+            Some("unwrap_or_default")
+        );
+        assert_eq!(StructuralPattern::new("$A + $B").required_word(), None);
+        assert_eq!(
+            StructuralPattern::new("(call_expression) @match").required_word(),
+            None
+        );
+    }
+}
@@ -835,6 +835,23 @@ impl SyntaxSnapshot {
         )
     }
 
+    /// Like [`Self::matches`], for queries that aren't part of a grammar, such as ones that are
+    /// compiled for each grammar at runtime.
+    pub(crate) fn matches_with_queries<'a>(
+        &'a self,
+        range: Range<usize>,
+        buffer: &'a BufferSnapshot,
+        query: impl Fn(&'a Grammar) -> Option<&'a Query>,
+    ) -> SyntaxMapMatches<'a> {
+        SyntaxMapMatches::new(
+            range.clone(),
+            buffer.as_rope(),
+            self.layers_for_range(range, buffer, true),
+            query,
+            TreeSitterOptions::default(),
+        )
+    }
+
     #[cfg(test)]
     pub fn layers<'a>(&'a self, buffer: &'a BufferSnapshot) -> Vec<SyntaxLayer<'a>> {
         self.layers_for_range(0..buffer.len(), buffer, true)
@@ -1036,7 +1053,7 @@ impl<'a> SyntaxMapMatches<'a> {
         range: Range<usize>,
         text: &'a Rope,
         layers: impl Iterator<Item = SyntaxLayer<'a>>,
-        query: fn(&Grammar) -> Option<&Query>,
+        query: impl Fn(&'a Grammar) -> Option<&'a Query>,
         options: TreeSitterOptions,
     ) -> Self {
         let mut result = Self::default();
@@ -0,0 +1,5 @@
+(comment) @spellcheck
+[
+  (string)
+  (raw_string)
+] @spellcheck
@@ -0,0 +1,2 @@
+(comment) @spellcheck
+(string_literal) @spellcheck
@@ -0,0 +1,5 @@
+(comment) @spellcheck
+[
+  (interpreted_string_literal)
+  (raw_string_literal)
+] @spellcheck
@@ -0,0 +1,6 @@
+(inline) @spellcheck
+
+[
+  (code_span)
+  (link_destination)
+] @spellcheck.ignore
@@ -0,0 +1,5 @@
+(comment) @spellcheck
+[
+  (string)
+  (template_string)
+] @spellcheck
@@ -0,0 +1,124 @@
+use crate::LargeFileView;
+use editor::{Editor, actions::ToggleGoToLine};
+use gpui::{
+    App, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Render, SharedString,
+    Subscription, prelude::*,
+};
+use ui::prelude::*;
+use workspace::{ModalView, Workspace};
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(GoToLineModal::register).detach();
+}
+
+/// Moves a [`LargeFileView`] to a line, since it can't use the editor's go to line.
+pub struct GoToLineModal {
+    line_editor: Entity<Editor>,
+    large_file_view: Entity<LargeFileView>,
+    current_text: SharedString,
+    _subscription: Subscription,
+}
+
+impl ModalView for GoToLineModal {}
+
+impl Focusable for GoToLineModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.line_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for GoToLineModal {}
+
+impl GoToLineModal {
+    fn register(workspace: &mut Workspace, _: Option<&mut Window>, _: &mut Context<Workspace>) {
+        workspace.register_action(|workspace, _: &ToggleGoToLine, window, cx| {
+            let Some(large_file_view) = workspace
+                .active_item(cx)
+                .and_then(|item| item.downcast::<LargeFileView>())
+            else {
+                cx.propagate();
+                return;
+            };
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                GoToLineModal::new(large_file_view, window, cx)
+            });
+        });
+    }
+
+    fn new(
+        large_file_view: Entity<LargeFileView>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let view = large_file_view.read(cx);
+        let line = view.current_row() + 1;
+        let current_text = format!("Current Line: {} of {}", line, view.line_count(cx)).into();
+        let line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text(line.to_string(), cx);
+            editor
+        });
+        let subscription = cx.subscribe(&line_editor, |_, _, event, cx| match event {
+            editor::EditorEvent::Blurred => cx.emit(DismissEvent),
+            editor::EditorEvent::BufferEdited { .. } => cx.notify(),
+            _ => {}
+        });
+        Self {
+            line_editor,
+            large_file_view,
+            current_text,
+            _subscription: subscription,
+        }
+    }
+
+    fn row_from_query(&self, cx: &App) -> Option<usize> {
+        let query = self.line_editor.read(cx).text(cx);
+        let line = query.trim().parse::<usize>().ok()?;
+        Some(line.saturating_sub(1))
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self.row_from_query(cx) {
+            self.large_file_view.update(cx, |view, cx| {
+                view.go_to_row(row, cx);
+                view.focus_handle(cx).focus(window);
+            });
+        }
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for GoToLineModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let help_text = match self.row_from_query(cx) {
+            Some(row) => format!("Go to line {}", row + 1).into(),
+            None => self.current_text.clone(),
+        };
+
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("GoToLine")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.line_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_1()
+                    .child(Label::new(help_text).color(Color::Muted)),
+            )
+    }
+}
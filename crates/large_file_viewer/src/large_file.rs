@@ -0,0 +1,599 @@
+use crate::LargeFileViewerSettings;
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use gpui::{App, AppContext as _, Context, Entity, SharedString, Task};
+use parking_lot::RwLock;
+use project::{Fs, Project, ProjectEntryId, ProjectItem, ProjectPath, search::SearchQuery};
+use settings::Settings;
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    ops::Range,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// How many lines are read from disk at once, which is also the distance between the lines
+/// whose offsets are kept in the [`LineIndex`].
+pub(crate) const LINES_PER_BLOCK: usize = 256;
+/// How many blocks of lines are kept in memory at once.
+const MAX_CACHED_BLOCKS: usize = 64;
+/// Lines are cut off after this many bytes, so that a file without newlines can be displayed
+/// without reading all of it into memory.
+pub(crate) const MAX_LINE_LEN: usize = 4096;
+/// Searches only consider this many bytes of each line.
+const MAX_SEARCH_LINE_LEN: usize = 1024 * 1024;
+/// Searches stop after finding this many matches.
+pub(crate) const MAX_SEARCH_MATCHES: usize = 100_000;
+/// How many bytes are scanned for newlines before the scanned lines become visible.
+const INDEX_CHUNK_LEN: usize = 16 * 1024 * 1024;
+
+/// A file that is too large to be loaded into a buffer, and instead is read from disk as it is
+/// displayed.
+pub struct LargeFile {
+    project_path: ProjectPath,
+    entry_id: ProjectEntryId,
+    abs_path: PathBuf,
+    len: u64,
+    fs: Arc<dyn Fs>,
+    index: Arc<RwLock<LineIndex>>,
+    blocks: HashMap<usize, Arc<[SharedString]>>,
+    /// The cached blocks, from least to most recently used.
+    recent_blocks: VecDeque<usize>,
+    loading_blocks: HashMap<usize, Task<()>>,
+    indexing: Option<Task<()>>,
+}
+
+/// A search match within a single line of a [`LargeFile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LargeFileMatch {
+    pub row: usize,
+    /// The byte range of the match within the line.
+    pub range: Range<usize>,
+}
+
+impl LargeFile {
+    fn new(
+        project_path: ProjectPath,
+        entry_id: ProjectEntryId,
+        abs_path: PathBuf,
+        len: u64,
+        fs: Arc<dyn Fs>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let index = Arc::new(RwLock::new(LineIndex::default()));
+        let indexing = cx.spawn({
+            let abs_path = abs_path.clone();
+            let fs = fs.clone();
+            let index = index.clone();
+            async move |this, cx| {
+                let open = cx.background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { fs.open_sync(&abs_path).await }
+                });
+                let mut file = match open.await {
+                    Ok(file) => file,
+                    Err(error) => {
+                        log::error!("failed to open {abs_path:?}: {error}");
+                        return;
+                    }
+                };
+                loop {
+                    let index = index.clone();
+                    let scan = cx.background_spawn(async move {
+                        let mut chunk = Vec::with_capacity(INDEX_CHUNK_LEN);
+                        (&mut file)
+                            .take(INDEX_CHUNK_LEN as u64)
+                            .read_to_end(&mut chunk)?;
+                        index.write().scan(&chunk);
+                        anyhow::Ok((file, chunk.len() < INDEX_CHUNK_LEN))
+                    });
+                    let done;
+                    (file, done) = match scan.await {
+                        Ok(result) => result,
+                        Err(error) => {
+                            log::error!("failed to index {abs_path:?}: {error}");
+                            break;
+                        }
+                    };
+                    if this.update(cx, |_, cx| cx.notify()).is_err() || done {
+                        break;
+                    }
+                }
+                this.update(cx, |this, cx| {
+                    this.indexing.take();
+                    cx.notify();
+                })
+                .ok();
+            }
+        });
+
+        Self {
+            project_path,
+            entry_id,
+            abs_path,
+            len,
+            fs,
+            index,
+            blocks: HashMap::default(),
+            recent_blocks: VecDeque::new(),
+            loading_blocks: HashMap::default(),
+            indexing: Some(indexing),
+        }
+    }
+
+    pub fn abs_path(&self) -> &PathBuf {
+        &self.abs_path
+    }
+
+    pub fn project_path(&self) -> &ProjectPath {
+        &self.project_path
+    }
+
+    /// The size of the file in bytes when it was opened.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// The number of lines that have been found so far.
+    pub fn line_count(&self) -> usize {
+        self.index.read().line_count()
+    }
+
+    /// Whether the file is still being scanned for lines.
+    pub fn is_indexing(&self) -> bool {
+        self.indexing.is_some()
+    }
+
+    /// Returns the given lines, or `None` for lines that haven't been read from disk yet, in
+    /// which case they are loaded in the background.
+    pub fn lines(
+        &mut self,
+        rows: Range<usize>,
+        cx: &mut Context<Self>,
+    ) -> Vec<Option<SharedString>> {
+        let rows = rows.start..rows.end.min(self.line_count());
+        let mut lines = Vec::with_capacity(rows.len());
+        let mut row = rows.start;
+        while row < rows.end {
+            let block = row / LINES_PER_BLOCK;
+            let block_rows = row..rows.end.min((block + 1) * LINES_PER_BLOCK);
+            if let Some(block_lines) = self.block(block, cx) {
+                lines.extend(block_rows.map(|row| {
+                    // The last line of a file that ends in a newline is empty.
+                    Some(
+                        block_lines
+                            .get(row % LINES_PER_BLOCK)
+                            .cloned()
+                            .unwrap_or_default(),
+                    )
+                }));
+            } else {
+                lines.extend(block_rows.map(|_| None));
+            }
+            row = (block + 1) * LINES_PER_BLOCK;
+        }
+        lines
+    }
+
+    fn block(&mut self, block: usize, cx: &mut Context<Self>) -> Option<Arc<[SharedString]>> {
+        if let Some(lines) = self.blocks.get(&block) {
+            self.recent_blocks
+                .retain(|recent_block| *recent_block != block);
+            self.recent_blocks.push_back(block);
+            return Some(lines.clone());
+        }
+        if self.loading_blocks.contains_key(&block) {
+            return None;
+        }
+
+        let offset = self.index.read().block_offset(block)?;
+        let abs_path = self.abs_path.clone();
+        let fs = self.fs.clone();
+        let read = cx.background_spawn(async move {
+            let mut file = fs.open_seekable(&abs_path).await?;
+            anyhow::Ok(read_block(&mut file, offset)?)
+        });
+        let task = cx.spawn(async move |this, cx| {
+            let lines = read.await;
+            this.update(cx, |this, cx| {
+                this.loading_blocks.remove(&block);
+                match lines {
+                    Ok(lines) => {
+                        this.blocks.insert(block, lines.into());
+                        this.recent_blocks.push_back(block);
+                        while this.recent_blocks.len() > MAX_CACHED_BLOCKS {
+                            if let Some(evicted) = this.recent_blocks.pop_front() {
+                                this.blocks.remove(&evicted);
+                            }
+                        }
+                        cx.notify();
+                    }
+                    Err(error) => log::error!("failed to read {:?}: {error}", this.abs_path),
+                }
+            })
+            .ok();
+        });
+        self.loading_blocks.insert(block, task);
+        None
+    }
+
+    /// Finds the matches for the query in the whole file, line by line.
+    pub fn search(&self, query: Arc<SearchQuery>, cx: &App) -> Task<Vec<LargeFileMatch>> {
+        let abs_path = self.abs_path.clone();
+        let fs = self.fs.clone();
+        cx.background_spawn(async move {
+            let file = match fs.open_sync(&abs_path).await {
+                Ok(file) => file,
+                Err(error) => {
+                    log::error!("failed to open {abs_path:?}: {error}");
+                    return Vec::new();
+                }
+            };
+            search_lines(BufReader::new(file), &query).unwrap_or_else(|error| {
+                log::error!("failed to search {abs_path:?}: {error}");
+                Vec::new()
+            })
+        })
+    }
+}
+
+impl ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        let threshold = LargeFileViewerSettings::get_global(cx).threshold_bytes();
+        let project = project.read(cx);
+        if !project.is_local() {
+            return None;
+        }
+        // Excluded files aren't scanned, so their size is unknown to the worktree and they are
+        // opened as buffers.
+        let entry = project.entry_for_path(path, cx)?;
+        if !entry.is_file() || entry.size <= threshold {
+            return None;
+        }
+        let abs_path = project.absolute_path(path, cx)?;
+        let fs = project.fs().clone();
+
+        let path = path.clone();
+        let entry_id = entry.id;
+        Some(cx.spawn(async move |cx| {
+            // The file may have changed since the worktree was scanned.
+            let metadata = cx
+                .background_spawn({
+                    let fs = fs.clone();
+                    let abs_path = abs_path.clone();
+                    async move { fs.metadata(&abs_path).await }
+                })
+                .await
+                .with_context(|| format!("reading metadata of {abs_path:?}"))?
+                .with_context(|| format!("{abs_path:?} doesn't exist"))?;
+            cx.new(|cx| LargeFile::new(path, entry_id, abs_path, metadata.len, fs, cx))
+        }))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        Some(self.entry_id)
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+/// The byte offsets of a sample of a file's lines, so that any line can be found by reading at
+/// most [`LINES_PER_BLOCK`] lines.
+#[derive(Debug)]
+pub(crate) struct LineIndex {
+    /// The offset of every [`LINES_PER_BLOCK`]th line.
+    block_offsets: Vec<u64>,
+    line_count: usize,
+    scanned_len: u64,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self {
+            block_offsets: vec![0],
+            line_count: 1,
+            scanned_len: 0,
+        }
+    }
+}
+
+impl LineIndex {
+    /// Adds the lines in the next chunk of the file to the index.
+    pub fn scan(&mut self, chunk: &[u8]) {
+        let mut offset = 0;
+        while let Some(newline_ix) = chunk[offset..].iter().position(|byte| *byte == b'\n') {
+            offset += newline_ix + 1;
+            if self.line_count % LINES_PER_BLOCK == 0 {
+                self.block_offsets.push(self.scanned_len + offset as u64);
+            }
+            self.line_count += 1;
+        }
+        self.scanned_len += chunk.len() as u64;
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    pub fn block_offset(&self, block: usize) -> Option<u64> {
+        self.block_offsets.get(block).copied()
+    }
+}
+
+/// Reads the block of lines that starts at the given offset.
+pub(crate) fn read_block(
+    reader: &mut (impl Read + Seek),
+    offset: u64,
+) -> io::Result<Vec<SharedString>> {
+    reader.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    let mut lines = Vec::with_capacity(LINES_PER_BLOCK);
+    while lines.len() < LINES_PER_BLOCK {
+        let Some(len) = read_line(&mut reader, &mut line, MAX_LINE_LEN)? else {
+            break;
+        };
+        let mut text = String::from_utf8_lossy(&line).into_owned();
+        if len > MAX_LINE_LEN {
+            text.push('…');
+        }
+        lines.push(text.into());
+    }
+    Ok(lines)
+}
+
+pub(crate) fn search_lines(
+    mut reader: impl BufRead,
+    query: &SearchQuery,
+) -> io::Result<Vec<LargeFileMatch>> {
+    let mut matches = Vec::new();
+    if query.as_str().is_empty() {
+        return Ok(matches);
+    }
+    let mut line = Vec::new();
+    let mut row = 0;
+    while read_line(&mut reader, &mut line, MAX_SEARCH_LINE_LEN)?.is_some() {
+        let text = String::from_utf8_lossy(&line);
+        for range in find_in_line(query, &text) {
+            matches.push(LargeFileMatch { row, range });
+            if matches.len() == MAX_SEARCH_MATCHES {
+                return Ok(matches);
+            }
+        }
+        row += 1;
+    }
+    Ok(matches)
+}
+
+/// Finds the matches for the query within a line. Multiline regexes are limited to the line.
+fn find_in_line(query: &SearchQuery, line: &str) -> Vec<Range<usize>> {
+    match query {
+        SearchQuery::Text {
+            search, whole_word, ..
+        } => search
+            .find_iter(line.as_bytes())
+            .map(|mat| mat.start()..mat.end())
+            .filter(|range| !*whole_word || is_whole_word(line, range))
+            .collect(),
+        SearchQuery::Regex { regex, .. } => regex
+            .find_iter(line)
+            .flatten()
+            .map(|mat| mat.start()..mat.end())
+            .collect(),
+        // Large files aren't parsed, so there are no syntax trees to match.
+        SearchQuery::Structural { .. } => Vec::new(),
+    }
+}
+
+fn is_whole_word(line: &str, range: &Range<usize>) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let text = &line[range.clone()];
+    let starts_word = text.chars().next().is_some_and(is_word);
+    let ends_word = text.chars().next_back().is_some_and(is_word);
+    !(starts_word && line[..range.start].chars().next_back().is_some_and(is_word))
+        && !(ends_word && line[range.end..].chars().next().is_some_and(is_word))
+}
+
+/// Reads the next line into `line` without its line ending, keeping at most `max_len` bytes of
+/// it. Returns the full length of the line, or `None` at the end of the file.
+fn read_line(
+    reader: &mut impl BufRead,
+    line: &mut Vec<u8>,
+    max_len: usize,
+) -> io::Result<Option<usize>> {
+    line.clear();
+    let mut len = 0;
+    let mut read_any = false;
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            break;
+        }
+        read_any = true;
+        let newline_ix = available.iter().position(|byte| *byte == b'\n');
+        let content = &available[..newline_ix.unwrap_or(available.len())];
+        let room = max_len.saturating_sub(line.len());
+        line.extend_from_slice(&content[..content.len().min(room)]);
+        len += content.len();
+        let consumed = newline_ix.map_or(available.len(), |ix| ix + 1);
+        reader.consume(consumed);
+        if newline_ix.is_some() {
+            break;
+        }
+    }
+    if !read_any {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\r') && len <= max_len {
+        line.pop();
+        len -= 1;
+    }
+    Ok(Some(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::{io::Cursor, path::Path};
+    use util::path;
+
+    #[gpui::test]
+    async fn test_open_large_file(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            language::init(cx);
+            Project::init_settings(cx);
+            LargeFileViewerSettings::register(cx);
+            LargeFileViewerSettings::override_global(
+                LargeFileViewerSettings { threshold_mb: 0 },
+                cx,
+            );
+        });
+
+        let text = (0..1000)
+            .map(|row| format!("line {row}\n"))
+            .collect::<String>();
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({"empty.txt": "", "large.log": text.clone()}),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let project_path = |path: &str| ProjectPath {
+            worktree_id,
+            path: Path::new(path).into(),
+        };
+
+        assert!(
+            cx.update(|cx| LargeFile::try_open(&project, &project_path("empty.txt"), cx))
+                .is_none()
+        );
+        let large_file = cx
+            .update(|cx| LargeFile::try_open(&project, &project_path("large.log"), cx))
+            .unwrap()
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        large_file.update(cx, |large_file, cx| {
+            assert!(!large_file.is_indexing());
+            assert_eq!(large_file.len(), text.len() as u64);
+            assert_eq!(large_file.line_count(), 1001);
+            assert_eq!(large_file.lines(300..302, cx), vec![None, None]);
+        });
+        cx.run_until_parked();
+        large_file.update(cx, |large_file, cx| {
+            assert_eq!(
+                large_file.lines(300..302, cx),
+                vec![
+                    Some(SharedString::from("line 300")),
+                    Some(SharedString::from("line 301"))
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn test_line_index() {
+        let text = (0..1000)
+            .map(|row| format!("line {row}\n"))
+            .collect::<String>();
+
+        let mut index = LineIndex::default();
+        // Scan in chunks that split lines, like reading a file does.
+        for chunk in text.as_bytes().chunks(7) {
+            index.scan(chunk);
+        }
+        assert_eq!(index.line_count(), 1001);
+        assert_eq!(index.block_offset(0), Some(0));
+        assert_eq!(index.block_offset(4), None);
+
+        let mut reader = Cursor::new(text.as_bytes());
+        let block = read_block(&mut reader, index.block_offset(3).unwrap()).unwrap();
+        assert_eq!(block.len(), 1000 - 3 * LINES_PER_BLOCK);
+        assert_eq!(block[0].as_ref(), "line 768");
+        assert_eq!(block.last().unwrap().as_ref(), "line 999");
+    }
+
+    #[test]
+    fn test_read_block_truncates_long_lines() {
+        let text = format!("a\r\n{}\nb", "x".repeat(MAX_LINE_LEN * 3));
+        let block = read_block(&mut Cursor::new(text.as_bytes()), 0).unwrap();
+        assert_eq!(block.len(), 3);
+        assert_eq!(block[0].as_ref(), "a");
+        assert_eq!(block[1].len(), MAX_LINE_LEN + '…'.len_utf8());
+        assert!(block[1].ends_with('…'));
+        assert_eq!(block[2].as_ref(), "b");
+    }
+
+    #[test]
+    fn test_search_lines() {
+        let text = "foo bar\nfoobar\n\nbar foo foo\n";
+        let query = SearchQuery::text(
+            "foo",
+            true,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            search_lines(Cursor::new(text), &query).unwrap(),
+            [
+                LargeFileMatch {
+                    row: 0,
+                    range: 0..3
+                },
+                LargeFileMatch {
+                    row: 3,
+                    range: 4..7
+                },
+                LargeFileMatch {
+                    row: 3,
+                    range: 8..11
+                },
+            ]
+        );
+
+        let query = SearchQuery::regex(
+            "o+b",
+            false,
+            false,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            search_lines(Cursor::new(text), &query).unwrap(),
+            [LargeFileMatch {
+                row: 1,
+                range: 1..4
+            }]
+        );
+    }
+}
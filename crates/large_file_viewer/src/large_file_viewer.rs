@@ -0,0 +1,579 @@
+mod go_to_line_modal;
+mod large_file;
+mod large_file_viewer_settings;
+
+use editor::{
+    EditorSettings,
+    actions::{MoveDown, MovePageDown, MovePageUp, MoveToBeginning, MoveToEnd, MoveUp},
+    items::entry_git_aware_label_color,
+};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, HighlightStyle,
+    InteractiveElement, IntoElement, ParentElement, Pixels, Render, ScrollWheelEvent, SharedString,
+    Styled, StyledText, Task, TextStyle, WhiteSpace, Window, canvas, relative,
+};
+use project::{Project, search::SearchQuery};
+use settings::Settings;
+use std::{cell::Cell, ops::Range, rc::Rc, sync::Arc};
+use theme::{Theme, ThemeSettings};
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane, ToolbarItemLocation,
+    item::{BreadcrumbText, Item, ItemEvent, ProjectItem, TabContentParams},
+    searchable::{Direction, SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
+};
+
+pub use crate::large_file::{LargeFile, LargeFileMatch};
+pub use crate::large_file_viewer_settings::*;
+
+/// The number of spaces a tab is displayed as.
+const TAB_SIZE: usize = 4;
+
+/// A read-only view of a [`LargeFile`], which only reads the lines that are visible from disk.
+pub struct LargeFileView {
+    large_file: Entity<LargeFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    /// The first visible row.
+    scroll_row: usize,
+    /// Scrolled pixels that don't yet add up to a whole row.
+    pending_scroll: Pixels,
+    /// The height of the text area when it was last painted.
+    viewport_height: Rc<Cell<Pixels>>,
+    matches: Arc<[LargeFileMatch]>,
+    active_match_index: Option<usize>,
+}
+
+impl LargeFileView {
+    pub fn new(
+        large_file: Entity<LargeFile>,
+        project: Entity<Project>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        cx.observe(&large_file, |_, _, cx| cx.notify()).detach();
+        Self {
+            large_file,
+            project,
+            focus_handle: cx.focus_handle(),
+            scroll_row: 0,
+            pending_scroll: Pixels::ZERO,
+            viewport_height: Rc::default(),
+            matches: Arc::default(),
+            active_match_index: None,
+        }
+    }
+
+    pub fn large_file(&self) -> &Entity<LargeFile> {
+        &self.large_file
+    }
+
+    /// The row that is considered the cursor's, for display and for go to line.
+    pub fn current_row(&self) -> usize {
+        self.active_match_index
+            .and_then(|ix| self.matches.get(ix))
+            .map_or(self.scroll_row, |mat| mat.row)
+    }
+
+    pub fn line_count(&self, cx: &App) -> usize {
+        self.large_file.read(cx).line_count()
+    }
+
+    /// Scrolls so that the given row is visible, a few rows from the top of the viewport.
+    pub fn go_to_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        let row = row.min(self.line_count(cx).saturating_sub(1));
+        if !(self.scroll_row..self.scroll_row + self.page_rows(cx)).contains(&row) {
+            let margin = self.page_rows(cx) / 3;
+            self.set_scroll_row(row.saturating_sub(margin), cx);
+        }
+        cx.notify();
+    }
+
+    fn set_scroll_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        let max_row = self.line_count(cx).saturating_sub(1);
+        self.scroll_row = row.min(max_row);
+        cx.notify();
+    }
+
+    fn line_height(cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        settings.buffer_font_size(cx) * settings.line_height()
+    }
+
+    /// The number of rows that fit in the viewport.
+    fn page_rows(&self, cx: &App) -> usize {
+        let rows = (self.viewport_height.get() / Self::line_height(cx)).floor() as usize;
+        rows.max(1)
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_scroll_row(self.scroll_row.saturating_sub(1), cx);
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_scroll_row(self.scroll_row + 1, cx);
+    }
+
+    fn move_page_up(&mut self, _: &MovePageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self.page_rows(cx);
+        self.set_scroll_row(self.scroll_row.saturating_sub(rows), cx);
+    }
+
+    fn move_page_down(&mut self, _: &MovePageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self.page_rows(cx);
+        self.set_scroll_row(self.scroll_row + rows, cx);
+    }
+
+    fn move_to_beginning(&mut self, _: &MoveToBeginning, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_scroll_row(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        let rows = self.page_rows(cx);
+        let row = self.line_count(cx).saturating_sub(rows);
+        self.set_scroll_row(row, cx);
+    }
+
+    fn scroll(&mut self, event: &ScrollWheelEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let line_height = Self::line_height(cx);
+        self.pending_scroll += event.delta.pixel_delta(line_height).y;
+        let rows = (self.pending_scroll / line_height).trunc();
+        self.pending_scroll -= line_height * rows;
+        let rows = rows as isize;
+        if rows > 0 {
+            self.set_scroll_row(self.scroll_row.saturating_sub(rows as usize), cx);
+        } else if rows < 0 {
+            self.set_scroll_row(self.scroll_row + rows.unsigned_abs(), cx);
+        }
+        cx.stop_propagation();
+    }
+
+    fn render_line(
+        &self,
+        row: usize,
+        line: Option<SharedString>,
+        text_style: &TextStyle,
+        line_height: Pixels,
+        cx: &App,
+    ) -> AnyElement {
+        let Some(line) = line else {
+            return div().h(line_height).into_any_element();
+        };
+        let colors = cx.theme().colors();
+        let first_match = self.matches.partition_point(|mat| mat.row < row);
+        let highlights = self.matches[first_match..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, mat)| mat.row == row)
+            .filter(|(_, mat)| {
+                mat.range.end <= line.len()
+                    && line.is_char_boundary(mat.range.start)
+                    && line.is_char_boundary(mat.range.end)
+            })
+            .map(|(ix, mat)| {
+                let background_color = if self.active_match_index == Some(first_match + ix) {
+                    cx.theme().players().local().selection
+                } else {
+                    colors.search_match_background
+                };
+                (
+                    mat.range.clone(),
+                    HighlightStyle {
+                        background_color: Some(background_color),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        let (text, highlights) = expand_tabs(&line, highlights);
+        div()
+            .h(line_height)
+            .child(StyledText::new(text).with_default_highlights(text_style, highlights))
+            .into_any_element()
+    }
+}
+
+/// Replaces tabs with spaces, because the text is laid out without tab stops, and moves the
+/// highlighted ranges accordingly.
+fn expand_tabs<T>(
+    line: &str,
+    highlights: Vec<(Range<usize>, T)>,
+) -> (String, Vec<(Range<usize>, T)>) {
+    if !line.contains('\t') {
+        return (line.to_string(), highlights);
+    }
+    let mut text = String::with_capacity(line.len());
+    let mut offsets = Vec::with_capacity(line.len() + 1);
+    let mut column = 0;
+    for (ix, character) in line.char_indices() {
+        offsets.resize(ix + 1, text.len());
+        if character == '\t' {
+            let spaces = TAB_SIZE - column % TAB_SIZE;
+            text.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+        } else {
+            text.push(character);
+            column += 1;
+        }
+    }
+    offsets.resize(line.len() + 1, text.len());
+    let highlights = highlights
+        .into_iter()
+        .map(|(range, style)| (offsets[range.start]..offsets[range.end], style))
+        .collect();
+    (text, highlights)
+}
+
+impl EventEmitter<ItemEvent> for LargeFileView {}
+impl EventEmitter<SearchEvent> for LargeFileView {}
+
+impl Item for LargeFileView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.large_file.entity_id(), self.large_file.read(cx))
+    }
+
+    fn is_singleton(&self, _cx: &App) -> bool {
+        true
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.large_file.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().to_string().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &Window, cx: &App) -> AnyElement {
+        let project_path = self.large_file.read(cx).project_path().clone();
+
+        let label_color = if ItemSettings::get_global(cx).git_status {
+            let git_status = self
+                .project
+                .read(cx)
+                .project_path_git_status(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        let title = project_path
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        Label::new(title)
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.large_file.read(cx).project_path().path.clone();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(&path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        let show_breadcrumb = EditorSettings::get_global(cx).toolbar.breadcrumbs;
+        if show_breadcrumb {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, _theme: &Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        let path = &self.large_file.read(cx).project_path().path;
+        Some(vec![BreadcrumbText {
+            text: path.to_string_lossy().to_string(),
+            highlights: None,
+            font: None,
+        }])
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<workspace::WorkspaceId>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Self>>
+    where
+        Self: Sized,
+    {
+        Some(cx.new(|cx| {
+            let mut view = Self::new(self.large_file.clone(), self.project.clone(), cx);
+            view.scroll_row = self.scroll_row;
+            view
+        }))
+    }
+
+    fn as_searchable(&self, handle: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+}
+
+impl SearchableItem for LargeFileView {
+    type Match = LargeFileMatch;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: true,
+            word: true,
+            regex: true,
+            replacement: false,
+            selection: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.matches = Arc::default();
+        self.active_match_index = None;
+        cx.notify();
+    }
+
+    fn update_matches(&mut self, matches: &[Self::Match], _: &mut Window, cx: &mut Context<Self>) {
+        if *self.matches != *matches {
+            self.matches = matches.into();
+            self.active_match_index = None;
+            cx.notify();
+        }
+    }
+
+    fn query_suggestion(&mut self, _: &mut Window, _: &mut Context<Self>) -> String {
+        String::new()
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(mat) = matches.get(index) {
+            self.active_match_index = Some(index);
+            self.go_to_row(mat.row, cx);
+            cx.emit(SearchEvent::ActiveMatchChanged);
+        }
+    }
+
+    fn select_matches(&mut self, _: &[Self::Match], _: &mut Window, _: &mut Context<Self>) {}
+
+    fn replace(&mut self, _: &Self::Match, _: &SearchQuery, _: &mut Window, _: &mut Context<Self>) {
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        self.large_file.read(cx).search(query, cx)
+    }
+
+    fn active_match_index(
+        &mut self,
+        direction: Direction,
+        matches: &[Self::Match],
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        let row = self.current_row();
+        let ix = match direction {
+            Direction::Next => matches
+                .partition_point(|mat| mat.row < row)
+                .min(matches.len() - 1),
+            Direction::Prev => matches
+                .partition_point(|mat| mat.row <= row)
+                .saturating_sub(1),
+        };
+        Some(ix)
+    }
+}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_height = Self::line_height(cx);
+        // Render enough rows to fill the window, the rest is clipped.
+        let row_count = (window.viewport_size().height / line_height).ceil() as usize + 1;
+        let line_count = self.line_count(cx);
+        self.scroll_row = self.scroll_row.min(line_count.saturating_sub(1));
+        let rows = self.scroll_row..(self.scroll_row + row_count).min(line_count);
+        let lines = self
+            .large_file
+            .update(cx, |large_file, cx| large_file.lines(rows.clone(), cx));
+
+        let settings = ThemeSettings::get_global(cx);
+        let colors = cx.theme().colors();
+        let text_style = TextStyle {
+            color: colors.editor_foreground,
+            font_family: settings.buffer_font.family.clone(),
+            font_features: settings.buffer_font.features.clone(),
+            font_fallbacks: settings.buffer_font.fallbacks.clone(),
+            font_size: settings.buffer_font_size(cx).into(),
+            font_weight: settings.buffer_font.weight,
+            line_height: relative(settings.line_height()),
+            white_space: WhiteSpace::Nowrap,
+            ..Default::default()
+        };
+        let current_row = self.current_row();
+        let gutter_width = line_count.to_string().len().max(3);
+
+        let gutter = v_flex()
+            .flex_none()
+            .pr_4()
+            .items_end()
+            .children(rows.clone().map(|row| {
+                let color = if row == current_row {
+                    colors.editor_active_line_number
+                } else {
+                    colors.editor_line_number
+                };
+                let number = format!("{:>gutter_width$}", row + 1);
+                div()
+                    .h(line_height)
+                    .child(StyledText::new(number).with_default_highlights(
+                        &TextStyle {
+                            color,
+                            ..text_style.clone()
+                        },
+                        [],
+                    ))
+            }));
+        let text = v_flex().flex_1().overflow_hidden().children(
+            rows.zip(lines)
+                .map(|(row, line)| self.render_line(row, line, &text_style, line_height, cx)),
+        );
+
+        let viewport_height = self.viewport_height.clone();
+        let large_file = self.large_file.read(cx);
+        let status = if large_file.is_indexing() {
+            format!("Line {} of {}+ (indexing…)", current_row + 1, line_count)
+        } else {
+            format!("Line {} of {}", current_row + 1, line_count)
+        };
+
+        v_flex()
+            .key_context("LargeFileView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::move_up))
+            .on_action(cx.listener(Self::move_down))
+            .on_action(cx.listener(Self::move_page_up))
+            .on_action(cx.listener(Self::move_page_down))
+            .on_action(cx.listener(Self::move_to_beginning))
+            .on_action(cx.listener(Self::move_to_end))
+            .on_scroll_wheel(cx.listener(Self::scroll))
+            .size_full()
+            .bg(colors.editor_background)
+            .child(
+                h_flex()
+                    .relative()
+                    .flex_1()
+                    .items_start()
+                    .overflow_hidden()
+                    .pl_2()
+                    .child(
+                        canvas(
+                            move |bounds, _, _| viewport_height.set(bounds.size.height),
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    )
+                    .child(gutter)
+                    .child(text),
+            )
+            .child(
+                h_flex()
+                    .flex_none()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_t_1()
+                    .border_color(colors.border_variant)
+                    .child(
+                        Label::new("Large file, read-only")
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(status)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: &Pane,
+        item: Entity<Self::Item>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, cx)
+    }
+}
+
+pub fn init(cx: &mut App) {
+    LargeFileViewerSettings::register(cx);
+    workspace::register_project_item::<LargeFileView>(cx);
+    go_to_line_modal::init(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tabs() {
+        assert_eq!(
+            expand_tabs("a\tbc\td", vec![(2..4, ()), (5..6, ())]),
+            ("a   bc  d".to_string(), vec![(4..6, ()), (8..9, ())])
+        );
+        assert_eq!(
+            expand_tabs("no tabs", vec![(3..7, ())]),
+            ("no tabs".to_string(), vec![(3..7, ())])
+        );
+    }
+}
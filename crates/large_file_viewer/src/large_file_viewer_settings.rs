@@ -0,0 +1,51 @@
+use gpui::App;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+/// The settings for the large file viewer.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LargeFileViewerSettings {
+    /// Files larger than this many megabytes are opened in a read-only view that reads them
+    /// from disk as they are displayed, instead of loading them into an editor.
+    ///
+    /// Default: 256
+    #[serde(default = "default_threshold_mb")]
+    pub threshold_mb: u64,
+}
+
+fn default_threshold_mb() -> u64 {
+    256
+}
+
+impl Default for LargeFileViewerSettings {
+    fn default() -> Self {
+        Self {
+            threshold_mb: default_threshold_mb(),
+        }
+    }
+}
+
+impl LargeFileViewerSettings {
+    pub fn threshold_bytes(&self) -> u64 {
+        self.threshold_mb.saturating_mul(1024 * 1024)
+    }
+}
+
+impl Settings for LargeFileViewerSettings {
+    const KEY: Option<&'static str> = Some("large_file_viewer");
+
+    type FileContent = Self;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut App,
+    ) -> Result<Self, anyhow::Error> {
+        SettingsSources::<Self::FileContent>::json_merge_with(
+            [sources.default]
+                .into_iter()
+                .chain(sources.user)
+                .chain(sources.server),
+        )
+    }
+}
@@ -0,0 +1,197 @@
+mod local_history_view;
+pub mod persistence;
+
+use anyhow::Result;
+use editor::Editor;
+use gpui::{App, AppContext as _, Entity, actions};
+use language::{Buffer, BufferEvent};
+use persistence::{LOCAL_HISTORY, RetentionPolicy};
+use project::buffer_store::BufferStoreEvent;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use time::OffsetDateTime;
+use util::ResultExt as _;
+use workspace::Workspace;
+
+pub use local_history_view::LocalHistoryView;
+
+actions!(local_history, [ShowLocalHistory, RestoreSelectedVersion]);
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct LocalHistorySettings {
+    pub enabled: bool,
+    pub max_entries_per_file: usize,
+    pub max_age_days: u64,
+    pub max_file_size: u64,
+    pub max_total_size: u64,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to keep a snapshot of files every time they are saved.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The number of snapshots to keep for every file.
+    ///
+    /// Default: 50
+    pub max_entries_per_file: Option<usize>,
+    /// The number of days after which snapshots are discarded.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u64>,
+    /// The size in bytes above which saved files are not recorded.
+    ///
+    /// Default: 1048576
+    pub max_file_size: Option<u64>,
+    /// The total size in bytes of the snapshots kept across all files, above which the oldest
+    /// snapshots are discarded.
+    ///
+    /// Default: 104857600
+    pub max_total_size: Option<u64>,
+}
+
+impl Settings for LocalHistorySettings {
+    const KEY: Option<&'static str> = Some("local_history");
+
+    type FileContent = LocalHistorySettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+impl LocalHistorySettings {
+    fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            max_entries_per_file: self.max_entries_per_file,
+            max_age_secs: i64::try_from(self.max_age_days.saturating_mul(24 * 60 * 60))
+                .unwrap_or(i64::MAX),
+            max_total_bytes: i64::try_from(self.max_total_size).unwrap_or(i64::MAX),
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    LocalHistorySettings::register(cx);
+
+    cx.observe_new(|workspace: &mut Workspace, _window, cx| {
+        workspace.register_action(|workspace, _: &ShowLocalHistory, window, cx| {
+            let Some(buffer) = workspace
+                .active_item_as::<Editor>(cx)
+                .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+            else {
+                return;
+            };
+            LocalHistoryView::open(buffer, workspace, window, cx);
+        });
+
+        let project = workspace.project().read(cx);
+        if !project.is_local() {
+            return;
+        }
+        let buffer_store = project.buffer_store().clone();
+        cx.subscribe(&buffer_store, |_, _, event, cx| {
+            if let BufferStoreEvent::BufferAdded(buffer) = event {
+                record_snapshots_on_save(buffer, cx);
+            }
+        })
+        .detach();
+    })
+    .detach();
+}
+
+fn record_snapshots_on_save(buffer: &Entity<Buffer>, cx: &mut App) {
+    cx.subscribe(buffer, |buffer, event, cx| {
+        if let BufferEvent::Saved = event {
+            record_snapshot(&buffer, cx);
+        }
+    })
+    .detach();
+}
+
+fn record_snapshot(buffer: &Entity<Buffer>, cx: &mut App) {
+    let settings = LocalHistorySettings::get_global(cx);
+    if !settings.enabled {
+        return;
+    }
+    let buffer = buffer.read(cx);
+    let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+        return;
+    };
+    if buffer.len() as u64 > settings.max_file_size {
+        return;
+    }
+
+    let abs_path = file.abs_path(cx);
+    let content = buffer.text();
+    let retention = settings.retention_policy();
+    let saved_at = OffsetDateTime::now_utc().unix_timestamp();
+    cx.background_spawn(async move {
+        LOCAL_HISTORY
+            .record_snapshot(abs_path, content, saved_at, retention)
+            .await
+            .log_err();
+    })
+    .detach();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::PathBuf;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_saving_buffer_records_snapshot(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/root"), json!({ "a.txt": "one" }))
+            .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        let _workspace =
+            cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..3, "two")], None, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        let history = LOCAL_HISTORY
+            .history_for_path(PathBuf::from(path!("/root/a.txt")))
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            LOCAL_HISTORY
+                .entry_content(history[0].id)
+                .unwrap()
+                .as_deref(),
+            Some("two")
+        );
+    }
+}
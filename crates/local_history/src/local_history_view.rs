@@ -0,0 +1,379 @@
+use std::{
+    any::{Any, TypeId},
+    path::PathBuf,
+};
+
+use buffer_diff::BufferDiff;
+use editor::{Editor, EditorEvent, MultiBuffer};
+use gpui::{AnyView, Entity, EventEmitter, FocusHandle, Focusable, Render, Subscription, Task};
+use language::{Buffer, BufferEvent, BufferSnapshot};
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Item, ItemNavHistory, ToolbarItemLocation, Workspace,
+    item::{BreadcrumbText, ItemEvent, TabContentParams},
+    searchable::SearchableItemHandle,
+};
+
+use crate::{
+    RestoreSelectedVersion,
+    persistence::{LOCAL_HISTORY, LocalHistoryEntry},
+};
+
+/// Shows the snapshots recorded for a file as a timeline, along with the changes made to the file
+/// since the selected snapshot.
+pub struct LocalHistoryView {
+    buffer: Entity<Buffer>,
+    abs_path: PathBuf,
+    entries: Vec<LocalHistoryEntry>,
+    selected_entry: Option<usize>,
+    base_text: Option<String>,
+    base_snapshot: Option<BufferSnapshot>,
+    diff: Entity<BufferDiff>,
+    editor: Entity<Editor>,
+    focus_handle: FocusHandle,
+    load_entries_task: Task<()>,
+    load_entry_task: Task<()>,
+    _buffer_subscription: Subscription,
+}
+
+impl LocalHistoryView {
+    pub fn open(
+        buffer: Entity<Buffer>,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(abs_path) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx))
+        else {
+            return;
+        };
+
+        let pane = workspace.active_pane().clone();
+        let existing = pane.read(cx).items().enumerate().find_map(|(ix, item)| {
+            let view = item.downcast::<LocalHistoryView>()?;
+            (view.read(cx).abs_path == abs_path).then_some((ix, view))
+        });
+        if let Some((ix, view)) = existing {
+            view.update(cx, |view, cx| view.reload_entries(cx));
+            pane.update(cx, |pane, cx| {
+                pane.activate_item(ix, true, true, window, cx)
+            });
+        } else {
+            let view = cx.new(|cx| LocalHistoryView::new(buffer, abs_path, window, cx));
+            pane.update(cx, |pane, cx| {
+                pane.add_item(Box::new(view), true, true, None, window, cx)
+            });
+        }
+    }
+
+    fn new(
+        buffer: Entity<Buffer>,
+        abs_path: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let diff = cx.new(|cx| BufferDiff::new(&buffer.read(cx).text_snapshot(), cx));
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            // The editor is given no project, so that it doesn't replace the diff against the
+            // selected snapshot with the one against the git index.
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+        let buffer_subscription = cx.subscribe(&buffer, |this, _, event, cx| {
+            if let BufferEvent::Edited = event {
+                this.update_diff(cx);
+            }
+        });
+
+        let mut this = Self {
+            buffer,
+            abs_path,
+            entries: Vec::new(),
+            selected_entry: None,
+            base_text: None,
+            base_snapshot: None,
+            diff,
+            editor,
+            focus_handle: cx.focus_handle(),
+            load_entries_task: Task::ready(()),
+            load_entry_task: Task::ready(()),
+            _buffer_subscription: buffer_subscription,
+        };
+        this.reload_entries(cx);
+        this
+    }
+
+    fn reload_entries(&mut self, cx: &mut Context<Self>) {
+        let abs_path = self.abs_path.clone();
+        self.load_entries_task = cx.spawn(async move |this, cx| {
+            let entries = LOCAL_HISTORY
+                .history_for_path(abs_path)
+                .await
+                .log_err()
+                .unwrap_or_default();
+            this.update(cx, |this, cx| {
+                this.entries = entries;
+                if this.entries.is_empty() {
+                    cx.notify();
+                } else {
+                    this.select_entry(0, cx);
+                }
+            })
+            .log_err();
+        });
+    }
+
+    fn select_entry(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        self.selected_entry = Some(ix);
+        let entry_id = entry.id;
+        let content = cx.background_spawn(async move { LOCAL_HISTORY.entry_content(entry_id) });
+        self.load_entry_task = cx.spawn(async move |this, cx| {
+            let Some(content) = content.await.log_err().flatten() else {
+                return;
+            };
+            let base_snapshot = this.update(cx, |this, cx| {
+                let buffer = this.buffer.read(cx);
+                let language = buffer.language().cloned();
+                let language_registry = buffer.language_registry();
+                Buffer::build_snapshot(content.as_str().into(), language, language_registry, cx)
+            });
+            let Some(base_snapshot) = base_snapshot.log_err() else {
+                return;
+            };
+            let base_snapshot = base_snapshot.await;
+            this.update(cx, |this, cx| {
+                this.base_text = Some(content);
+                this.base_snapshot = Some(base_snapshot);
+                this.update_diff(cx);
+                cx.notify();
+            })
+            .log_err();
+        });
+        cx.notify();
+    }
+
+    fn update_diff(&mut self, cx: &mut Context<Self>) {
+        let Some(base_snapshot) = self.base_snapshot.clone() else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let language_registry = buffer.language_registry();
+        let snapshot = buffer.text_snapshot();
+        self.diff.update(cx, |diff, cx| {
+            diff.set_base_text(base_snapshot, language_registry, snapshot, cx);
+        });
+    }
+
+    fn restore_selected_version(
+        &mut self,
+        _: &RestoreSelectedVersion,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(base_text) = self.base_text.clone() else {
+            return;
+        };
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.set_text(base_text, cx);
+        });
+        window.focus(&self.editor.focus_handle(cx));
+    }
+
+    fn render_timeline(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let now = OffsetDateTime::now_utc();
+        let entries = self.entries.iter().enumerate().map(|(ix, entry)| {
+            let timestamp = OffsetDateTime::from_unix_timestamp(entry.saved_at)
+                .map(|saved_at| {
+                    time_format::format_local_timestamp(
+                        saved_at,
+                        now,
+                        TimestampFormat::EnhancedAbsolute,
+                    )
+                })
+                .unwrap_or_default();
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(self.selected_entry == Some(ix))
+                .start_slot(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+                .child(Label::new(timestamp).size(LabelSize::Small))
+                .on_click(cx.listener(move |this, _, _, cx| this.select_entry(ix, cx)))
+        });
+
+        v_flex()
+            .id("local-history-timeline")
+            .w(rems(16.))
+            .h_full()
+            .p_1()
+            .gap_1()
+            .border_r_1()
+            .border_color(cx.theme().colors().border_variant)
+            .overflow_y_scroll()
+            .child(
+                h_flex()
+                    .px_1()
+                    .justify_between()
+                    .child(Label::new("Local History").color(Color::Muted))
+                    .child(
+                        Button::new("restore-version", "Restore")
+                            .disabled(self.base_text.is_none())
+                            .tooltip(Tooltip::text("Restore the selected version of the file"))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.restore_selected_version(&RestoreSelectedVersion, window, cx)
+                            })),
+                    ),
+            )
+            .when(self.entries.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px_1()
+                        .child(Label::new("No saved versions yet").color(Color::Muted)),
+                )
+            })
+            .children(entries)
+    }
+}
+
+impl EventEmitter<EditorEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        if self.entries.is_empty() {
+            self.focus_handle.clone()
+        } else {
+            self.editor.focus_handle(cx)
+        }
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        let file_name = self
+            .buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string_lossy().to_string())
+            .unwrap_or_default();
+        Label::new(format!("{file_name} History"))
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn tab_tooltip_text(&self, _: &App) -> Option<SharedString> {
+        Some(format!("Local history of {}", self.abs_path.display()).into())
+    }
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Local History Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn is_singleton(&self, _: &App) -> bool {
+        false
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Box<dyn Any>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, theme: &theme::Theme, cx: &App) -> Option<Vec<BreadcrumbText>> {
+        self.editor.breadcrumbs(theme, cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .key_context("LocalHistoryView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::restore_selected_version))
+            .size_full()
+            .child(self.render_timeline(cx))
+            .child(div().flex_1().h_full().child(self.editor.clone()))
+    }
+}
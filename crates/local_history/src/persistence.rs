@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context as _, Result};
+use db::{define_connection, query, sqlez_macros::sql};
+
+/// A snapshot of a file as it was when it got saved.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalHistoryEntry {
+    pub id: i64,
+    /// Seconds since the Unix epoch.
+    pub saved_at: i64,
+}
+
+/// Limits applied to the local history after a snapshot is recorded.
+#[derive(Clone, Copy, Debug)]
+pub struct RetentionPolicy {
+    pub max_entries_per_file: usize,
+    pub max_age_secs: i64,
+    /// The total size in bytes of the snapshots kept across all files.
+    pub max_total_bytes: i64,
+}
+
+define_connection!(pub static ref LOCAL_HISTORY: LocalHistoryDb<()> =
+    &[sql!(
+        CREATE TABLE local_history_entries(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            abs_path TEXT NOT NULL,
+            saved_at INTEGER NOT NULL,
+            content TEXT NOT NULL
+        ) STRICT;
+        CREATE INDEX local_history_entries_by_path ON local_history_entries(abs_path, id);
+    )];
+);
+
+impl LocalHistoryDb {
+    /// Stores `content` as the newest snapshot of the file at `abs_path`, unless it is identical to
+    /// the previous snapshot, then prunes entries that fall outside of the retention policy.
+    ///
+    /// The number of entries is limited per file, while the age and the total size of the
+    /// entries are limited across all files, so that the history of files that are no longer
+    /// saved gets discarded as well.
+    ///
+    /// Returns whether a new snapshot was stored.
+    pub async fn record_snapshot(
+        &self,
+        abs_path: PathBuf,
+        content: String,
+        saved_at: i64,
+        retention: RetentionPolicy,
+    ) -> Result<bool> {
+        self.write(move |conn| {
+            conn.with_savepoint("record_local_history_snapshot", || {
+                let latest_content = conn.select_row_bound::<&Path, String>(sql!(
+                    SELECT content FROM local_history_entries
+                    WHERE abs_path = ?
+                    ORDER BY id DESC
+                    LIMIT 1
+                ))?(abs_path.as_path())
+                .context("loading latest local history snapshot")?;
+                if latest_content.as_ref() == Some(&content) {
+                    return Ok(false);
+                }
+
+                conn.exec_bound(sql!(
+                    INSERT INTO local_history_entries (abs_path, saved_at, content)
+                    VALUES (?1, ?2, ?3)
+                ))?((abs_path.as_path(), saved_at, content))
+                .context("inserting local history snapshot")?;
+
+                conn.exec_bound(sql!(
+                    DELETE FROM local_history_entries
+                    WHERE abs_path = ?1 AND id NOT IN (
+                        SELECT id FROM local_history_entries
+                        WHERE abs_path = ?1
+                        ORDER BY id DESC
+                        LIMIT ?2
+                    )
+                ))?((
+                    abs_path.as_path(),
+                    i64::try_from(retention.max_entries_per_file).unwrap_or(i64::MAX),
+                ))
+                .context("pruning local history of file")?;
+
+                conn.exec_bound(sql!(
+                    DELETE FROM local_history_entries
+                    WHERE saved_at < ?
+                ))?(saved_at.saturating_sub(retention.max_age_secs))
+                .context("pruning old local history")?;
+
+                // Drop the oldest entries once the newest ones add up to the maximum size.
+                conn.exec_bound(sql!(
+                    DELETE FROM local_history_entries
+                    WHERE id IN (
+                        SELECT id FROM (
+                            SELECT
+                                id,
+                                SUM(length(CAST(content AS BLOB))) OVER (ORDER BY id DESC) AS total
+                            FROM local_history_entries
+                        )
+                        WHERE total > ?
+                    )
+                ))?(retention.max_total_bytes)
+                .context("pruning local history by size")?;
+                Ok(true)
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub async fn entries(abs_path: PathBuf) -> Result<Vec<(i64, i64)>> {
+            SELECT id, saved_at FROM local_history_entries
+            WHERE abs_path = ?
+            ORDER BY id DESC
+        }
+    }
+
+    query! {
+        pub fn entry_content(id: i64) -> Result<Option<String>> {
+            SELECT content FROM local_history_entries
+            WHERE id = ?
+        }
+    }
+
+    /// Returns the snapshots recorded for the given file, newest first.
+    pub async fn history_for_path(&self, abs_path: PathBuf) -> Result<Vec<LocalHistoryEntry>> {
+        Ok(self
+            .entries(abs_path)
+            .await?
+            .into_iter()
+            .map(|(id, saved_at)| LocalHistoryEntry { id, saved_at })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RETENTION: RetentionPolicy = RetentionPolicy {
+        max_entries_per_file: 3,
+        max_age_secs: 100,
+        max_total_bytes: 20,
+    };
+
+    #[gpui::test]
+    async fn test_record_snapshots() {
+        let db = LocalHistoryDb(db::open_test_db("test_record_snapshots").await);
+        let path = PathBuf::from("/root/a.txt");
+        let other_path = PathBuf::from("/root/b.txt");
+
+        assert!(
+            db.record_snapshot(path.clone(), "one".into(), 10, RETENTION)
+                .await
+                .unwrap()
+        );
+        // Saving unchanged contents does not add a new snapshot.
+        assert!(
+            !db.record_snapshot(path.clone(), "one".into(), 11, RETENTION)
+                .await
+                .unwrap()
+        );
+        assert!(
+            db.record_snapshot(other_path.clone(), "one".into(), 12, RETENTION)
+                .await
+                .unwrap()
+        );
+        for (ix, content) in ["two", "three", "four"].into_iter().enumerate() {
+            db.record_snapshot(path.clone(), content.into(), 20 + ix as i64, RETENTION)
+                .await
+                .unwrap();
+        }
+
+        // Only the most recent entries are kept.
+        let history = db.history_for_path(path.clone()).await.unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| db.entry_content(entry.id).unwrap().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["four", "three", "two"]
+        );
+        assert_eq!(
+            history
+                .iter()
+                .map(|entry| entry.saved_at)
+                .collect::<Vec<_>>(),
+            vec![22, 21, 20]
+        );
+
+        // Entries older than the maximum age are dropped, including those of other files.
+        db.record_snapshot(path.clone(), "five".into(), 122, RETENTION)
+            .await
+            .unwrap();
+        assert_eq!(
+            db.history_for_path(path.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|entry| entry.saved_at)
+                .collect::<Vec<_>>(),
+            vec![122, 22]
+        );
+        assert!(
+            db.history_for_path(other_path.clone())
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // The oldest entries are dropped once all entries exceed the maximum total size.
+        db.record_snapshot(
+            other_path.clone(),
+            "0123456789abcdef".into(),
+            123,
+            RETENTION,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            db.history_for_path(path.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|entry| entry.saved_at)
+                .collect::<Vec<_>>(),
+            vec![122]
+        );
+        assert_eq!(db.history_for_path(other_path).await.unwrap().len(), 1);
+    }
+}
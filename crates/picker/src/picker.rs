@@ -1,10 +1,10 @@
 use anyhow::Result;
 use editor::{Editor, scroll::Autoscroll};
 use gpui::{
-    AnyElement, App, ClickEvent, Context, DismissEvent, Entity, EventEmitter, FocusHandle,
-    Focusable, Length, ListSizingBehavior, ListState, MouseButton, MouseUpEvent, Render,
-    ScrollHandle, ScrollStrategy, Stateful, Task, UniformListScrollHandle, Window, actions, div,
-    impl_actions, list, prelude::*, uniform_list,
+    AccessibilityRole, AnyElement, App, ClickEvent, Context, DismissEvent, Entity, EventEmitter,
+    FocusHandle, Focusable, Length, ListSizingBehavior, ListState, MouseButton, MouseUpEvent,
+    Render, ScrollHandle, ScrollStrategy, Stateful, Task, UniformListScrollHandle, Window, actions,
+    div, impl_actions, list, prelude::*, uniform_list,
 };
 use head::Head;
 use schemars::JsonSchema;
@@ -643,8 +643,11 @@ impl<D: PickerDelegate> Picker<D> {
         cx: &mut Context<Self>,
         ix: usize,
     ) -> impl IntoElement + use<D> {
+        let selected = ix == self.delegate.selected_index();
         div()
             .id(("item", ix))
+            .role(AccessibilityRole::ListBoxOption)
+            .accessible_selected(selected)
             .cursor_pointer()
             .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
                 this.handle_click(ix, event.modifiers().secondary(), window, cx)
@@ -661,12 +664,7 @@ impl<D: PickerDelegate> Picker<D> {
                     this.handle_click(ix, event.modifiers.platform, window, cx)
                 }),
             )
-            .children(self.delegate.render_match(
-                ix,
-                ix == self.delegate.selected_index(),
-                window,
-                cx,
-            ))
+            .children(self.delegate.render_match(ix, selected, window, cx))
             .when(
                 self.delegate.separators_after_indices().contains(&ix),
                 |picker| {
@@ -823,6 +821,7 @@ impl<D: PickerDelegate> Render for Picker<D> {
                 el.child(
                     v_flex()
                         .id("element-container")
+                        .role(AccessibilityRole::ListBox)
                         .relative()
                         .flex_grow()
                         .when_some(self.max_height, |div, max_h| div.max_h(max_h))
@@ -0,0 +1,316 @@
+//! Module for managing bookmarks in a project.
+//!
+//! Bookmarks are stored as anchors into their buffers, so they keep pointing at the same line as
+//! the file is edited. They are persisted per workspace, keyed by the worktree-relative path of
+//! the file they belong to.
+use anyhow::{Result, anyhow};
+use collections::BTreeMap;
+use gpui::{App, Context, Entity, EventEmitter, SharedString, Subscription, Task};
+use language::{Buffer, BufferEvent};
+use std::{path::Path, sync::Arc};
+use text::{Point, ToPoint as _};
+
+use crate::{
+    ProjectItem as _, ProjectPath, buffer_store::BufferStore, worktree_store::WorktreeStore,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub position: text::Anchor,
+    pub label: Option<SharedString>,
+}
+
+/// A bookmark as it is stored between sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializedBookmark {
+    /// The absolute path of the worktree root the bookmarked file belongs to.
+    pub worktree_path: Arc<Path>,
+    /// The path of the bookmarked file, relative to its worktree root.
+    pub path: Arc<Path>,
+    pub row: u32,
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BookmarkDirection {
+    Next,
+    Previous,
+}
+
+pub enum BookmarkStoreEvent {
+    BookmarksUpdated,
+}
+
+struct BookmarksInFile {
+    buffer: Entity<Buffer>,
+    bookmarks: Vec<Bookmark>,
+    _subscription: Subscription,
+}
+
+impl BookmarksInFile {
+    fn new(buffer: Entity<Buffer>, cx: &mut Context<BookmarkStore>) -> Self {
+        let subscription = cx.subscribe(&buffer, |this, buffer, event, cx| {
+            if let BufferEvent::FileHandleChanged = event {
+                this.on_file_handle_changed(buffer, cx);
+            }
+        });
+        Self {
+            buffer,
+            bookmarks: Vec::new(),
+            _subscription: subscription,
+        }
+    }
+}
+
+pub struct BookmarkStore {
+    bookmarks: BTreeMap<ProjectPath, BookmarksInFile>,
+    worktree_store: Entity<WorktreeStore>,
+    buffer_store: Entity<BufferStore>,
+}
+
+impl EventEmitter<BookmarkStoreEvent> for BookmarkStore {}
+
+impl BookmarkStore {
+    pub fn new(worktree_store: Entity<WorktreeStore>, buffer_store: Entity<BufferStore>) -> Self {
+        Self {
+            bookmarks: BTreeMap::new(),
+            worktree_store,
+            buffer_store,
+        }
+    }
+
+    /// Adds a bookmark on the line containing `position`, or removes the one already there.
+    pub fn toggle_bookmark(
+        &mut self,
+        buffer: Entity<Buffer>,
+        position: text::Anchor,
+        label: Option<SharedString>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project_path) = buffer.read(cx).project_path(cx) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let row = position.to_point(&snapshot).row;
+
+        let bookmarks_in_file = self
+            .bookmarks
+            .entry(project_path.clone())
+            .or_insert_with(|| BookmarksInFile::new(buffer, cx));
+        let len_before = bookmarks_in_file.bookmarks.len();
+        bookmarks_in_file
+            .bookmarks
+            .retain(|bookmark| bookmark.position.to_point(&snapshot).row != row);
+        if len_before == bookmarks_in_file.bookmarks.len() {
+            let position = snapshot.anchor_after(Point::new(row, 0));
+            let ix = bookmarks_in_file
+                .bookmarks
+                .partition_point(|bookmark| bookmark.position.cmp(&position, &snapshot).is_lt());
+            bookmarks_in_file
+                .bookmarks
+                .insert(ix, Bookmark { position, label });
+        }
+
+        if bookmarks_in_file.bookmarks.is_empty() {
+            self.bookmarks.remove(&project_path);
+        }
+        cx.emit(BookmarkStoreEvent::BookmarksUpdated);
+        cx.notify();
+    }
+
+    pub fn clear_bookmarks(&mut self, cx: &mut Context<Self>) {
+        self.bookmarks.clear();
+        cx.emit(BookmarkStoreEvent::BookmarksUpdated);
+        cx.notify();
+    }
+
+    /// Returns the bookmarks in the given buffer, ordered by position.
+    pub fn bookmarks_for_buffer(&self, buffer: &Entity<Buffer>, cx: &App) -> &[Bookmark] {
+        buffer
+            .read(cx)
+            .project_path(cx)
+            .and_then(|project_path| self.bookmarks.get(&project_path))
+            .map(|bookmarks_in_file| bookmarks_in_file.bookmarks.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns all bookmarks in the project, ordered by path and position.
+    pub fn all_bookmarks(
+        &self,
+    ) -> impl Iterator<Item = (&ProjectPath, &Entity<Buffer>, &Bookmark)> + '_ {
+        self.bookmarks
+            .iter()
+            .flat_map(|(project_path, bookmarks_in_file)| {
+                bookmarks_in_file
+                    .bookmarks
+                    .iter()
+                    .map(move |bookmark| (project_path, &bookmarks_in_file.buffer, bookmark))
+            })
+    }
+
+    /// Returns the bookmark following (or preceding) the given row, wrapping around
+    /// from the last bookmarked file of the project to the first one.
+    pub fn adjacent_bookmark(
+        &self,
+        buffer: &Entity<Buffer>,
+        row: u32,
+        direction: BookmarkDirection,
+        cx: &App,
+    ) -> Option<(Entity<Buffer>, Bookmark)> {
+        let current_path = buffer.read(cx).project_path(cx);
+        let mut bookmarks = self
+            .bookmarks
+            .iter()
+            .flat_map(|(project_path, bookmarks_in_file)| {
+                let snapshot = bookmarks_in_file.buffer.read(cx).snapshot();
+                bookmarks_in_file.bookmarks.iter().map(move |bookmark| {
+                    let bookmark_row = bookmark.position.to_point(&snapshot).row;
+                    ((project_path, bookmark_row), bookmarks_in_file, bookmark)
+                })
+            })
+            .collect::<Vec<_>>();
+        if direction == BookmarkDirection::Previous {
+            bookmarks.reverse();
+        }
+
+        let is_past_cursor = |(path, bookmark_row): (&ProjectPath, u32)| {
+            let key = (Some(path), bookmark_row);
+            let cursor = (current_path.as_ref(), row);
+            match direction {
+                BookmarkDirection::Next => key > cursor,
+                BookmarkDirection::Previous => key < cursor,
+            }
+        };
+        bookmarks
+            .iter()
+            .find(|(key, _, _)| is_past_cursor(*key))
+            .or_else(|| bookmarks.first())
+            .map(|(_, bookmarks_in_file, bookmark)| {
+                (bookmarks_in_file.buffer.clone(), (*bookmark).clone())
+            })
+    }
+
+    pub fn serialized_bookmarks(&self, cx: &App) -> Vec<SerializedBookmark> {
+        let worktree_store = self.worktree_store.read(cx);
+        self.bookmarks
+            .iter()
+            .filter_map(|(project_path, bookmarks_in_file)| {
+                let worktree = worktree_store.worktree_for_id(project_path.worktree_id, cx)?;
+                Some((
+                    worktree.read(cx).abs_path(),
+                    project_path,
+                    bookmarks_in_file,
+                ))
+            })
+            .flat_map(|(worktree_path, project_path, bookmarks_in_file)| {
+                let snapshot = bookmarks_in_file.buffer.read(cx).snapshot();
+                bookmarks_in_file
+                    .bookmarks
+                    .iter()
+                    .map(move |bookmark| SerializedBookmark {
+                        worktree_path: worktree_path.clone(),
+                        path: project_path.path.clone(),
+                        row: bookmark.position.to_point(&snapshot).row,
+                        label: bookmark.label.as_ref().map(ToString::to_string),
+                    })
+            })
+            .collect()
+    }
+
+    pub fn with_serialized_bookmarks(
+        &self,
+        bookmarks: Vec<SerializedBookmark>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let worktree_store = self.worktree_store.clone();
+        let buffer_store = self.buffer_store.clone();
+        cx.spawn(async move |this, cx| {
+            let mut bookmarks_by_path = BTreeMap::<_, Vec<SerializedBookmark>>::new();
+            for bookmark in bookmarks {
+                bookmarks_by_path
+                    .entry((bookmark.worktree_path.clone(), bookmark.path.clone()))
+                    .or_default()
+                    .push(bookmark);
+            }
+
+            for ((worktree_path, path), bookmarks) in bookmarks_by_path {
+                let open_buffer = buffer_store.update(cx, |buffer_store, cx| {
+                    let worktree = worktree_store
+                        .read(cx)
+                        .worktrees()
+                        .find(|worktree| worktree.read(cx).abs_path() == worktree_path)
+                        .ok_or_else(|| anyhow!("no worktree found for {worktree_path:?}"))?;
+                    let project_path = ProjectPath {
+                        worktree_id: worktree.read(cx).id(),
+                        path: path.clone(),
+                    };
+                    anyhow::Ok(buffer_store.open_buffer(project_path, cx))
+                })?;
+                let buffer = match open_buffer {
+                    Ok(open_buffer) => open_buffer.await,
+                    Err(error) => Err(error),
+                };
+                let buffer = match buffer {
+                    Ok(buffer) => buffer,
+                    Err(error) => {
+                        log::error!("Failed to restore bookmarks in {path:?}: {error}");
+                        continue;
+                    }
+                };
+
+                this.update(cx, |this, cx| {
+                    let Some(project_path) = buffer.read(cx).project_path(cx) else {
+                        return;
+                    };
+                    let snapshot = buffer.read(cx).snapshot();
+                    let mut bookmarks_in_file = BookmarksInFile::new(buffer, cx);
+                    bookmarks_in_file.bookmarks = bookmarks
+                        .into_iter()
+                        .map(|bookmark| {
+                            let row = bookmark.row.min(snapshot.max_point().row);
+                            Bookmark {
+                                position: snapshot.anchor_after(Point::new(row, 0)),
+                                label: bookmark.label.map(SharedString::from),
+                            }
+                        })
+                        .collect();
+                    bookmarks_in_file
+                        .bookmarks
+                        .sort_by(|a, b| a.position.cmp(&b.position, &snapshot));
+                    bookmarks_in_file.bookmarks.dedup_by(|a, b| {
+                        a.position.to_point(&snapshot).row == b.position.to_point(&snapshot).row
+                    });
+                    this.bookmarks.insert(project_path, bookmarks_in_file);
+                })?;
+            }
+
+            this.update(cx, |_, cx| {
+                cx.emit(BookmarkStoreEvent::BookmarksUpdated);
+                cx.notify();
+            })
+        })
+    }
+
+    fn on_file_handle_changed(&mut self, buffer: Entity<Buffer>, cx: &mut Context<Self>) {
+        let Some(old_path) = self
+            .bookmarks
+            .iter()
+            .find(|(_, bookmarks_in_file)| bookmarks_in_file.buffer == buffer)
+            .map(|(project_path, _)| project_path.clone())
+        else {
+            return;
+        };
+        let new_path = buffer.read(cx).project_path(cx);
+        if new_path.as_ref() == Some(&old_path) {
+            return;
+        }
+
+        if let Some(bookmarks_in_file) = self.bookmarks.remove(&old_path) {
+            if let Some(new_path) = new_path {
+                self.bookmarks.insert(new_path, bookmarks_in_file);
+            }
+            cx.emit(BookmarkStoreEvent::BookmarksUpdated);
+            cx.notify();
+        }
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
 use anyhow::{Context as _, Result, anyhow};
 use client::Client;
 use collections::{HashMap, HashSet, hash_map};
-use fs::Fs;
+use fs::{Encoding, Fs};
 use futures::{Future, FutureExt as _, StreamExt, channel::oneshot, future::Shared};
 use gpui::{
     App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity,
@@ -15,8 +15,8 @@ use gpui::{
 use language::{
     Buffer, BufferEvent, Capability, DiskState, File as _, Language, Operation,
     proto::{
-        deserialize_line_ending, deserialize_version, serialize_line_ending, serialize_version,
-        split_operations,
+        deserialize_encoding, deserialize_line_ending, deserialize_version, serialize_encoding,
+        serialize_line_ending, serialize_version, split_operations,
     },
 };
 use rpc::{
@@ -118,6 +118,7 @@ impl RemoteBufferStore {
         &self,
         buffer_handle: Entity<Buffer>,
         new_path: Option<proto::ProjectPath>,
+        encoding: Option<Encoding>,
         cx: &Context<BufferStore>,
     ) -> Task<Result<()>> {
         let buffer = buffer_handle.read(cx);
@@ -132,12 +133,17 @@ impl RemoteBufferStore {
                     buffer_id,
                     new_path,
                     version: serialize_version(&version),
+                    encoding: encoding.map(serialize_encoding),
                 })
                 .await?;
             let version = deserialize_version(&response.version);
             let mtime = response.mtime.map(|mtime| mtime.into());
+            let encoding = response.encoding.as_ref().map(deserialize_encoding);
 
             buffer_handle.update(cx, |buffer, cx| {
+                if let Some(encoding) = encoding {
+                    buffer.set_encoding(encoding);
+                }
                 buffer.did_save(version.clone(), mtime, cx);
             })?;
 
@@ -329,6 +335,7 @@ impl RemoteBufferStore {
     fn reload_buffers(
         &self,
         buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
         push_to_history: bool,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<ProjectTransaction>> {
@@ -338,6 +345,7 @@ impl RemoteBufferStore {
                 .iter()
                 .map(|buffer| buffer.read(cx).remote_id().to_proto())
                 .collect(),
+            encoding: encoding.map(serialize_encoding),
         });
 
         cx.spawn(async move |this, cx| {
@@ -360,12 +368,14 @@ impl LocalBufferStore {
         worktree: Entity<Worktree>,
         path: Arc<Path>,
         mut has_changed_file: bool,
+        encoding: Option<Encoding>,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<()>> {
         let buffer = buffer_handle.read(cx);
 
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = encoding.unwrap_or(buffer.encoding());
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
         if buffer
@@ -376,7 +386,7 @@ impl LocalBufferStore {
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            worktree.write_file(path.as_ref(), text, line_ending, encoding, cx)
         });
 
         cx.spawn(async move |this, cx| {
@@ -399,6 +409,7 @@ impl LocalBufferStore {
                             buffer_id: buffer_id.to_proto(),
                             version: serialize_version(&version),
                             mtime: mtime.map(|time| time.into()),
+                            encoding: Some(serialize_encoding(encoding)),
                         })
                         .log_err();
                 }
@@ -407,6 +418,7 @@ impl LocalBufferStore {
                 if has_changed_file {
                     buffer.file_updated(new_file, cx);
                 }
+                buffer.set_encoding(encoding);
                 buffer.did_save(version.clone(), mtime, cx);
             })
         })
@@ -606,13 +618,14 @@ impl LocalBufferStore {
     fn save_buffer(
         &self,
         buffer: Entity<Buffer>,
+        encoding: Option<Encoding>,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<()>> {
         let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
             return Task::ready(Err(anyhow!("buffer doesn't have a file")));
         };
         let worktree = file.worktree.clone();
-        self.save_local_buffer(buffer, worktree, file.path.clone(), false, cx)
+        self.save_local_buffer(buffer, worktree, file.path.clone(), false, encoding, cx)
     }
 
     fn save_buffer_as(
@@ -628,7 +641,7 @@ impl LocalBufferStore {
         else {
             return Task::ready(Err(anyhow!("no such worktree")));
         };
-        self.save_local_buffer(buffer, worktree, path.path.clone(), true, cx)
+        self.save_local_buffer(buffer, worktree, path.path.clone(), true, None, cx)
     }
 
     fn open_buffer(
@@ -647,7 +660,10 @@ impl LocalBufferStore {
                     .background_spawn(async move { text::Buffer::new(0, buffer_id, loaded.text) })
                     .await;
                 cx.insert_entity(reservation, |_| {
-                    Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite)
+                    let mut buffer =
+                        Buffer::build(text_buffer, Some(loaded.file), Capability::ReadWrite);
+                    buffer.set_encoding(loaded.encoding);
+                    buffer
                 })
             })
         });
@@ -713,13 +729,19 @@ impl LocalBufferStore {
     fn reload_buffers(
         &self,
         buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
         push_to_history: bool,
         cx: &mut Context<BufferStore>,
     ) -> Task<Result<ProjectTransaction>> {
         cx.spawn(async move |_, cx| {
             let mut project_transaction = ProjectTransaction::default();
             for buffer in buffers {
-                let transaction = buffer.update(cx, |buffer, cx| buffer.reload(cx))?.await?;
+                let transaction = buffer
+                    .update(cx, |buffer, cx| {
+                        let encoding = encoding.unwrap_or(buffer.encoding());
+                        buffer.reload_with_encoding(encoding, cx)
+                    })?
+                    .await?;
                 buffer.update(cx, |buffer, cx| {
                     if let Some(transaction) = transaction {
                         if !push_to_history {
@@ -884,10 +906,32 @@ impl BufferStore {
         &mut self,
         buffer: Entity<Buffer>,
         cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.save_buffer_in_encoding(buffer, None, cx)
+    }
+
+    /// Saves the buffer in the given encoding rather than the one its file was loaded with,
+    /// which is used for subsequent saves.
+    pub fn save_buffer_with_encoding(
+        &mut self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.save_buffer_in_encoding(buffer, Some(encoding), cx)
+    }
+
+    fn save_buffer_in_encoding(
+        &mut self,
+        buffer: Entity<Buffer>,
+        encoding: Option<Encoding>,
+        cx: &mut Context<Self>,
     ) -> Task<Result<()>> {
         match &mut self.state {
-            BufferStoreState::Local(this) => this.save_buffer(buffer, cx),
-            BufferStoreState::Remote(this) => this.save_remote_buffer(buffer.clone(), None, cx),
+            BufferStoreState::Local(this) => this.save_buffer(buffer, encoding, cx),
+            BufferStoreState::Remote(this) => {
+                this.save_remote_buffer(buffer.clone(), None, encoding, cx)
+            }
         }
     }
 
@@ -901,7 +945,7 @@ impl BufferStore {
         let task = match &self.state {
             BufferStoreState::Local(this) => this.save_buffer_as(buffer.clone(), path, cx),
             BufferStoreState::Remote(this) => {
-                this.save_remote_buffer(buffer.clone(), Some(path.to_proto()), cx)
+                this.save_remote_buffer(buffer.clone(), Some(path.to_proto()), None, cx)
             }
         };
         cx.spawn(async move |this, cx| {
@@ -1134,6 +1178,7 @@ impl BufferStore {
                         version: serialize_version(&buffer.version()),
                         mtime: buffer.saved_mtime().map(|t| t.into()),
                         line_ending: serialize_line_ending(buffer.line_ending()) as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
             }
@@ -1250,6 +1295,7 @@ impl BufferStore {
                         mtime: buffer.saved_mtime().map(|time| time.into()),
                         line_ending: language::proto::serialize_line_ending(buffer.line_ending())
                             as i32,
+                        encoding: Some(serialize_encoding(buffer.encoding())),
                     })
                     .log_err();
 
@@ -1372,8 +1418,11 @@ impl BufferStore {
             })?
             .await?;
         } else {
-            this.update(&mut cx, |this, cx| this.save_buffer(buffer.clone(), cx))?
-                .await?;
+            let encoding = envelope.payload.encoding.as_ref().map(deserialize_encoding);
+            this.update(&mut cx, |this, cx| {
+                this.save_buffer_in_encoding(buffer.clone(), encoding, cx)
+            })?
+            .await?;
         }
 
         buffer.update(&mut cx, |buffer, _| proto::BufferSaved {
@@ -1381,6 +1430,7 @@ impl BufferStore {
             buffer_id: buffer_id.into(),
             version: serialize_version(buffer.saved_version()),
             mtime: buffer.saved_mtime().map(|time| time.into()),
+            encoding: Some(serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -1417,9 +1467,13 @@ impl BufferStore {
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let version = deserialize_version(&envelope.payload.version);
         let mtime = envelope.payload.mtime.clone().map(|time| time.into());
+        let encoding = envelope.payload.encoding.as_ref().map(deserialize_encoding);
         this.update(&mut cx, move |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding);
+                    }
                     buffer.did_save(version, mtime, cx);
                 });
             }
@@ -1431,6 +1485,7 @@ impl BufferStore {
                         buffer_id: buffer_id.into(),
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -1449,9 +1504,13 @@ impl BufferStore {
             proto::LineEnding::from_i32(envelope.payload.line_ending)
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
+        let encoding = envelope.payload.encoding.as_ref().map(deserialize_encoding);
         this.update(&mut cx, |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding);
+                    }
                     buffer.did_reload(version, line_ending, mtime, cx);
                 });
             }
@@ -1464,6 +1523,7 @@ impl BufferStore {
                         mtime: envelope.payload.mtime,
                         version: envelope.payload.version,
                         line_ending: envelope.payload.line_ending,
+                        encoding: envelope.payload.encoding,
                     })
                     .log_err();
             }
@@ -1475,13 +1535,47 @@ impl BufferStore {
         buffers: HashSet<Entity<Buffer>>,
         push_to_history: bool,
         cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.reload_buffers_in_encoding(buffers, None, push_to_history, cx)
+    }
+
+    /// Reloads the buffer from disk, decoding its file with the given encoding rather than the
+    /// one it was loaded with. The reload can't be undone.
+    pub fn reload_buffer_with_encoding(
+        &self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let reload = self.reload_buffers_in_encoding(
+            HashSet::from_iter([buffer]),
+            Some(encoding),
+            false,
+            cx,
+        );
+        cx.background_spawn(async move {
+            reload.await?;
+            Ok(())
+        })
+    }
+
+    fn reload_buffers_in_encoding(
+        &self,
+        buffers: HashSet<Entity<Buffer>>,
+        encoding: Option<Encoding>,
+        push_to_history: bool,
+        cx: &mut Context<Self>,
     ) -> Task<Result<ProjectTransaction>> {
         if buffers.is_empty() {
             return Task::ready(Ok(ProjectTransaction::default()));
         }
         match &self.state {
-            BufferStoreState::Local(this) => this.reload_buffers(buffers, push_to_history, cx),
-            BufferStoreState::Remote(this) => this.reload_buffers(buffers, push_to_history, cx),
+            BufferStoreState::Local(this) => {
+                this.reload_buffers(buffers, encoding, push_to_history, cx)
+            }
+            BufferStoreState::Remote(this) => {
+                this.reload_buffers(buffers, encoding, push_to_history, cx)
+            }
         }
     }
 
@@ -1491,13 +1585,14 @@ impl BufferStore {
         mut cx: AsyncApp,
     ) -> Result<proto::ReloadBuffersResponse> {
         let sender_id = envelope.original_sender_id().unwrap_or_default();
+        let encoding = envelope.payload.encoding.as_ref().map(deserialize_encoding);
         let reload = this.update(&mut cx, |this, cx| {
             let mut buffers = HashSet::default();
             for buffer_id in &envelope.payload.buffer_ids {
                 let buffer_id = BufferId::new(*buffer_id)?;
                 buffers.insert(this.get_existing(buffer_id)?);
             }
-            Ok::<_, anyhow::Error>(this.reload_buffers(buffers, false, cx))
+            Ok::<_, anyhow::Error>(this.reload_buffers_in_encoding(buffers, encoding, false, cx))
         })??;
 
         let project_transaction = reload.await?;
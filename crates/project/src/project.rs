@@ -1,3 +1,4 @@
+pub mod bookmark_store;
 pub mod buffer_store;
 mod color_extractor;
 pub mod connection_manager;
@@ -40,6 +41,7 @@ use clock::ReplicaId;
 
 use dap::{DapRegistry, DebugAdapterConfig, client::DebugAdapterClient};
 
+use bookmark_store::BookmarkStore;
 use collections::{BTreeSet, HashMap, HashSet};
 use debounced_delay::DebouncedDelay;
 use debugger::{
@@ -64,8 +66,8 @@ use gpui::{
 use itertools::Itertools;
 use language::{
     Buffer, BufferEvent, Capability, CodeLabel, Language, LanguageName, LanguageRegistry,
-    PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction, Unclipped,
-    language_settings::InlayHintKind, proto::split_operations,
+    ParseStatus, PointUtf16, ToOffset, ToPointUtf16, Toolchain, ToolchainList, Transaction,
+    Unclipped, language_settings::InlayHintKind, proto::split_operations,
 };
 use lsp::{
     CodeActionKind, CompletionContext, CompletionItemKind, DocumentHighlightKind, InsertTextMode,
@@ -166,6 +168,7 @@ pub struct Project {
     dap_store: Entity<DapStore>,
 
     breakpoint_store: Entity<BreakpointStore>,
+    bookmark_store: Entity<BookmarkStore>,
     client: Arc<client::Client>,
     join_project_response_message_id: u32,
     task_store: Entity<TaskStore>,
@@ -861,6 +864,8 @@ impl Project {
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::local(worktree_store.clone(), buffer_store.clone()));
+            let bookmark_store =
+                cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
 
             let dap_store = cx.new(|cx| {
                 DapStore::new_local(
@@ -962,6 +967,7 @@ impl Project {
                 fs,
                 ssh_client: None,
                 breakpoint_store,
+                bookmark_store,
                 dap_store,
 
                 buffers_needing_diff: Default::default(),
@@ -1065,6 +1071,8 @@ impl Project {
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::remote(SSH_PROJECT_ID, client.clone().into()));
+            let bookmark_store =
+                cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
 
             let dap_store = cx.new(|_| {
                 DapStore::new_remote(
@@ -1088,6 +1096,7 @@ impl Project {
                 image_store,
                 lsp_store,
                 breakpoint_store,
+                bookmark_store,
                 dap_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -1250,6 +1259,8 @@ impl Project {
 
         let breakpoint_store =
             cx.new(|_| BreakpointStore::remote(remote_id, client.clone().into()))?;
+        let bookmark_store =
+            cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()))?;
         let dap_store = cx.new(|_cx| {
             DapStore::new_remote(remote_id, client.clone().into(), breakpoint_store.clone())
         })?;
@@ -1353,6 +1364,7 @@ impl Project {
                     replica_id,
                 },
                 breakpoint_store,
+                bookmark_store,
                 dap_store: dap_store.clone(),
                 git_store: git_store.clone(),
                 buffers_needing_diff: Default::default(),
@@ -1609,6 +1621,10 @@ impl Project {
         self.breakpoint_store.clone()
     }
 
+    pub fn bookmark_store(&self) -> Entity<BookmarkStore> {
+        self.bookmark_store.clone()
+    }
+
     pub fn lsp_store(&self) -> Entity<LspStore> {
         self.lsp_store.clone()
     }
@@ -2458,6 +2474,18 @@ impl Project {
             .update(cx, |buffer_store, cx| buffer_store.save_buffer(buffer, cx))
     }
 
+    /// Saves the buffer in the given encoding, which is also used for subsequent saves.
+    pub fn save_buffer_with_encoding(
+        &self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.save_buffer_with_encoding(buffer, encoding, cx)
+        })
+    }
+
     pub fn save_buffer_as(
         &mut self,
         buffer: Entity<Buffer>,
@@ -3150,6 +3178,18 @@ impl Project {
         })
     }
 
+    /// Reopens the buffer's file, decoding it with the given encoding.
+    pub fn reload_buffer_with_encoding(
+        &self,
+        buffer: Entity<Buffer>,
+        encoding: Encoding,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.reload_buffer_with_encoding(buffer, encoding, cx)
+        })
+    }
+
     pub fn reload_images(
         &self,
         images: HashSet<Entity<ImageItem>>,
@@ -3591,9 +3631,21 @@ impl Project {
             let mut chunks = pin!(chunks);
             'outer: while let Some(matching_buffer_chunk) = chunks.next().await {
                 let mut chunk_results = Vec::new();
-                for buffer in matching_buffer_chunk {
+                'buffers: for buffer in matching_buffer_chunk {
                     let buffer = buffer.clone();
                     let query = query.clone();
+                    // Structural queries match syntax trees, so they need the buffers to be parsed.
+                    if query.is_structural() {
+                        let mut parse_status =
+                            buffer.read_with(cx, |buffer, _| buffer.parse_status())?;
+                        while *parse_status.borrow() != ParseStatus::Idle {
+                            // The buffer stopped being parsed, so it's skipped rather than
+                            // ending the search.
+                            if parse_status.changed().await.is_err() {
+                                continue 'buffers;
+                            }
+                        }
+                    }
                     let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot())?;
                     chunk_results.push(cx.background_spawn(async move {
                         let ranges = query
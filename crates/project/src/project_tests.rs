@@ -3487,6 +3487,78 @@ async fn test_save_file(cx: &mut gpui::TestAppContext) {
     assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
 }
 
+#[gpui::test]
+async fn test_save_file_with_encoding(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({})).await;
+    // "こんにちは" in Shift_JIS.
+    let shift_jis_bytes = b"\x82\xb1\x82\xf1\x82\xc9\x82\xbf\x82\xcd".to_vec();
+    fs.insert_file(path!("/dir/file1"), shift_jis_bytes.clone())
+        .await;
+
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer(path!("/dir/file1"), cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "こんにちは");
+        assert_eq!(buffer.encoding().name(), "Shift_JIS");
+        buffer.edit([(buffer.len()..buffer.len(), "!")], None, cx);
+    });
+
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    let mut expected_bytes = shift_jis_bytes.clone();
+    expected_bytes.push(b'!');
+    assert_eq!(
+        fs.load_bytes(Path::new(path!("/dir/file1"))).await.unwrap(),
+        expected_bytes
+    );
+
+    let utf8 = Encoding::from_name("utf-8").unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer_with_encoding(buffer.clone(), utf8, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load(Path::new(path!("/dir/file1"))).await.unwrap(),
+        "こんにちは!"
+    );
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.encoding(), utf8);
+        assert!(!buffer.is_dirty());
+    });
+
+    // Reopening the file with the wrong encoding fails without changing the buffer.
+    fs.insert_file(path!("/dir/file1"), shift_jis_bytes).await;
+    cx.run_until_parked();
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffer_with_encoding(buffer.clone(), utf8, cx)
+        })
+        .await
+        .unwrap_err();
+    let shift_jis = Encoding::from_name("shift_jis").unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.reload_buffer_with_encoding(buffer.clone(), shift_jis, cx)
+        })
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "こんにちは");
+        assert_eq!(buffer.encoding(), shift_jis);
+        assert!(!buffer.is_dirty());
+    });
+}
+
 #[gpui::test(iterations = 30)]
 async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4871,6 +4943,53 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "fn a() { foo(1, 2); foo(3); }",
+            "two.rs": "fn b() { bar(1, 2); }",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural(
+                "foo($A, $B)",
+                false,
+                Default::default(),
+                Default::default(),
+                None,
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([(separator!("dir/one.rs").to_string(), vec![9..18])])
+    );
+
+    assert!(
+        SearchQuery::structural(
+            "foo($A, $B",
+            false,
+            Default::default(),
+            Default::default(),
+            None,
+        )
+        .is_err()
+    );
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -8182,6 +8301,122 @@ async fn test_rescan_with_gitignore(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_bookmarks(cx: &mut gpui::TestAppContext) {
+    use crate::bookmark_store::BookmarkDirection;
+
+    init_test(cx);
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "a.rs": "one\ntwo\nthree\n",
+            "b.rs": "four\nfive\n",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let bookmark_store = project.read_with(cx, |project, _| project.bookmark_store());
+    let buffer_a = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/a.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let buffer_b = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/b.rs"), cx)
+        })
+        .await
+        .unwrap();
+
+    let bookmark_rows = |buffer: &Entity<Buffer>, cx: &mut gpui::TestAppContext| {
+        bookmark_store.read_with(cx, |bookmark_store, cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            bookmark_store
+                .bookmarks_for_buffer(buffer, cx)
+                .iter()
+                .map(|bookmark| bookmark.position.to_point(&snapshot).row)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    bookmark_store.update(cx, |bookmark_store, cx| {
+        for (buffer, row) in [(&buffer_a, 2), (&buffer_a, 1), (&buffer_b, 1)] {
+            let position = buffer.read(cx).anchor_before(Point::new(row, 1));
+            bookmark_store.toggle_bookmark(buffer.clone(), position, None, cx);
+        }
+    });
+    assert_eq!(bookmark_rows(&buffer_a, cx), vec![1, 2]);
+    assert_eq!(bookmark_rows(&buffer_b, cx), vec![1]);
+
+    // Bookmarks follow their lines as the buffer is edited.
+    buffer_a.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+    assert_eq!(bookmark_rows(&buffer_a, cx), vec![2, 3]);
+
+    // A line inserted directly above a bookmark pushes it down with its line.
+    buffer_a.update(cx, |buffer, cx| {
+        let offset = buffer.point_to_offset(Point::new(2, 0));
+        buffer.edit([(offset..offset, "one and a half\n")], None, cx)
+    });
+    assert_eq!(bookmark_rows(&buffer_a, cx), vec![3, 4]);
+    buffer_a.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "zero\none\none and a half\ntwo\nthree\n");
+        buffer.edit([(Point::new(2, 0)..Point::new(3, 0), "")], None, cx)
+    });
+    assert_eq!(bookmark_rows(&buffer_a, cx), vec![2, 3]);
+
+    // Toggling a bookmarked line anywhere on that line removes the bookmark.
+    bookmark_store.update(cx, |bookmark_store, cx| {
+        let position = buffer_a.read(cx).anchor_before(Point::new(3, 4));
+        bookmark_store.toggle_bookmark(buffer_a.clone(), position, None, cx);
+    });
+    assert_eq!(bookmark_rows(&buffer_a, cx), vec![2]);
+
+    // Navigation moves across files and wraps around the project.
+    let adjacent = |buffer: &Entity<Buffer>,
+                    row: u32,
+                    direction: BookmarkDirection,
+                    cx: &mut gpui::TestAppContext| {
+        bookmark_store.read_with(cx, |bookmark_store, cx| {
+            let (buffer, bookmark) =
+                bookmark_store.adjacent_bookmark(buffer, row, direction, cx)?;
+            let row = bookmark.position.to_point(&buffer.read(cx).snapshot()).row;
+            Some((buffer.read(cx).remote_id(), row))
+        })
+    };
+    let buffer_a_id = buffer_a.read_with(cx, |buffer, _| buffer.remote_id());
+    let buffer_b_id = buffer_b.read_with(cx, |buffer, _| buffer.remote_id());
+    assert_eq!(
+        adjacent(&buffer_a, 2, BookmarkDirection::Next, cx),
+        Some((buffer_b_id, 1))
+    );
+    assert_eq!(
+        adjacent(&buffer_b, 1, BookmarkDirection::Next, cx),
+        Some((buffer_a_id, 2))
+    );
+    assert_eq!(
+        adjacent(&buffer_a, 2, BookmarkDirection::Previous, cx),
+        Some((buffer_b_id, 1))
+    );
+
+    let serialized = bookmark_store.read_with(cx, |bookmark_store, cx| {
+        bookmark_store.serialized_bookmarks(cx)
+    });
+    assert_eq!(
+        serialized
+            .iter()
+            .map(|bookmark| (bookmark.path.to_string_lossy().to_string(), bookmark.row))
+            .collect::<Vec<_>>(),
+        vec![("a.rs".to_string(), 2), ("b.rs".to_string(), 1)]
+    );
+
+    bookmark_store.update(cx, |bookmark_store, cx| bookmark_store.clear_bookmarks(cx));
+    assert_eq!(bookmark_rows(&buffer_a, cx), Vec::<u32>::new());
+    assert_eq!(adjacent(&buffer_a, 0, BookmarkDirection::Next, cx), None);
+}
+
 #[gpui::test]
 async fn test_repository_deduplication(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -49,6 +49,9 @@ pub enum TerminalKind {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshCommand {
     arguments: Vec<String>,
+    /// Whether the arguments are a command that starts a shell on the remote server, which is
+    /// run instead of `ssh`.
+    is_shell_command: bool,
 }
 
 impl Project {
@@ -76,9 +79,13 @@ impl Project {
         if let Some(ssh_client) = &self.ssh_client {
             let ssh_client = ssh_client.read(cx);
             if let Some(args) = ssh_client.ssh_args() {
+                let connection_options = ssh_client.connection_options();
                 return Some((
-                    ssh_client.connection_options().host.clone(),
-                    SshCommand { arguments: args },
+                    connection_options.host,
+                    SshCommand {
+                        arguments: args,
+                        is_shell_command: connection_options.shell_command.is_some(),
+                    },
                 ));
             }
         }
@@ -596,6 +603,15 @@ fn wrap_for_ssh(
     } else {
         format!("cd; {env_changes} {to_run}")
     };
+    if ssh_command.is_shell_command {
+        if let Some((program, args)) = ssh_command.arguments.split_first() {
+            let mut args = args.to_vec();
+            args.push("-c".to_string());
+            args.push(commands);
+            return (program.clone(), args);
+        }
+    }
+
     let shell_invocation = format!("sh -c {}", shlex::try_quote(&commands).unwrap());
 
     let program = "ssh".to_string();
@@ -658,4 +674,63 @@ mod tests {
         }
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
+
+    #[test]
+    fn test_wrap_for_ssh_with_shell_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let ssh_command = super::SshCommand {
+            arguments: vec!["sh".to_string()],
+            is_shell_command: true,
+        };
+        let command = "sh".to_string();
+        let args = vec![
+            "-c".to_string(),
+            "printf '%s|%s' \"$GREETING\" \"$(pwd -P)\"".to_string(),
+        ];
+        let mut env = HashMap::default();
+        env.insert("GREETING".to_string(), "it's me".to_string());
+
+        let (program, args) = super::wrap_for_ssh(
+            &ssh_command,
+            Some((&command, &args)),
+            Some(dir.path()),
+            env,
+            None,
+        );
+        assert_eq!(program, "sh");
+        assert_eq!(args.len(), 2);
+        assert_eq!(args[0], "-c");
+
+        // The script runs in the shell started by the command, without going through `ssh`.
+        let output = std::process::Command::new(program)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("it's me|{}", dir.path().canonicalize().unwrap().display())
+        );
+    }
+
+    #[test]
+    fn test_wrap_for_ssh_with_ssh_arguments() {
+        let ssh_command = super::SshCommand {
+            arguments: vec!["-p".to_string(), "2222".to_string(), "host".to_string()],
+            is_shell_command: false,
+        };
+        let (program, args) =
+            super::wrap_for_ssh(&ssh_command, None, None, HashMap::default(), None);
+        assert_eq!(program, "ssh");
+        assert_eq!(
+            args,
+            [
+                "-p",
+                "2222",
+                "host",
+                "-t",
+                "sh -c 'cd;  exec ${SHELL:-sh} -l'"
+            ]
+        );
+    }
 }
@@ -0,0 +1,356 @@
+use std::{collections::BTreeMap, ops::Bound, path::Path, sync::Arc};
+
+use collections::HashMap;
+use project::{GitEntry, ProjectEntryId};
+
+use crate::NEW_ENTRY_ID;
+
+/// Rules for showing related files nested under another file in the same directory, such as
+/// `Cargo.lock` under `Cargo.toml`.
+///
+/// Each rule pairs a pattern for the parent file's name with patterns for the names of the files
+/// nested under it. In the parent pattern, `*` matches any text, and the text matched by the
+/// first `*` can be referred to as `${capture}` in the patterns of the nested files, which may
+/// use `*` as well. For example, `"*.ts": ["${capture}.test.ts"]` nests `foo.test.ts` under
+/// `foo.ts`.
+///
+/// Files are only nested one level deep: a file that has files nested under it is never nested
+/// itself.
+pub(crate) struct FileNestingRules {
+    rules: Vec<(Vec<Token>, Vec<Vec<Token>>)>,
+}
+
+/// Which files are nested under which in the project panel.
+#[derive(Debug, Default)]
+pub(crate) struct NestedFiles {
+    /// The file that each nested file is nested under.
+    pub parents: HashMap<ProjectEntryId, ProjectEntryId>,
+    /// The paths of the files nested under each file.
+    pub children: HashMap<ProjectEntryId, Vec<Arc<Path>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Literal(String),
+    Wildcard,
+    Capture,
+}
+
+impl FileNestingRules {
+    pub fn new(patterns: &BTreeMap<String, Vec<String>>) -> Self {
+        let rules = patterns
+            .iter()
+            .filter(|(_, child_patterns)| !child_patterns.is_empty())
+            .map(|(parent_pattern, child_patterns)| {
+                (
+                    parse_pattern(parent_pattern, false),
+                    child_patterns
+                        .iter()
+                        .map(|pattern| parse_pattern(pattern, true))
+                        .collect(),
+                )
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// For each of the given names of files in a directory, returns the index of the file it
+    /// should be nested under, if any.
+    pub fn nest(&self, file_names: &[impl AsRef<str>]) -> Vec<Option<usize>> {
+        // Names are looked up by the text that the names matching a pattern start with.
+        let mut ixs_by_name = BTreeMap::<&str, Vec<usize>>::new();
+        for (ix, name) in file_names.iter().enumerate() {
+            ixs_by_name.entry(name.as_ref()).or_default().push(ix);
+        }
+
+        let mut parents = vec![None; file_names.len()];
+        let mut has_children = vec![false; file_names.len()];
+        let mut child_ixs = Vec::new();
+        for (parent_ix, parent_name) in file_names.iter().enumerate() {
+            if parents[parent_ix].is_some() {
+                continue;
+            }
+
+            for (parent_pattern, child_patterns) in &self.rules {
+                let mut captures = Vec::new();
+                if !match_tokens(parent_pattern, parent_name.as_ref(), None, &mut captures) {
+                    continue;
+                }
+                let capture = captures.first().copied().unwrap_or_default();
+
+                child_ixs.clear();
+                for pattern in child_patterns {
+                    let prefix = literal_prefix(pattern, capture);
+                    child_ixs.extend(
+                        ixs_by_name
+                            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+                            .take_while(|(name, _)| name.starts_with(&prefix))
+                            .flat_map(|(_, ixs)| ixs.iter().copied()),
+                    );
+                }
+                child_ixs.sort_unstable();
+                child_ixs.dedup();
+
+                for &child_ix in &child_ixs {
+                    if child_ix == parent_ix
+                        || parents[child_ix].is_some()
+                        || has_children[child_ix]
+                    {
+                        continue;
+                    }
+                    let child_name = file_names[child_ix].as_ref();
+                    if child_patterns.iter().any(|pattern| {
+                        match_tokens(pattern, child_name, Some(capture), &mut Vec::new())
+                    }) {
+                        parents[child_ix] = Some(parent_ix);
+                        has_children[parent_ix] = true;
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    /// Reorders the sorted entries of a worktree so that nested files directly follow the file
+    /// they're nested under, leaving out the ones nested under collapsed files. The git statuses
+    /// of nested files are rolled up into the status of the file they're nested under.
+    pub fn nest_entries(
+        &self,
+        mut entries: Vec<GitEntry>,
+        expanded_entry_ids: &[ProjectEntryId],
+        nested_files: &mut NestedFiles,
+    ) -> Vec<GitEntry> {
+        let mut files_by_directory = HashMap::<&Path, Vec<usize>>::default();
+        for (ix, entry) in entries.iter().enumerate() {
+            if entry.is_file() && entry.id != NEW_ENTRY_ID {
+                if let Some(directory) = entry.path.parent() {
+                    files_by_directory.entry(directory).or_default().push(ix);
+                }
+            }
+        }
+
+        let mut parent_ixs = vec![None; entries.len()];
+        for file_ixs in files_by_directory.values() {
+            let file_names = file_ixs
+                .iter()
+                .map(|ix| {
+                    entries[*ix]
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<_>>();
+            for (ix, parent_ix) in self.nest(&file_names).into_iter().enumerate() {
+                parent_ixs[file_ixs[ix]] = parent_ix.map(|parent_ix| file_ixs[parent_ix]);
+            }
+        }
+
+        let mut child_ixs = HashMap::<usize, Vec<usize>>::default();
+        for (ix, parent_ix) in parent_ixs.iter().enumerate() {
+            if let Some(parent_ix) = parent_ix {
+                child_ixs.entry(*parent_ix).or_default().push(ix);
+            }
+        }
+        for (parent_ix, child_ixs) in &child_ixs {
+            let parent_id = entries[*parent_ix].id;
+            for child_ix in child_ixs {
+                let child_summary = entries[*child_ix].git_summary;
+                entries[*parent_ix].git_summary += child_summary;
+                nested_files
+                    .parents
+                    .insert(entries[*child_ix].id, parent_id);
+                nested_files
+                    .children
+                    .entry(parent_id)
+                    .or_default()
+                    .push(entries[*child_ix].path.clone());
+            }
+        }
+
+        let mut entries = entries.into_iter().map(Some).collect::<Vec<_>>();
+        let mut nested_entries = Vec::with_capacity(entries.len());
+        for ix in 0..entries.len() {
+            if parent_ixs[ix].is_some() {
+                continue;
+            }
+            let Some(entry) = entries[ix].take() else {
+                continue;
+            };
+            let is_expanded = expanded_entry_ids.binary_search(&entry.id).is_ok();
+            nested_entries.push(entry);
+            if let Some(child_ixs) = child_ixs.get(&ix).filter(|_| is_expanded) {
+                nested_entries.extend(child_ixs.iter().filter_map(|ix| entries[*ix].take()));
+            }
+        }
+        nested_entries
+    }
+}
+
+fn parse_pattern(pattern: &str, allow_capture: bool) -> Vec<Token> {
+    const CAPTURE: &str = "${capture}";
+
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut rest = pattern;
+    while let Some(c) = rest.chars().next() {
+        if allow_capture && rest.starts_with(CAPTURE) {
+            tokens.extend(
+                (!literal.is_empty()).then(|| Token::Literal(std::mem::take(&mut literal))),
+            );
+            tokens.push(Token::Capture);
+            rest = &rest[CAPTURE.len()..];
+        } else if c == '*' {
+            tokens.extend(
+                (!literal.is_empty()).then(|| Token::Literal(std::mem::take(&mut literal))),
+            );
+            tokens.push(Token::Wildcard);
+            rest = &rest[1..];
+        } else {
+            literal.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    tokens.extend((!literal.is_empty()).then(|| Token::Literal(literal)));
+    tokens
+}
+
+/// Returns the text that every name matching the tokens starts with, which is everything up to
+/// the first wildcard.
+fn literal_prefix(tokens: &[Token], capture: &str) -> String {
+    let mut prefix = String::new();
+    for token in tokens {
+        match token {
+            Token::Literal(literal) => prefix.push_str(literal),
+            Token::Capture => prefix.push_str(capture),
+            Token::Wildcard => break,
+        }
+    }
+    prefix
+}
+
+/// Matches the whole text against the tokens, collecting the text matched by each wildcard.
+fn match_tokens<'a>(
+    tokens: &[Token],
+    text: &'a str,
+    capture: Option<&str>,
+    captures: &mut Vec<&'a str>,
+) -> bool {
+    match tokens.split_first() {
+        None => text.is_empty(),
+        Some((Token::Literal(literal), rest)) => text
+            .strip_prefix(literal.as_str())
+            .is_some_and(|text| match_tokens(rest, text, capture, captures)),
+        Some((Token::Capture, rest)) => text
+            .strip_prefix(capture.unwrap_or_default())
+            .is_some_and(|text| match_tokens(rest, text, capture, captures)),
+        Some((Token::Wildcard, rest)) => {
+            let ends = text.char_indices().map(|(ix, _)| ix).chain([text.len()]);
+            for end in ends {
+                captures.push(&text[..end]);
+                if match_tokens(rest, &text[end..], capture, captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[(&str, &[&str])]) -> FileNestingRules {
+        FileNestingRules::new(
+            &patterns
+                .iter()
+                .map(|(parent, children)| {
+                    (
+                        parent.to_string(),
+                        children.iter().map(|child| child.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_nest_files() {
+        let rules = rules(&[
+            ("*.ts", &["${capture}.test.ts", "${capture}.d.ts"]),
+            ("Cargo.toml", &["Cargo.lock"]),
+            (".env", &[".env.*"]),
+        ]);
+
+        assert_eq!(
+            rules.nest(&[
+                ".env",
+                ".env.local",
+                ".env.production",
+                "Cargo.lock",
+                "Cargo.toml",
+                "bar.test.ts",
+                "foo.d.ts",
+                "foo.test.ts",
+                "foo.ts",
+                "main.rs",
+            ]),
+            [
+                None,
+                Some(0),
+                Some(0),
+                Some(4),
+                None,
+                None,
+                Some(8),
+                Some(8),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nest_files_one_level_deep() {
+        let rules = rules(&[
+            ("*.js", &["${capture}.js.map"]),
+            ("*.ts", &["${capture}.js"]),
+        ]);
+
+        // `foo.js` has a file nested under it, so it isn't nested under `foo.ts`.
+        assert_eq!(
+            rules.nest(&["foo.js", "foo.js.map", "foo.ts"]),
+            [None, Some(0), None]
+        );
+        assert_eq!(rules.nest(&["foo.js", "foo.ts"]), [Some(1), None]);
+    }
+
+    #[test]
+    fn test_nest_many_files() {
+        let rules = rules(&[
+            ("*.ts", &["${capture}.test.ts", "${capture}.*.snap"]),
+            ("*", &["${capture}.orig"]),
+        ]);
+        let file_names = (0..1000)
+            .flat_map(|ix| {
+                [
+                    format!("file{ix}.test.ts"),
+                    format!("file{ix}.ts"),
+                    format!("file{ix}.ts.1.snap"),
+                ]
+            })
+            .chain(["file1.ts.orig".to_string()])
+            .collect::<Vec<_>>();
+
+        let parents = rules.nest(&file_names);
+        for ix in 0..1000 {
+            assert_eq!(parents[ix * 3], Some(ix * 3 + 1));
+            assert_eq!(parents[ix * 3 + 1], None);
+            assert_eq!(parents[ix * 3 + 2], Some(ix * 3 + 1));
+        }
+        // Files can be nested under the same file by different rules.
+        assert_eq!(parents[3000], Some(4));
+    }
+}
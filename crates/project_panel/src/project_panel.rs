@@ -1,4 +1,6 @@
+mod file_nesting;
 mod project_panel_settings;
+mod sort_order;
 mod utils;
 
 use anyhow::{Context as _, Result, anyhow};
@@ -7,7 +9,7 @@ use collections::{BTreeSet, HashMap, hash_map};
 use command_palette_hooks::CommandPaletteFilter;
 use db::kvp::KEY_VALUE_STORE;
 use editor::{
-    Editor, EditorEvent, EditorSettings, ShowScrollbar,
+    Editor, EditorEvent, EditorSettings, ShowScrollbar, TextDiffView,
     items::{
         entry_diagnostic_aware_icon_decoration_and_color,
         entry_diagnostic_aware_icon_name_and_color, entry_git_aware_label_color,
@@ -15,14 +17,17 @@ use editor::{
     scroll::{Autoscroll, ScrollbarAutoHide},
 };
 use file_icons::FileIcons;
+use file_nesting::{FileNestingRules, NestedFiles};
+use fuzzy::PathMatchCandidate;
 use git::status::GitSummary;
 use gpui::{
-    Action, AnyElement, App, ArcCow, AsyncWindowContext, Bounds, ClipboardItem, Context,
-    DismissEvent, Div, DragMoveEvent, Entity, EventEmitter, ExternalPaths, FocusHandle, Focusable,
-    Hsla, InteractiveElement, KeyContext, ListHorizontalSizingBehavior, ListSizingBehavior,
-    MouseButton, MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, ScrollStrategy,
-    Stateful, Styled, Subscription, Task, UniformListScrollHandle, WeakEntity, Window, actions,
-    anchored, deferred, div, impl_actions, point, px, size, uniform_list,
+    AccessibilityRole, Action, AnyElement, App, ArcCow, AsyncWindowContext, Bounds, ClipboardItem,
+    Context, DismissEvent, Div, DragMoveEvent, Entity, EventEmitter, ExternalPaths, FocusHandle,
+    Focusable, Hsla, InteractiveElement, KeyContext, ListHorizontalSizingBehavior,
+    ListSizingBehavior, MouseButton, MouseDownEvent, ParentElement, Pixels, Point, PromptLevel,
+    Render, ScrollStrategy, Stateful, Styled, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, Window, actions, anchored, deferred, div, impl_actions, point, px, size,
+    uniform_list,
 };
 use indexmap::IndexMap;
 use language::DiagnosticSeverity;
@@ -40,12 +45,14 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsStore, update_settings_file};
 use smallvec::SmallVec;
+use sort_order::{SortMode, SortOrder};
 use std::any::TypeId;
 use std::{
     cell::OnceCell,
     cmp,
     collections::HashSet,
     ffi::OsStr,
+    iter,
     ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
@@ -57,7 +64,10 @@ use ui::{
     IndentGuideLayout, KeyBinding, Label, LabelSize, ListItem, ListItemSpacing, Scrollbar,
     ScrollbarState, Tooltip, prelude::*, v_flex,
 };
-use util::{ResultExt, TakeUntilExt, TryFutureExt, maybe, paths::compare_paths};
+use util::{
+    ResultExt, TakeUntilExt, TryFutureExt, maybe,
+    paths::{PathMatcher, compare_paths},
+};
 use workspace::{
     DraggedSelection, OpenInTerminal, OpenOptions, OpenVisible, PreviewTabsSettings, SelectedEntry,
     Workspace,
@@ -88,12 +98,28 @@ pub struct ProjectPanel {
     last_external_paths_drag_over_entry: Option<ProjectEntryId>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
     unfolded_dir_ids: HashSet<ProjectEntryId>,
+    /// Files shown nested under related files. Files with nested files are expanded and
+    /// collapsed like directories, and their ids are kept in `expanded_dir_ids` when expanded.
+    nested_files: NestedFiles,
+    /// The rules for nesting files, built from the settings when file nesting is enabled.
+    file_nesting: Option<FileNestingRules>,
     // Currently selected leaf entry (see auto-folding for a definition of that) in a file tree
     selection: Option<SelectedEntry>,
     marked_entries: BTreeSet<SelectedEntry>,
     context_menu: Option<(Entity<ContextMenu>, Point<Pixels>, Subscription)>,
     edit_state: Option<EditState>,
     filename_editor: Entity<Editor>,
+    /// Narrows the tree down to the entries matching its query and their ancestors, while
+    /// `show_filter` is set.
+    filter_editor: Entity<Editor>,
+    show_filter: bool,
+    /// The paths matching the filter query in each worktree, which are recomputed in the
+    /// background when the query or the worktree changes.
+    filtered_paths: HashMap<WorktreeId, FilteredPaths>,
+    pending_filter: Option<PendingFilter>,
+    sort_order: SortOrder,
+    /// The key under which the state specific to this panel's workspace is persisted.
+    serialization_key: Option<String>,
     clipboard: Option<ClipboardEntry>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakEntity<Workspace>,
@@ -111,6 +137,36 @@ pub struct ProjectPanel {
     hover_expand_task: Option<Task<()>>,
 }
 
+/// The entries of a worktree matching a filter query, along with their ancestors.
+struct FilteredPaths {
+    request: FilterRequest,
+    paths: Arc<HashSet<Arc<Path>>>,
+}
+
+/// What the paths matching a filter query in a worktree are computed from.
+#[derive(Clone, PartialEq, Eq)]
+struct FilterRequest {
+    query: String,
+    include_ignored: bool,
+    /// The worktree's scan id and entry count, which change along with its entries.
+    snapshot_version: (usize, usize),
+}
+
+impl FilterRequest {
+    fn new(query: String, include_ignored: bool, snapshot: &worktree::Snapshot) -> Self {
+        Self {
+            query,
+            include_ignored,
+            snapshot_version: (snapshot.scan_id(), snapshot.entry_count()),
+        }
+    }
+}
+
+struct PendingFilter {
+    requests: Vec<(WorktreeId, FilterRequest)>,
+    _task: Task<()>,
+}
+
 #[derive(Copy, Clone, Debug)]
 struct FoldedDirectoryDragTarget {
     entry_id: ProjectEntryId,
@@ -159,6 +215,7 @@ struct EntryDetails {
     kind: EntryKind,
     is_ignored: bool,
     is_expanded: bool,
+    has_nested_files: bool,
     is_selected: bool,
     is_marked: bool,
     is_editing: bool,
@@ -186,7 +243,13 @@ struct Trash {
     pub skip_prompt: bool,
 }
 
-impl_actions!(project_panel, [Delete, Trash]);
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+struct SortBy {
+    pub mode: SortMode,
+}
+
+impl_actions!(project_panel, [Delete, Trash, SortBy]);
 
 actions!(
     project_panel,
@@ -218,6 +281,9 @@ actions!(
         SelectPrevDiagnostic,
         SelectNextDirectory,
         SelectPrevDirectory,
+        CompareMarkedFiles,
+        ToggleFilter,
+        ToggleDirectoriesFirst,
     ]
 );
 
@@ -273,6 +339,12 @@ struct SerializedProjectPanel {
     width: Option<Pixels>,
 }
 
+/// The state of the project panel that is persisted separately for each workspace.
+#[derive(Serialize, Deserialize)]
+struct SerializedWorkspaceProjectPanel {
+    sort_order: SortOrder,
+}
+
 struct DraggedProjectEntryView {
     selection: SelectedEntry,
     details: EntryDetails,
@@ -439,16 +511,32 @@ impl ProjectPanel {
             )
             .detach();
 
+            let filter_editor = cx.new(|cx| {
+                let mut editor = Editor::single_line(window, cx);
+                editor.set_placeholder_text("Filter…", cx);
+                editor
+            });
+
+            cx.subscribe(&filter_editor, |project_panel, _, editor_event, cx| {
+                if let EditorEvent::BufferEdited = editor_event {
+                    project_panel.update_filter(cx);
+                }
+            })
+            .detach();
+
             cx.observe_global::<FileIcons>(|_, cx| {
                 cx.notify();
             })
             .detach();
 
-            let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
+            let mut project_panel_settings = ProjectPanelSettings::get_global(cx).clone();
             cx.observe_global::<SettingsStore>(move |this, cx| {
-                let new_settings = *ProjectPanelSettings::get_global(cx);
+                let new_settings = ProjectPanelSettings::get_global(cx).clone();
                 if project_panel_settings != new_settings {
-                    if project_panel_settings.hide_gitignore != new_settings.hide_gitignore {
+                    if project_panel_settings.file_nesting != new_settings.file_nesting {
+                        this.file_nesting = file_nesting_rules(&new_settings);
+                        this.update_visible_entries(None, cx);
+                    } else if project_panel_settings.hide_gitignore != new_settings.hide_gitignore {
                         this.update_visible_entries(None, cx);
                     }
                     project_panel_settings = new_settings;
@@ -472,11 +560,19 @@ impl ProjectPanel {
                 last_selection_drag_over_entry: None,
                 expanded_dir_ids: Default::default(),
                 unfolded_dir_ids: Default::default(),
+                nested_files: Default::default(),
+                file_nesting: file_nesting_rules(ProjectPanelSettings::get_global(cx)),
                 selection: None,
                 marked_entries: Default::default(),
                 edit_state: None,
                 context_menu: None,
                 filename_editor,
+                filter_editor,
+                show_filter: false,
+                filtered_paths: HashMap::default(),
+                pending_filter: None,
+                sort_order: SortOrder::default(),
+                serialization_key: Self::serialization_key(workspace),
                 clipboard: None,
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
@@ -598,6 +694,24 @@ impl ProjectPanel {
             .transpose()
             .log_err()
             .flatten();
+        let serialization_key = workspace
+            .read_with(&cx, |workspace, _| {
+                ProjectPanel::serialization_key(workspace)
+            })
+            .ok()
+            .flatten();
+        let serialized_workspace_panel = match serialization_key {
+            Some(serialization_key) => cx
+                .background_spawn(async move { KEY_VALUE_STORE.read_kvp(&serialization_key) })
+                .await
+                .log_err()
+                .flatten()
+                .map(|panel| serde_json::from_str::<SerializedWorkspaceProjectPanel>(&panel))
+                .transpose()
+                .log_err()
+                .flatten(),
+            None => None,
+        };
 
         workspace.update_in(&mut cx, |workspace, window, cx| {
             let panel = ProjectPanel::new(workspace, window, cx);
@@ -607,10 +721,23 @@ impl ProjectPanel {
                     cx.notify();
                 });
             }
+            if let Some(serialized_workspace_panel) = serialized_workspace_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.sort_order = serialized_workspace_panel.sort_order;
+                    panel.update_visible_entries(None, cx);
+                    cx.notify();
+                });
+            }
             panel
         })
     }
 
+    fn serialization_key(workspace: &Workspace) -> Option<String> {
+        workspace
+            .database_id()
+            .map(|id| format!("{}-{}", PROJECT_PANEL_KEY, i64::from(id)))
+    }
+
     fn update_diagnostics(&mut self, cx: &mut Context<Self>) {
         let mut diagnostics: HashMap<(WorktreeId, PathBuf), DiagnosticSeverity> =
             Default::default();
@@ -671,6 +798,8 @@ impl ProjectPanel {
 
     fn serialize(&mut self, cx: &mut Context<Self>) {
         let width = self.width;
+        let sort_order = self.sort_order;
+        let serialization_key = self.serialization_key.clone();
         self.pending_serialization = cx.background_spawn(
             async move {
                 KEY_VALUE_STORE
@@ -679,6 +808,14 @@ impl ProjectPanel {
                         serde_json::to_string(&SerializedProjectPanel { width })?,
                     )
                     .await?;
+                if let Some(serialization_key) = serialization_key {
+                    KEY_VALUE_STORE
+                        .write_kvp(
+                            serialization_key,
+                            serde_json::to_string(&SerializedWorkspaceProjectPanel { sort_order })?,
+                        )
+                        .await?;
+                }
                 anyhow::Ok(())
             }
             .log_err(),
@@ -727,6 +864,9 @@ impl ProjectPanel {
             let is_read_only = project.is_read_only(cx);
             let is_remote = project.is_via_collab();
             let is_local = project.is_local();
+            let can_compare_marked_files = self.marked_files_to_compare(cx).is_some();
+            let sort_order = self.sort_order;
+            let project_panel = cx.entity().downgrade();
 
             let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -748,6 +888,10 @@ impl ProjectPanel {
                                 menu.action("Open in Default App", Box::new(OpenWithSystem))
                             })
                             .action("Open in Terminal", Box::new(OpenInTerminal))
+                            .when(can_compare_marked_files, |menu| {
+                                menu.separator()
+                                    .action("Compare Marked Files", Box::new(CompareMarkedFiles))
+                            })
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
@@ -797,6 +941,50 @@ impl ProjectPanel {
                             .when(is_root, |menu| {
                                 menu.separator()
                                     .action("Collapse All", Box::new(CollapseAllEntries))
+                                    .action("Filter…", Box::new(ToggleFilter))
+                            })
+                            .when(is_root, |menu| {
+                                let menu = menu.separator().header("Sort By");
+                                SortMode::ALL
+                                    .into_iter()
+                                    .fold(menu, |menu, mode| {
+                                        let project_panel = project_panel.clone();
+                                        menu.toggleable_entry(
+                                            mode.label(),
+                                            sort_order.mode == mode,
+                                            IconPosition::Start,
+                                            Some(Box::new(SortBy { mode })),
+                                            move |window, cx| {
+                                                project_panel
+                                                    .update(cx, |project_panel, cx| {
+                                                        project_panel.sort_by(
+                                                            &SortBy { mode },
+                                                            window,
+                                                            cx,
+                                                        )
+                                                    })
+                                                    .ok();
+                                            },
+                                        )
+                                    })
+                                    .separator()
+                                    .toggleable_entry(
+                                        "Directories First",
+                                        sort_order.directories_first,
+                                        IconPosition::Start,
+                                        Some(Box::new(ToggleDirectoriesFirst)),
+                                        move |window, cx| {
+                                            project_panel
+                                                .update(cx, |project_panel, cx| {
+                                                    project_panel.toggle_directories_first(
+                                                        &ToggleDirectoriesFirst,
+                                                        window,
+                                                        cx,
+                                                    )
+                                                })
+                                                .ok();
+                                        },
+                                    )
                             })
                     }
                 })
@@ -858,7 +1046,8 @@ impl ProjectPanel {
                     return;
                 }
             }
-            if entry.is_dir() {
+            let is_dir = entry.is_dir();
+            if is_dir || self.nested_files.children.contains_key(&entry.id) {
                 let worktree_id = worktree.id();
                 let entry_id = entry.id;
                 let expanded_dir_ids =
@@ -871,9 +1060,11 @@ impl ProjectPanel {
                 match expanded_dir_ids.binary_search(&entry_id) {
                     Ok(_) => self.select_next(&SelectNext, window, cx),
                     Err(ix) => {
-                        self.project.update(cx, |project, cx| {
-                            project.expand_entry(worktree_id, entry_id, cx);
-                        });
+                        if is_dir {
+                            self.project.update(cx, |project, cx| {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            });
+                        }
 
                         expanded_dir_ids.insert(ix, entry_id);
                         self.update_visible_entries(None, cx);
@@ -913,7 +1104,10 @@ impl ProjectPanel {
                 return;
             };
 
-        let mut entry = &entry;
+        let mut entry = match self.nested_files.parents.get(&entry.id) {
+            Some(parent_id) => worktree.entry_for_id(*parent_id).unwrap_or(&entry),
+            None => &entry,
+        };
         loop {
             let entry_id = entry.id;
             match expanded_dir_ids.binary_search(&entry_id) {
@@ -950,6 +1144,113 @@ impl ProjectPanel {
         cx.notify();
     }
 
+    fn toggle_filter(&mut self, _: &ToggleFilter, window: &mut Window, cx: &mut Context<Self>) {
+        if self.show_filter {
+            self.hide_filter(window, cx);
+        } else {
+            self.show_filter = true;
+            window.focus(&self.filter_editor.focus_handle(cx));
+            cx.notify();
+        }
+    }
+
+    fn hide_filter(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.show_filter = false;
+        self.filter_editor
+            .update(cx, |editor, cx| editor.clear(window, cx));
+        // Keep the entry selected while filtering visible once all entries are shown again.
+        let selected_entry = self
+            .selection
+            .map(|selection| (selection.worktree_id, selection.entry_id));
+        if let Some((worktree_id, entry_id)) = selected_entry {
+            self.expand_entry(worktree_id, entry_id, cx);
+        }
+        self.update_visible_entries(selected_entry, cx);
+        self.autoscroll(cx);
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    fn filter_query(&self, cx: &App) -> Option<String> {
+        let query = self.filter_editor.read(cx).text(cx);
+        let query = query.trim();
+        if query.is_empty() {
+            None
+        } else {
+            Some(query.to_string())
+        }
+    }
+
+    fn update_filter(&mut self, cx: &mut Context<Self>) {
+        self.update_visible_entries(None, cx);
+        let is_selection_visible = self
+            .selection
+            .is_some_and(|selection| self.index_for_selection(selection).is_some());
+        // The selection moves to the first match once the matches are computed.
+        if !is_selection_visible && self.filter_query(cx).is_some() && !self.is_filter_pending() {
+            self.selection = self
+                .visible_entries
+                .iter()
+                .find_map(|(worktree_id, entries, _)| {
+                    let entry = entries.iter().find(|entry| entry.is_file())?;
+                    Some(SelectedEntry {
+                        worktree_id: *worktree_id,
+                        entry_id: entry.id,
+                    })
+                });
+        }
+        self.autoscroll(cx);
+        cx.notify();
+    }
+
+    fn is_filter_pending(&self) -> bool {
+        self.pending_filter.as_ref().is_some_and(|pending_filter| {
+            pending_filter
+                .requests
+                .iter()
+                .any(|(worktree_id, request)| {
+                    self.filtered_paths
+                        .get(worktree_id)
+                        .is_none_or(|filtered_paths| filtered_paths.request != *request)
+                })
+        })
+    }
+
+    fn sort_by(&mut self, action: &SortBy, _: &mut Window, cx: &mut Context<Self>) {
+        self.set_sort_order(
+            SortOrder {
+                mode: action.mode,
+                ..self.sort_order
+            },
+            cx,
+        );
+    }
+
+    fn toggle_directories_first(
+        &mut self,
+        _: &ToggleDirectoriesFirst,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_sort_order(
+            SortOrder {
+                directories_first: !self.sort_order.directories_first,
+                ..self.sort_order
+            },
+            cx,
+        );
+    }
+
+    fn set_sort_order(&mut self, sort_order: SortOrder, cx: &mut Context<Self>) {
+        if self.sort_order != sort_order {
+            self.sort_order = sort_order;
+            self.serialize(cx);
+            self.update_visible_entries(None, cx);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
     fn toggle_expanded(
         &mut self,
         entry_id: ProjectEntryId,
@@ -957,6 +1258,7 @@ impl ProjectPanel {
         cx: &mut Context<Self>,
     ) {
         if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) {
+            let has_nested_files = self.nested_files.children.contains_key(&entry_id);
             if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
                 self.project.update(cx, |project, cx| {
                     match expanded_dir_ids.binary_search(&entry_id) {
@@ -964,7 +1266,9 @@ impl ProjectPanel {
                             expanded_dir_ids.remove(ix);
                         }
                         Err(ix) => {
-                            project.expand_entry(worktree_id, entry_id, cx);
+                            if !has_nested_files {
+                                project.expand_entry(worktree_id, entry_id, cx);
+                            }
                             expanded_dir_ids.insert(ix, entry_id);
                         }
                     }
@@ -1114,6 +1418,10 @@ impl ProjectPanel {
     }
 
     fn confirm(&mut self, _: &Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        if self.filter_editor.focus_handle(cx).is_focused(window) {
+            window.focus(&self.focus_handle);
+            return;
+        }
         if let Some(task) = self.confirm_edit(window, cx) {
             task.detach_and_notify_err(window, cx);
         }
@@ -1342,6 +1650,10 @@ impl ProjectPanel {
     }
 
     fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        if self.show_filter && self.edit_state.is_none() {
+            self.hide_filter(window, cx);
+            return;
+        }
         let previous_edit_state = self.edit_state.take();
         self.update_visible_entries(None, cx);
         self.marked_entries.clear();
@@ -2411,6 +2723,56 @@ impl ProjectPanel {
         }
     }
 
+    fn compare_marked_files(
+        &mut self,
+        _: &CompareMarkedFiles,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((old_path, new_path)) = self.marked_files_to_compare(cx) else {
+            return;
+        };
+        let (old_buffer, new_buffer) = self.project.update(cx, |project, cx| {
+            (
+                project.open_buffer(old_path, cx),
+                project.open_buffer(new_path, cx),
+            )
+        });
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            let old_buffer = old_buffer.await?;
+            let new_buffer = new_buffer.await?;
+            workspace.update_in(cx, |workspace, window, cx| {
+                TextDiffView::open_for_buffers(old_buffer, new_buffer, workspace, window, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to compare files", window, cx, |_, _, _| None);
+    }
+
+    /// The two files to compare when exactly two files are marked, or when one file is marked
+    /// and another one is selected. The selected file is compared against the other one.
+    fn marked_files_to_compare(&self, cx: &App) -> Option<(ProjectPath, ProjectPath)> {
+        let selection = self.selection?;
+        let mut entries = self.marked_entries.clone();
+        entries.insert(selection);
+        if entries.len() != 2 {
+            return None;
+        }
+        entries.remove(&selection);
+        let other = entries.into_iter().next()?;
+
+        let project = self.project.read(cx);
+        let file_path = |entry: SelectedEntry| {
+            let worktree = project.worktree_for_id(entry.worktree_id, cx)?;
+            let entry = worktree.read(cx).entry_for_id(entry.entry_id)?;
+            entry.is_file().then(|| ProjectPath {
+                worktree_id: worktree.read(cx).id(),
+                path: entry.path.clone(),
+            })
+        };
+        Some((file_path(other)?, file_path(selection)?))
+    }
+
     pub fn new_search_in_directory(
         &mut self,
         _: &NewSearchInDirectory,
@@ -2708,7 +3070,10 @@ impl ProjectPanel {
         cx: &mut Context<Self>,
     ) {
         let settings = ProjectPanelSettings::get_global(cx);
-        let auto_collapse_dirs = settings.auto_fold_dirs;
+        let filter_query = self.filter_query(cx);
+        // Directories aren't folded while filtering, as the directories they're folded into may
+        // not match the query.
+        let auto_collapse_dirs = settings.auto_fold_dirs && filter_query.is_none();
         let hide_gitignore = settings.hide_gitignore;
         let project = self.project.read(cx);
         let repo_snapshots = project.git_store().read(cx).repo_snapshots(cx);
@@ -2719,8 +3084,10 @@ impl ProjectPanel {
             .map(|entry| entry.id);
 
         let old_ancestors = std::mem::take(&mut self.ancestors);
+        self.nested_files = NestedFiles::default();
         self.visible_entries.clear();
         let mut max_width_item = None;
+        let mut stale_filters = Vec::new();
         for worktree in project.visible_worktrees(cx) {
             let worktree_snapshot = worktree.read(cx).snapshot();
             let worktree_id = worktree_snapshot.id();
@@ -2751,11 +3118,37 @@ impl ProjectPanel {
                 }
             }
 
+            // Until the paths matching the query are computed, the ones matching the previous
+            // query or worktree snapshot are shown.
+            let filtered_paths = filter_query.as_ref().map(|query| {
+                let request =
+                    FilterRequest::new(query.clone(), !hide_gitignore, &worktree_snapshot);
+                let filtered_paths = self.filtered_paths.get(&worktree_id);
+                if filtered_paths.is_none_or(|filtered_paths| filtered_paths.request != request) {
+                    stale_filters.push((worktree_snapshot.clone(), request));
+                }
+                match filtered_paths {
+                    Some(filtered_paths) => filtered_paths.paths.clone(),
+                    None => Arc::new(HashSet::from_iter(
+                        worktree_snapshot
+                            .root_entry()
+                            .map(|entry| entry.path.clone()),
+                    )),
+                }
+            });
+
             let mut visible_worktree_entries = Vec::new();
             let mut entry_iter =
                 GitTraversal::new(&repo_snapshots, worktree_snapshot.entries(true, 0));
             let mut auto_folded_ancestors = vec![];
             while let Some(entry) = entry_iter.entry() {
+                if filtered_paths
+                    .as_ref()
+                    .is_some_and(|paths| !paths.contains(&entry.path))
+                {
+                    entry_iter.advance_to_sibling();
+                    continue;
+                }
                 if auto_collapse_dirs && entry.kind.is_dir() {
                     auto_folded_ancestors.push(entry.id);
                     if !self.unfolded_dir_ids.contains(&entry.id) {
@@ -2901,7 +3294,9 @@ impl ProjectPanel {
                     }
                 }
 
-                if expanded_dir_ids.binary_search(&entry.id).is_err()
+                // While filtering, all directories with matching entries are expanded.
+                if filtered_paths.is_none()
+                    && expanded_dir_ids.binary_search(&entry.id).is_err()
                     && entry_iter.advance_to_sibling()
                 {
                     continue;
@@ -2909,7 +3304,15 @@ impl ProjectPanel {
                 entry_iter.advance();
             }
 
-            project::sort_worktree_entries(&mut visible_worktree_entries);
+            let mut visible_worktree_entries =
+                self.sort_order.sort_entries(visible_worktree_entries);
+            if let Some(file_nesting) = &self.file_nesting {
+                visible_worktree_entries = file_nesting.nest_entries(
+                    visible_worktree_entries,
+                    expanded_dir_ids,
+                    &mut self.nested_files,
+                );
+            }
 
             self.visible_entries
                 .push((worktree_id, visible_worktree_entries, OnceCell::new()));
@@ -2937,6 +3340,64 @@ impl ProjectPanel {
                 entry_id,
             });
         }
+
+        if filter_query.is_some() {
+            self.schedule_filter(stale_filters, cx);
+        } else {
+            self.filtered_paths.clear();
+            self.pending_filter = None;
+        }
+    }
+
+    /// Computes the paths matching the filter query in the given worktrees in the background,
+    /// replacing any computation for an older query or worktree snapshot.
+    fn schedule_filter(
+        &mut self,
+        stale_filters: Vec<(worktree::Snapshot, FilterRequest)>,
+        cx: &mut Context<Self>,
+    ) {
+        let requests = stale_filters
+            .iter()
+            .map(|(snapshot, request)| (snapshot.id(), request.clone()))
+            .collect::<Vec<_>>();
+        if requests.is_empty()
+            || self
+                .pending_filter
+                .as_ref()
+                .is_some_and(|pending_filter| pending_filter.requests == requests)
+        {
+            return;
+        }
+
+        let task = cx.spawn(async move |this, cx| {
+            let filtered_paths = cx
+                .background_spawn(async move {
+                    stale_filters
+                        .into_iter()
+                        .map(|(snapshot, request)| {
+                            let paths =
+                                filtered_paths(&request.query, &snapshot, request.include_ignored);
+                            (
+                                snapshot.id(),
+                                FilteredPaths {
+                                    request,
+                                    paths: Arc::new(paths),
+                                },
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.filtered_paths.extend(filtered_paths);
+                this.update_filter(cx);
+            })
+            .ok();
+        });
+        self.pending_filter = Some(PendingFilter {
+            requests,
+            _task: task,
+        });
     }
 
     fn expand_entry(
@@ -2953,6 +3414,12 @@ impl ProjectPanel {
                 project.expand_entry(worktree_id, entry_id, cx);
                 let worktree = worktree.read(cx);
 
+                if let Some(parent_id) = self.nested_files.parents.get(&entry_id) {
+                    if let Err(ix) = expanded_dir_ids.binary_search(parent_id) {
+                        expanded_dir_ids.insert(ix, *parent_id);
+                    }
+                }
+
                 if let Some(mut entry) = worktree.entry_for_id(entry_id) {
                     loop {
                         if let Err(ix) = expanded_dir_ids.binary_search(&entry.id) {
@@ -3254,8 +3721,7 @@ impl ProjectPanel {
                         }
                     };
 
-                    let (depth, difference) =
-                        ProjectPanel::calculate_depth_and_difference(&entry, entries);
+                    let (depth, difference) = self.depth_and_difference(&entry, entries);
 
                     let filename = match difference {
                         diff if diff > 1 => entry
@@ -3279,10 +3745,15 @@ impl ProjectPanel {
 
                     let is_marked = self.marked_entries.contains(&selection);
 
-                    let diagnostic_severity = self
-                        .diagnostics
-                        .get(&(*worktree_id, entry.path.to_path_buf()))
-                        .cloned();
+                    let nested_file_paths = self.nested_files.children.get(&entry.id);
+                    let diagnostic_severity = iter::once(&entry.path)
+                        .chain(nested_file_paths.into_iter().flatten())
+                        .filter_map(|path| {
+                            self.diagnostics
+                                .get(&(*worktree_id, path.to_path_buf()))
+                                .copied()
+                        })
+                        .min();
 
                     let filename_text_color =
                         entry_git_aware_label_color(status, entry.is_ignored, is_marked);
@@ -3295,6 +3766,7 @@ impl ProjectPanel {
                         kind: entry.kind,
                         is_ignored: entry.is_ignored,
                         is_expanded,
+                        has_nested_files: nested_file_paths.is_some(),
                         is_selected: self.selection == Some(selection),
                         is_marked,
                         is_editing: false,
@@ -3623,6 +4095,22 @@ impl ProjectPanel {
         last_found
     }
 
+    /// The depth of the entry in the panel, taking nested files into account, and the number of
+    /// path components it has beyond its parent's.
+    fn depth_and_difference(
+        &self,
+        entry: &Entry,
+        visible_worktree_entries: &HashSet<Arc<Path>>,
+    ) -> (usize, usize) {
+        let (depth, difference) =
+            Self::calculate_depth_and_difference(entry, visible_worktree_entries);
+        if self.nested_files.parents.contains_key(&entry.id) {
+            (depth + 1, difference)
+        } else {
+            (depth, difference)
+        }
+    }
+
     fn calculate_depth_and_difference(
         entry: &Entry,
         visible_worktree_entries: &HashSet<Arc<Path>>,
@@ -3696,6 +4184,7 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let nested_files_toggle = details.has_nested_files.then_some(details.is_expanded);
         let selections = Arc::new(self.marked_entries.clone());
         let is_local = self.project.read(cx).is_local();
 
@@ -3754,6 +4243,12 @@ impl ProjectPanel {
 
         div()
             .id(entry_id.to_proto() as usize)
+            .role(AccessibilityRole::TreeItem)
+            .accessible_name(file_name.clone())
+            .accessible_selected(is_active || is_marked)
+            .when(kind.is_dir() || details.has_nested_files, |div| {
+                div.accessible_expanded(details.is_expanded)
+            })
             .group(GROUP_NAME)
             .cursor_pointer()
             .rounded_none()
@@ -3968,6 +4463,13 @@ impl ProjectPanel {
                 ListItem::new(entry_id.to_proto() as usize)
                     .indent_level(depth)
                     .indent_step_size(px(settings.indent_size))
+                    .when_some(nested_files_toggle, |this, is_expanded| {
+                        this.toggle(is_expanded).on_toggle(cx.listener(
+                            move |this, _, window, cx| {
+                                this.toggle_expanded(entry_id, window, cx);
+                            },
+                        ))
+                    })
                     .spacing(match settings.entry_spacing {
                         project_panel_settings::EntrySpacing::Comfortable => ListItemSpacing::Dense,
                         project_panel_settings::EntrySpacing::Standard => {
@@ -4357,12 +4859,31 @@ impl ProjectPanel {
         )
     }
 
+    fn render_filter_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .flex_none()
+            .w_full()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Icon::new(IconName::Filter)
+                    .size(IconSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(self.filter_editor.clone())
+    }
+
     fn dispatch_context(&self, window: &Window, cx: &Context<Self>) -> KeyContext {
         let mut dispatch_context = KeyContext::new_with_defaults();
         dispatch_context.add("ProjectPanel");
         dispatch_context.add("menu");
 
-        let identifier = if self.filename_editor.focus_handle(cx).is_focused(window) {
+        let identifier = if self.filename_editor.focus_handle(cx).is_focused(window)
+            || self.filter_editor.focus_handle(cx).is_focused(window)
+        {
             "editing"
         } else {
             "not_editing"
@@ -4456,20 +4977,24 @@ impl ProjectPanel {
         let (worktree, entry) = self.selected_entry(cx)?;
 
         // Find the parent entry of the indent guide, this will either be the
-        // expanded folder we have selected, or the parent of the currently
-        // selected file/collapsed directory
+        // expanded folder or file with nested files we have selected, or the
+        // parent of the currently selected file/collapsed directory
         let mut entry = entry;
         loop {
-            let is_expanded_dir = entry.is_dir()
+            let is_expanded = (entry.is_dir()
+                || self.nested_files.children.contains_key(&entry.id))
                 && self
                     .expanded_dir_ids
                     .get(&worktree.id())
                     .map(|ids| ids.binary_search(&entry.id).is_ok())
                     .unwrap_or(false);
-            if is_expanded_dir {
+            if is_expanded {
                 break;
             }
-            entry = worktree.entry_for_path(&entry.path.parent()?)?;
+            entry = match self.nested_files.parents.get(&entry.id) {
+                Some(parent_id) => worktree.entry_for_id(*parent_id)?,
+                None => worktree.entry_for_path(&entry.path.parent()?)?,
+            };
         }
 
         let (active_indent_range, depth) = {
@@ -4477,8 +5002,13 @@ impl ProjectPanel {
             let child_paths = &self.visible_entries[worktree_ix].1;
             let mut child_count = 0;
             let depth = entry.path.ancestors().count();
-            while let Some(entry) = child_paths.get(child_offset + child_count + 1) {
-                if entry.path.ancestors().count() <= depth {
+            while let Some(child) = child_paths.get(child_offset + child_count + 1) {
+                let is_child = if entry.is_dir() {
+                    child.path.ancestors().count() > depth
+                } else {
+                    self.nested_files.parents.get(&child.id) == Some(&entry.id)
+                };
+                if !is_child {
                     break;
                 }
                 child_count += 1;
@@ -4492,7 +5022,7 @@ impl ProjectPanel {
                 paths.get_or_init(|| entries.iter().map(|e| (e.path.clone())).collect());
 
             // Calculate the actual depth of the entry, taking into account that directories can be auto-folded.
-            let (depth, _) = Self::calculate_depth_and_difference(entry, visible_worktree_entries);
+            let (depth, _) = self.depth_and_difference(entry, visible_worktree_entries);
             (start..end, depth)
         };
 
@@ -4522,6 +5052,76 @@ fn item_width_estimate(depth: usize, item_text_chars: usize, is_symlink: bool) -
     item_width
 }
 
+fn file_nesting_rules(settings: &ProjectPanelSettings) -> Option<FileNestingRules> {
+    settings
+        .file_nesting
+        .enabled
+        .then(|| FileNestingRules::new(&settings.file_nesting.patterns))
+}
+
+/// Returns the paths of the worktree's entries matching the filter query, along with the paths
+/// of their ancestors. Queries that are valid globs with wildcards are matched against the
+/// entries' paths and names, and other queries are matched fuzzily against their paths.
+fn filtered_paths(
+    query: &str,
+    worktree: &worktree::Snapshot,
+    include_ignored: bool,
+) -> HashSet<Arc<Path>> {
+    let entries = worktree
+        .entries(include_ignored, 0)
+        .filter(|entry| !entry.path.as_os_str().is_empty());
+    let glob = if query.contains(['*', '?', '[', '{']) {
+        PathMatcher::new([query]).ok()
+    } else {
+        None
+    };
+    let matching_paths = match glob {
+        Some(glob) => entries
+            .filter(|entry| {
+                glob.is_match(&entry.path)
+                    || entry
+                        .path
+                        .file_name()
+                        .is_some_and(|file_name| glob.is_match(file_name))
+            })
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>(),
+        None => {
+            let candidates = entries
+                .map(|entry| PathMatchCandidate {
+                    is_dir: entry.is_dir(),
+                    path: &entry.path,
+                    char_bag: entry.char_bag,
+                })
+                .collect();
+            fuzzy::match_fixed_path_set(
+                candidates,
+                worktree.id().to_usize(),
+                query,
+                true,
+                usize::MAX,
+            )
+            .into_iter()
+            .map(|path_match| path_match.path)
+            .collect()
+        }
+    };
+
+    let mut paths = HashSet::default();
+    if let Some(root_entry) = worktree.root_entry() {
+        paths.insert(root_entry.path.clone());
+    }
+    for path in matching_paths {
+        for ancestor in path.ancestors() {
+            // The ancestors of paths already in the set are in it too.
+            if !paths.insert(Arc::from(ancestor)) {
+                break;
+            }
+        }
+    }
+    paths
+}
+
 impl Render for ProjectPanel {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let has_worktree = !self.visible_entries.is_empty();
@@ -4595,7 +5195,7 @@ impl Render for ProjectPanel {
                     }
                 }));
             }
-            h_flex()
+            v_flex()
                 .id("project-panel")
                 .group("project-panel")
                 .on_drag_move(cx.listener(handle_drag_move_scroll::<ExternalPaths>))
@@ -4641,6 +5241,10 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
                 .on_action(cx.listener(Self::remove_from_project))
+                .on_action(cx.listener(Self::compare_marked_files))
+                .on_action(cx.listener(Self::toggle_filter))
+                .on_action(cx.listener(Self::sort_by))
+                .on_action(cx.listener(Self::toggle_directories_first))
                 .when(!project.is_read_only(cx), |el| {
                     el.on_action(cx.listener(Self::new_file))
                         .on_action(cx.listener(Self::new_directory))
@@ -4693,127 +5297,156 @@ impl Render for ProjectPanel {
                     }),
                 )
                 .track_focus(&self.focus_handle(cx))
+                .when(self.show_filter, |this| {
+                    this.child(self.render_filter_bar(cx))
+                })
+                // The list and its scrollbar are laid out below the filter bar.
                 .child(
-                    uniform_list(cx.entity().clone(), "entries", item_count, {
-                        |this, range, window, cx| {
-                            let mut items = Vec::with_capacity(range.end - range.start);
-                            this.for_each_visible_entry(
-                                range,
-                                window,
-                                cx,
-                                |id, details, window, cx| {
-                                    items.push(this.render_entry(id, details, window, cx));
-                                },
-                            );
-                            items
-                        }
-                    })
-                    .when(show_indent_guides, |list| {
-                        list.with_decoration(
-                            ui::indent_guides(
-                                cx.entity().clone(),
-                                px(indent_size),
-                                IndentGuideColors::panel(cx),
+                    div()
+                        .relative()
+                        .flex_1()
+                        .min_h_0()
+                        .w_full()
+                        .child(
+                            uniform_list(cx.entity().clone(), "entries", item_count, {
                                 |this, range, window, cx| {
-                                    let mut items =
-                                        SmallVec::with_capacity(range.end - range.start);
-                                    this.iter_visible_entries(
+                                    let mut items = Vec::with_capacity(range.end - range.start);
+                                    this.for_each_visible_entry(
                                         range,
                                         window,
                                         cx,
-                                        |entry, entries, _, _| {
-                                            let (depth, _) = Self::calculate_depth_and_difference(
-                                                entry, entries,
-                                            );
-                                            items.push(depth);
+                                        |id, details, window, cx| {
+                                            items.push(this.render_entry(id, details, window, cx));
                                         },
                                     );
                                     items
-                                },
-                            )
-                            .on_click(cx.listener(
-                                |this, active_indent_guide: &IndentGuideLayout, window, cx| {
-                                    if window.modifiers().secondary() {
-                                        let ix = active_indent_guide.offset.y;
-                                        let Some((target_entry, worktree)) = maybe!({
-                                            let (worktree_id, entry) = this.entry_at_index(ix)?;
-                                            let worktree = this
-                                                .project
-                                                .read(cx)
-                                                .worktree_for_id(worktree_id, cx)?;
-                                            let target_entry = worktree
-                                                .read(cx)
-                                                .entry_for_path(&entry.path.parent()?)?;
-                                            Some((target_entry, worktree))
-                                        }) else {
-                                            return;
-                                        };
-
-                                        this.collapse_entry(target_entry.clone(), worktree, cx);
-                                    }
-                                },
-                            ))
-                            .with_render_fn(
-                                cx.entity().clone(),
-                                move |this, params, _, cx| {
-                                    const LEFT_OFFSET: Pixels = px(14.);
-                                    const PADDING_Y: Pixels = px(4.);
-                                    const HITBOX_OVERDRAW: Pixels = px(3.);
-
-                                    let active_indent_guide_index =
-                                        this.find_active_indent_guide(&params.indent_guides, cx);
-
-                                    let indent_size = params.indent_size;
-                                    let item_height = params.item_height;
-
-                                    params
-                                        .indent_guides
-                                        .into_iter()
-                                        .enumerate()
-                                        .map(|(idx, layout)| {
-                                            let offset = if layout.continues_offscreen {
-                                                px(0.)
-                                            } else {
-                                                PADDING_Y
-                                            };
-                                            let bounds = Bounds::new(
-                                                point(
-                                                    layout.offset.x * indent_size + LEFT_OFFSET,
-                                                    layout.offset.y * item_height + offset,
-                                                ),
-                                                size(
-                                                    px(1.),
-                                                    layout.length * item_height - offset * 2.,
-                                                ),
+                                }
+                            })
+                            .role(AccessibilityRole::Tree)
+                            .accessible_name("Project")
+                            .when(show_indent_guides, |list| {
+                                list.with_decoration(
+                                    ui::indent_guides(
+                                        cx.entity().clone(),
+                                        px(indent_size),
+                                        IndentGuideColors::panel(cx),
+                                        |this, range, window, cx| {
+                                            let mut items =
+                                                SmallVec::with_capacity(range.end - range.start);
+                                            this.iter_visible_entries(
+                                                range,
+                                                window,
+                                                cx,
+                                                |entry, entries, _, _| {
+                                                    let (depth, _) =
+                                                        this.depth_and_difference(entry, entries);
+                                                    items.push(depth);
+                                                },
                                             );
-                                            ui::RenderedIndentGuide {
-                                                bounds,
-                                                layout,
-                                                is_active: Some(idx) == active_indent_guide_index,
-                                                hitbox: Some(Bounds::new(
-                                                    point(
-                                                        bounds.origin.x - HITBOX_OVERDRAW,
-                                                        bounds.origin.y,
-                                                    ),
-                                                    size(
-                                                        bounds.size.width + HITBOX_OVERDRAW * 2.,
-                                                        bounds.size.height,
-                                                    ),
-                                                )),
+                                            items
+                                        },
+                                    )
+                                    .on_click(cx.listener(
+                                        |this,
+                                         active_indent_guide: &IndentGuideLayout,
+                                         window,
+                                         cx| {
+                                            if window.modifiers().secondary() {
+                                                let ix = active_indent_guide.offset.y;
+                                                let Some((target_entry, worktree)) = maybe!({
+                                                    let (worktree_id, entry) =
+                                                        this.entry_at_index(ix)?;
+                                                    let worktree = this
+                                                        .project
+                                                        .read(cx)
+                                                        .worktree_for_id(worktree_id, cx)?;
+                                                    let target_entry = worktree
+                                                        .read(cx)
+                                                        .entry_for_path(&entry.path.parent()?)?;
+                                                    Some((target_entry, worktree))
+                                                }) else {
+                                                    return;
+                                                };
+
+                                                this.collapse_entry(
+                                                    target_entry.clone(),
+                                                    worktree,
+                                                    cx,
+                                                );
                                             }
-                                        })
-                                        .collect()
-                                },
-                            ),
+                                        },
+                                    ))
+                                    .with_render_fn(
+                                        cx.entity().clone(),
+                                        move |this, params, _, cx| {
+                                            const LEFT_OFFSET: Pixels = px(14.);
+                                            const PADDING_Y: Pixels = px(4.);
+                                            const HITBOX_OVERDRAW: Pixels = px(3.);
+
+                                            let active_indent_guide_index = this
+                                                .find_active_indent_guide(
+                                                    &params.indent_guides,
+                                                    cx,
+                                                );
+
+                                            let indent_size = params.indent_size;
+                                            let item_height = params.item_height;
+
+                                            params
+                                                .indent_guides
+                                                .into_iter()
+                                                .enumerate()
+                                                .map(|(idx, layout)| {
+                                                    let offset = if layout.continues_offscreen {
+                                                        px(0.)
+                                                    } else {
+                                                        PADDING_Y
+                                                    };
+                                                    let bounds = Bounds::new(
+                                                        point(
+                                                            layout.offset.x * indent_size
+                                                                + LEFT_OFFSET,
+                                                            layout.offset.y * item_height + offset,
+                                                        ),
+                                                        size(
+                                                            px(1.),
+                                                            layout.length * item_height
+                                                                - offset * 2.,
+                                                        ),
+                                                    );
+                                                    ui::RenderedIndentGuide {
+                                                        bounds,
+                                                        layout,
+                                                        is_active: Some(idx)
+                                                            == active_indent_guide_index,
+                                                        hitbox: Some(Bounds::new(
+                                                            point(
+                                                                bounds.origin.x - HITBOX_OVERDRAW,
+                                                                bounds.origin.y,
+                                                            ),
+                                                            size(
+                                                                bounds.size.width
+                                                                    + HITBOX_OVERDRAW * 2.,
+                                                                bounds.size.height,
+                                                            ),
+                                                        )),
+                                                    }
+                                                })
+                                                .collect()
+                                        },
+                                    ),
+                                )
+                            })
+                            .size_full()
+                            .with_sizing_behavior(ListSizingBehavior::Infer)
+                            .with_horizontal_sizing_behavior(
+                                ListHorizontalSizingBehavior::Unconstrained,
+                            )
+                            .with_width_from_item(self.max_width_item_index)
+                            .track_scroll(self.scroll_handle.clone()),
                         )
-                    })
-                    .size_full()
-                    .with_sizing_behavior(ListSizingBehavior::Infer)
-                    .with_horizontal_sizing_behavior(ListHorizontalSizingBehavior::Unconstrained)
-                    .with_width_from_item(self.max_width_item_index)
-                    .track_scroll(self.scroll_handle.clone()),
+                        .children(self.render_vertical_scrollbar(cx)),
                 )
-                .children(self.render_vertical_scrollbar(cx))
                 .when_some(self.render_horizontal_scrollbar(cx), |this, scrollbar| {
                     this.pb_4().child(scrollbar)
                 })
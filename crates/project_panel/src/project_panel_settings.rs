@@ -3,6 +3,7 @@ use gpui::Pixels;
 use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
+use std::collections::BTreeMap;
 
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -28,7 +29,7 @@ pub enum EntrySpacing {
     Standard,
 }
 
-#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct ProjectPanelSettings {
     pub button: bool,
     pub hide_gitignore: bool,
@@ -42,6 +43,7 @@ pub struct ProjectPanelSettings {
     pub indent_guides: IndentGuidesSettings,
     pub auto_reveal_entries: bool,
     pub auto_fold_dirs: bool,
+    pub file_nesting: FileNestingSettings,
     pub scrollbar: ScrollbarSettings,
     pub show_diagnostics: ShowDiagnostics,
 }
@@ -57,6 +59,27 @@ pub struct IndentGuidesSettingsContent {
     pub show: Option<ShowIndentGuides>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettings {
+    pub enabled: bool,
+    pub patterns: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct FileNestingSettingsContent {
+    /// Whether to show related files nested under another file.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// Patterns for the names of files that other files get nested under, mapped to patterns
+    /// for the names of the files nested under them. A `*` matches any text, and the text
+    /// matched by the first `*` of a parent pattern can be used as `${capture}` in the patterns
+    /// of the nested files.
+    ///
+    /// For example, `{ "*.ts": ["${capture}.test.ts"] }` nests `foo.test.ts` under `foo.ts`.
+    pub patterns: Option<BTreeMap<String, Vec<String>>>,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct ScrollbarSettings {
     /// When to show the scrollbar in the project panel.
@@ -137,6 +160,8 @@ pub struct ProjectPanelSettingsContent {
     ///
     /// Default: true
     pub auto_fold_dirs: Option<bool>,
+    /// Settings related to nesting files under related files in the project panel.
+    pub file_nesting: Option<FileNestingSettingsContent>,
     /// Scrollbar-related settings
     pub scrollbar: Option<ScrollbarSettingsContent>,
     /// Which files containing diagnostic errors/warnings to mark in the project panel.
@@ -318,7 +318,7 @@ async fn test_auto_collapse_dir_paths(cx: &mut gpui::TestAppContext) {
     let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -1726,6 +1726,399 @@ async fn test_copy_paste_nested_and_root_entries(cx: &mut gpui::TestAppContext)
     );
 }
 
+#[gpui::test]
+async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "src": {
+                "bar.ts": "",
+                "foo.d.ts": "",
+                "foo.test.ts": "",
+                "foo.ts": "",
+            },
+            "Cargo.lock": "",
+            "Cargo.toml": "",
+            "README.md": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    cx.update(|_, cx| {
+        let settings = ProjectPanelSettings::get_global(cx).clone();
+        ProjectPanelSettings::override_global(
+            ProjectPanelSettings {
+                file_nesting: project_panel_settings::FileNestingSettings {
+                    enabled: true,
+                    patterns: [
+                        (
+                            "*.ts".to_string(),
+                            vec![
+                                "${capture}.test.ts".to_string(),
+                                "${capture}.d.ts".to_string(),
+                            ],
+                        ),
+                        ("Cargo.toml".to_string(), vec!["Cargo.lock".to_string()]),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+                ..settings
+            },
+            cx,
+        );
+    });
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    toggle_expand_dir(&panel, "root/src", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src  <== selected",
+            "          bar.ts",
+            "        > foo.ts",
+            "    > Cargo.toml",
+            "      README.md",
+        ]
+    );
+
+    select_path(&panel, "root/src/foo.ts", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.expand_selected_entry(&ExpandSelectedEntry, window, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "          bar.ts",
+            "        v foo.ts  <== selected",
+            "              foo.d.ts",
+            "              foo.test.ts",
+            "    > Cargo.toml",
+            "      README.md",
+        ]
+    );
+
+    select_path(&panel, "root/src/foo.test.ts", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.collapse_selected_entry(&CollapseSelectedEntry, window, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "          bar.ts",
+            "        > foo.ts  <== selected",
+            "    > Cargo.toml",
+            "      README.md",
+        ],
+        "Collapsing a nested file should collapse the file it's nested under"
+    );
+
+    let cargo_lock = find_project_entry(&panel, "root/Cargo.lock", cx).unwrap();
+    panel.update(cx, |panel, cx| {
+        panel.reveal_entry(project.clone(), cargo_lock, false, cx)
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "          bar.ts",
+            "        > foo.ts",
+            "    v Cargo.toml",
+            "          Cargo.lock  <== selected  <== marked",
+            "      README.md",
+        ],
+        "Revealing a nested file should expand the file it's nested under"
+    );
+}
+
+#[gpui::test]
+async fn test_filter_entries(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "docs": {
+                "guide.md": "",
+                "notes.txt": "",
+            },
+            "src": {
+                "utils": {
+                    "mod.rs": "",
+                },
+                "lib.rs": "",
+                "main.rs": "",
+            },
+            "build.rs": "",
+            "README.md": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    > docs",
+            "    > src",
+            "      build.rs",
+            "      README.md",
+        ]
+    );
+
+    set_filter_query(&panel, "*.rs", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v src",
+            "        v utils",
+            "              mod.rs  <== selected",
+            "          lib.rs",
+            "          main.rs",
+            "      build.rs",
+        ],
+        "Glob queries should show the matching entries along with their ancestors"
+    );
+
+    set_filter_query(&panel, "mai", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &["v root", "    v src", "          main.rs  <== selected"],
+        "Other queries should be matched fuzzily, selecting the first match when the selection is filtered out"
+    );
+
+    set_filter_query(&panel, "nothing matches", cx);
+    assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), &["v root"]);
+
+    // Matches are computed in the background, while the previous ones are still shown.
+    panel.update_in(cx, |panel, window, cx| {
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text("guide", window, cx));
+    });
+    assert!(panel.read_with(cx, |panel, _| panel.is_filter_pending()));
+    assert_eq!(visible_entries_as_strings(&panel, 0..10, cx), &["v root"]);
+    cx.run_until_parked();
+    assert!(!panel.read_with(cx, |panel, _| panel.is_filter_pending()));
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &["v root", "    v docs", "          guide.md  <== selected"]
+    );
+
+    set_filter_query(&panel, "mai", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.cancel(&menu::Cancel, window, cx);
+    });
+    cx.run_until_parked();
+    assert!(!panel.read_with(cx, |panel, _| panel.show_filter));
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    > docs",
+            "    v src",
+            "        > utils",
+            "          lib.rs",
+            "          main.rs  <== selected",
+            "      build.rs",
+            "      README.md",
+        ],
+        "Hiding the filter should show all entries, keeping the selected entry visible"
+    );
+}
+
+#[gpui::test]
+async fn test_sort_entries(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/root",
+        json!({
+            "alpha": {
+                "x.txt": "",
+            },
+            "zeta": {
+                "y.txt": "",
+            },
+            "2-notes.txt": "",
+            "10-notes.txt": "",
+            "a.md": "",
+            "b.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    > alpha",
+            "    > zeta",
+            "      2-notes.txt",
+            "      10-notes.txt",
+            "      a.md",
+            "      b.rs",
+        ]
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel.sort_by(
+            &SortBy {
+                mode: SortMode::Name,
+            },
+            window,
+            cx,
+        )
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    > alpha",
+            "    > zeta",
+            "      10-notes.txt",
+            "      2-notes.txt",
+            "      a.md",
+            "      b.rs",
+        ]
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel.toggle_directories_first(&ToggleDirectoriesFirst, window, cx)
+    });
+    toggle_expand_dir(&panel, "root/alpha", cx);
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "      10-notes.txt",
+            "      2-notes.txt",
+            "      a.md",
+            "    v alpha  <== selected",
+            "          x.txt",
+            "      b.rs",
+            "    > zeta",
+        ],
+        "Directories should be mixed in with files, followed by their contents"
+    );
+
+    panel.update_in(cx, |panel, window, cx| {
+        panel.sort_by(
+            &SortBy {
+                mode: SortMode::Extension,
+            },
+            window,
+            cx,
+        )
+    });
+    assert_eq!(
+        visible_entries_as_strings(&panel, 0..10, cx),
+        &[
+            "v root",
+            "    v alpha  <== selected",
+            "          x.txt",
+            "    > zeta",
+            "      a.md",
+            "      b.rs",
+            "      2-notes.txt",
+            "      10-notes.txt",
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_compare_marked_files(cx: &mut gpui::TestAppContext) {
+    init_test_with_editor(cx);
+
+    let fs = FakeFs::new(cx.executor().clone());
+    fs.insert_tree(
+        "/test",
+        json!({
+            "dir1": {},
+            "a.txt": "one\ntwo\n",
+            "b.txt": "one\n2\n",
+            "c.txt": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+    let workspace = cx.add_window(|window, cx| Workspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let panel = workspace.update(cx, ProjectPanel::new).unwrap();
+
+    select_path_with_mark(&panel, "test/a.txt", cx);
+    panel.update(cx, |panel, cx| {
+        assert_eq!(panel.marked_files_to_compare(cx), None);
+    });
+
+    select_path_with_mark(&panel, "test/dir1", cx);
+    panel.update(cx, |panel, cx| {
+        assert_eq!(
+            panel.marked_files_to_compare(cx),
+            None,
+            "Directories can't be compared"
+        );
+        panel.marked_entries.clear();
+    });
+
+    select_path_with_mark(&panel, "test/a.txt", cx);
+    select_path_with_mark(&panel, "test/b.txt", cx);
+    select_path_with_mark(&panel, "test/c.txt", cx);
+    panel.update(cx, |panel, cx| {
+        assert_eq!(
+            panel.marked_files_to_compare(cx),
+            None,
+            "Only two files can be compared"
+        );
+        panel.marked_entries.clear();
+    });
+
+    select_path_with_mark(&panel, "test/a.txt", cx);
+    select_path(&panel, "test/b.txt", cx);
+    panel.update_in(cx, |panel, window, cx| {
+        panel.compare_marked_files(&CompareMarkedFiles, window, cx)
+    });
+    cx.executor().run_until_parked();
+
+    let active_item = workspace
+        .update(cx, |workspace, _, cx| workspace.active_item(cx))
+        .unwrap()
+        .unwrap();
+    let view = active_item
+        .downcast::<TextDiffView>()
+        .expect("Compared files should be shown in a diff view");
+    assert_eq!(
+        cx.update(|window, cx| view.read(cx).tab_content_text(window, cx)),
+        Some("a.txt ↔ b.txt".into())
+    );
+}
+
 #[gpui::test]
 async fn test_remove_opened_file(cx: &mut gpui::TestAppContext) {
     init_test_with_editor(cx);
@@ -3833,7 +4226,7 @@ async fn test_deletion_gitignored(cx: &mut gpui::TestAppContext) {
 
     // Test 1: Auto selection with one gitignored file next to the deleted file
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -3930,7 +4323,7 @@ async fn test_nested_deletion_gitignore(cx: &mut gpui::TestAppContext) {
     let cx = &mut VisualTestContext::from_window(*workspace, cx);
 
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 hide_gitignore: true,
@@ -4599,7 +4992,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 1: When auto-fold is enabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: true,
@@ -4661,7 +5054,7 @@ async fn test_expand_all_for_entry(cx: &mut gpui::TestAppContext) {
 
     // Test 2: When auto-fold is disabled
     cx.update(|_, cx| {
-        let settings = *ProjectPanelSettings::get_global(cx);
+        let settings = ProjectPanelSettings::get_global(cx).clone();
         ProjectPanelSettings::override_global(
             ProjectPanelSettings {
                 auto_fold_dirs: false,
@@ -4825,7 +5218,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 2: With auto-fold enabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: true,
@@ -4880,7 +5273,7 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     // Test 3: With auto-fold disabled
     {
         cx.update(|_, cx| {
-            let settings = *ProjectPanelSettings::get_global(cx);
+            let settings = ProjectPanelSettings::get_global(cx).clone();
             ProjectPanelSettings::override_global(
                 ProjectPanelSettings {
                     auto_fold_dirs: false,
@@ -4934,6 +5327,18 @@ async fn test_collapse_all_for_entry(cx: &mut gpui::TestAppContext) {
     }
 }
 
+fn set_filter_query(panel: &Entity<ProjectPanel>, query: &str, cx: &mut VisualTestContext) {
+    panel.update_in(cx, |panel, window, cx| {
+        if !panel.show_filter {
+            panel.toggle_filter(&ToggleFilter, window, cx);
+        }
+        panel
+            .filter_editor
+            .update(cx, |editor, cx| editor.set_text(query, window, cx));
+    });
+    cx.run_until_parked();
+}
+
 fn select_path(panel: &Entity<ProjectPanel>, path: impl AsRef<Path>, cx: &mut VisualTestContext) {
     let path = path.as_ref();
     panel.update(cx, |panel, cx| {
@@ -5019,7 +5424,7 @@ fn visible_entries_as_strings(
             }
 
             let indent = "    ".repeat(details.depth);
-            let icon = if details.kind.is_dir() {
+            let icon = if details.kind.is_dir() || details.has_nested_files {
                 if details.is_expanded { "v " } else { "> " }
             } else {
                 "  "
@@ -0,0 +1,161 @@
+use std::{cmp::Ordering, path::Path};
+
+use project::{Entry, GitEntry};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::paths::compare_paths;
+
+use crate::NEW_ENTRY_ID;
+
+/// How entries are ordered among the other entries in their directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SortMode {
+    /// Alphabetically by name, ignoring case: `10.txt` comes before `2.txt`.
+    Name,
+    /// Alphabetically by name, with leading numbers compared by their value: `2.txt` comes
+    /// before `10.txt`.
+    #[default]
+    Natural,
+    /// By modification time, most recently modified first.
+    Modified,
+    /// Alphabetically by extension, then naturally by name.
+    Extension,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 4] = [
+        SortMode::Name,
+        SortMode::Natural,
+        SortMode::Modified,
+        SortMode::Extension,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "Name",
+            SortMode::Natural => "Natural",
+            SortMode::Modified => "Modified Time",
+            SortMode::Extension => "Extension",
+        }
+    }
+}
+
+/// The order in which the project panel shows the entries of each directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SortOrder {
+    pub mode: SortMode,
+    /// Whether directories are shown before files, rather than mixed in with them.
+    pub directories_first: bool,
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self {
+            mode: SortMode::default(),
+            directories_first: true,
+        }
+    }
+}
+
+impl SortOrder {
+    /// Sorts the entries of a worktree, given in the order the worktree traverses them, so that
+    /// every directory is directly followed by its sorted contents.
+    pub fn sort_entries(&self, mut entries: Vec<GitEntry>) -> Vec<GitEntry> {
+        if *self == Self::default() {
+            project::sort_worktree_entries(&mut entries);
+            return entries;
+        }
+
+        // Entries without a visible parent directory, such as the worktree root, come first.
+        let mut top_level_ixs = Vec::new();
+        let mut child_ixs = vec![Vec::new(); entries.len()];
+        let mut directory_ixs: Vec<usize> = Vec::new();
+        for (ix, entry) in entries.iter().enumerate() {
+            while let Some(directory_ix) = directory_ixs.last() {
+                if entry.path.starts_with(&entries[*directory_ix].path) {
+                    break;
+                }
+                directory_ixs.pop();
+            }
+            match directory_ixs.last() {
+                Some(directory_ix) => child_ixs[*directory_ix].push(ix),
+                None => top_level_ixs.push(ix),
+            }
+            if entry.is_dir() {
+                directory_ixs.push(ix);
+            }
+        }
+
+        let compare = |a: &usize, b: &usize| self.compare_siblings(&entries[*a], &entries[*b]);
+        top_level_ixs.sort_by(compare);
+        for ixs in &mut child_ixs {
+            ixs.sort_by(compare);
+        }
+
+        let mut sorted_ixs = Vec::with_capacity(entries.len());
+        let mut stack = top_level_ixs;
+        stack.reverse();
+        while let Some(ix) = stack.pop() {
+            sorted_ixs.push(ix);
+            stack.extend(child_ixs[ix].iter().rev());
+        }
+
+        let mut entries = entries.into_iter().map(Some).collect::<Vec<_>>();
+        sorted_ixs
+            .into_iter()
+            .filter_map(|ix| entries[ix].take())
+            .collect()
+    }
+
+    fn compare_siblings(&self, a: &Entry, b: &Entry) -> Ordering {
+        let kind_ordering = if self.directories_first {
+            b.is_dir().cmp(&a.is_dir())
+        } else {
+            Ordering::Equal
+        };
+        kind_ordering
+            // An entry being created goes first, where its name is being edited.
+            .then_with(|| (b.id == NEW_ENTRY_ID).cmp(&(a.id == NEW_ENTRY_ID)))
+            .then_with(|| match self.mode {
+                SortMode::Name => compare_names(&a.path, &b.path),
+                SortMode::Natural => Ordering::Equal,
+                SortMode::Modified => {
+                    let a_mtime = a.mtime.map(|mtime| mtime.timestamp_for_user());
+                    let b_mtime = b.mtime.map(|mtime| mtime.timestamp_for_user());
+                    b_mtime.cmp(&a_mtime)
+                }
+                SortMode::Extension => extension(a).cmp(&extension(b)),
+            })
+            .then_with(|| {
+                // Compare directories and files by the same rules when they're mixed together.
+                let (a_is_file, b_is_file) = if a.is_dir() == b.is_dir() {
+                    (a.is_file(), b.is_file())
+                } else {
+                    (true, true)
+                };
+                compare_paths((&a.path, a_is_file), (&b.path, b_is_file))
+            })
+    }
+}
+
+fn compare_names(a: &Path, b: &Path) -> Ordering {
+    let lowercase_components = |path: &Path| {
+        path.iter()
+            .map(|component| component.to_string_lossy().to_lowercase())
+            .collect::<Vec<_>>()
+    };
+    lowercase_components(a)
+        .cmp(&lowercase_components(b))
+        .then_with(|| a.cmp(b))
+}
+
+fn extension(entry: &Entry) -> Option<String> {
+    if entry.is_dir() {
+        return None;
+    }
+    entry
+        .path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
@@ -62,22 +62,27 @@ impl ProjectSymbolsDelegate {
 
     fn filter(&mut self, query: &str, window: &mut Window, cx: &mut Context<Picker<Self>>) {
         const MAX_MATCHES: usize = 100;
-        let mut visible_matches = cx.background_executor().block(fuzzy::match_strings(
-            &self.visible_match_candidates,
-            query,
-            false,
-            MAX_MATCHES,
-            &Default::default(),
-            cx.background_executor().clone(),
-        ));
-        let mut external_matches = cx.background_executor().block(fuzzy::match_strings(
-            &self.external_match_candidates,
-            query,
-            false,
-            MAX_MATCHES - visible_matches.len().min(MAX_MATCHES),
-            &Default::default(),
-            cx.background_executor().clone(),
-        ));
+        let query = fuzzy::Query::parse(query);
+        let mut visible_matches = cx
+            .background_executor()
+            .block(fuzzy::match_strings_with_query(
+                &self.visible_match_candidates,
+                &query,
+                false,
+                MAX_MATCHES,
+                &Default::default(),
+                cx.background_executor().clone(),
+            ));
+        let mut external_matches = cx
+            .background_executor()
+            .block(fuzzy::match_strings_with_query(
+                &self.external_match_candidates,
+                &query,
+                false,
+                MAX_MATCHES - visible_matches.len().min(MAX_MATCHES),
+                &Default::default(),
+                cx.background_executor().clone(),
+            ));
         let sort_key_for_match = |mat: &StringMatch| {
             let symbol = &self.symbols[mat.candidate_id];
             (Reverse(OrderedFloat(mat.score)), symbol.label.filter_text())
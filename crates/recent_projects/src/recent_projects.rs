@@ -326,6 +326,7 @@ impl PickerDelegate for RecentProjectsDelegate {
                                         ssh_project.host.clone(),
                                         ssh_project.port,
                                         ssh_project.user.clone(),
+                                        ssh_project.shell_command.clone(),
                                     );
 
                                 let paths = ssh_project.paths.iter().map(PathBuf::from).collect();
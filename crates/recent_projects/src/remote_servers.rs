@@ -873,6 +873,7 @@ impl RemoteServerProjects {
                     args: connection_options.args.unwrap_or_default(),
                     upload_binary_over_ssh: None,
                     port_forwards: connection_options.port_forwards,
+                    shell_command: connection_options.shell_command,
                 })
         });
     }
@@ -37,11 +37,15 @@ impl SshSettings {
         self.ssh_connections.clone().into_iter().flatten()
     }
 
+    /// Returns the options for connecting to the given host, taking any matching connection
+    /// from the settings into account. `shell_command` is the command the project was last
+    /// opened with, if any, and takes precedence over the one in the settings.
     pub fn connection_options_for(
         &self,
         host: String,
         port: Option<u16>,
         username: Option<String>,
+        shell_command: Option<Vec<String>>,
     ) -> SshConnectionOptions {
         for conn in self.ssh_connections() {
             if conn.host == host && conn.username == username && conn.port == port {
@@ -54,6 +58,7 @@ impl SshSettings {
                     username,
                     port_forwards: conn.port_forwards,
                     password: None,
+                    shell_command: shell_command.or(conn.shell_command),
                 };
             }
         }
@@ -61,6 +66,7 @@ impl SshSettings {
             host,
             port,
             username,
+            shell_command,
             ..Default::default()
         }
     }
@@ -90,6 +96,11 @@ pub struct SshConnection {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port_forwards: Option<Vec<SshPortForwardOption>>,
+
+    /// A command to connect with instead of ssh, which starts a POSIX shell on
+    /// the host, such as `["docker", "exec", "-i", "my-container", "sh"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell_command: Option<Vec<String>>,
 }
 
 impl From<SshConnection> for SshConnectionOptions {
@@ -103,6 +114,7 @@ impl From<SshConnection> for SshConnectionOptions {
             nickname: val.nickname,
             upload_binary_over_ssh: val.upload_binary_over_ssh.unwrap_or_default(),
             port_forwards: val.port_forwards,
+            shell_command: val.shell_command,
         }
     }
 }
@@ -0,0 +1,381 @@
+use crate::{
+    shell_script,
+    ssh_session::{
+        RemoteConnection, RemoteShell, SshClientDelegate, SshConnectionOptions,
+        SshRemoteConnection, start_proxy_command,
+    },
+};
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use futures::channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender};
+use gpui::{App, AppContext as _, AsyncApp, Task};
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use rpc::proto::Envelope;
+use smol::process::{self, Stdio};
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering::SeqCst},
+    },
+};
+
+/// Runs scripts on a host through a user-specified command that starts a shell there, such as
+/// `docker exec -i <container> sh` or `kubectl exec -i <pod> -- sh`.
+#[derive(Clone)]
+pub(crate) struct CommandSocket {
+    connection_options: SshConnectionOptions,
+    command: Vec<String>,
+}
+
+impl CommandSocket {
+    pub(crate) fn new(connection_options: SshConnectionOptions) -> Result<Self> {
+        let command = connection_options
+            .shell_command
+            .clone()
+            .filter(|command| !command.is_empty())
+            .ok_or_else(|| anyhow!("no command to connect to {}", connection_options.host))?;
+        Ok(Self {
+            connection_options,
+            command,
+        })
+    }
+
+    /// Runs the script in the shell started by the command, from the home directory.
+    fn shell_command(&self, script: &str) -> process::Command {
+        let script = format!("cd; {script}");
+        log::debug!("{} -c {:?}", self.command.join(" "), script);
+        let mut command = util::command::new_smol_command(&self.command[0]);
+        command
+            .args(&self.command[1..])
+            .arg("-c")
+            .arg(script)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        command
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: PathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let archive = util::command::new_smol_command("tar")
+            .arg("-C")
+            .arg(&src_path)
+            .args(["-cf", "-", "."])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let extract = self
+            .shell_command(&shell_script!(
+                "mkdir -p {dest_path} && tar -C {dest_path} -xf -",
+                dest_path = &dest_path.to_string_lossy(),
+            ))
+            .spawn();
+
+        cx.background_spawn(async move {
+            let mut archive = archive?;
+            let mut extract = extract?;
+            let mut archive_stdout = archive.stdout.take().context("no tar stdout")?;
+            let mut extract_stdin = extract.stdin.take().context("no shell stdin")?;
+            futures::io::copy(&mut archive_stdout, &mut extract_stdin).await?;
+            drop(extract_stdin);
+
+            let archive_output = archive.output().await?;
+            let extract_output = extract.output().await?;
+            for output in [archive_output, extract_output] {
+                if !output.status.success() {
+                    return Err(anyhow!(
+                        "failed to upload directory {} -> {}: {}",
+                        src_path.display(),
+                        dest_path.display(),
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteShell for CommandSocket {
+    fn connection_options(&self) -> &SshConnectionOptions {
+        &self.connection_options
+    }
+
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        let script = shlex::try_join(iter::once(program).chain(args.iter().copied()))
+            .with_context(|| format!("quoting the command {program}"))?;
+        let output = self.shell_command(&script).output().await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(anyhow!(
+                "failed to run command: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
+        let mut src_file = smol::fs::File::open(src_path).await?;
+        let mut child = self
+            .shell_command(&shell_script!(
+                "cat > {dest_path}",
+                dest_path = &dest_path.to_string_lossy(),
+            ))
+            .spawn()?;
+        let mut stdin = child.stdin.take().context("no shell stdin")?;
+        futures::io::copy(&mut src_file, &mut stdin).await?;
+        drop(stdin);
+
+        let output = child.output().await?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+}
+
+/// A connection to a host through a [`CommandSocket`], which starts a new instance of the
+/// command for every script it runs, including the remote server's proxy.
+pub(crate) struct CommandRemoteConnection {
+    socket: CommandSocket,
+    remote_binary_path: PathBuf,
+    killed: AtomicBool,
+}
+
+impl CommandRemoteConnection {
+    pub(crate) async fn new(
+        connection_options: SshConnectionOptions,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        delegate.set_status(Some("Connecting"), cx);
+        let socket = CommandSocket::new(connection_options)?;
+        socket
+            .run_command("true", &[])
+            .await
+            .context("Failed to connect to host")?;
+
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        })?;
+        let remote_binary_path = socket
+            .ensure_server_binary(&delegate, release_channel, version, commit, cx)
+            .await?;
+
+        Ok(Self {
+            socket,
+            remote_binary_path,
+            killed: AtomicBool::new(false),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for CommandRemoteConnection {
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn SshClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let start_proxy_command =
+            start_proxy_command(&self.remote_binary_path, &unique_identifier, reconnect);
+        let proxy_process = match self
+            .socket
+            .shell_command(&start_proxy_command)
+            // IMPORTANT: we kill this process when we drop the task that uses it.
+            .kill_on_drop(true)
+            .spawn()
+        {
+            Ok(process) => process,
+            Err(error) => {
+                return Task::ready(Err(anyhow!("failed to spawn remote server: {}", error)));
+            }
+        };
+
+        SshRemoteConnection::multiplex(
+            proxy_process,
+            incoming_tx,
+            outgoing_rx,
+            connection_activity_tx,
+            &cx,
+        )
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: PathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        self.socket.upload_directory(src_path, dest_path, cx)
+    }
+
+    async fn kill(&self) -> Result<()> {
+        // Every command exits with the script it runs, so there is no process to kill.
+        self.killed.store(true, SeqCst);
+        Ok(())
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.killed.load(SeqCst)
+    }
+
+    fn ssh_args(&self) -> Vec<String> {
+        // Terminals are started by appending `-c <script>` to the command, like everything else.
+        self.socket.command.clone()
+    }
+
+    fn connection_options(&self) -> SshConnectionOptions {
+        self.socket.connection_options.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssh_session::fake::Delegate;
+    use futures::{StreamExt as _, channel::mpsc};
+    use gpui::{SemanticVersion, TestAppContext};
+
+    fn sh_socket() -> CommandSocket {
+        CommandSocket::new(SshConnectionOptions {
+            host: "sh".to_string(),
+            shell_command: Some(vec!["sh".to_string()]),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run_command() {
+        let socket = sh_socket();
+        smol::block_on(async {
+            assert_eq!(
+                socket
+                    .run_command("printf", &["%s|%s", "two words", "it's"])
+                    .await
+                    .unwrap(),
+                "two words|it's"
+            );
+            assert!(socket.run_command("false", &[]).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_upload_file() {
+        let socket = sh_socket();
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().join("src file");
+        let dest_path = dir.path().join("dest file");
+        let contents = (0..=255).cycle().take(200_000).collect::<Vec<u8>>();
+        std::fs::write(&src_path, &contents).unwrap();
+
+        smol::block_on(socket.upload_file(&src_path, &dest_path)).unwrap();
+        assert_eq!(std::fs::read(&dest_path).unwrap(), contents);
+    }
+
+    #[gpui::test]
+    async fn test_remote_connection_through_command(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+        cx.update(|cx| release_channel::init(SemanticVersion::default(), cx));
+        assert_eq!(
+            cx.update(|cx| ReleaseChannel::global(cx)),
+            ReleaseChannel::Dev
+        );
+
+        // The command sets up the "remote" home directory, where a fake server binary that
+        // echoes every message back serves as the remote server.
+        let home = tempfile::tempdir().unwrap();
+        let server_dir = home.path().join(paths::remote_server_dir_relative());
+        std::fs::create_dir_all(&server_dir).unwrap();
+        let server_path = server_dir.join("zed-remote-server-dev-build");
+        std::fs::write(
+            &server_path,
+            "#!/bin/sh\ncase \"$1\" in\n  version) echo build ;;\n  proxy) exec cat ;;\nesac\n",
+        )
+        .unwrap();
+        let command = vec![
+            "env".to_string(),
+            format!("HOME={}", home.path().display()),
+            "sh".to_string(),
+        ];
+        CommandSocket::new(SshConnectionOptions {
+            host: "container".to_string(),
+            shell_command: Some(command.clone()),
+            ..Default::default()
+        })
+        .unwrap()
+        .run_command("chmod", &["+x", &server_path.to_string_lossy()])
+        .await
+        .unwrap();
+
+        let delegate: Arc<dyn SshClientDelegate> = Arc::new(Delegate);
+        let connection = CommandRemoteConnection::new(
+            SshConnectionOptions {
+                host: "container".to_string(),
+                shell_command: Some(command.clone()),
+                ..Default::default()
+            },
+            delegate.clone(),
+            &mut cx.to_async(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            connection.remote_binary_path,
+            paths::remote_server_dir_relative().join("zed-remote-server-dev-build")
+        );
+        assert_eq!(connection.ssh_args(), command);
+
+        let (incoming_tx, mut incoming_rx) = mpsc::unbounded();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
+        let (connection_activity_tx, _connection_activity_rx) = mpsc::channel(1);
+        let proxy = connection.start_proxy(
+            "identifier".to_string(),
+            false,
+            incoming_tx,
+            outgoing_rx,
+            connection_activity_tx,
+            delegate,
+            &mut cx.to_async(),
+        );
+
+        for id in 1..=3 {
+            let envelope = Envelope {
+                id,
+                ..Default::default()
+            };
+            outgoing_tx.unbounded_send(envelope.clone()).unwrap();
+            assert_eq!(incoming_rx.next().await.unwrap(), envelope);
+        }
+
+        drop(outgoing_tx);
+        assert_eq!(proxy.await.unwrap(), 0);
+    }
+}
@@ -1,3 +1,4 @@
+mod command_session;
 pub mod json_log;
 pub mod protocol;
 pub mod proxy;
@@ -1,4 +1,5 @@
 use crate::{
+    command_session::CommandRemoteConnection,
     json_log::LogRecord,
     protocol::{
         MESSAGE_LEN_SIZE, MessageId, message_len_from_buffer, read_message_with_len, write_message,
@@ -83,6 +84,9 @@ pub struct SshConnectionOptions {
 
     pub nickname: Option<String>,
     pub upload_binary_over_ssh: bool,
+    /// A command that starts a POSIX shell on the host, such as `docker exec -i <container> sh`,
+    /// to connect with instead of ssh. Scripts are run on the host by appending `-c <script>`.
+    pub shell_command: Option<Vec<String>>,
 }
 
 #[macro_export]
@@ -234,6 +238,7 @@ impl SshConnectionOptions {
             password: None,
             nickname: None,
             upload_binary_over_ssh: false,
+            shell_command: None,
         })
     }
 
@@ -367,18 +372,6 @@ impl SshSocket {
         command
     }
 
-    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
-        let output = self.ssh_command(program, args).output().await?;
-        if output.status.success() {
-            Ok(String::from_utf8_lossy(&output.stdout).to_string())
-        } else {
-            Err(anyhow!(
-                "failed to run command: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
     fn ssh_options<'a>(&self, command: &'a mut process::Command) -> &'a mut process::Command {
         command
             .stdin(Stdio::piped())
@@ -1240,9 +1233,15 @@ impl ConnectionPool {
                 let opts = opts.clone();
                 let delegate = delegate.clone();
                 async move |cx| {
-                    let connection = SshRemoteConnection::new(opts.clone(), delegate, cx)
-                        .await
-                        .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>);
+                    let connection = if opts.shell_command.is_some() {
+                        CommandRemoteConnection::new(opts.clone(), delegate, cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    } else {
+                        SshRemoteConnection::new(opts.clone(), delegate, cx)
+                            .await
+                            .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                    };
 
                     cx.update_global(|pool: &mut Self, _| {
                         debug_assert!(matches!(
@@ -1280,7 +1279,7 @@ impl From<SshRemoteClient> for AnyProtoClient {
 }
 
 #[async_trait(?Send)]
-trait RemoteConnection: Send + Sync {
+pub(crate) trait RemoteConnection: Send + Sync {
     fn start_proxy(
         &self,
         unique_identifier: String,
@@ -1302,7 +1301,7 @@ trait RemoteConnection: Send + Sync {
     fn simulate_disconnect(&self, _: &AsyncApp) {}
 }
 
-struct SshRemoteConnection {
+pub(crate) struct SshRemoteConnection {
     socket: SshSocket,
     master_process: Mutex<Option<Child>>,
     remote_binary_path: Option<PathBuf>,
@@ -1391,29 +1390,8 @@ impl RemoteConnection for SshRemoteConnection {
             return Task::ready(Err(anyhow!("Remote binary path not set")));
         };
 
-        let mut start_proxy_command = shell_script!(
-            "exec {binary_path} proxy --identifier {identifier}",
-            binary_path = &remote_binary_path.to_string_lossy(),
-            identifier = &unique_identifier,
-        );
-
-        if let Some(rust_log) = std::env::var("RUST_LOG").ok() {
-            start_proxy_command = format!(
-                "RUST_LOG={} {}",
-                shlex::try_quote(&rust_log).unwrap(),
-                start_proxy_command
-            )
-        }
-        if let Some(rust_backtrace) = std::env::var("RUST_BACKTRACE").ok() {
-            start_proxy_command = format!(
-                "RUST_BACKTRACE={} {}",
-                shlex::try_quote(&rust_backtrace).unwrap(),
-                start_proxy_command
-            )
-        }
-        if reconnect {
-            start_proxy_command.push_str(" --reconnect");
-        }
+        let start_proxy_command =
+            start_proxy_command(&remote_binary_path, &unique_identifier, reconnect);
 
         let ssh_proxy_process = match self
             .socket
@@ -1438,6 +1416,39 @@ impl RemoteConnection for SshRemoteConnection {
     }
 }
 
+/// The script that starts the remote server's proxy, which relays messages over its stdin and
+/// stdout to the server.
+pub(crate) fn start_proxy_command(
+    remote_binary_path: &Path,
+    unique_identifier: &str,
+    reconnect: bool,
+) -> String {
+    let mut start_proxy_command = shell_script!(
+        "exec {binary_path} proxy --identifier {identifier}",
+        binary_path = &remote_binary_path.to_string_lossy(),
+        identifier = &unique_identifier,
+    );
+
+    if let Some(rust_log) = std::env::var("RUST_LOG").ok() {
+        start_proxy_command = format!(
+            "RUST_LOG={} {}",
+            shlex::try_quote(&rust_log).unwrap(),
+            start_proxy_command
+        )
+    }
+    if let Some(rust_backtrace) = std::env::var("RUST_BACKTRACE").ok() {
+        start_proxy_command = format!(
+            "RUST_BACKTRACE={} {}",
+            shlex::try_quote(&rust_backtrace).unwrap(),
+            start_proxy_command
+        )
+    }
+    if reconnect {
+        start_proxy_command.push_str(" --reconnect");
+    }
+    start_proxy_command
+}
+
 impl SshRemoteConnection {
     #[cfg(not(unix))]
     async fn new(
@@ -1555,45 +1566,15 @@ impl SshRemoteConnection {
             )
         })?;
         this.remote_binary_path = Some(
-            this.ensure_server_binary(&delegate, release_channel, version, commit, cx)
+            this.socket
+                .ensure_server_binary(&delegate, release_channel, version, commit, cx)
                 .await?,
         );
 
         Ok(this)
     }
 
-    async fn platform(&self) -> Result<SshPlatform> {
-        let uname = self.socket.run_command("sh", &["-c", "uname -sm"]).await?;
-        let Some((os, arch)) = uname.split_once(" ") else {
-            Err(anyhow!("unknown uname: {uname:?}"))?
-        };
-
-        let os = match os.trim() {
-            "Darwin" => "macos",
-            "Linux" => "linux",
-            _ => Err(anyhow!(
-                "Prebuilt remote servers are not yet available for {os:?}. See https://zed.dev/docs/remote-development"
-            ))?,
-        };
-        // exclude armv5,6,7 as they are 32-bit.
-        let arch = if arch.starts_with("armv8")
-            || arch.starts_with("armv9")
-            || arch.starts_with("arm64")
-            || arch.starts_with("aarch64")
-        {
-            "aarch64"
-        } else if arch.starts_with("x86") {
-            "x86_64"
-        } else {
-            Err(anyhow!(
-                "Prebuilt remote servers are not yet available for {arch:?}. See https://zed.dev/docs/remote-development"
-            ))?
-        };
-
-        Ok(SshPlatform { os, arch })
-    }
-
-    fn multiplex(
+    pub(crate) fn multiplex(
         mut ssh_proxy_process: Child,
         incoming_tx: UnboundedSender<Envelope>,
         mut outgoing_rx: UnboundedReceiver<Envelope>,
@@ -1694,6 +1675,48 @@ impl SshRemoteConnection {
             }
         })
     }
+}
+
+/// Runs commands and copies files on a remote host, which is all that's needed to install the
+/// remote server there.
+#[async_trait(?Send)]
+pub(crate) trait RemoteShell {
+    fn connection_options(&self) -> &SshConnectionOptions;
+
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String>;
+
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()>;
+
+    async fn platform(&self) -> Result<SshPlatform> {
+        let uname = self.run_command("sh", &["-c", "uname -sm"]).await?;
+        let Some((os, arch)) = uname.split_once(" ") else {
+            Err(anyhow!("unknown uname: {uname:?}"))?
+        };
+
+        let os = match os.trim() {
+            "Darwin" => "macos",
+            "Linux" => "linux",
+            _ => Err(anyhow!(
+                "Prebuilt remote servers are not yet available for {os:?}. See https://zed.dev/docs/remote-development"
+            ))?,
+        };
+        // exclude armv5,6,7 as they are 32-bit.
+        let arch = if arch.starts_with("armv8")
+            || arch.starts_with("armv9")
+            || arch.starts_with("arm64")
+            || arch.starts_with("aarch64")
+        {
+            "aarch64"
+        } else if arch.starts_with("x86") {
+            "x86_64"
+        } else {
+            Err(anyhow!(
+                "Prebuilt remote servers are not yet available for {arch:?}. See https://zed.dev/docs/remote-development"
+            ))?
+        };
+
+        Ok(SshPlatform { os, arch })
+    }
 
     #[allow(unused)]
     async fn ensure_server_binary(
@@ -1738,7 +1761,6 @@ impl SshRemoteConnection {
         }
 
         if self
-            .socket
             .run_command(&dst_path.to_string_lossy(), &["version"])
             .await
             .is_ok()
@@ -1759,7 +1781,7 @@ impl SshRemoteConnection {
 
         let platform = self.platform().await?;
 
-        if !self.socket.connection_options.upload_binary_over_ssh {
+        if !self.connection_options().upload_binary_over_ssh {
             if let Some((url, body)) = delegate
                 .get_download_params(platform, release_channel, wanted_version, cx)
                 .await?
@@ -1802,15 +1824,13 @@ impl SshRemoteConnection {
         cx: &mut AsyncApp,
     ) -> Result<()> {
         if let Some(parent) = tmp_path_gz.parent() {
-            self.socket
-                .run_command("mkdir", &["-p", &parent.to_string_lossy()])
+            self.run_command("mkdir", &["-p", &parent.to_string_lossy()])
                 .await?;
         }
 
         delegate.set_status(Some("Downloading remote development server on host"), cx);
 
         match self
-            .socket
             .run_command(
                 "curl",
                 &[
@@ -1831,12 +1851,11 @@ impl SshRemoteConnection {
         {
             Ok(_) => {}
             Err(e) => {
-                if self.socket.run_command("which", &["curl"]).await.is_ok() {
+                if self.run_command("which", &["curl"]).await.is_ok() {
                     return Err(e);
                 }
 
                 match self
-                    .socket
                     .run_command(
                         "wget",
                         &[
@@ -1853,7 +1872,7 @@ impl SshRemoteConnection {
                 {
                     Ok(_) => {}
                     Err(e) => {
-                        if self.socket.run_command("which", &["wget"]).await.is_ok() {
+                        if self.run_command("which", &["wget"]).await.is_ok() {
                             return Err(e);
                         } else {
                             anyhow::bail!("Neither curl nor wget is available");
@@ -1874,8 +1893,7 @@ impl SshRemoteConnection {
         cx: &mut AsyncApp,
     ) -> Result<()> {
         if let Some(parent) = tmp_path_gz.parent() {
-            self.socket
-                .run_command("mkdir", &["-p", &parent.to_string_lossy()])
+            self.run_command("mkdir", &["-p", &parent.to_string_lossy()])
                 .await?;
         }
 
@@ -1913,44 +1931,10 @@ impl SshRemoteConnection {
             server_mode = &format!("{:o}", server_mode),
             dst_path = &dst_path.to_string_lossy()
         );
-        self.socket.run_command("sh", &["-c", &script]).await?;
+        self.run_command("sh", &["-c", &script]).await?;
         Ok(())
     }
 
-    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
-        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
-        let mut command = util::command::new_smol_command("scp");
-        let output = self
-            .socket
-            .ssh_options(&mut command)
-            .args(
-                self.socket
-                    .connection_options
-                    .port
-                    .map(|port| vec!["-P".to_string(), port.to_string()])
-                    .unwrap_or_default(),
-            )
-            .arg(src_path)
-            .arg(format!(
-                "{}:{}",
-                self.socket.connection_options.scp_url(),
-                dest_path.display()
-            ))
-            .output()
-            .await?;
-
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "failed to upload file {} -> {}: {}",
-                src_path.display(),
-                dest_path.display(),
-                String::from_utf8_lossy(&output.stderr)
-            ))
-        }
-    }
-
     #[cfg(debug_assertions)]
     async fn build_local(
         &self,
@@ -2059,6 +2043,57 @@ impl SshRemoteConnection {
     }
 }
 
+#[async_trait(?Send)]
+impl RemoteShell for SshSocket {
+    fn connection_options(&self) -> &SshConnectionOptions {
+        &self.connection_options
+    }
+
+    async fn run_command(&self, program: &str, args: &[&str]) -> Result<String> {
+        let output = self.ssh_command(program, args).output().await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            Err(anyhow!(
+                "failed to run command: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+
+    async fn upload_file(&self, src_path: &Path, dest_path: &Path) -> Result<()> {
+        log::debug!("uploading file {:?} to {:?}", src_path, dest_path);
+        let mut command = util::command::new_smol_command("scp");
+        let output = self
+            .ssh_options(&mut command)
+            .args(
+                self.connection_options
+                    .port
+                    .map(|port| vec!["-P".to_string(), port.to_string()])
+                    .unwrap_or_default(),
+            )
+            .arg(src_path)
+            .arg(format!(
+                "{}:{}",
+                self.connection_options.scp_url(),
+                dest_path.display()
+            ))
+            .output()
+            .await?;
+
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "failed to upload file {} -> {}: {}",
+                src_path.display(),
+                dest_path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            ))
+        }
+    }
+}
+
 type ResponseChannels = Mutex<HashMap<MessageId, oneshot::Sender<(Envelope, oneshot::Sender<()>)>>>;
 
 pub struct ChannelClient {
@@ -2366,7 +2401,7 @@ impl ProtoClient for ChannelClient {
 }
 
 #[cfg(any(test, feature = "test-support"))]
-mod fake {
+pub(crate) mod fake {
     use std::{path::PathBuf, sync::Arc};
 
     use anyhow::Result;
@@ -2485,7 +2520,7 @@ mod fake {
         }
     }
 
-    pub(super) struct Delegate;
+    pub(crate) struct Delegate;
 
     impl SshClientDelegate for Delegate {
         fn ask_password(&self, _: String, _: oneshot::Sender<String>, _: &mut AsyncApp) {
@@ -18,8 +18,9 @@ pub use keymap_file::{
 };
 pub use settings_file::*;
 pub use settings_store::{
-    InvalidSettingsError, LocalSettingsKind, Settings, SettingsLocation, SettingsSources,
-    SettingsStore, TaskKind, parse_json_with_comments,
+    InvalidSettingsError, LocalSettingsKind, SettingContribution, SettingProvenance, Settings,
+    SettingsLayer, SettingsLocation, SettingsSources, SettingsStore, TaskKind,
+    json_key_path_at_offset, json_key_path_range, parse_json_with_comments,
 };
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
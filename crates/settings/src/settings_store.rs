@@ -1,5 +1,5 @@
 use anyhow::{Context as _, Result, anyhow};
-use collections::{BTreeMap, HashMap, btree_map, hash_map};
+use collections::{BTreeMap, BTreeSet, HashMap, btree_map, hash_map};
 use ec4rs::{ConfigParser, PropertiesSource, Section};
 use fs::Fs;
 use futures::{FutureExt, StreamExt, channel::mpsc, future::LocalBoxFuture};
@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use smallvec::SmallVec;
 use std::{
     any::{Any, TypeId, type_name},
+    borrow::Cow,
     fmt::Debug,
     ops::Range,
     path::{Path, PathBuf},
@@ -176,6 +177,7 @@ pub struct SettingsStore {
     setting_values: HashMap<TypeId, Box<dyn AnySettingValue>>,
     raw_default_settings: serde_json::Value,
     raw_user_settings: serde_json::Value,
+    active_profile: Option<String>,
     raw_server_settings: Option<serde_json::Value>,
     raw_extension_settings: serde_json::Value,
     raw_local_settings: BTreeMap<(WorktreeId, Arc<Path>), serde_json::Value>,
@@ -269,6 +271,7 @@ impl SettingsStore {
             setting_values: Default::default(),
             raw_default_settings: serde_json::json!({}),
             raw_user_settings: serde_json::json!({}),
+            active_profile: None,
             raw_server_settings: None,
             raw_extension_settings: serde_json::json!({}),
             raw_local_settings: Default::default(),
@@ -308,9 +311,11 @@ impl SettingsStore {
             .deserialize_setting(&self.raw_default_settings)
             .log_err()
         {
-            let user_value = setting_value
-                .deserialize_setting(&self.raw_user_settings)
-                .log_err();
+            let user_settings = Self::user_settings_with_profile(
+                &self.raw_user_settings,
+                self.active_profile.as_deref(),
+            );
+            let user_value = setting_value.deserialize_setting(&user_settings).log_err();
 
             let mut release_channel_value = None;
             if let Some(release_settings) = &self
@@ -522,6 +527,38 @@ impl SettingsStore {
         edits
     }
 
+    /// Returns the edits that merge the given JSON object into the JSON object in `text`,
+    /// preserving the comments and formatting of the parts that don't change.
+    pub fn json_merge_edits(
+        &self,
+        text: &str,
+        new_values: &serde_json::Value,
+    ) -> Result<Vec<(Range<usize>, String)>> {
+        let tab_size = self.json_tab_size();
+        if text.trim().is_empty() {
+            return Ok(vec![(
+                0..text.len(),
+                to_pretty_json(new_values, tab_size, 0),
+            )]);
+        }
+
+        let old_value: serde_json::Value = parse_json_with_comments(text)?;
+        let mut new_value = old_value.clone();
+        merge_non_null_json_value_into(new_values.clone(), &mut new_value);
+
+        let mut edits = Vec::new();
+        update_value_in_json_text(
+            &mut text.to_string(),
+            &mut Vec::new(),
+            tab_size,
+            &old_value,
+            &new_value,
+            &[],
+            &mut edits,
+        );
+        Ok(edits)
+    }
+
     /// Configure the tab sized when updating JSON files.
     pub fn set_json_tab_size_callback<T: Settings>(
         &mut self,
@@ -583,6 +620,56 @@ impl SettingsStore {
         Ok(settings)
     }
 
+    /// Returns the names of the settings profiles defined in the user settings.
+    pub fn profile_names(&self) -> Vec<String> {
+        self.raw_user_settings
+            .get("profiles")
+            .and_then(|profiles| profiles.as_object())
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the name of the settings profile layered above the user settings, if any.
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
+    /// Layers the settings profile with the given name above the user settings, or removes the
+    /// active profile when `None` is given.
+    pub fn set_active_profile(&mut self, profile: Option<String>, cx: &mut App) -> Result<()> {
+        if let Some(profile) = profile.as_ref() {
+            anyhow::ensure!(
+                self.profile_names().contains(profile),
+                "no settings profile named {profile:?}"
+            );
+        }
+        if self.active_profile != profile {
+            self.active_profile = profile;
+            self.recompute_values(None, cx)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the user settings, with the settings of the given profile merged into them.
+    fn user_settings_with_profile<'a>(
+        raw_user_settings: &'a serde_json::Value,
+        profile: Option<&str>,
+    ) -> Cow<'a, serde_json::Value> {
+        let profile = profile.and_then(|profile| {
+            raw_user_settings
+                .get("profiles")
+                .and_then(|profiles| profiles.get(profile))
+        });
+        match profile {
+            Some(profile) if profile.is_object() => {
+                let mut settings = raw_user_settings.clone();
+                merge_non_null_json_value_into(profile.clone(), &mut settings);
+                Cow::Owned(settings)
+            }
+            _ => Cow::Borrowed(raw_user_settings),
+        }
+    }
+
     pub fn set_server_settings(
         &mut self,
         server_settings_content: &str,
@@ -849,6 +936,7 @@ impl SettingsStore {
             }
         }
 
+        let profile_schema = combined_schema.schema.clone();
         for release_stage in ["dev", "nightly", "stable", "preview"] {
             let schema = combined_schema.schema.clone();
             combined_schema
@@ -857,6 +945,25 @@ impl SettingsStore {
                 .properties
                 .insert(release_stage.to_string(), schema.into());
         }
+        combined_schema.schema.object().properties.insert(
+            "profiles".to_string(),
+            SchemaObject {
+                metadata: Some(Box::new(schemars::schema::Metadata {
+                    description: Some(
+                        "Named sets of settings that can be layered above the user settings \
+                        with `settings profile selector: toggle`."
+                            .to_string(),
+                    ),
+                    ..Default::default()
+                })),
+                object: Some(Box::new(schemars::schema::ObjectValidation {
+                    additional_properties: Some(Box::new(profile_schema.into())),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            }
+            .into(),
+        );
 
         serde_json::to_value(&combined_schema).unwrap()
     }
@@ -869,6 +976,10 @@ impl SettingsStore {
         // Reload the global and local values for every setting.
         let mut project_settings_stack = Vec::<DeserializedSetting>::new();
         let mut paths_stack = Vec::<Option<(WorktreeId, &Path)>>::new();
+        let raw_user_settings = Self::user_settings_with_profile(
+            &self.raw_user_settings,
+            self.active_profile.as_deref(),
+        );
         for setting_value in self.setting_values.values_mut() {
             let default_settings = setting_value
                 .deserialize_setting(&self.raw_default_settings)
@@ -880,7 +991,7 @@ impl SettingsStore {
                 .deserialize_setting(&self.raw_extension_settings)
                 .log_err();
 
-            let user_settings = match setting_value.deserialize_setting(&self.raw_user_settings) {
+            let user_settings = match setting_value.deserialize_setting(&raw_user_settings) {
                 Ok(settings) => Some(settings),
                 Err(error) => {
                     return Err(InvalidSettingsError::UserSettings {
@@ -1015,6 +1126,216 @@ impl SettingsStore {
         properties.use_fallbacks();
         Some(properties)
     }
+
+    /// Returns the value of the setting at the given JSON key path, along with the value each
+    /// settings layer contributed to it, in the order the layers are merged.
+    ///
+    /// When a location is given, the project settings and `.editorconfig` files that apply to it
+    /// are included.
+    pub fn setting_provenance(
+        &self,
+        key_path: &[&str],
+        location: Option<SettingsLocation>,
+    ) -> SettingProvenance {
+        fn value_at<'a>(
+            json: &'a serde_json::Value,
+            key_path: &[&str],
+        ) -> Option<&'a serde_json::Value> {
+            key_path
+                .iter()
+                .try_fold(json, |json, key| json.get(key))
+                .filter(|value| !value.is_null())
+        }
+
+        let mut layers = vec![
+            (SettingsLayer::Default, Some(&self.raw_default_settings)),
+            (
+                SettingsLayer::Extensions,
+                Some(&self.raw_extension_settings),
+            ),
+            (SettingsLayer::User, Some(&self.raw_user_settings)),
+        ];
+        if let Some(profile) = self.active_profile.as_ref() {
+            layers.push((
+                SettingsLayer::Profile(profile.clone()),
+                self.raw_user_settings
+                    .get("profiles")
+                    .and_then(|profiles| profiles.get(profile)),
+            ));
+        }
+        layers.push((
+            SettingsLayer::ReleaseChannel,
+            self.raw_user_settings
+                .get(release_channel::RELEASE_CHANNEL.dev_name()),
+        ));
+        layers.push((SettingsLayer::Server, self.raw_server_settings.as_ref()));
+        if let Some(location) = location {
+            for ((worktree_id, directory), settings) in &self.raw_local_settings {
+                if *worktree_id == location.worktree_id && location.path.starts_with(directory) {
+                    layers.push((
+                        SettingsLayer::Project {
+                            worktree_id: *worktree_id,
+                            directory: directory.clone(),
+                        },
+                        Some(settings),
+                    ));
+                }
+            }
+        }
+
+        let mut provenance = SettingProvenance::default();
+        for (layer, json) in layers {
+            if let Some(value) = json.and_then(|json| value_at(json, key_path)) {
+                provenance.contributions.push(SettingContribution {
+                    layer,
+                    value: value.clone(),
+                });
+            }
+        }
+
+        if let Some(location) = location {
+            let mut editorconfig_contributions = Vec::new();
+            for (directory, _, editorconfig) in
+                self.local_editorconfig_settings(location.worktree_id)
+            {
+                let Some(editorconfig) = editorconfig else {
+                    continue;
+                };
+                if !location.path.starts_with(&directory) {
+                    continue;
+                }
+                if editorconfig.is_root {
+                    editorconfig_contributions.clear();
+                }
+                let mut properties = EditorconfigProperties::new();
+                for section in editorconfig.sections {
+                    section.apply_to(&mut properties, location.path).log_err();
+                }
+                properties.use_fallbacks();
+                if let Some(value) = editorconfig_setting_value(&properties, key_path) {
+                    editorconfig_contributions.push(SettingContribution {
+                        layer: SettingsLayer::Editorconfig {
+                            worktree_id: location.worktree_id,
+                            directory,
+                        },
+                        value,
+                    });
+                }
+            }
+            provenance.contributions.extend(editorconfig_contributions);
+        }
+
+        for contribution in &provenance.contributions {
+            let value = provenance.value.get_or_insert(serde_json::Value::Null);
+            if contribution.value.is_object() && value.is_object() {
+                merge_non_null_json_value_into(contribution.value.clone(), value);
+            } else {
+                *value = contribution.value.clone();
+            }
+        }
+        provenance
+    }
+
+    /// Returns the JSON key paths of every setting defined in the default or user settings,
+    /// including the objects containing other settings.
+    pub fn setting_key_paths(&self) -> Vec<Vec<String>> {
+        fn collect(
+            json: &serde_json::Value,
+            key_path: &mut Vec<String>,
+            key_paths: &mut BTreeSet<Vec<String>>,
+        ) {
+            if let serde_json::Value::Object(object) = json {
+                for (key, value) in object {
+                    key_path.push(key.clone());
+                    key_paths.insert(key_path.clone());
+                    collect(value, key_path, key_paths);
+                    key_path.pop();
+                }
+            }
+        }
+
+        let mut key_paths = BTreeSet::default();
+        for json in [&self.raw_default_settings, &self.raw_user_settings] {
+            collect(json, &mut Vec::new(), &mut key_paths);
+        }
+        key_paths.into_iter().collect()
+    }
+}
+
+/// A source of settings merged into the effective settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsLayer {
+    /// The default Zed settings.
+    Default,
+    /// Settings provided by extensions.
+    Extensions,
+    /// The user settings.
+    User,
+    /// The active settings profile, defined in the user settings.
+    Profile(String),
+    /// The user settings for the current release channel.
+    ReleaseChannel,
+    /// The server's settings.
+    Server,
+    /// A `.zed/settings.json` file in the given directory of a worktree.
+    Project {
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+    },
+    /// An `.editorconfig` file in the given directory of a worktree.
+    Editorconfig {
+        worktree_id: WorktreeId,
+        directory: Arc<Path>,
+    },
+}
+
+/// The value a settings layer sets for a setting.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingContribution {
+    pub layer: SettingsLayer,
+    pub value: serde_json::Value,
+}
+
+/// Where the effective value of a setting comes from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SettingProvenance {
+    /// The effective value of the setting, or `None` if no layer sets it.
+    pub value: Option<serde_json::Value>,
+    /// The layers setting a value, from least to most specific.
+    pub contributions: Vec<SettingContribution>,
+}
+
+/// Returns the value an `.editorconfig` file gives to the language setting at the given key path,
+/// mirroring how `.editorconfig` properties are applied to the language settings.
+fn editorconfig_setting_value(
+    properties: &EditorconfigProperties,
+    key_path: &[&str],
+) -> Option<serde_json::Value> {
+    use ec4rs::property::{FinalNewline, IndentSize, IndentStyle, TabWidth, TrimTrailingWs};
+
+    let key = match key_path {
+        [key] | ["languages", _, key] => *key,
+        _ => return None,
+    };
+    match key {
+        "tab_size" => {
+            let tab_size = match properties.get::<IndentSize>().ok()? {
+                IndentSize::Value(size) => size,
+                IndentSize::UseTabWidth => match properties.get::<TabWidth>().ok()? {
+                    TabWidth::Value(width) => width,
+                },
+            };
+            (tab_size > 0).then(|| tab_size.into())
+        }
+        "hard_tabs" => Some((properties.get::<IndentStyle>().ok()? == IndentStyle::Tabs).into()),
+        "ensure_final_newline_on_save" => match properties.get::<FinalNewline>().ok()? {
+            FinalNewline::Value(value) => Some(value.into()),
+        },
+        "remove_trailing_whitespace_on_save" => match properties.get::<TrimTrailingWs>().ok()? {
+            TrimTrailingWs::Value(value) => Some(value.into()),
+        },
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1335,6 +1656,64 @@ fn replace_value_in_json_text(
     }
 }
 
+/// Returns the JSON key path of the innermost property containing the given offset.
+pub fn json_key_path_at_offset(text: &str, offset: usize) -> Vec<String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_json::LANGUAGE.into())
+        .unwrap();
+    let Some(syntax_tree) = parser.parse(text, None) else {
+        return Vec::new();
+    };
+
+    let mut key_path = Vec::new();
+    let mut node = syntax_tree
+        .root_node()
+        .descendant_for_byte_range(offset, offset);
+    while let Some(current) = node {
+        if current.kind() == "pair" {
+            if let Some(key) = current.child_by_field_name("key") {
+                key_path.push(json_key_text(text, key.byte_range()));
+            }
+        }
+        node = current.parent();
+    }
+    key_path.reverse();
+    key_path
+}
+
+/// Returns the range of the key of the property at the given JSON key path, if it exists.
+pub fn json_key_path_range(text: &str, key_path: &[&str]) -> Option<Range<usize>> {
+    let mut parser = tree_sitter::Parser::new();
+    parser
+        .set_language(&tree_sitter_json::LANGUAGE.into())
+        .unwrap();
+    let syntax_tree = parser.parse(text, None)?;
+
+    let root = syntax_tree.root_node();
+    let mut cursor = root.walk();
+    let mut object = root
+        .named_children(&mut cursor)
+        .find(|node| node.kind() == "object")?;
+    let mut key_range = None;
+    for key in key_path {
+        let mut cursor = object.walk();
+        let pair = object.named_children(&mut cursor).find(|pair| {
+            pair.kind() == "pair"
+                && pair
+                    .child_by_field_name("key")
+                    .is_some_and(|node| json_key_text(text, node.byte_range()) == *key)
+        })?;
+        key_range = Some(pair.child_by_field_name("key")?.byte_range());
+        object = pair.child_by_field_name("value")?;
+    }
+    key_range
+}
+
+fn json_key_text(text: &str, range: Range<usize>) -> String {
+    text[range].trim_matches('"').to_string()
+}
+
 fn to_pretty_json(value: &impl Serialize, indent_size: usize, indent_prefix_len: usize) -> String {
     const SPACES: [u8; 32] = [b' '; 32];
 
@@ -1683,6 +2062,239 @@ mod tests {
         pretty_assertions::assert_eq!(new_json, expected_new_json);
     }
 
+    #[gpui::test]
+    fn test_json_merge_edits(cx: &mut App) {
+        let store = SettingsStore::new(cx);
+        let old_json = r#"{
+            // A comment
+            "buffer_font_size": 16,
+            "languages": {
+                "Rust": {
+                    "tab_size": 2
+                }
+            }
+        }"#
+        .unindent();
+        let mut new_json = old_json.clone();
+        let new_values = serde_json::json!({
+            "buffer_font_size": 13,
+            "languages": { "Rust": { "hard_tabs": true } }
+        });
+        for (range, replacement) in store.json_merge_edits(&old_json, &new_values).unwrap() {
+            new_json.replace_range(range, &replacement);
+        }
+        pretty_assertions::assert_eq!(
+            new_json,
+            r#"{
+                // A comment
+                "buffer_font_size": 13,
+                "languages": {
+                    "Rust": {
+                        "hard_tabs": true,
+                        "tab_size": 2
+                    }
+                }
+            }"#
+            .unindent()
+        );
+
+        let edits = store
+            .json_merge_edits("", &serde_json::json!({ "tab_size": 4 }))
+            .unwrap();
+        pretty_assertions::assert_eq!(edits, vec![(0..0, "{\n  \"tab_size\": 4\n}".to_string())]);
+    }
+
+    #[gpui::test]
+    fn test_settings_profiles(cx: &mut App) {
+        let mut store = SettingsStore::new(cx);
+        store.register_setting::<UserSettings>(cx);
+        store.register_setting::<TurboSetting>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "turbo": false,
+                    "user": {
+                        "name": "John Doe",
+                        "age": 30,
+                        "staff": false
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(
+                r#"{
+                    "user": { "age": 31 },
+                    "profiles": {
+                        "presentation": {
+                            "turbo": true,
+                            "user": { "name": "Presenter" },
+                            "key1": "a"
+                        },
+                        "empty": {}
+                    }
+                }"#,
+                cx,
+            )
+            .unwrap();
+
+        let mut profile_names = store.profile_names();
+        profile_names.sort();
+        assert_eq!(profile_names, ["empty", "presentation"]);
+        assert_eq!(store.active_profile(), None);
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(false));
+
+        store
+            .set_active_profile(Some("presentation".into()), cx)
+            .unwrap();
+        assert_eq!(store.active_profile(), Some("presentation"));
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(true));
+        assert_eq!(
+            store.get::<UserSettings>(None),
+            &UserSettings {
+                name: "Presenter".to_string(),
+                age: 31,
+                staff: false,
+            }
+        );
+
+        // Settings registered while a profile is active include it.
+        store.register_setting::<MultiKeySettings>(cx);
+        assert_eq!(store.get::<MultiKeySettings>(None).key1, "a");
+
+        assert!(
+            store
+                .set_active_profile(Some("missing".into()), cx)
+                .is_err()
+        );
+        assert_eq!(store.active_profile(), Some("presentation"));
+
+        store.set_active_profile(None, cx).unwrap();
+        assert_eq!(store.get::<TurboSetting>(None), &TurboSetting(false));
+        assert_eq!(store.get::<UserSettings>(None).name, "John Doe");
+    }
+
+    #[gpui::test]
+    fn test_setting_provenance(cx: &mut App) {
+        let mut store = SettingsStore::new(cx);
+        store.register_setting::<UserSettings>(cx);
+        store
+            .set_default_settings(
+                r#"{
+                    "tab_size": 4,
+                    "user": { "name": "John Doe", "age": 30, "staff": false }
+                }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(r#"{ "user": { "age": 31 } }"#, cx)
+            .unwrap();
+        let worktree_id = WorktreeId::from_usize(1);
+        store
+            .set_local_settings(
+                worktree_id,
+                Path::new("").into(),
+                LocalSettingsKind::Settings,
+                Some(r#"{ "user": { "staff": true } }"#),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                worktree_id,
+                Path::new("b").into(),
+                LocalSettingsKind::Editorconfig,
+                Some("[*.rs]\nindent_size = 2\n"),
+                cx,
+            )
+            .unwrap();
+
+        let location = SettingsLocation {
+            worktree_id,
+            path: Path::new("b/main.rs"),
+        };
+        let project_layer = SettingsLayer::Project {
+            worktree_id,
+            directory: Path::new("").into(),
+        };
+
+        let provenance = store.setting_provenance(&["user", "age"], Some(location));
+        assert_eq!(provenance.value, Some(31.into()));
+        assert_eq!(
+            provenance
+                .contributions
+                .iter()
+                .map(|contribution| (&contribution.layer, &contribution.value))
+                .collect::<Vec<_>>(),
+            [
+                (&SettingsLayer::Default, &30.into()),
+                (&SettingsLayer::User, &31.into())
+            ]
+        );
+
+        let provenance = store.setting_provenance(&["user"], Some(location));
+        assert_eq!(
+            provenance.value,
+            Some(serde_json::json!({ "name": "John Doe", "age": 31, "staff": true }))
+        );
+        assert_eq!(provenance.contributions.len(), 3);
+        assert_eq!(provenance.contributions[2].layer, project_layer);
+
+        let provenance = store.setting_provenance(&["tab_size"], Some(location));
+        assert_eq!(provenance.value, Some(2.into()));
+        assert_eq!(
+            provenance.contributions.last().unwrap().layer,
+            SettingsLayer::Editorconfig {
+                worktree_id,
+                directory: Path::new("b").into(),
+            }
+        );
+
+        // Without a location, project settings don't apply.
+        let provenance = store.setting_provenance(&["tab_size"], None);
+        assert_eq!(provenance.value, Some(4.into()));
+        assert_eq!(provenance.contributions.len(), 1);
+
+        assert_eq!(
+            store.setting_provenance(&["missing"], None),
+            SettingProvenance::default()
+        );
+    }
+
+    #[test]
+    fn test_json_key_paths() {
+        let text = r#"{
+            // A comment
+            "user": {
+                "name": "John Doe",
+                "age": 31
+            },
+            "languages": {
+                "Rust": { "tab_size": 2 }
+            }
+        }"#;
+
+        let key_path_at = |needle: &str| json_key_path_at_offset(text, text.find(needle).unwrap());
+        assert_eq!(key_path_at("John"), ["user", "name"]);
+        assert_eq!(key_path_at("\"age"), ["user", "age"]);
+        assert_eq!(key_path_at("\"Rust"), ["languages", "Rust"]);
+        assert_eq!(key_path_at("2 }"), ["languages", "Rust", "tab_size"]);
+        assert!(key_path_at("A comment").is_empty());
+
+        assert_eq!(
+            json_key_path_range(text, &["user", "age"]),
+            text.find("\"age\"").map(|start| start..start + 5)
+        );
+        assert_eq!(
+            json_key_path_range(text, &["languages", "Rust", "tab_size"]),
+            text.find("\"tab_size\"").map(|start| start..start + 10)
+        );
+        assert_eq!(json_key_path_range(text, &["user", "staff"]), None);
+        assert_eq!(json_key_path_range(text, &["age"]), None);
+    }
+
     #[derive(Debug, PartialEq, Deserialize)]
     struct UserSettings {
         name: String,
@@ -0,0 +1,231 @@
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, Focusable, Render, UpdateGlobal, WeakEntity,
+    Window, actions,
+};
+use picker::{Picker, PickerDelegate};
+use settings::SettingsStore;
+use std::sync::Arc;
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+actions!(settings_profile_selector, [Toggle]);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &Toggle, window, cx| {
+            workspace.toggle_modal(window, cx, |window, cx| {
+                let delegate = SettingsProfileSelectorDelegate::new(cx.entity().downgrade(), cx);
+                SettingsProfileSelector::new(delegate, window, cx)
+            });
+        });
+    })
+    .detach();
+}
+
+pub struct SettingsProfileSelector {
+    picker: Entity<Picker<SettingsProfileSelectorDelegate>>,
+}
+
+impl ModalView for SettingsProfileSelector {}
+
+impl EventEmitter<DismissEvent> for SettingsProfileSelector {}
+
+impl Focusable for SettingsProfileSelector {
+    fn focus_handle(&self, cx: &App) -> gpui::FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingsProfileSelector {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl SettingsProfileSelector {
+    fn new(
+        delegate: SettingsProfileSelectorDelegate,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+        Self { picker }
+    }
+}
+
+/// The label of the entry that removes the active profile.
+const NO_PROFILE: &str = "Disabled";
+
+pub struct SettingsProfileSelectorDelegate {
+    /// The profiles defined in the user settings, with `None` standing for no profile.
+    profiles: Vec<Option<String>>,
+    matches: Vec<StringMatch>,
+    original_profile: Option<String>,
+    selection_completed: bool,
+    selected_index: usize,
+    selector: WeakEntity<SettingsProfileSelector>,
+}
+
+impl SettingsProfileSelectorDelegate {
+    fn new(selector: WeakEntity<SettingsProfileSelector>, cx: &App) -> Self {
+        let store = cx.global::<SettingsStore>();
+        let original_profile = store.active_profile().map(ToString::to_string);
+        let mut profile_names = store.profile_names();
+        profile_names.sort_unstable();
+
+        let profiles = std::iter::once(None)
+            .chain(profile_names.into_iter().map(Some))
+            .collect::<Vec<_>>();
+        let matches = profiles
+            .iter()
+            .enumerate()
+            .map(|(id, profile)| StringMatch {
+                candidate_id: id,
+                score: 0.0,
+                positions: Default::default(),
+                string: profile_label(profile).to_string(),
+            })
+            .collect();
+        let selected_index = profiles
+            .iter()
+            .position(|profile| *profile == original_profile)
+            .unwrap_or(0);
+
+        Self {
+            profiles,
+            matches,
+            original_profile,
+            selection_completed: false,
+            selected_index,
+            selector,
+        }
+    }
+
+    fn show_selected_profile(&self, cx: &mut App) {
+        if let Some(mat) = self.matches.get(self.selected_index) {
+            Self::set_profile(self.profiles[mat.candidate_id].clone(), cx);
+        }
+    }
+
+    fn set_profile(profile: Option<String>, cx: &mut App) {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.set_active_profile(profile, cx).log_err();
+        });
+        cx.refresh_windows();
+    }
+}
+
+fn profile_label(profile: &Option<String>) -> &str {
+    profile.as_deref().unwrap_or(NO_PROFILE)
+}
+
+impl PickerDelegate for SettingsProfileSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select Settings Profile...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+        self.show_selected_profile(cx);
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> gpui::Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .profiles
+            .iter()
+            .enumerate()
+            .map(|(id, profile)| StringMatchCandidate::new(id, profile_label(profile)))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = this
+                    .delegate
+                    .selected_index
+                    .min(this.delegate.matches.len().saturating_sub(1));
+                this.delegate.show_selected_profile(cx);
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.selection_completed = true;
+        self.selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if !self.selection_completed {
+            Self::set_profile(self.original_profile.clone(), cx);
+            self.selection_completed = true;
+        }
+
+        self.selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let profile_match = &self.matches[ix];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    profile_match.string.clone(),
+                    profile_match.positions.clone(),
+                )),
+        )
+    }
+}
@@ -0,0 +1,534 @@
+//! A reader for Hunspell dictionaries.
+//!
+//! Only the subset of the affix file format needed to check words and suggest corrections is
+//! supported: prefixes and suffixes (including their cross products), the `FLAG` types, `TRY`,
+//! `REP`, `FORBIDDENWORD`, `NEEDAFFIX` and `NOSUGGEST`. Compounding and morphological analysis
+//! are ignored.
+
+use anyhow::{Result, anyhow};
+use collections::{HashMap, HashSet};
+
+type Flag = u64;
+
+const DEFAULT_TRY_CHARS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+pub struct Dictionary {
+    words: HashMap<String, Vec<Flag>>,
+    /// Prefix entries, keyed by the text they add to the start of a word.
+    prefixes: HashMap<String, Vec<AffixEntry>>,
+    /// Suffix entries, keyed by the text they add to the end of a word.
+    suffixes: HashMap<String, Vec<AffixEntry>>,
+    try_chars: Vec<char>,
+    replacements: Vec<(String, String)>,
+    forbidden_flag: Option<Flag>,
+    need_affix_flag: Option<Flag>,
+    no_suggest_flag: Option<Flag>,
+}
+
+#[derive(Clone, Copy, Default)]
+enum FlagType {
+    #[default]
+    Short,
+    Long,
+    Numeric,
+    Utf8,
+}
+
+impl FlagType {
+    fn parse(self, flags: &str) -> Vec<Flag> {
+        match self {
+            FlagType::Short | FlagType::Utf8 => flags.chars().map(|flag| flag as Flag).collect(),
+            FlagType::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| {
+                    pair.iter()
+                        .fold(0, |flag, &char| (flag << 32) | char as Flag)
+                })
+                .collect(),
+            FlagType::Numeric => flags
+                .split(',')
+                .filter_map(|flag| flag.trim().parse().ok())
+                .collect(),
+        }
+    }
+
+    fn parse_one(self, flag: &str) -> Option<Flag> {
+        self.parse(flag).first().copied()
+    }
+}
+
+struct AffixEntry {
+    flag: Flag,
+    strip: String,
+    condition: Condition,
+    cross_product: bool,
+}
+
+/// The condition an affix entry places on the stem it is applied to, written as a sequence of
+/// characters, `.` wildcards and `[...]`/`[^...]` character classes.
+struct Condition(Vec<ConditionPart>);
+
+enum ConditionPart {
+    Any,
+    Char(char),
+    Class { chars: Vec<char>, negated: bool },
+}
+
+impl Condition {
+    fn parse(source: &str) -> Self {
+        if source == "." {
+            return Self(Vec::new());
+        }
+
+        let mut parts = Vec::new();
+        let mut chars = source.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                '.' => parts.push(ConditionPart::Any),
+                '[' => {
+                    let mut class = Vec::new();
+                    let mut negated = false;
+                    for (ix, char) in chars.by_ref().enumerate() {
+                        match char {
+                            '^' if ix == 0 => negated = true,
+                            ']' => break,
+                            _ => class.push(char),
+                        }
+                    }
+                    parts.push(ConditionPart::Class {
+                        chars: class,
+                        negated,
+                    });
+                }
+                _ => parts.push(ConditionPart::Char(char)),
+            }
+        }
+        Self(parts)
+    }
+
+    fn matches_start(&self, stem: &str) -> bool {
+        let mut chars = stem.chars();
+        self.0
+            .iter()
+            .all(|part| chars.next().is_some_and(|char| part.matches(char)))
+    }
+
+    fn matches_end(&self, stem: &str) -> bool {
+        let mut chars = stem.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|part| chars.next().is_some_and(|char| part.matches(char)))
+    }
+}
+
+impl ConditionPart {
+    fn matches(&self, char: char) -> bool {
+        match self {
+            ConditionPart::Any => true,
+            ConditionPart::Char(expected) => *expected == char,
+            ConditionPart::Class { chars, negated } => chars.contains(&char) != *negated,
+        }
+    }
+}
+
+impl Dictionary {
+    /// Parses a dictionary from the contents of its `.aff` and `.dic` files. Files that aren't
+    /// valid UTF-8 are decoded as ISO-8859-1, the encoding most legacy dictionaries use.
+    pub fn from_bytes(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        Self::parse(&decode(aff), &decode(dic))
+    }
+
+    pub fn parse(aff: &str, dic: &str) -> Result<Self> {
+        let mut dictionary = Self {
+            words: HashMap::default(),
+            prefixes: HashMap::default(),
+            suffixes: HashMap::default(),
+            try_chars: DEFAULT_TRY_CHARS.chars().collect(),
+            replacements: Vec::new(),
+            forbidden_flag: None,
+            need_affix_flag: None,
+            no_suggest_flag: None,
+        };
+
+        let lines = aff
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .filter(|fields| fields.first().is_some_and(|field| !field.starts_with('#')))
+            .collect::<Vec<_>>();
+
+        // Flags can't be interpreted before knowing their type.
+        let flag_type = lines
+            .iter()
+            .find(|fields| fields[0] == "FLAG")
+            .and_then(|fields| match fields.get(1).copied() {
+                Some("long") => Some(FlagType::Long),
+                Some("num") => Some(FlagType::Numeric),
+                Some("UTF-8") => Some(FlagType::Utf8),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let mut cross_products = HashMap::default();
+        for fields in &lines {
+            match fields.as_slice() {
+                ["TRY", chars, ..] => dictionary.try_chars = chars.chars().collect(),
+                ["REP", count] if count.parse::<usize>().is_ok() => {}
+                ["REP", from, to, ..] => dictionary
+                    .replacements
+                    .push((from.replace('_', " "), to.replace('_', " "))),
+                ["FORBIDDENWORD", flag, ..] => {
+                    dictionary.forbidden_flag = flag_type.parse_one(flag);
+                }
+                ["NEEDAFFIX", flag, ..] => dictionary.need_affix_flag = flag_type.parse_one(flag),
+                ["NOSUGGEST", flag, ..] => dictionary.no_suggest_flag = flag_type.parse_one(flag),
+                [kind @ ("PFX" | "SFX"), flag, rest @ ..] => {
+                    let flag = flag_type
+                        .parse_one(flag)
+                        .ok_or_else(|| anyhow!("invalid affix flag {flag:?}"))?;
+                    if let [cross_product @ ("Y" | "N"), count] = rest {
+                        if count.parse::<usize>().is_ok()
+                            && !cross_products.contains_key(&(*kind, flag))
+                        {
+                            cross_products.insert((*kind, flag), *cross_product == "Y");
+                            continue;
+                        }
+                    }
+
+                    let [strip, add, condition @ ..] = rest else {
+                        return Err(anyhow!("invalid affix entry {fields:?}"));
+                    };
+                    let add = add.split('/').next().unwrap_or_default();
+                    let entry = AffixEntry {
+                        flag,
+                        strip: if *strip == "0" {
+                            String::new()
+                        } else {
+                            strip.to_string()
+                        },
+                        condition: Condition::parse(condition.first().copied().unwrap_or(".")),
+                        cross_product: cross_products.get(&(*kind, flag)).copied().unwrap_or(false),
+                    };
+                    let add = if add == "0" { "" } else { add };
+                    let affixes = if *kind == "PFX" {
+                        &mut dictionary.prefixes
+                    } else {
+                        &mut dictionary.suffixes
+                    };
+                    affixes.entry(add.to_string()).or_default().push(entry);
+                }
+                _ => {}
+            }
+        }
+
+        let mut lines = dic.lines();
+        let first_line = lines.next().unwrap_or_default().trim();
+        let lines = first_line
+            .parse::<usize>()
+            .is_err()
+            .then_some(first_line)
+            .into_iter()
+            .chain(lines);
+        for line in lines {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+            let (word, flags) = split_dic_entry(entry);
+            if word.is_empty() {
+                continue;
+            }
+            dictionary
+                .words
+                .entry(word)
+                .or_default()
+                .extend(flag_type.parse(flags));
+        }
+
+        Ok(dictionary)
+    }
+
+    /// Returns whether the word is spelled correctly. Capitalized and upper case variants of words
+    /// in the dictionary are accepted as well.
+    pub fn check(&self, word: &str) -> bool {
+        if word.is_empty() || self.check_case_sensitive(word) {
+            return true;
+        }
+
+        let is_uppercase = word.chars().all(|char| !char.is_lowercase());
+        let is_capitalized = word.chars().skip(1).all(|char| !char.is_uppercase());
+        if !is_uppercase && !is_capitalized {
+            return false;
+        }
+
+        let lowercase = word.to_lowercase();
+        self.check_case_sensitive(&lowercase)
+            || (is_uppercase && self.check_case_sensitive(&capitalize(&lowercase)))
+    }
+
+    /// Returns up to `limit` correctly spelled words that are similar to the given word.
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        let mut seen = HashSet::default();
+        let mut push = |candidate: String| {
+            if suggestions.len() < limit
+                && seen.insert(candidate.clone())
+                && candidate != word
+                && candidate
+                    .split(' ')
+                    .all(|word| self.check(word) && self.is_suggestible(word))
+            {
+                suggestions.push(candidate);
+            }
+        };
+
+        for (from, to) in &self.replacements {
+            for (ix, _) in word.match_indices(from.as_str()) {
+                push(format!("{}{to}{}", &word[..ix], &word[ix + from.len()..]));
+            }
+        }
+
+        let chars = word.chars().collect::<Vec<_>>();
+        let candidate = |chars: &[char]| chars.iter().collect::<String>();
+        for ix in 0..chars.len() {
+            if ix + 1 < chars.len() {
+                let mut swapped = chars.clone();
+                swapped.swap(ix, ix + 1);
+                push(candidate(&swapped));
+            }
+
+            let mut deleted = chars.clone();
+            deleted.remove(ix);
+            push(candidate(&deleted));
+
+            for &try_char in &self.try_chars {
+                let mut replaced = chars.clone();
+                replaced[ix] = try_char;
+                push(candidate(&replaced));
+            }
+        }
+        for ix in 0..=chars.len() {
+            for &try_char in &self.try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(ix, try_char);
+                push(candidate(&inserted));
+            }
+        }
+        for ix in 1..chars.len() {
+            push(format!(
+                "{} {}",
+                candidate(&chars[..ix]),
+                candidate(&chars[ix..])
+            ));
+        }
+
+        if word.chars().next().is_some_and(char::is_uppercase) {
+            for suggestion in &mut suggestions {
+                *suggestion = capitalize(suggestion);
+            }
+        }
+        suggestions
+    }
+
+    fn check_case_sensitive(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self
+                .forbidden_flag
+                .is_some_and(|flag| flags.contains(&flag))
+            {
+                return false;
+            }
+            if !self
+                .need_affix_flag
+                .is_some_and(|flag| flags.contains(&flag))
+            {
+                return true;
+            }
+        }
+        self.check_suffixed(word) || self.check_prefixed(word, None)
+    }
+
+    fn check_suffixed(&self, word: &str) -> bool {
+        char_boundaries(word).any(|ix| {
+            let Some(entries) = self.suffixes.get(&word[ix..]) else {
+                return false;
+            };
+            entries.iter().any(|entry| {
+                let stem = format!("{}{}", &word[..ix], entry.strip);
+                !stem.is_empty()
+                    && entry.condition.matches_end(&stem)
+                    && (self.stem_has_flag(&stem, entry.flag)
+                        || entry.cross_product && self.check_prefixed(&stem, Some(entry.flag)))
+            })
+        })
+    }
+
+    /// Checks whether the word is a stem with a prefix applied. When `suffix_flag` is given, the
+    /// stem must also allow the suffix that was already stripped from the word.
+    fn check_prefixed(&self, word: &str, suffix_flag: Option<Flag>) -> bool {
+        char_boundaries(word).any(|ix| {
+            let Some(entries) = self.prefixes.get(&word[..ix]) else {
+                return false;
+            };
+            entries.iter().any(|entry| {
+                let stem = format!("{}{}", entry.strip, &word[ix..]);
+                !stem.is_empty()
+                    && entry.condition.matches_start(&stem)
+                    && self.stem_has_flag(&stem, entry.flag)
+                    && suffix_flag.is_none_or(|suffix_flag| {
+                        entry.cross_product && self.stem_has_flag(&stem, suffix_flag)
+                    })
+            })
+        })
+    }
+
+    fn stem_has_flag(&self, stem: &str, flag: Flag) -> bool {
+        self.words.get(stem).is_some_and(|flags| {
+            flags.contains(&flag)
+                && !self
+                    .forbidden_flag
+                    .is_some_and(|flag| flags.contains(&flag))
+        })
+    }
+
+    fn is_suggestible(&self, word: &str) -> bool {
+        let Some(no_suggest_flag) = self.no_suggest_flag else {
+            return true;
+        };
+        [word.to_string(), word.to_lowercase()].iter().all(|word| {
+            !self
+                .words
+                .get(word)
+                .is_some_and(|flags| flags.contains(&no_suggest_flag))
+        })
+    }
+}
+
+fn char_boundaries(word: &str) -> impl Iterator<Item = usize> + '_ {
+    word.char_indices()
+        .map(|(ix, _)| ix)
+        .chain(Some(word.len()))
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+/// Splits a `.dic` entry into its word and flags, honoring escaped slashes in the word.
+fn split_dic_entry(entry: &str) -> (String, &str) {
+    let mut word = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((ix, char)) = chars.next() {
+        match char {
+            '\\' => {
+                if let Some((_, escaped)) = chars.next() {
+                    word.push(escaped);
+                }
+            }
+            '/' => return (word, &entry[ix + 1..]),
+            _ => word.push(char),
+        }
+    }
+    (word, "")
+}
+
+fn decode(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::parse(
+            indoc! {"
+                SET UTF-8
+                TRY esianrtolcdugmphbyfvkwz
+                FORBIDDENWORD !
+                NOSUGGEST ?
+
+                REP 1
+                REP f ph
+
+                PFX A Y 1
+                PFX A 0 re .
+
+                SFX B Y 2
+                SFX B 0 ed [^y]
+                SFX B y ied y
+
+                SFX S N 1
+                SFX S 0 s .
+            "},
+            indoc! {"
+                6
+                hello/S
+                work/AB
+                carry/B
+                phone/S
+                teh/!
+                darn/?
+            "},
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_check() {
+        let dictionary = dictionary();
+        for word in [
+            "hello", "hellos", "Hello", "HELLO", "worked", "rework", "reworked", "carried",
+            "phones", "darn",
+        ] {
+            assert!(dictionary.check(word), "{word:?} should be correct");
+        }
+        for word in ["helo", "carryed", "rehello", "teh", "Teh", "hELLO"] {
+            assert!(!dictionary.check(word), "{word:?} should be misspelled");
+        }
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dictionary = dictionary();
+        assert_eq!(dictionary.suggest("helo", 3), vec!["hello"]);
+        assert_eq!(dictionary.suggest("fone", 3), vec!["phone"]);
+        assert_eq!(dictionary.suggest("Wrok", 3), vec!["Work"]);
+        assert_eq!(dictionary.suggest("hellowork", 3), vec!["hello work"]);
+        // Words marked as not to be suggested are still accepted.
+        assert_eq!(dictionary.suggest("darnn", 3), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_flag_types() {
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                FLAG long
+                SFX Aa Y 1
+                SFX Aa 0 s .
+            "},
+            "cat/AaBb\n",
+        )
+        .unwrap();
+        assert!(dictionary.check("cats"));
+
+        let dictionary = Dictionary::parse(
+            indoc! {"
+                FLAG num
+                SFX 101 Y 1
+                SFX 101 0 s .
+            "},
+            "dog/7,101\n",
+        )
+        .unwrap();
+        assert!(dictionary.check("dogs"));
+    }
+}
@@ -0,0 +1,379 @@
+use std::{ops::Range, path::Path, rc::Rc, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use editor::{Addon, CodeActionProvider, Editor, EditorEvent, ExcerptId};
+use gpui::{
+    App, AppContext as _, Context, Entity, HighlightStyle, Subscription, Task, UnderlineStyle,
+    WeakEntity, Window, px,
+};
+use language::{Buffer, BufferId, LanguageServerId};
+use project::{CodeAction, LspAction, Project, ProjectTransaction};
+use serde::{Deserialize, Serialize};
+use settings::{Settings as _, SettingsStore};
+use text::ToOffset as _;
+use theme::ActiveTheme as _;
+use util::ResultExt as _;
+
+use crate::{SpellcheckSettings, Spellchecker, WordChecker, tokenizer};
+
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(300);
+const MAX_SUGGESTIONS: usize = 5;
+const CODE_ACTION_PROVIDER_ID: &str = "spellcheck";
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(EditorSpellcheck::register).detach();
+}
+
+struct Misspelling {
+    range: Range<text::Anchor>,
+    word: String,
+}
+
+/// The action a spell checking code action performs, stored in the code action's data.
+#[derive(Serialize, Deserialize)]
+enum SpellcheckAction {
+    Replace { replacement: String },
+    AddToProjectDictionary { word: String },
+}
+
+/// Underlines the misspelled words in an editor and offers code actions to fix them.
+struct EditorSpellcheck {
+    editor: WeakEntity<Editor>,
+    project: Option<Entity<Project>>,
+    misspellings: HashMap<BufferId, Vec<Misspelling>>,
+    refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct SpellcheckAddon(Entity<EditorSpellcheck>);
+
+impl Addon for SpellcheckAddon {
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl EditorSpellcheck {
+    fn register(editor: &mut Editor, window: Option<&mut Window>, cx: &mut Context<Editor>) {
+        let Some(window) = window else {
+            return;
+        };
+        if !editor.mode().is_full() {
+            return;
+        }
+        let Some(spellchecker) = Spellchecker::global(cx) else {
+            return;
+        };
+
+        let editor_handle = cx.entity();
+        let project = editor.project.clone();
+        let spellcheck = cx.new(|cx| {
+            let subscriptions = vec![
+                cx.subscribe(&editor_handle, |this, _, event, cx| match event {
+                    EditorEvent::BufferEdited
+                    | EditorEvent::Reparsed(_)
+                    | EditorEvent::ExcerptsAdded { .. }
+                    | EditorEvent::ExcerptsEdited { .. } => this.schedule_refresh(cx),
+                    _ => {}
+                }),
+                cx.observe(&spellchecker, |this, _, cx| this.schedule_refresh(cx)),
+                cx.observe_global::<SettingsStore>(|this, cx| this.schedule_refresh(cx)),
+            ];
+            let mut this = Self {
+                editor: editor_handle.downgrade(),
+                project,
+                misspellings: HashMap::default(),
+                refresh_task: Task::ready(()),
+                _subscriptions: subscriptions,
+            };
+            this.load_project_dictionaries(cx);
+            this.schedule_refresh(cx);
+            this
+        });
+
+        editor.add_code_action_provider(
+            Rc::new(SpellcheckCodeActionProvider {
+                spellcheck: spellcheck.downgrade(),
+            }),
+            window,
+            cx,
+        );
+        editor.register_addon(SpellcheckAddon(spellcheck));
+    }
+
+    fn worktree_roots(&self, cx: &App) -> Vec<Arc<Path>> {
+        let Some(project) = &self.project else {
+            return Vec::new();
+        };
+        let project = project.read(cx);
+        if !project.is_local() {
+            return Vec::new();
+        }
+        project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+            .collect()
+    }
+
+    fn load_project_dictionaries(&self, cx: &mut Context<Self>) {
+        let Some(spellchecker) = Spellchecker::global(cx) else {
+            return;
+        };
+        let worktree_roots = self.worktree_roots(cx);
+        spellchecker.update(cx, |spellchecker, cx| {
+            for root in worktree_roots {
+                spellchecker.load_project_dictionary(root, cx);
+            }
+        });
+    }
+
+    fn word_checker(&self, cx: &App) -> Option<WordChecker> {
+        let spellchecker = Spellchecker::global(cx)?;
+        let worktree_roots = self.worktree_roots(cx);
+        Some(
+            spellchecker
+                .read(cx)
+                .word_checker(worktree_roots.iter().map(AsRef::as_ref)),
+        )
+    }
+
+    fn schedule_refresh(&mut self, cx: &mut Context<Self>) {
+        let Some(editor) = self.editor.upgrade() else {
+            return;
+        };
+        let checker = self
+            .word_checker(cx)
+            .filter(|checker| SpellcheckSettings::get_global(cx).enabled && !checker.is_empty());
+        let Some(checker) = checker else {
+            self.misspellings.clear();
+            self.refresh_task = Task::ready(());
+            editor.update(cx, |editor, cx| editor.clear_highlights::<Self>(cx));
+            return;
+        };
+
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        let excerpts = snapshot
+            .excerpts()
+            .map(|(excerpt_id, buffer, range)| (excerpt_id, buffer.clone(), range.context))
+            .collect::<Vec<_>>();
+        self.refresh_task = cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            let misspellings = cx
+                .background_spawn(async move {
+                    excerpts
+                        .into_iter()
+                        .map(|(excerpt_id, buffer, range)| {
+                            let misspellings = find_misspellings(&buffer, range, &checker);
+                            (excerpt_id, buffer.remote_id(), misspellings)
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+
+            this.update(cx, |this, cx| {
+                let Some(editor) = this.editor.upgrade() else {
+                    return;
+                };
+                editor.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let ranges = misspellings
+                        .iter()
+                        .flat_map(|(excerpt_id, _, misspellings)| {
+                            misspellings.iter().filter_map(|misspelling| {
+                                Some(
+                                    snapshot
+                                        .anchor_in_excerpt(*excerpt_id, misspelling.range.start)?
+                                        ..snapshot.anchor_in_excerpt(
+                                            *excerpt_id,
+                                            misspelling.range.end,
+                                        )?,
+                                )
+                            })
+                        })
+                        .collect();
+                    let style = HighlightStyle {
+                        underline: Some(UnderlineStyle {
+                            thickness: px(1.),
+                            color: Some(cx.theme().status().info),
+                            wavy: true,
+                        }),
+                        ..Default::default()
+                    };
+                    editor.highlight_text::<Self>(ranges, style, cx);
+                });
+
+                this.misspellings.clear();
+                for (_, buffer_id, misspellings) in misspellings {
+                    this.misspellings
+                        .entry(buffer_id)
+                        .or_default()
+                        .extend(misspellings);
+                }
+            })
+            .log_err();
+        });
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        cx: &App,
+    ) -> Vec<CodeAction> {
+        let buffer = buffer.read(cx);
+        let Some(misspellings) = self.misspellings.get(&buffer.remote_id()) else {
+            return Vec::new();
+        };
+        let Some(checker) = self.word_checker(cx) else {
+            return Vec::new();
+        };
+        let can_add_to_project_dictionary = !self.worktree_roots(cx).is_empty();
+
+        let mut actions = Vec::new();
+        for misspelling in misspellings {
+            let intersects = misspelling.range.start.cmp(&range.end, buffer).is_le()
+                && misspelling.range.end.cmp(&range.start, buffer).is_ge();
+            if !intersects {
+                continue;
+            }
+
+            let word = &misspelling.word;
+            let mut push_action = |title: String, action: SpellcheckAction| {
+                actions.push(CodeAction {
+                    server_id: LanguageServerId(0),
+                    range: misspelling.range.clone(),
+                    lsp_action: LspAction::Action(Box::new(lsp::CodeAction {
+                        title,
+                        data: serde_json::to_value(action).log_err(),
+                        ..Default::default()
+                    })),
+                    resolved: true,
+                });
+            };
+            for replacement in checker.suggest(word, MAX_SUGGESTIONS) {
+                push_action(
+                    format!("Change to “{replacement}”"),
+                    SpellcheckAction::Replace { replacement },
+                );
+            }
+            if can_add_to_project_dictionary {
+                push_action(
+                    format!("Add “{word}” to project dictionary"),
+                    SpellcheckAction::AddToProjectDictionary { word: word.clone() },
+                );
+            }
+        }
+        actions
+    }
+
+    /// Returns the root of the worktree whose dictionary words in the given buffer should be added
+    /// to, preferring the worktree that contains the buffer's file.
+    fn project_dictionary_root(&self, buffer: &Entity<Buffer>, cx: &App) -> Option<Arc<Path>> {
+        let worktree_roots = self.worktree_roots(cx);
+        self.project
+            .as_ref()
+            .zip(buffer.read(cx).file())
+            .and_then(|(project, file)| project.read(cx).worktree_for_id(file.worktree_id(cx), cx))
+            .map(|worktree| worktree.read(cx).abs_path())
+            .filter(|root| worktree_roots.contains(root))
+            .or_else(|| worktree_roots.into_iter().next())
+    }
+}
+
+fn find_misspellings(
+    buffer: &language::BufferSnapshot,
+    range: Range<text::Anchor>,
+    checker: &WordChecker,
+) -> Vec<Misspelling> {
+    let range = range.start.to_offset(buffer)..range.end.to_offset(buffer);
+    let mut misspellings = Vec::new();
+    for range in buffer.spellcheck_ranges(range) {
+        let text = buffer.text_for_range(range.clone()).collect::<String>();
+        for word_range in tokenizer::words(&text) {
+            let word = &text[word_range.clone()];
+            if !checker.check(word) {
+                misspellings.push(Misspelling {
+                    range: buffer.anchor_after(range.start + word_range.start)
+                        ..buffer.anchor_before(range.start + word_range.end),
+                    word: word.to_string(),
+                });
+            }
+        }
+    }
+    misspellings
+}
+
+struct SpellcheckCodeActionProvider {
+    spellcheck: WeakEntity<EditorSpellcheck>,
+}
+
+impl CodeActionProvider for SpellcheckCodeActionProvider {
+    fn id(&self) -> Arc<str> {
+        CODE_ACTION_PROVIDER_ID.into()
+    }
+
+    fn code_actions(
+        &self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        _: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Vec<CodeAction>>> {
+        let actions = self
+            .spellcheck
+            .upgrade()
+            .map(|spellcheck| spellcheck.read(cx).code_actions(buffer, range, cx))
+            .unwrap_or_default();
+        Task::ready(Ok(actions))
+    }
+
+    fn apply_code_action(
+        &self,
+        buffer: Entity<Buffer>,
+        action: CodeAction,
+        _excerpt_id: ExcerptId,
+        _push_to_history: bool,
+        _window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<ProjectTransaction>> {
+        let LspAction::Action(lsp_action) = &action.lsp_action else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+        let spellcheck_action = lsp_action
+            .data
+            .clone()
+            .context("missing spellcheck action data")
+            .and_then(|data| Ok(serde_json::from_value::<SpellcheckAction>(data)?));
+        let spellcheck_action = match spellcheck_action {
+            Ok(spellcheck_action) => spellcheck_action,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        match spellcheck_action {
+            SpellcheckAction::Replace { replacement } => {
+                buffer.update(cx, |buffer, cx| {
+                    buffer.edit([(action.range, replacement)], None, cx);
+                });
+                Task::ready(Ok(ProjectTransaction::default()))
+            }
+            SpellcheckAction::AddToProjectDictionary { word } => {
+                let worktree_root = self.spellcheck.upgrade().and_then(|spellcheck| {
+                    spellcheck.read(cx).project_dictionary_root(&buffer, cx)
+                });
+                let Some((worktree_root, spellchecker)) =
+                    worktree_root.zip(Spellchecker::global(cx))
+                else {
+                    return Task::ready(Ok(ProjectTransaction::default()));
+                };
+                let task = spellchecker.update(cx, |spellchecker, cx| {
+                    spellchecker.add_to_project_dictionary(&worktree_root, word, cx)
+                });
+                cx.background_spawn(async move {
+                    task.await?;
+                    Ok(ProjectTransaction::default())
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+mod dictionary;
+mod editor_spellcheck;
+mod tokenizer;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use fs::Fs;
+use gpui::{App, AppContext as _, Context, Entity, Global, Task};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources, SettingsStore};
+use util::ResultExt as _;
+
+pub use dictionary::Dictionary;
+pub use tokenizer::words;
+
+/// The file, relative to a worktree root, listing the words accepted in that project.
+const PROJECT_DICTIONARY_PATH: &str = ".zed/dictionary.txt";
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SpellcheckSettings {
+    pub enabled: bool,
+    pub languages: Vec<String>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct SpellcheckSettingsContent {
+    /// Whether to underline misspelled words in comments, strings and prose.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The Hunspell dictionaries to check words against, such as "en_US" or "de_DE".
+    ///
+    /// Default: ["en_US"]
+    pub languages: Option<Vec<String>>,
+}
+
+impl Settings for SpellcheckSettings {
+    const KEY: Option<&'static str> = Some("spellcheck");
+
+    type FileContent = SpellcheckSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut App) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut App) {
+    SpellcheckSettings::register(cx);
+
+    let fs = <dyn Fs>::global(cx);
+    let spellchecker = cx.new(|cx| Spellchecker::new(fs, cx));
+    cx.set_global(GlobalSpellchecker(spellchecker));
+
+    editor_spellcheck::init(cx);
+}
+
+struct GlobalSpellchecker(Entity<Spellchecker>);
+
+impl Global for GlobalSpellchecker {}
+
+/// Holds the dictionaries configured in the settings, along with the words that were added to the
+/// dictionaries of individual projects.
+pub struct Spellchecker {
+    fs: Arc<dyn Fs>,
+    languages: Vec<String>,
+    dictionaries: Vec<Arc<Dictionary>>,
+    project_words: HashMap<PathBuf, Arc<HashSet<String>>>,
+    load_dictionaries_task: Task<()>,
+}
+
+/// A snapshot of the words accepted by the spellchecker, which can be used on a background thread.
+#[derive(Clone)]
+pub struct WordChecker {
+    dictionaries: Vec<Arc<Dictionary>>,
+    project_words: Vec<Arc<HashSet<String>>>,
+}
+
+impl WordChecker {
+    pub fn is_empty(&self) -> bool {
+        self.dictionaries.is_empty()
+    }
+
+    pub fn check(&self, word: &str) -> bool {
+        self.project_words.iter().any(|words| words.contains(word))
+            || self
+                .dictionaries
+                .iter()
+                .any(|dictionary| dictionary.check(word))
+    }
+
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let mut suggestions = Vec::new();
+        for dictionary in &self.dictionaries {
+            for suggestion in dictionary.suggest(word, limit) {
+                if suggestions.len() < limit && !suggestions.contains(&suggestion) {
+                    suggestions.push(suggestion);
+                }
+            }
+        }
+        suggestions
+    }
+}
+
+impl Spellchecker {
+    pub fn global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalSpellchecker>()
+            .map(|spellchecker| spellchecker.0.clone())
+    }
+
+    fn new(fs: Arc<dyn Fs>, cx: &mut Context<Self>) -> Self {
+        cx.observe_global::<SettingsStore>(|this, cx| this.load_dictionaries(cx))
+            .detach();
+        let mut this = Self {
+            fs,
+            languages: Vec::new(),
+            dictionaries: Vec::new(),
+            project_words: HashMap::default(),
+            load_dictionaries_task: Task::ready(()),
+        };
+        this.load_dictionaries(cx);
+        this
+    }
+
+    /// Returns the words accepted in files belonging to any of the given worktrees.
+    pub fn word_checker<'a>(
+        &self,
+        worktree_roots: impl IntoIterator<Item = &'a Path>,
+    ) -> WordChecker {
+        WordChecker {
+            dictionaries: self.dictionaries.clone(),
+            project_words: worktree_roots
+                .into_iter()
+                .filter_map(|root| self.project_words.get(root).cloned())
+                .collect(),
+        }
+    }
+
+    /// Loads the project dictionary of the worktree at the given path, if it wasn't loaded yet.
+    pub fn load_project_dictionary(&mut self, worktree_root: Arc<Path>, cx: &mut Context<Self>) {
+        if self.project_words.contains_key(worktree_root.as_ref()) {
+            return;
+        }
+        self.project_words
+            .insert(worktree_root.to_path_buf(), Arc::default());
+
+        let fs = self.fs.clone();
+        cx.spawn(async move |this, cx| {
+            let path = worktree_root.join(PROJECT_DICTIONARY_PATH);
+            let Ok(contents) = fs.load(&path).await else {
+                return;
+            };
+            let words = contents
+                .lines()
+                .map(str::trim)
+                .filter(|word| !word.is_empty())
+                .map(ToString::to_string)
+                .collect();
+            this.update(cx, |this, cx| {
+                this.project_words
+                    .insert(worktree_root.to_path_buf(), Arc::new(words));
+                cx.notify();
+            })
+            .log_err();
+        })
+        .detach();
+    }
+
+    /// Accepts the given word in files of the worktree at the given path, storing it in the
+    /// worktree's `.zed/dictionary.txt`.
+    pub fn add_to_project_dictionary(
+        &mut self,
+        worktree_root: &Path,
+        word: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let words = self
+            .project_words
+            .entry(worktree_root.to_path_buf())
+            .or_default();
+        let mut new_words = words.as_ref().clone();
+        if !new_words.insert(word) {
+            return Task::ready(Ok(()));
+        }
+        *words = Arc::new(new_words.clone());
+        cx.notify();
+
+        let fs = self.fs.clone();
+        let path = worktree_root.join(PROJECT_DICTIONARY_PATH);
+        cx.background_spawn(async move {
+            let mut words = new_words.into_iter().collect::<Vec<_>>();
+            words.sort_unstable();
+            let mut contents = words.join("\n");
+            contents.push('\n');
+            if let Some(parent) = path.parent() {
+                fs.create_dir(parent).await?;
+            }
+            fs.atomic_write(path.clone(), contents)
+                .await
+                .with_context(|| format!("writing project dictionary {path:?}"))
+        })
+    }
+
+    fn load_dictionaries(&mut self, cx: &mut Context<Self>) {
+        let languages = SpellcheckSettings::get_global(cx).languages.clone();
+        if languages == self.languages {
+            return;
+        }
+        self.languages = languages.clone();
+
+        let fs = self.fs.clone();
+        let dictionaries = cx.background_spawn(async move {
+            let mut dictionaries = Vec::new();
+            for language in languages {
+                match load_dictionary(fs.as_ref(), &language).await {
+                    Ok(dictionary) => dictionaries.push(Arc::new(dictionary)),
+                    Err(error) => log::warn!("failed to load {language} dictionary: {error:#}"),
+                }
+            }
+            dictionaries
+        });
+        self.load_dictionaries_task = cx.spawn(async move |this, cx| {
+            let dictionaries = dictionaries.await;
+            this.update(cx, |this, cx| {
+                this.dictionaries = dictionaries;
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+}
+
+/// The directories searched for `<language>.aff` and `<language>.dic` files, in order.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![paths::dictionaries_dir().clone()];
+    if cfg!(target_os = "macos") {
+        dirs.push(util::paths::home_dir().join("Library/Spelling"));
+        dirs.push(PathBuf::from("/Library/Spelling"));
+    } else {
+        dirs.push(PathBuf::from("/usr/share/hunspell"));
+        dirs.push(PathBuf::from("/usr/share/myspell"));
+        dirs.push(PathBuf::from("/usr/share/myspell/dicts"));
+    }
+    dirs
+}
+
+async fn load_dictionary(fs: &dyn Fs, language: &str) -> Result<Dictionary> {
+    for dir in dictionary_dirs() {
+        let aff_path = dir.join(format!("{language}.aff"));
+        let dic_path = dir.join(format!("{language}.dic"));
+        if !fs.is_file(&aff_path).await || !fs.is_file(&dic_path).await {
+            continue;
+        }
+
+        let aff = fs.load_bytes(&aff_path).await?;
+        let dic = fs.load_bytes(&dic_path).await?;
+        return Dictionary::from_bytes(&aff, &dic).with_context(|| format!("parsing {aff_path:?}"));
+    }
+    anyhow::bail!("no dictionary found in {:?}", dictionary_dirs())
+}
@@ -0,0 +1,126 @@
+use std::ops::Range;
+
+/// Splits prose into the words that should be spell checked, returning their byte ranges.
+///
+/// Anything that looks like code rather than prose is skipped: tokens containing digits, URLs,
+/// paths, e-mail addresses, qualified names and all-caps acronyms. Identifiers written in camel
+/// case are split into the words they are made of.
+pub fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    for (chunk_start, chunk) in chunks(text) {
+        if looks_like_code(chunk) {
+            continue;
+        }
+
+        let mut chars = chunk.char_indices().peekable();
+        let mut token_start = None;
+        let mut token_has_digit = false;
+        while let Some((ix, char)) = chars.next() {
+            let next = chars.peek().map(|(_, char)| *char);
+            let continues_token = char.is_alphanumeric()
+                || (char == '\'' && token_start.is_some() && next.is_some_and(char::is_alphabetic));
+            if continues_token {
+                token_start.get_or_insert(ix);
+                token_has_digit |= char.is_numeric();
+                continue;
+            }
+
+            if let Some(start) = token_start.take() {
+                if !token_has_digit {
+                    push_words(&mut words, chunk, start..ix, chunk_start);
+                }
+            }
+            token_has_digit = false;
+            // Escape sequences such as `\n` aren't part of the surrounding word.
+            if char == '\\' {
+                chars.next();
+            }
+        }
+        if let Some(start) = token_start {
+            if !token_has_digit {
+                push_words(&mut words, chunk, start..chunk.len(), chunk_start);
+            }
+        }
+    }
+    words
+}
+
+fn chunks(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |chunk| (chunk.as_ptr() as usize - text.as_ptr() as usize, chunk))
+}
+
+fn looks_like_code(chunk: &str) -> bool {
+    if chunk.contains(['/', '@', '_']) || chunk.contains("::") || chunk.contains("->") {
+        return true;
+    }
+
+    // Dots between alphanumeric characters appear in file names, qualified names and
+    // abbreviations like "e.g.", none of which should be checked.
+    let chars = chunk.chars().collect::<Vec<_>>();
+    chars.windows(3).any(|window| {
+        window[1] == '.' && window[0].is_alphanumeric() && window[2].is_alphanumeric()
+    })
+}
+
+fn push_words(words: &mut Vec<Range<usize>>, chunk: &str, range: Range<usize>, offset: usize) {
+    let token = &chunk[range.clone()];
+    for part in camel_case_parts(token) {
+        let word = &token[part.clone()];
+        let is_acronym = word.chars().all(|char| !char.is_lowercase());
+        if word.chars().count() > 1 && !is_acronym {
+            words.push(offset + range.start + part.start..offset + range.start + part.end);
+        }
+    }
+}
+
+/// Splits a token at the boundaries of its camel case words, so that `HTMLParser` becomes
+/// `HTML` and `Parser`, and `parseHtml` becomes `parse` and `Html`.
+fn camel_case_parts(token: &str) -> Vec<Range<usize>> {
+    let chars = token.char_indices().collect::<Vec<_>>();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (ix, &(offset, char)) in chars.iter().enumerate().skip(1) {
+        let previous = chars[ix - 1].1;
+        let next = chars.get(ix + 1).map(|(_, char)| *char);
+        let is_boundary = char.is_uppercase()
+            && (previous.is_lowercase()
+                || (previous.is_uppercase() && next.is_some_and(char::is_lowercase)));
+        if is_boundary {
+            parts.push(start..offset);
+            start = offset;
+        }
+    }
+    parts.push(start..token.len());
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word_texts(text: &str) -> Vec<&str> {
+        words(text).into_iter().map(|range| &text[range]).collect()
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            word_texts("Thiss is a sentense, isn't it?"),
+            vec!["Thiss", "is", "sentense", "isn't", "it"]
+        );
+        assert_eq!(
+            word_texts("See https://zed.dev/docs or docs/languages.md, e.g. ask me@zed.dev"),
+            vec!["See", "or", "ask"]
+        );
+        assert_eq!(
+            word_texts("Call parseHtml on the HTMLParser (not my_parser or std::io)"),
+            vec!["Call", "parse", "Html", "on", "the", "Parser", "not", "or"]
+        );
+        assert_eq!(
+            word_texts("The utf8 value\\nwith 'quoted' words and the JSON API"),
+            vec!["The", "value", "with", "quoted", "words", "and", "the"]
+        );
+        assert_eq!(word_texts("héllo wörld"), vec!["héllo", "wörld"]);
+    }
+}
@@ -10,9 +10,9 @@ use crate::statement::{SqlType, Statement};
 
 /// Define the number of columns that a type occupies in a query/database
 pub trait StaticColumnCount {
-    fn column_count() -> usize {
-        1
-    }
+    /// The number of columns, available in constant contexts so that derived impls can sum the
+    /// counts of their fields.
+    const COLUMN_COUNT: usize = 1;
 }
 
 /// Bind values of different types to placeholders in a prepared SQL statement.
@@ -21,6 +21,10 @@ pub trait Bind {
 }
 
 pub trait Column: Sized {
+    /// When set, [`Statement::column`] fails unless the query returns exactly this many columns.
+    /// `#[derive(Column)]` sets this for types read as a whole row.
+    const CHECKED_COLUMN_COUNT: Option<usize> = None;
+
     fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)>;
 }
 
@@ -269,16 +273,14 @@ impl Column for String {
 }
 
 impl<T: StaticColumnCount> StaticColumnCount for Option<T> {
-    fn column_count() -> usize {
-        T::column_count()
-    }
+    const COLUMN_COUNT: usize = T::COLUMN_COUNT;
 }
 impl<T: Bind + StaticColumnCount> Bind for Option<T> {
     fn bind(&self, statement: &Statement, mut start_index: i32) -> Result<i32> {
         if let Some(this) = self {
             this.bind(statement, start_index)
         } else {
-            for _ in 0..T::column_count() {
+            for _ in 0..T::COLUMN_COUNT {
                 statement.bind_null(start_index)?;
                 start_index += 1;
             }
@@ -290,7 +292,7 @@ impl<T: Bind + StaticColumnCount> Bind for Option<T> {
 impl<T: Column + StaticColumnCount> Column for Option<T> {
     fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
         if let SqlType::Null = statement.column_type(start_index)? {
-            Ok((None, start_index + T::column_count() as i32))
+            Ok((None, start_index + T::COLUMN_COUNT as i32))
         } else {
             T::column(statement, start_index).map(|(result, next_index)| (Some(result), next_index))
         }
@@ -298,9 +300,7 @@ impl<T: Column + StaticColumnCount> Column for Option<T> {
 }
 
 impl<T: StaticColumnCount, const COUNT: usize> StaticColumnCount for [T; COUNT] {
-    fn column_count() -> usize {
-        T::column_count() * COUNT
-    }
+    const COLUMN_COUNT: usize = T::COLUMN_COUNT * COUNT;
 }
 impl<T: Bind, const COUNT: usize> Bind for [T; COUNT] {
     fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
@@ -351,11 +351,7 @@ impl Column for PathBuf {
     }
 }
 
-impl StaticColumnCount for uuid::Uuid {
-    fn column_count() -> usize {
-        1
-    }
-}
+impl StaticColumnCount for uuid::Uuid {}
 
 impl Bind for uuid::Uuid {
     fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
@@ -371,9 +367,7 @@ impl Column for uuid::Uuid {
 }
 
 impl StaticColumnCount for () {
-    fn column_count() -> usize {
-        0
-    }
+    const COLUMN_COUNT: usize = 0;
 }
 /// Unit impls do nothing. This simplifies query macros
 impl Bind for () {
@@ -391,11 +385,11 @@ impl Column for () {
 macro_rules! impl_tuple_row_traits {
     ( $($local:ident: $type:ident),+ ) => {
         impl<$($type: StaticColumnCount),+> StaticColumnCount for ($($type,)+) {
-            fn column_count() -> usize {
+            const COLUMN_COUNT: usize = {
                 let mut count = 0;
-                $(count += $type::column_count();)+
+                $(count += $type::COLUMN_COUNT;)+
                 count
-            }
+            };
         }
 
         impl<$($type: Bind),+> Bind for ($($type,)+) {
@@ -87,6 +87,33 @@ impl Connection {
         self.backup_main(&destination)
     }
 
+    /// Deletes all of the tables, indices and data in the database, leaving an empty schema.
+    /// Must not be called inside a savepoint.
+    pub fn reset_database(&self) -> Result<()> {
+        self.set_reset_database_flag(true)?;
+        let result = self.exec("VACUUM").and_then(|mut vacuum| vacuum());
+        self.set_reset_database_flag(false)?;
+        result
+    }
+
+    fn set_reset_database_flag(&self, enabled: bool) -> Result<()> {
+        let code = unsafe {
+            sqlite3_db_config(
+                self.sqlite3,
+                SQLITE_DBCONFIG_RESET_DATABASE,
+                enabled as i32,
+                ptr::null_mut::<i32>(),
+            )
+        };
+        if code == SQLITE_OK {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Setting SQLITE_DBCONFIG_RESET_DATABASE failed with code {code}"
+            ))
+        }
+    }
+
     pub fn sql_has_syntax_error(&self, sql: &str) -> Option<(String, usize)> {
         let sql = CString::new(sql).unwrap();
         let mut remaining_sql = sql.as_c_str();
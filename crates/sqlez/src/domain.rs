@@ -1,8 +1,13 @@
-use crate::connection::Connection;
+use crate::{connection::Connection, migrations::MigrationRewrite};
 
 pub trait Domain: 'static {
     fn name() -> &'static str;
     fn migrations() -> &'static [&'static str];
+
+    /// Compatible changes to migrations that may already have been run with different text.
+    fn compatible_rewrites() -> &'static [MigrationRewrite] {
+        &[]
+    }
 }
 
 pub trait Migrator: 'static {
@@ -17,7 +22,11 @@ impl Migrator for () {
 
 impl<D: Domain> Migrator for D {
     fn migrate(connection: &Connection) -> anyhow::Result<()> {
-        connection.migrate(Self::name(), Self::migrations())
+        connection.migrate_with_rewrites(
+            Self::name(),
+            Self::migrations(),
+            Self::compatible_rewrites(),
+        )
     }
 }
 
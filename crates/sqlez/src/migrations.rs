@@ -1,17 +1,61 @@
 // Migrations are constructed by domain, and stored in a table in the connection db with domain name,
 // effected tables, actual query text, and order.
-// If a migration is run and any of the query texts don't match, the app panics on startup (maybe fallback
-// to creating a new db?)
+// If a migration is run and any of the query texts don't match, the migration fails with a
+// `MigrationMismatch`, unless the domain declares a `MigrationRewrite` for the stored text. Callers
+// can recover from a mismatch by backing up the database and resetting the domain's tables (see
+// `db::open_db`).
 // Otherwise any missing migrations are run on the connection
 
 use std::ffi::CString;
+use std::fmt;
 
-use anyhow::{Context as _, Result, anyhow};
+use anyhow::{Context as _, Result};
 use indoc::{formatdoc, indoc};
 use libsqlite3_sys::sqlite3_exec;
 
 use crate::connection::Connection;
 
+/// A compatible change to a migration step that has already been run, for example when switching
+/// between release channels whose migrations diverged. When the stored text of the step matches
+/// `stored`, the `rewrite` SQL is run to bring the schema in line with the current migration, and
+/// the stored text is replaced with the current one.
+#[derive(Clone, Copy, Debug)]
+pub struct MigrationRewrite {
+    pub step: usize,
+    pub stored: &'static str,
+    /// Left empty when the two migrations produce the same schema.
+    pub rewrite: &'static str,
+}
+
+/// The error returned when the stored text of a migration step that has already been run doesn't
+/// match the current migration, and no [`MigrationRewrite`] applies.
+#[derive(Debug)]
+pub struct MigrationMismatch {
+    pub domain: &'static str,
+    pub step: usize,
+    pub stored: String,
+    pub proposed: String,
+}
+
+impl fmt::Display for MigrationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            formatdoc! {"
+                Migration changed for {} at step {}
+
+                Stored migration:
+                {}
+
+                Proposed migration:
+                {}", self.domain, self.step, self.stored, self.proposed}
+        )
+    }
+}
+
+impl std::error::Error for MigrationMismatch {}
+
 impl Connection {
     fn eager_exec(&self, sql: &str) -> anyhow::Result<()> {
         let sql_str = CString::new(sql).context("Error creating cstr")?;
@@ -35,6 +79,17 @@ impl Connection {
     /// preparing the SQL statements. This makes it possible to do multi-statement schema
     /// updates in a single string without running into prepare errors.
     pub fn migrate(&self, domain: &'static str, migrations: &[&'static str]) -> Result<()> {
+        self.migrate_with_rewrites(domain, migrations, &[])
+    }
+
+    /// Migrate the database, for the given domain, applying the given rewrites to steps whose
+    /// stored text no longer matches the migration.
+    pub fn migrate_with_rewrites(
+        &self,
+        domain: &'static str,
+        migrations: &[&'static str],
+        rewrites: &[MigrationRewrite],
+    ) -> Result<()> {
         self.with_savepoint("migrating", || {
             // Setup the migrations table unconditionally
             self.exec(indoc! {"
@@ -53,31 +108,38 @@ impl Connection {
 
             let mut store_completed_migration = self
                 .exec_bound("INSERT INTO migrations (domain, step, migration) VALUES (?, ?, ?)")?;
+            let mut replace_completed_migration = self
+                .exec_bound("UPDATE migrations SET migration = ? WHERE domain = ? AND step = ?")?;
 
             for (index, migration) in migrations.iter().enumerate() {
-                let migration =
-                    sqlformat::format(migration, &sqlformat::QueryParams::None, Default::default());
+                let migration = format_migration(migration);
                 if let Some((_, _, completed_migration)) = completed_migrations.get(index) {
                     // Reformat completed migrations with the current `sqlformat` version, so that past migrations stored
                     // conform to the new formatting rules.
-                    let completed_migration = sqlformat::format(
-                        completed_migration,
-                        &sqlformat::QueryParams::None,
-                        Default::default(),
-                    );
+                    let completed_migration = format_migration(completed_migration);
                     if completed_migration == migration {
                         // Migration already run. Continue
                         continue;
-                    } else {
-                        return Err(anyhow!(formatdoc! {"
-                            Migration changed for {} at step {}
-
-                            Stored migration:
-                            {}
+                    }
 
-                            Proposed migration:
-                            {}", domain, index, completed_migration, migration}));
+                    let rewrite = rewrites.iter().find(|rewrite| {
+                        rewrite.step == index
+                            && format_migration(rewrite.stored) == completed_migration
+                    });
+                    let Some(rewrite) = rewrite else {
+                        return Err(MigrationMismatch {
+                            domain,
+                            step: index,
+                            stored: completed_migration,
+                            proposed: migration,
+                        }
+                        .into());
+                    };
+                    if !rewrite.rewrite.trim().is_empty() {
+                        self.eager_exec(rewrite.rewrite)?;
                     }
+                    replace_completed_migration((migration, domain, index))?;
+                    continue;
                 }
 
                 self.eager_exec(&migration)?;
@@ -89,12 +151,68 @@ impl Connection {
     }
 }
 
+impl Connection {
+    /// Drops the tables created by the stored migrations of the given domain, along with the
+    /// record of those migrations, so that the domain can be migrated from scratch while the
+    /// tables of other domains are kept.
+    pub fn reset_domain(&self, domain: &str) -> Result<()> {
+        self.with_savepoint("reset_domain", || {
+            for table in self.tables_created_by(domain)? {
+                self.exec(&format!(
+                    "DROP TABLE IF EXISTS \"{}\"",
+                    table.replace('"', "\"\"")
+                ))?()?;
+            }
+            self.exec_bound("DELETE FROM migrations WHERE domain = ?")?(domain)
+        })
+    }
+
+    /// Returns the tables left by the stored migrations of the given domain. The stored
+    /// migrations of every domain are replayed on an empty database in the order they were run,
+    /// comparing its tables before and after each of the domain's steps.
+    fn tables_created_by(&self, domain: &str) -> Result<Vec<String>> {
+        let stored_migrations = self.select::<(String, String)>(
+            "SELECT domain, migration FROM migrations ORDER BY rowid",
+        )?()?;
+        let scratch = Connection::open_memory(None);
+        let mut tables = Vec::new();
+        for (migration_domain, migration) in stored_migrations {
+            if migration_domain != domain {
+                scratch.eager_exec(&migration)?;
+                continue;
+            }
+            let tables_before = scratch.table_names()?;
+            scratch.eager_exec(&migration)?;
+            let tables_after = scratch.table_names()?;
+            tables.retain(|table| tables_after.contains(table));
+            tables.extend(
+                tables_after
+                    .into_iter()
+                    .filter(|table| !tables_before.contains(table)),
+            );
+        }
+        Ok(tables)
+    }
+
+    fn table_names(&self) -> Result<Vec<String>> {
+        self.select::<String>(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )?()
+    }
+}
+
+fn format_migration(migration: &str) -> String {
+    sqlformat::format(migration, &sqlformat::QueryParams::None, Default::default())
+}
+
 #[cfg(test)]
 mod test {
     use indoc::indoc;
 
     use crate::connection::Connection;
 
+    use super::{MigrationMismatch, MigrationRewrite};
+
     #[test]
     fn test_migrations_are_added_to_table() {
         let connection = Connection::open_memory(Some("migrations_are_added_to_table"));
@@ -279,6 +397,105 @@ mod test {
         assert!(second_migration_result.is_err())
     }
 
+    #[test]
+    fn changed_migration_with_rewrite_succeeds() {
+        let connection = Connection::open_memory(Some("changed_migration_with_rewrite_succeeds"));
+
+        connection
+            .migrate("test", &["CREATE TABLE test (col INTEGER)"])
+            .unwrap();
+        connection
+            .exec("INSERT INTO test (col) VALUES (1)")
+            .unwrap()()
+        .unwrap();
+
+        // Without a rewrite, the changed migration fails with a mismatch
+        let result = connection.migrate("test", &["CREATE TABLE test (color INTEGER)"]);
+        let mismatch = result.unwrap_err().downcast::<MigrationMismatch>().unwrap();
+        assert_eq!(mismatch.step, 0);
+
+        // With a rewrite for the stored migration, the schema is updated and the data kept
+        connection
+            .migrate_with_rewrites(
+                "test",
+                &["CREATE TABLE test (color INTEGER)"],
+                &[MigrationRewrite {
+                    step: 0,
+                    stored: "CREATE TABLE test (col INTEGER)",
+                    rewrite: "ALTER TABLE test RENAME COLUMN col TO color",
+                }],
+            )
+            .unwrap();
+        assert_eq!(
+            connection
+                .select_row::<usize>("SELECT color FROM test")
+                .unwrap()()
+            .unwrap(),
+            Some(1)
+        );
+
+        // The new migration text is stored, so the rewrite isn't needed anymore
+        connection
+            .migrate("test", &["CREATE TABLE test (color INTEGER)"])
+            .unwrap();
+    }
+
+    #[test]
+    fn changed_migration_after_reset_succeeds() {
+        let connection = Connection::open_memory(Some("changed_migration_after_reset_succeeds"));
+
+        connection
+            .migrate(
+                "test",
+                &[
+                    "CREATE TABLE test (col INTEGER); CREATE TABLE scratch (col INTEGER)",
+                    "ALTER TABLE scratch RENAME TO renamed; DROP TABLE IF EXISTS test",
+                    "CREATE TABLE IF NOT EXISTS \"test\" (col INTEGER)",
+                ],
+            )
+            .unwrap();
+        connection
+            .migrate("other", &["CREATE TABLE other (col INTEGER)"])
+            .unwrap();
+        connection
+            .exec("INSERT INTO other (col) VALUES (1)")
+            .unwrap()()
+        .unwrap();
+        assert!(
+            connection
+                .migrate("test", &["CREATE TABLE test (color INTEGER)"])
+                .is_err()
+        );
+
+        // Only the tables of the reset domain are dropped
+        connection.reset_domain("test").unwrap();
+        connection
+            .migrate("test", &["CREATE TABLE test (color INTEGER)"])
+            .unwrap();
+        assert_eq!(
+            connection
+                .select_row::<usize>("SELECT color FROM test")
+                .unwrap()()
+            .unwrap(),
+            None
+        );
+        assert!(
+            connection
+                .select_row::<usize>("SELECT * FROM renamed")
+                .is_err()
+        );
+        assert_eq!(
+            connection
+                .select_row::<usize>("SELECT col FROM other")
+                .unwrap()()
+            .unwrap(),
+            Some(1)
+        );
+        connection
+            .migrate("other", &["CREATE TABLE other (col INTEGER)"])
+            .unwrap();
+    }
+
     #[test]
     fn test_create_alter_drop() {
         let connection = Connection::open_memory(Some("test_create_alter_drop"));
@@ -249,9 +249,23 @@ impl<'a> Statement<'a> {
     }
 
     pub fn column<T: Column>(&mut self) -> Result<T> {
+        if let Some(expected) = T::CHECKED_COLUMN_COUNT {
+            let actual = self.column_count();
+            if actual as usize != expected {
+                bail!(
+                    "{} reads {expected} columns, but the query returns {actual}",
+                    std::any::type_name::<T>()
+                );
+            }
+        }
         Ok(T::column(self, 0)?.0)
     }
 
+    /// Returns the number of columns in the result set of the current statement.
+    pub fn column_count(&self) -> i32 {
+        unsafe { sqlite3_column_count(self.current_statement()) }
+    }
+
     pub fn column_type(&mut self, index: i32) -> Result<SqlType> {
         let result = unsafe { sqlite3_column_type(self.current_statement(), index) };
         self.connection.last_error()?;
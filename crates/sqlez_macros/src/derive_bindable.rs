@@ -0,0 +1,317 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta, Path, Result, Variant,
+    parse_macro_input,
+};
+
+pub fn derive_bind(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_bind(&ast)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+pub fn derive_column(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    expand_column(&ast)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct SqlezAttributes {
+    krate: Option<Path>,
+    repr: Option<EnumRepr>,
+    rename: Option<String>,
+    skip: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum EnumRepr {
+    Text,
+    Int,
+}
+
+fn parse_attributes(attrs: &[Attribute]) -> Result<SqlezAttributes> {
+    let mut result = SqlezAttributes::default();
+    for attr in attrs {
+        if !attr.path.is_ident("sqlez") {
+            continue;
+        }
+        let Meta::List(list) = attr.parse_meta()? else {
+            return Err(Error::new_spanned(attr, "expected #[sqlez(...)]"));
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                    result.skip = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value)) => {
+                    let Lit::Str(value) = &name_value.lit else {
+                        return Err(Error::new_spanned(name_value.lit, "expected a string"));
+                    };
+                    if name_value.path.is_ident("crate") {
+                        result.krate = Some(value.parse()?);
+                    } else if name_value.path.is_ident("rename") {
+                        result.rename = Some(value.value());
+                    } else if name_value.path.is_ident("repr") {
+                        result.repr = Some(match value.value().as_str() {
+                            "text" => EnumRepr::Text,
+                            "int" => EnumRepr::Int,
+                            _ => {
+                                return Err(Error::new_spanned(
+                                    value,
+                                    "expected `repr = \"text\"` or `repr = \"int\"`",
+                                ));
+                            }
+                        });
+                    } else {
+                        return Err(Error::new_spanned(
+                            name_value.path,
+                            "unknown sqlez attribute",
+                        ));
+                    }
+                }
+                other => return Err(Error::new_spanned(other, "unknown sqlez attribute")),
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The path to the `sqlez` crate, which can be overridden with `#[sqlez(crate = "db::sqlez")]`
+/// for crates that only depend on it through a re-export.
+fn sqlez_path(attrs: &SqlezAttributes) -> TokenStream2 {
+    match &attrs.krate {
+        Some(path) => quote!(#path),
+        None => quote!(sqlez),
+    }
+}
+
+struct BoundField {
+    member: TokenStream2,
+    ty: syn::Type,
+    skip: bool,
+}
+
+fn struct_fields(fields: &Fields) -> Result<Vec<BoundField>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(ix, field)| {
+            let member = match &field.ident {
+                Some(ident) => quote!(#ident),
+                None => {
+                    let index = syn::Index::from(ix);
+                    quote!(#index)
+                }
+            };
+            Ok(BoundField {
+                member,
+                ty: field.ty.clone(),
+                skip: parse_attributes(&field.attrs)?.skip,
+            })
+        })
+        .collect()
+}
+
+fn unit_variants(ast: &DeriveInput, variants: &[&Variant]) -> Result<()> {
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "sqlez can only derive Bind and Column for enums without fields",
+            ));
+        }
+    }
+    if variants.is_empty() {
+        return Err(Error::new_spanned(
+            &ast.ident,
+            "sqlez can't derive Bind and Column for empty enums",
+        ));
+    }
+    Ok(())
+}
+
+fn variant_name(variant: &Variant) -> Result<String> {
+    Ok(parse_attributes(&variant.attrs)?
+        .rename
+        .unwrap_or_else(|| variant.ident.to_string()))
+}
+
+fn expand_bind(ast: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = parse_attributes(&ast.attrs)?;
+    let sqlez = sqlez_path(&attrs);
+    let type_name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
+
+    let body = match &ast.data {
+        Data::Struct(data) => {
+            let binds = struct_fields(&data.fields)?
+                .into_iter()
+                .filter(|field| !field.skip)
+                .map(|BoundField { member, ty, .. }| {
+                    quote! {
+                        next_index = <#ty as #sqlez::bindable::Bind>::bind(
+                            &self.#member,
+                            statement,
+                            next_index,
+                        )?;
+                    }
+                });
+            quote! {
+                let mut next_index = start_index;
+                #(#binds)*
+                Ok(next_index)
+            }
+        }
+        Data::Enum(data) => {
+            let variants = data.variants.iter().collect::<Vec<_>>();
+            unit_variants(ast, &variants)?;
+            let idents = variants.iter().map(|variant| &variant.ident);
+            match attrs.repr.unwrap_or(EnumRepr::Text) {
+                EnumRepr::Text => {
+                    let names = variants
+                        .iter()
+                        .map(|variant| variant_name(variant))
+                        .collect::<Result<Vec<_>>>()?;
+                    quote! {
+                        let value: &str = match self {
+                            #(Self::#idents => #names,)*
+                        };
+                        #sqlez::bindable::Bind::bind(&value, statement, start_index)
+                    }
+                }
+                EnumRepr::Int => quote! {
+                    let value: i64 = match self {
+                        #(Self::#idents => Self::#idents as i64,)*
+                    };
+                    #sqlez::bindable::Bind::bind(&value, statement, start_index)
+                },
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ast,
+                "sqlez can't derive Bind for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #sqlez::bindable::Bind for #type_name #type_generics #where_clause {
+            fn bind(
+                &self,
+                statement: &#sqlez::statement::Statement,
+                start_index: i32,
+            ) -> #sqlez::anyhow::Result<i32> {
+                #body
+            }
+        }
+    })
+}
+
+fn expand_column(ast: &DeriveInput) -> Result<TokenStream2> {
+    let attrs = parse_attributes(&ast.attrs)?;
+    let sqlez = sqlez_path(&attrs);
+    let type_name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = ast.generics.split_for_impl();
+
+    let (column_count, body) = match &ast.data {
+        Data::Struct(data) => {
+            let fields = struct_fields(&data.fields)?;
+            let counts = fields
+                .iter()
+                .filter(|field| !field.skip)
+                .map(|BoundField { ty, .. }| {
+                    quote!(<#ty as #sqlez::bindable::StaticColumnCount>::COLUMN_COUNT)
+                });
+            let column_count = quote!(0 #(+ #counts)*);
+
+            let reads = fields.iter().map(|BoundField { member, ty, skip }| {
+                let value = if *skip {
+                    quote!(::std::default::Default::default())
+                } else {
+                    quote! {{
+                        let (value, index) =
+                            <#ty as #sqlez::bindable::Column>::column(statement, next_index)?;
+                        next_index = index;
+                        value
+                    }}
+                };
+                quote!(#member: #value)
+            });
+            let body = quote! {
+                let mut next_index = start_index;
+                let value = Self { #(#reads,)* };
+                Ok((value, next_index))
+            };
+            (column_count, body)
+        }
+        Data::Enum(data) => {
+            let variants = data.variants.iter().collect::<Vec<_>>();
+            unit_variants(ast, &variants)?;
+            let idents = variants.iter().map(|variant| &variant.ident);
+            let body = match attrs.repr.unwrap_or(EnumRepr::Text) {
+                EnumRepr::Text => {
+                    let names = variants
+                        .iter()
+                        .map(|variant| variant_name(variant))
+                        .collect::<Result<Vec<_>>>()?;
+                    quote! {
+                        let (value, next_index) =
+                            <String as #sqlez::bindable::Column>::column(statement, start_index)?;
+                        let value = match value.as_str() {
+                            #(#names => Self::#idents,)*
+                            _ => #sqlez::anyhow::bail!(
+                                "unknown {} variant {value:?} at index {start_index}",
+                                stringify!(#type_name),
+                            ),
+                        };
+                        Ok((value, next_index))
+                    }
+                }
+                EnumRepr::Int => quote! {
+                    let (value, next_index) =
+                        <i64 as #sqlez::bindable::Column>::column(statement, start_index)?;
+                    #(
+                        if value == Self::#idents as i64 {
+                            return Ok((Self::#idents, next_index));
+                        }
+                    )*
+                    #sqlez::anyhow::bail!(
+                        "unknown {} variant {value} at index {start_index}",
+                        stringify!(#type_name),
+                    )
+                },
+            };
+            (quote!(1), body)
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ast,
+                "sqlez can't derive Column for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics #sqlez::bindable::StaticColumnCount for #type_name #type_generics #where_clause {
+            const COLUMN_COUNT: usize = #column_count;
+        }
+
+        impl #impl_generics #sqlez::bindable::Column for #type_name #type_generics #where_clause {
+            const CHECKED_COLUMN_COUNT: Option<usize> =
+                Some(<Self as #sqlez::bindable::StaticColumnCount>::COLUMN_COUNT);
+
+            fn column(
+                statement: &mut #sqlez::statement::Statement,
+                start_index: i32,
+            ) -> #sqlez::anyhow::Result<(Self, i32)> {
+                #body
+            }
+        }
+    })
+}
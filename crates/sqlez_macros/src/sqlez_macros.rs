@@ -1,3 +1,5 @@
+mod derive_bindable;
+
 use proc_macro::{Delimiter, Span, TokenStream, TokenTree};
 use syn::Error;
 
@@ -31,6 +33,28 @@ pub fn sql(tokens: TokenStream) -> TokenStream {
     }
 }
 
+/// #[derive(Bind)] binds each field of a struct in declaration order, or an enum without fields
+/// as a single column.
+///
+/// - `#[sqlez(skip)]` on a field leaves it out of the bound columns.
+/// - `#[sqlez(repr = "int")]` on an enum binds the discriminant instead of the variant name, and
+///   `#[sqlez(rename = "...")]` on a variant changes its stored name.
+/// - `#[sqlez(crate = "db::sqlez")]` names the path to `sqlez` when it's only available through
+///   a re-export.
+#[proc_macro_derive(Bind, attributes(sqlez))]
+pub fn derive_bind(input: TokenStream) -> TokenStream {
+    derive_bindable::derive_bind(input)
+}
+
+/// #[derive(Column)] reads a type back from the columns written by #[derive(Bind)], filling
+/// skipped fields with their `Default` value. It also implements `StaticColumnCount` as the sum
+/// of the field types' counts. Reading the type as a whole row returns an error when the statement
+/// runs if the query doesn't return exactly that many columns.
+#[proc_macro_derive(Column, attributes(sqlez))]
+pub fn derive_column(input: TokenStream) -> TokenStream {
+    derive_bindable::derive_column(input)
+}
+
 fn create_error(
     spans: Vec<(usize, Span)>,
     error_offset: usize,
@@ -1674,6 +1674,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal output has no syntax trees to match.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
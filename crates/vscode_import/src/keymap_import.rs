@@ -0,0 +1,455 @@
+use std::ops::Range;
+
+use anyhow::{Context as _, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+use crate::Untranslated;
+
+/// The result of translating a VS Code `keybindings.json` file.
+#[derive(Debug, Default)]
+pub struct KeymapImport {
+    /// The translated bindings, grouped into Zed keymap sections by context.
+    pub sections: Vec<KeymapImportSection>,
+    pub untranslated: Vec<Untranslated>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct KeymapImportSection {
+    pub context: &'static str,
+    /// The bindings, mapping keystrokes to actions as they're written in Zed's `keymap.json`.
+    pub bindings: Map<String, Value>,
+}
+
+impl KeymapImportSection {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "context": self.context,
+            "bindings": self.bindings,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VsCodeKeybinding {
+    key: String,
+    command: String,
+    #[serde(default)]
+    when: Option<String>,
+    #[serde(default)]
+    args: Option<Value>,
+}
+
+/// VS Code commands and the Zed actions that do the same thing.
+const COMMANDS: &[(&str, &str)] = &[
+    ("actions.find", "buffer_search::Deploy"),
+    (
+        "editor.action.addSelectionToNextFindMatch",
+        "editor::SelectNext",
+    ),
+    ("editor.action.clipboardCopyAction", "editor::Copy"),
+    ("editor.action.clipboardCutAction", "editor::Cut"),
+    ("editor.action.clipboardPasteAction", "editor::Paste"),
+    ("editor.action.commentLine", "editor::ToggleComments"),
+    (
+        "editor.action.copyLinesDownAction",
+        "editor::DuplicateLineDown",
+    ),
+    ("editor.action.copyLinesUpAction", "editor::DuplicateLineUp"),
+    ("editor.action.deleteLines", "editor::DeleteLine"),
+    ("editor.action.formatDocument", "editor::Format"),
+    (
+        "editor.action.goToImplementation",
+        "editor::GoToImplementation",
+    ),
+    ("editor.action.goToReferences", "editor::FindAllReferences"),
+    (
+        "editor.action.goToTypeDefinition",
+        "editor::GoToTypeDefinition",
+    ),
+    (
+        "editor.action.insertCursorAbove",
+        "editor::AddSelectionAbove",
+    ),
+    (
+        "editor.action.insertCursorBelow",
+        "editor::AddSelectionBelow",
+    ),
+    (
+        "editor.action.jumpToBracket",
+        "editor::MoveToEnclosingBracket",
+    ),
+    ("editor.action.marker.next", "editor::GoToDiagnostic"),
+    (
+        "editor.action.marker.prev",
+        "editor::GoToPreviousDiagnostic",
+    ),
+    ("editor.action.moveLinesDownAction", "editor::MoveLineDown"),
+    ("editor.action.moveLinesUpAction", "editor::MoveLineUp"),
+    ("editor.action.quickFix", "editor::ToggleCodeActions"),
+    ("editor.action.rename", "editor::Rename"),
+    ("editor.action.revealDefinition", "editor::GoToDefinition"),
+    ("editor.action.selectHighlights", "editor::SelectAllMatches"),
+    ("editor.action.showHover", "editor::Hover"),
+    (
+        "editor.action.smartSelect.expand",
+        "editor::SelectLargerSyntaxNode",
+    ),
+    (
+        "editor.action.smartSelect.shrink",
+        "editor::SelectSmallerSyntaxNode",
+    ),
+    (
+        "editor.action.startFindReplaceAction",
+        "buffer_search::DeployReplace",
+    ),
+    ("editor.action.toggleWordWrap", "editor::ToggleSoftWrap"),
+    ("editor.action.triggerSuggest", "editor::ShowCompletions"),
+    ("editor.fold", "editor::Fold"),
+    ("editor.foldAll", "editor::FoldAll"),
+    ("editor.toggleFold", "editor::ToggleFold"),
+    ("editor.unfold", "editor::UnfoldLines"),
+    ("editor.unfoldAll", "editor::UnfoldAll"),
+    ("expandLineSelection", "editor::SelectLine"),
+    ("redo", "editor::Redo"),
+    ("undo", "editor::Undo"),
+    (
+        "workbench.action.closeActiveEditor",
+        "pane::CloseActiveItem",
+    ),
+    ("workbench.action.closeWindow", "workspace::CloseWindow"),
+    (
+        "workbench.action.files.newUntitledFile",
+        "workspace::NewFile",
+    ),
+    ("workbench.action.files.save", "workspace::Save"),
+    ("workbench.action.files.saveAll", "workspace::SaveAll"),
+    ("workbench.action.findInFiles", "pane::DeploySearch"),
+    ("workbench.action.gotoLine", "go_to_line::Toggle"),
+    ("workbench.action.gotoSymbol", "outline::Toggle"),
+    ("workbench.action.navigateBack", "pane::GoBack"),
+    ("workbench.action.navigateForward", "pane::GoForward"),
+    ("workbench.action.newWindow", "workspace::NewWindow"),
+    ("workbench.action.nextEditor", "pane::ActivateNextItem"),
+    ("workbench.action.openSettings", "zed::OpenSettings"),
+    (
+        "workbench.action.previousEditor",
+        "pane::ActivatePreviousItem",
+    ),
+    ("workbench.action.quickOpen", "file_finder::Toggle"),
+    (
+        "workbench.action.reopenClosedEditor",
+        "pane::ReopenClosedItem",
+    ),
+    ("workbench.action.selectTheme", "theme_selector::Toggle"),
+    ("workbench.action.showAllSymbols", "project_symbols::Toggle"),
+    ("workbench.action.showCommands", "command_palette::Toggle"),
+    ("workbench.action.splitEditor", "pane::SplitRight"),
+    ("workbench.action.terminal.new", "workspace::NewTerminal"),
+    (
+        "workbench.action.terminal.toggleTerminal",
+        "terminal_panel::ToggleFocus",
+    ),
+    (
+        "workbench.action.toggleAuxiliaryBar",
+        "workspace::ToggleRightDock",
+    ),
+    (
+        "workbench.action.togglePanel",
+        "workspace::ToggleBottomDock",
+    ),
+    (
+        "workbench.action.toggleSidebarVisibility",
+        "workspace::ToggleLeftDock",
+    ),
+    ("workbench.action.zoomIn", "zed::IncreaseBufferFontSize"),
+    ("workbench.action.zoomOut", "zed::DecreaseBufferFontSize"),
+    ("workbench.action.zoomReset", "zed::ResetBufferFontSize"),
+    ("workbench.view.explorer", "project_panel::ToggleFocus"),
+];
+
+/// Terms of `when` clauses that identify the focused part of VS Code, and the Zed contexts that
+/// correspond to them.
+const WHEN_CONTEXTS: &[(&str, &str)] = &[
+    ("editorFocus", "Editor"),
+    ("editorTextFocus", "Editor"),
+    ("explorerViewletFocus", "ProjectPanel"),
+    ("filesExplorerFocus", "ProjectPanel"),
+    ("inQuickOpen", "Picker"),
+    ("terminalFocus", "Terminal"),
+    ("textInputFocus", "Editor"),
+];
+
+/// Terms of `when` clauses that can be dropped without changing where a binding applies in Zed.
+const IGNORED_WHEN_TERMS: &[&str] = &["!editorReadonly", "!inDebugRepl", "!isInDiffEditor"];
+
+/// Translates the contents of a VS Code `keybindings.json` file into Zed keymap sections.
+pub fn import_keybindings(text: &str) -> Result<KeymapImport> {
+    let keybindings: Vec<VsCodeKeybinding> =
+        serde_json_lenient::from_str(text).context("parsing VS Code keybindings")?;
+
+    let mut import = KeymapImport::default();
+    for keybinding in keybindings {
+        let item = format!("{}: {}", keybinding.key, keybinding.command);
+        match translate_keybinding(&keybinding) {
+            Ok((context, keystrokes, action)) => {
+                let section = match import
+                    .sections
+                    .iter_mut()
+                    .position(|section| section.context == context)
+                {
+                    Some(ix) => &mut import.sections[ix],
+                    None => {
+                        import.sections.push(KeymapImportSection {
+                            context,
+                            bindings: Map::new(),
+                        });
+                        import.sections.last_mut().unwrap()
+                    }
+                };
+                section.bindings.insert(keystrokes, action);
+            }
+            Err(reason) => import.untranslated.push(Untranslated::new(item, reason)),
+        }
+    }
+    Ok(import)
+}
+
+fn translate_keybinding(
+    keybinding: &VsCodeKeybinding,
+) -> Result<(&'static str, String, Value), String> {
+    if keybinding.command.starts_with('-') {
+        return Err("removing a default binding isn't supported".into());
+    }
+    let action = match COMMANDS
+        .iter()
+        .find(|(command, _)| *command == keybinding.command)
+    {
+        Some((_, "editor::SelectNext")) => {
+            json!(["editor::SelectNext", { "replace_newest": false }])
+        }
+        Some((_, action)) if keybinding.args.is_none() => json!(action),
+        Some(_) => return Err("command arguments aren't supported".into()),
+        None => return Err("no equivalent Zed action".into()),
+    };
+    let context = translate_when(keybinding.when.as_deref())?;
+    let keystrokes = translate_keystrokes(&keybinding.key)?;
+    Ok((context, keystrokes, action))
+}
+
+fn translate_when(when: Option<&str>) -> Result<&'static str, String> {
+    let Some(when) = when else {
+        return Ok("Workspace");
+    };
+    if when.contains("||") || when.contains('(') {
+        return Err(format!("unsupported `when` clause {when:?}"));
+    }
+
+    let mut context = None;
+    for term in when.split("&&").map(str::trim) {
+        if IGNORED_WHEN_TERMS.contains(&term) {
+            continue;
+        }
+        let Some((_, term_context)) = WHEN_CONTEXTS.iter().find(|(name, _)| *name == term) else {
+            return Err(format!("unsupported `when` condition {term:?}"));
+        };
+        if context.is_some_and(|context| context != *term_context) {
+            return Err(format!("unsupported `when` clause {when:?}"));
+        }
+        context = Some(*term_context);
+    }
+    Ok(context.unwrap_or("Workspace"))
+}
+
+/// Returns the edit that appends the given sections to the array of sections in the text of a Zed
+/// `keymap.json`.
+pub fn keymap_append_edit(
+    text: &str,
+    sections: &[KeymapImportSection],
+) -> Result<(Range<usize>, String)> {
+    let new_sections = sections
+        .iter()
+        .map(|section| {
+            let json = serde_json::to_string_pretty(&section.to_json())?;
+            anyhow::Ok(
+                json.lines()
+                    .map(|line| format!("  {line}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?
+        .join(",\n");
+    if text.trim().is_empty() {
+        return Ok((0..text.len(), format!("[\n{new_sections}\n]\n")));
+    }
+
+    let existing_sections: Vec<Value> =
+        serde_json_lenient::from_str(text).context("parsing the keymap")?;
+    let array_end = text.rfind(']').context("the keymap isn't a JSON array")?;
+    let insertion_point = text[..array_end].trim_end().len();
+    let separator = if existing_sections.is_empty() || text[..insertion_point].ends_with(',') {
+        "\n"
+    } else {
+        ",\n"
+    };
+    Ok((
+        insertion_point..insertion_point,
+        format!("{separator}{new_sections}"),
+    ))
+}
+
+/// Translates VS Code keystrokes like `ctrl+k ctrl+c` to Zed's `ctrl-k ctrl-c`.
+fn translate_keystrokes(keystrokes: &str) -> Result<String, String> {
+    keystrokes
+        .split_whitespace()
+        .map(translate_keystroke)
+        .collect::<Result<Vec<_>, _>>()
+        .map(|keystrokes| keystrokes.join(" "))
+}
+
+fn translate_keystroke(keystroke: &str) -> Result<String, String> {
+    let keystroke = keystroke.to_lowercase();
+    let (modifiers, key) = match keystroke.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => keystroke.rsplit_once('+').unwrap_or(("", &keystroke)),
+    };
+
+    let mut zed_keystroke = String::new();
+    for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+        let modifier = match modifier {
+            "ctrl" | "shift" | "alt" | "cmd" | "win" => modifier,
+            "meta" if cfg!(target_os = "macos") => "cmd",
+            "meta" => "super",
+            _ => return Err(format!("unknown modifier {modifier:?}")),
+        };
+        zed_keystroke.push_str(modifier);
+        zed_keystroke.push('-');
+    }
+
+    let is_function_key = key
+        .strip_prefix('f')
+        .and_then(|number| number.parse::<u8>().ok())
+        .is_some_and(|number| (1..=24).contains(&number));
+    let is_named_key = matches!(
+        key,
+        "escape"
+            | "enter"
+            | "tab"
+            | "space"
+            | "backspace"
+            | "delete"
+            | "insert"
+            | "home"
+            | "end"
+            | "pageup"
+            | "pagedown"
+            | "up"
+            | "down"
+            | "left"
+            | "right"
+    );
+    if key.chars().count() != 1 && !is_function_key && !is_named_key {
+        return Err(format!("unsupported key {key:?}"));
+    }
+    zed_keystroke.push_str(key);
+    Ok(zed_keystroke)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_keybindings() {
+        let import = import_keybindings(
+            r#"[
+                // Comments and trailing commas are allowed.
+                { "key": "ctrl+shift+p", "command": "workbench.action.showCommands" },
+                { "key": "ctrl+k ctrl+c", "command": "editor.action.commentLine", "when": "editorTextFocus && !editorReadonly" },
+                { "key": "ctrl+d", "command": "editor.action.addSelectionToNextFindMatch", "when": "editorFocus" },
+                { "key": "ctrl+`", "command": "workbench.action.terminal.toggleTerminal" },
+                { "key": "ctrl+=", "command": "workbench.action.zoomIn" },
+                { "key": "ctrl+shift+k", "command": "-editor.action.deleteLines" },
+                { "key": "ctrl+alt+l", "command": "extension.doSomething" },
+                { "key": "numpad_add", "command": "workbench.action.zoomIn" },
+                { "key": "ctrl+j", "command": "editor.action.showHover", "when": "editorTextFocus || terminalFocus" },
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            import
+                .sections
+                .iter()
+                .map(KeymapImportSection::to_json)
+                .collect::<Vec<_>>(),
+            vec![
+                json!({
+                    "context": "Workspace",
+                    "bindings": {
+                        "ctrl-shift-p": "command_palette::Toggle",
+                        "ctrl-`": "terminal_panel::ToggleFocus",
+                        "ctrl-=": "zed::IncreaseBufferFontSize",
+                    }
+                }),
+                json!({
+                    "context": "Editor",
+                    "bindings": {
+                        "ctrl-k ctrl-c": "editor::ToggleComments",
+                        "ctrl-d": ["editor::SelectNext", { "replace_newest": false }],
+                    }
+                }),
+            ]
+        );
+        let keymap = "[\n  {\n    \"bindings\": {}\n  }\n]\n";
+        let (range, new_text) = keymap_append_edit(keymap, &import.sections[1..]).unwrap();
+        let mut new_keymap = keymap.to_string();
+        new_keymap.replace_range(range, &new_text);
+        assert_eq!(
+            new_keymap,
+            indoc! {r#"
+                [
+                  {
+                    "bindings": {}
+                  },
+                  {
+                    "context": "Editor",
+                    "bindings": {
+                      "ctrl-k ctrl-c": "editor::ToggleComments",
+                      "ctrl-d": [
+                        "editor::SelectNext",
+                        {
+                          "replace_newest": false
+                        }
+                      ]
+                    }
+                  }
+                ]
+            "#}
+        );
+        assert_eq!(
+            import.untranslated,
+            vec![
+                Untranslated::new(
+                    "ctrl+shift+k: -editor.action.deleteLines",
+                    "removing a default binding isn't supported"
+                ),
+                Untranslated::new(
+                    "ctrl+alt+l: extension.doSomething",
+                    "no equivalent Zed action"
+                ),
+                Untranslated::new(
+                    "numpad_add: workbench.action.zoomIn",
+                    "unsupported key \"numpad_add\""
+                ),
+                Untranslated::new(
+                    "ctrl+j: editor.action.showHover",
+                    "unsupported `when` clause \"editorTextFocus || terminalFocus\""
+                ),
+            ]
+        );
+    }
+}
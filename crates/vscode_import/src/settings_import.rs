@@ -0,0 +1,456 @@
+use std::collections::HashSet;
+
+use anyhow::{Context as _, Result};
+use serde_json::{Map, Value, json};
+use theme::ThemeRegistry;
+
+use crate::Untranslated;
+
+/// The result of translating a VS Code `settings.json` file.
+#[derive(Debug, Default)]
+pub struct SettingsImport {
+    /// The translated settings, nested the way they're written in Zed's `settings.json`.
+    pub settings: Value,
+    pub untranslated: Vec<Untranslated>,
+}
+
+/// The names of the themes and icon themes available in Zed, which VS Code's themes are only
+/// translated to if they have the same name.
+#[derive(Debug, Default)]
+pub struct AvailableThemes {
+    pub themes: HashSet<String>,
+    pub icon_themes: HashSet<String>,
+}
+
+impl AvailableThemes {
+    pub fn new(registry: &ThemeRegistry) -> Self {
+        Self {
+            themes: registry
+                .list_names()
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect(),
+            icon_themes: registry
+                .list_icon_themes()
+                .into_iter()
+                .map(|theme| theme.name.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Zed settings that can be overridden per language, and so may be imported from VS Code's
+/// `[language]` sections.
+const LANGUAGE_SETTINGS: &[&str] = &[
+    "colorize_brackets",
+    "ensure_final_newline_on_save",
+    "format_on_save",
+    "hard_tabs",
+    "indent_guides",
+    "inlay_hints",
+    "preferred_line_length",
+    "remove_trailing_whitespace_on_save",
+    "show_whitespaces",
+    "soft_wrap",
+    "tab_size",
+    "use_autoclose",
+    "wrap_guides",
+];
+
+/// VS Code language identifiers and the names of the corresponding Zed languages.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("c", "C"),
+    ("cpp", "C++"),
+    ("css", "CSS"),
+    ("dart", "Dart"),
+    ("elixir", "Elixir"),
+    ("erlang", "Erlang"),
+    ("git-commit", "Git Commit"),
+    ("go", "Go"),
+    ("graphql", "GraphQL"),
+    ("html", "HTML"),
+    ("java", "Java"),
+    ("javascript", "JavaScript"),
+    ("javascriptreact", "JavaScript"),
+    ("json", "JSON"),
+    ("jsonc", "JSONC"),
+    ("latex", "LaTeX"),
+    ("markdown", "Markdown"),
+    ("php", "PHP"),
+    ("plaintext", "Plain Text"),
+    ("python", "Python"),
+    ("ruby", "Ruby"),
+    ("rust", "Rust"),
+    ("scss", "SCSS"),
+    ("shellscript", "Shell Script"),
+    ("sql", "SQL"),
+    ("svelte", "Svelte"),
+    ("toml", "TOML"),
+    ("typescript", "TypeScript"),
+    ("typescriptreact", "TSX"),
+    ("vue", "Vue.js"),
+    ("xml", "XML"),
+    ("yaml", "YAML"),
+    ("zig", "Zig"),
+];
+
+/// Translates the contents of a VS Code `settings.json` file into Zed settings.
+pub fn import_settings(text: &str, themes: &AvailableThemes) -> Result<SettingsImport> {
+    let vscode_settings: Map<String, Value> =
+        serde_json_lenient::from_str(text).context("parsing VS Code settings")?;
+
+    let mut import = SettingsImport {
+        settings: Value::Object(Map::new()),
+        untranslated: Vec::new(),
+    };
+    for (key, value) in &vscode_settings {
+        if let Some(language_id) = key.strip_prefix('[').and_then(|key| key.strip_suffix(']')) {
+            import_language_settings(language_id, value, &vscode_settings, themes, &mut import);
+            continue;
+        }
+
+        match translate_setting(key, value, &vscode_settings, themes) {
+            Ok(values) => {
+                for (path, value) in values {
+                    insert_at_path(&mut import.settings, path.split('.'), value);
+                }
+            }
+            Err(reason) => import.untranslated.push(Untranslated::new(key, reason)),
+        }
+    }
+    Ok(import)
+}
+
+fn import_language_settings(
+    language_id: &str,
+    value: &Value,
+    vscode_settings: &Map<String, Value>,
+    themes: &AvailableThemes,
+    import: &mut SettingsImport,
+) {
+    let item = format!("[{language_id}]");
+    let Some((_, language)) = LANGUAGES.iter().find(|(id, _)| *id == language_id) else {
+        import
+            .untranslated
+            .push(Untranslated::new(item, "no matching Zed language"));
+        return;
+    };
+    let Some(language_settings) = value.as_object() else {
+        import
+            .untranslated
+            .push(Untranslated::new(item, "expected an object"));
+        return;
+    };
+
+    for (key, value) in language_settings {
+        let item = format!("[{language_id}] {key}");
+        match translate_setting(key, value, vscode_settings, themes) {
+            Ok(values) => {
+                for (path, value) in values {
+                    if !LANGUAGE_SETTINGS.contains(&path.split('.').next().unwrap_or_default()) {
+                        import.untranslated.push(Untranslated::new(
+                            &item,
+                            "the Zed setting can't be set per language",
+                        ));
+                        continue;
+                    }
+                    insert_at_path(
+                        &mut import.settings,
+                        ["languages", language].into_iter().chain(path.split('.')),
+                        value,
+                    );
+                }
+            }
+            Err(reason) => import.untranslated.push(Untranslated::new(item, reason)),
+        }
+    }
+}
+
+/// Returns the dot-separated Zed setting paths and values for a VS Code setting, or the reason it
+/// can't be translated.
+fn translate_setting(
+    key: &str,
+    value: &Value,
+    vscode_settings: &Map<String, Value>,
+    themes: &AvailableThemes,
+) -> Result<Vec<(&'static str, Value)>, String> {
+    let setting = match key {
+        "editor.fontSize" => ("buffer_font_size", number(value)?),
+        "editor.fontFamily" => ("buffer_font_family", json!(font_family(value)?)),
+        "editor.fontWeight" => ("buffer_font_weight", font_weight(value)?),
+        "editor.fontLigatures" => match value {
+            Value::Bool(enabled) => ("buffer_font_features", json!({ "calt": enabled })),
+            _ => return Err("only `true` and `false` are supported".into()),
+        },
+        "editor.lineHeight" => ("buffer_line_height", line_height(value)?),
+        "editor.tabSize" => ("tab_size", number(value)?),
+        "editor.insertSpaces" => ("hard_tabs", json!(!boolean(value)?)),
+        "editor.wordWrap" => (
+            "soft_wrap",
+            json!(match string(value)? {
+                "off" => "none",
+                "on" => "editor_width",
+                "wordWrapColumn" => "preferred_line_length",
+                "bounded" => "bounded",
+                other => return Err(format!("unknown value {other:?}")),
+            }),
+        ),
+        "editor.wordWrapColumn" => ("preferred_line_length", number(value)?),
+        "editor.rulers" => ("wrap_guides", rulers(value)?),
+        "editor.formatOnSave" => (
+            "format_on_save",
+            json!(if boolean(value)? { "on" } else { "off" }),
+        ),
+        "editor.renderWhitespace" => (
+            "show_whitespaces",
+            json!(match string(value)? {
+                "none" => "none",
+                "all" => "all",
+                "boundary" => "boundary",
+                "selection" => "selection",
+                other => return Err(format!("unknown value {other:?}")),
+            }),
+        ),
+        "editor.cursorBlinking" => ("cursor_blink", json!(string(value)? != "solid")),
+        "editor.cursorStyle" => (
+            "cursor_shape",
+            json!(match string(value)? {
+                "line" | "line-thin" => "bar",
+                "block" => "block",
+                "block-outline" => "hollow",
+                "underline" | "underline-thin" => "underline",
+                other => return Err(format!("unknown value {other:?}")),
+            }),
+        ),
+        "editor.lineNumbers" => {
+            return match string(value)? {
+                "on" => Ok(vec![
+                    ("gutter.line_numbers", json!(true)),
+                    ("relative_line_numbers", json!(false)),
+                ]),
+                "off" => Ok(vec![("gutter.line_numbers", json!(false))]),
+                "relative" => Ok(vec![
+                    ("gutter.line_numbers", json!(true)),
+                    ("relative_line_numbers", json!(true)),
+                ]),
+                other => Err(format!("unknown value {other:?}")),
+            };
+        }
+        "editor.multiCursorModifier" => (
+            "multi_cursor_modifier",
+            json!(match string(value)? {
+                "ctrlCmd" => "cmd_or_ctrl",
+                "alt" => "alt",
+                other => return Err(format!("unknown value {other:?}")),
+            }),
+        ),
+        "editor.scrollBeyondLastLine" => (
+            "scroll_beyond_last_line",
+            json!(if boolean(value)? { "one_page" } else { "off" }),
+        ),
+        "editor.hover.enabled" => ("hover_popover_enabled", json!(boolean(value)?)),
+        "editor.autoClosingBrackets" => ("use_autoclose", json!(string(value)? != "never")),
+        "editor.guides.indentation" => ("indent_guides.enabled", json!(boolean(value)?)),
+        "editor.bracketPairColorization.enabled" => ("colorize_brackets", json!(boolean(value)?)),
+        "editor.inlayHints.enabled" => ("inlay_hints.enabled", json!(string(value)? != "off")),
+        "files.autoSave" => (
+            "autosave",
+            match string(value)? {
+                "off" => json!("off"),
+                "afterDelay" => {
+                    let milliseconds = vscode_settings
+                        .get("files.autoSaveDelay")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(1000);
+                    json!({ "after_delay": { "milliseconds": milliseconds } })
+                }
+                "onFocusChange" => json!("on_focus_change"),
+                "onWindowChange" => json!("on_window_change"),
+                other => return Err(format!("unknown value {other:?}")),
+            },
+        ),
+        // Translated along with `files.autoSave`.
+        "files.autoSaveDelay" => return Ok(Vec::new()),
+        "files.trimTrailingWhitespace" => {
+            ("remove_trailing_whitespace_on_save", json!(boolean(value)?))
+        }
+        "files.insertFinalNewline" => ("ensure_final_newline_on_save", json!(boolean(value)?)),
+        "workbench.colorTheme" => match string(value)? {
+            theme if themes.themes.contains(theme) => ("theme", json!(theme)),
+            _ => return Err("the theme isn't available in Zed".into()),
+        },
+        "workbench.iconTheme" => match string(value)? {
+            icon_theme if themes.icon_themes.contains(icon_theme) => {
+                ("icon_theme", json!(icon_theme))
+            }
+            _ => return Err("the icon theme isn't available in Zed".into()),
+        },
+        "workbench.sideBar.location" => ("project_panel.dock", json!(string(value)?)),
+        "terminal.integrated.fontSize" => ("terminal.font_size", number(value)?),
+        "terminal.integrated.fontFamily" => ("terminal.font_family", json!(font_family(value)?)),
+        "terminal.integrated.lineHeight" => ("terminal.line_height", line_height(value)?),
+        "telemetry.telemetryLevel" => {
+            let (diagnostics, metrics) = match string(value)? {
+                "all" => (true, true),
+                "error" | "crash" => (true, false),
+                "off" => (false, false),
+                other => return Err(format!("unknown value {other:?}")),
+            };
+            return Ok(vec![
+                ("telemetry.diagnostics", json!(diagnostics)),
+                ("telemetry.metrics", json!(metrics)),
+            ]);
+        }
+        _ => return Err("no equivalent Zed setting".into()),
+    };
+    Ok(vec![setting])
+}
+
+fn insert_at_path<'a>(settings: &mut Value, path: impl IntoIterator<Item = &'a str>, value: Value) {
+    let mut target = settings;
+    for key in path {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .unwrap()
+            .entry(key)
+            .or_insert(Value::Null);
+    }
+    *target = value;
+}
+
+fn number(value: &Value) -> Result<Value, String> {
+    if value.is_number() {
+        Ok(value.clone())
+    } else {
+        Err("expected a number".into())
+    }
+}
+
+fn boolean(value: &Value) -> Result<bool, String> {
+    value.as_bool().ok_or_else(|| "expected a boolean".into())
+}
+
+fn string(value: &Value) -> Result<&str, String> {
+    value.as_str().ok_or_else(|| "expected a string".into())
+}
+
+/// VS Code accepts a CSS font list, while Zed takes a single font family.
+fn font_family(value: &Value) -> Result<&str, String> {
+    string(value)?
+        .split(',')
+        .map(|family| family.trim().trim_matches(['\'', '"']))
+        .find(|family| !family.is_empty() && *family != "monospace")
+        .ok_or_else(|| "no font family other than `monospace`".into())
+}
+
+fn font_weight(value: &Value) -> Result<Value, String> {
+    match value {
+        Value::Number(_) => Ok(value.clone()),
+        Value::String(weight) => match weight.as_str() {
+            "normal" => Ok(json!(400)),
+            "bold" => Ok(json!(700)),
+            weight => weight
+                .parse::<u32>()
+                .map(|weight| json!(weight))
+                .map_err(|_| format!("unknown value {weight:?}")),
+        },
+        _ => Err("expected a number or a string".into()),
+    }
+}
+
+/// VS Code treats line heights below 8 as multiples of the font size, and larger ones as pixels.
+/// Zed only supports the former.
+fn line_height(value: &Value) -> Result<Value, String> {
+    match value.as_f64() {
+        Some(height) if height > 0. && height < 8. => Ok(json!({ "custom": height })),
+        Some(0.) => Ok(json!("standard")),
+        Some(_) => Err("line heights in pixels aren't supported".into()),
+        None => Err("expected a number".into()),
+    }
+}
+
+fn rulers(value: &Value) -> Result<Value, String> {
+    value
+        .as_array()
+        .ok_or_else(|| "expected an array".to_string())?
+        .iter()
+        .map(|ruler| {
+            ruler
+                .as_u64()
+                .or_else(|| ruler.get("column").and_then(Value::as_u64))
+                .map(|column| json!(column))
+                .ok_or_else(|| "expected columns".to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_import_settings() {
+        let themes = AvailableThemes {
+            themes: HashSet::from_iter(["One Dark".to_string()]),
+            icon_themes: HashSet::from_iter(["Zed (Default)".to_string()]),
+        };
+        let import = import_settings(
+            r##"{
+                // Comments and trailing commas are allowed.
+                "editor.fontSize": 13,
+                "editor.fontFamily": "'JetBrains Mono', Menlo, monospace",
+                "editor.insertSpaces": false,
+                "editor.lineNumbers": "relative",
+                "editor.rulers": [80, { "column": 100, "color": "#ff0000" }],
+                "files.autoSave": "afterDelay",
+                "files.autoSaveDelay": 500,
+                "workbench.startupEditor": "none",
+                "workbench.colorTheme": "One Dark",
+                "workbench.iconTheme": "material-icon-theme",
+                "[rust]": {
+                    "editor.tabSize": 4,
+                    "editor.fontSize": 14,
+                },
+                "[cobol]": {
+                    "editor.tabSize": 8,
+                },
+            }"##,
+            &themes,
+        )
+        .unwrap();
+
+        assert_eq!(
+            import.settings,
+            json!({
+                "buffer_font_size": 13,
+                "buffer_font_family": "JetBrains Mono",
+                "hard_tabs": true,
+                "gutter": { "line_numbers": true },
+                "relative_line_numbers": true,
+                "wrap_guides": [80, 100],
+                "autosave": { "after_delay": { "milliseconds": 500 } },
+                "theme": "One Dark",
+                "languages": { "Rust": { "tab_size": 4 } },
+            })
+        );
+        assert_eq!(
+            import.untranslated,
+            vec![
+                Untranslated::new("workbench.startupEditor", "no equivalent Zed setting"),
+                Untranslated::new(
+                    "workbench.iconTheme",
+                    "the icon theme isn't available in Zed"
+                ),
+                Untranslated::new(
+                    "[rust] editor.fontSize",
+                    "the Zed setting can't be set per language"
+                ),
+                Untranslated::new("[cobol]", "no matching Zed language"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+mod keymap_import;
+mod settings_import;
+
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use anyhow::{Context as _, Result};
+use editor::{Editor, MultiBuffer, ProposedChangeLocation, ProposedChangesEditor};
+use gpui::{App, AppContext as _, Context, Entity, Task, Window, actions};
+use language::Buffer;
+use settings::SettingsStore;
+use theme::ThemeRegistry;
+use workspace::{Workspace, notifications::DetachAndPromptErr as _};
+
+pub use keymap_import::*;
+pub use settings_import::*;
+
+actions!(vscode_import, [ImportSettings]);
+
+/// A VS Code setting or key binding that couldn't be translated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untranslated {
+    pub item: String,
+    pub reason: String,
+}
+
+impl Untranslated {
+    pub fn new(item: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            item: item.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(|workspace, _: &ImportSettings, window, cx| {
+            import_vscode_settings(workspace, window, cx).detach_and_prompt_err(
+                "Failed to import VS Code settings",
+                window,
+                cx,
+                |_, _, _| None,
+            );
+        });
+    })
+    .detach();
+}
+
+/// Returns the directory containing VS Code's user `settings.json` and `keybindings.json`.
+pub fn vscode_user_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("Code").join("User"))
+}
+
+/// Translates the VS Code user settings and key bindings, and opens the resulting changes to the
+/// Zed user settings and keymap for review.
+fn import_vscode_settings(
+    workspace: &mut Workspace,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<Result<()>> {
+    let fs = workspace.app_state().fs.clone();
+    let project = workspace.project().clone();
+    let markdown = workspace
+        .app_state()
+        .languages
+        .language_for_name("Markdown");
+
+    let themes = AvailableThemes::new(&ThemeRegistry::global(cx));
+
+    cx.spawn_in(window, async move |workspace, cx| {
+        let vscode_dir =
+            vscode_user_dir().context("couldn't determine the VS Code settings directory")?;
+        let vscode_settings = fs.load(&vscode_dir.join("settings.json")).await.ok();
+        let vscode_keybindings = fs.load(&vscode_dir.join("keybindings.json")).await.ok();
+        if vscode_settings.is_none() && vscode_keybindings.is_none() {
+            anyhow::bail!("no VS Code settings found in {vscode_dir:?}");
+        }
+        let settings_import = vscode_settings
+            .as_deref()
+            .map(|text| import_settings(text, &themes))
+            .transpose()?
+            .unwrap_or_default();
+        let keymap_import = vscode_keybindings
+            .as_deref()
+            .map(import_keybindings)
+            .transpose()?
+            .unwrap_or_default();
+
+        for (path, initial_content) in [
+            (
+                paths::settings_file(),
+                settings::initial_user_settings_content(),
+            ),
+            (paths::keymap_file(), settings::initial_keymap_content()),
+        ] {
+            if !fs.is_file(path).await {
+                fs.create_file(path, Default::default()).await?;
+                fs.save(path, &initial_content.as_ref().into(), Default::default())
+                    .await?;
+            }
+        }
+        project
+            .update(cx, |project, cx| {
+                project.find_or_create_worktree(paths::config_dir().as_path(), false, cx)
+            })?
+            .await?;
+        let settings_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(paths::settings_file(), cx)
+            })?
+            .await?;
+        let keymap_buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(paths::keymap_file(), cx)
+            })?
+            .await?;
+
+        let mut changes: Vec<(Entity<Buffer>, Vec<_>)> = Vec::new();
+        cx.update(|_, cx| {
+            if settings_import
+                .settings
+                .as_object()
+                .is_some_and(|settings| !settings.is_empty())
+            {
+                let text = settings_buffer.read(cx).text();
+                let edits = cx
+                    .global::<SettingsStore>()
+                    .json_merge_edits(&text, &settings_import.settings)
+                    .context("updating the user settings")?;
+                changes.push((settings_buffer, edits));
+            }
+            if !keymap_import.sections.is_empty() {
+                let text = keymap_buffer.read(cx).text();
+                let edit = keymap_append_edit(&text, &keymap_import.sections)
+                    .context("updating the keymap")?;
+                changes.push((keymap_buffer, vec![edit]));
+            }
+            anyhow::Ok(())
+        })??;
+
+        let has_untranslated =
+            !settings_import.untranslated.is_empty() || !keymap_import.untranslated.is_empty();
+        let markdown = markdown.await.ok();
+        workspace.update_in(cx, |workspace, window, cx| {
+            if has_untranslated {
+                let report = untranslated_report(&settings_import, &keymap_import);
+                let buffer = project.update(cx, |project, cx| {
+                    project.create_local_buffer(&report, markdown, cx)
+                });
+                let buffer = cx.new(|cx| {
+                    MultiBuffer::singleton(buffer, cx)
+                        .with_title("Untranslated VS Code Settings".into())
+                });
+                let editor = cx.new(|cx| {
+                    let mut editor =
+                        Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                    editor.set_read_only(true);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, false, window, cx);
+            }
+
+            if changes.is_empty() {
+                return;
+            }
+            let locations = changes
+                .iter()
+                .map(|(buffer, _)| ProposedChangeLocation {
+                    buffer: buffer.clone(),
+                    ranges: vec![0..buffer.read(cx).len()],
+                })
+                .collect();
+            let editor = cx.new(|cx| {
+                let editor = ProposedChangesEditor::new(
+                    "Imported VS Code Settings",
+                    locations,
+                    Some(project.clone()),
+                    window,
+                    cx,
+                );
+                for (buffer, edits) in changes {
+                    if let Some(branch) = editor.branch_buffer_for_base(&buffer) {
+                        branch.update(cx, |branch, cx| {
+                            // The edits are relative to the text as changed by the previous edits.
+                            for edit in edits {
+                                branch.edit([edit], None, cx);
+                            }
+                        });
+                    }
+                }
+                editor.recalculate_all_buffer_diffs();
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+        })
+    })
+}
+
+fn untranslated_report(settings_import: &SettingsImport, keymap_import: &KeymapImport) -> String {
+    let mut report = String::from("# Untranslated VS Code Settings\n");
+    for (title, untranslated) in [
+        ("Settings", &settings_import.untranslated),
+        ("Key Bindings", &keymap_import.untranslated),
+    ] {
+        if untranslated.is_empty() {
+            continue;
+        }
+        write!(report, "\n## {title}\n\n").ok();
+        for Untranslated { item, reason } in untranslated {
+            writeln!(report, "- `{item}`: {reason}").ok();
+        }
+    }
+    report
+}
@@ -13,7 +13,10 @@ use client::DevServerProjectId;
 use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::sql};
 use gpui::{Axis, Bounds, WindowBounds, WindowId, point, size};
 use itertools::Itertools;
-use project::debugger::breakpoint_store::{BreakpointState, SourceBreakpoint};
+use project::{
+    bookmark_store::SerializedBookmark,
+    debugger::breakpoint_store::{BreakpointState, SourceBreakpoint},
+};
 
 use language::{LanguageName, Toolchain};
 use project::WorktreeId;
@@ -75,9 +78,7 @@ impl sqlez::bindable::Column for SerializedAxis {
 pub(crate) struct SerializedWindowBounds(pub(crate) WindowBounds);
 
 impl StaticColumnCount for SerializedWindowBounds {
-    fn column_count() -> usize {
-        5
-    }
+    const COLUMN_COUNT: usize = 5;
 }
 
 impl Bind for SerializedWindowBounds {
@@ -161,11 +162,7 @@ impl From<BreakpointState> for BreakpointStateWrapper<'static> {
         BreakpointStateWrapper(Cow::Owned(kind))
     }
 }
-impl StaticColumnCount for BreakpointStateWrapper<'_> {
-    fn column_count() -> usize {
-        1
-    }
-}
+impl StaticColumnCount for BreakpointStateWrapper<'_> {}
 
 impl Bind for BreakpointStateWrapper<'_> {
     fn bind(&self, statement: &Statement, start_index: i32) -> anyhow::Result<i32> {
@@ -191,10 +188,8 @@ impl Column for BreakpointStateWrapper<'_> {
 struct Breakpoints(Vec<Breakpoint>);
 
 impl sqlez::bindable::StaticColumnCount for Breakpoint {
-    fn column_count() -> usize {
-        // Position, log message, condition message, and hit condition message
-        4 + BreakpointStateWrapper::column_count()
-    }
+    // Position, log message, condition message, and hit condition message
+    const COLUMN_COUNT: usize = 4 + BreakpointStateWrapper::COLUMN_COUNT;
 }
 
 impl sqlez::bindable::Bind for Breakpoint {
@@ -335,6 +330,14 @@ define_connection! {
     //      kind: int, // The kind of breakpoint (standard, log)
     //      log_message: String, // log message for log breakpoints, otherwise it's Null
     // )
+    //
+    // CREATE TABLE bookmarks(
+    //      workspace_id: usize Foreign Key, // References workspace table
+    //      worktree_path: PathBuf, // The absolute path of the worktree root the bookmarked file belongs to
+    //      path: PathBuf, // The path of the bookmarked file, relative to its worktree root
+    //      row: u32, // The row the bookmark is on
+    //      label: Option<String>, // The name of the bookmark, if it has one
+    // )
     pub static ref DB: WorkspaceDb<()> =
     &[
         sql!(
@@ -541,6 +544,21 @@ define_connection! {
         ALTER TABLE breakpoints ADD COLUMN condition TEXT;
         ALTER TABLE breakpoints ADD COLUMN hit_condition TEXT;
     ),
+    sql!(
+        CREATE TABLE bookmarks (
+            workspace_id INTEGER NOT NULL,
+            worktree_path TEXT NOT NULL,
+            path TEXT NOT NULL,
+            row INTEGER NOT NULL,
+            label TEXT,
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        );
+    ),
+    sql!(
+        ALTER TABLE ssh_projects ADD COLUMN shell_command TEXT;
+    ),
     ];
 }
 
@@ -629,6 +647,7 @@ impl WorkspaceDb {
             docks,
             session_id: None,
             breakpoints: self.breakpoints(workspace_id),
+            bookmarks: self.bookmarks(workspace_id),
             window_id,
         })
     }
@@ -683,6 +702,7 @@ impl WorkspaceDb {
             window_bounds,
             centered_layout: centered_layout.unwrap_or(false),
             breakpoints: self.breakpoints(workspace_id),
+            bookmarks: self.bookmarks(workspace_id),
             display,
             docks,
             session_id: None,
@@ -736,6 +756,33 @@ impl WorkspaceDb {
         }
     }
 
+    fn bookmarks(&self, workspace_id: WorkspaceId) -> Vec<SerializedBookmark> {
+        let bookmarks: Result<Vec<(PathBuf, PathBuf, u32, Option<String>)>> = self
+            .select_bound(sql! {
+                SELECT worktree_path, path, row, label
+                FROM bookmarks
+                WHERE workspace_id = ?
+                ORDER BY worktree_path, path, row
+            })
+            .and_then(|mut prepared_statement| (prepared_statement)(workspace_id));
+
+        match bookmarks {
+            Ok(bookmarks) => bookmarks
+                .into_iter()
+                .map(|(worktree_path, path, row, label)| SerializedBookmark {
+                    worktree_path: worktree_path.into(),
+                    path: path.into(),
+                    row,
+                    label,
+                })
+                .collect(),
+            Err(msg) => {
+                log::error!("Bookmarks query failed with msg: {msg}");
+                Default::default()
+            }
+        }
+    }
+
     /// Saves a workspace using the worktree roots. Will garbage collect any workspaces
     /// that used this workspace previously
     pub(crate) async fn save_workspace(&self, workspace: SerializedWorkspace) {
@@ -777,6 +824,21 @@ impl WorkspaceDb {
 
                 }
 
+                conn.exec_bound(sql!(DELETE FROM bookmarks WHERE workspace_id = ?1))?(workspace.id).context("Clearing old bookmarks")?;
+
+                for bookmark in workspace.bookmarks {
+                    conn.exec_bound(sql!(
+                        INSERT INTO bookmarks (workspace_id, worktree_path, path, row, label)
+                        VALUES (?1, ?2, ?3, ?4, ?5);))?
+                    ((
+                        workspace.id,
+                        bookmark.worktree_path.as_ref(),
+                        bookmark.path.as_ref(),
+                        bookmark.row,
+                        bookmark.label,
+                    ))
+                    .context("Storing bookmark")?;
+                }
 
                 match workspace.location {
                     SerializedWorkspaceLocation::Local(local_paths, local_paths_order) => {
@@ -901,38 +963,49 @@ impl WorkspaceDb {
         port: Option<u16>,
         paths: Vec<String>,
         user: Option<String>,
+        shell_command: Option<Vec<String>>,
     ) -> Result<SerializedSshProject> {
         let paths = serde_json::to_string(&paths)?;
+        let shell_command = shell_command
+            .map(|command| serde_json::to_string(&command))
+            .transpose()?;
         if let Some(project) = self
-            .get_ssh_project(host.clone(), port, paths.clone(), user.clone())
+            .get_ssh_project(
+                host.clone(),
+                port,
+                paths.clone(),
+                user.clone(),
+                shell_command.clone(),
+            )
             .await?
         {
             Ok(project)
         } else {
-            self.insert_ssh_project(host, port, paths, user)
+            self.insert_ssh_project(host, port, paths, user, shell_command)
                 .await?
                 .ok_or_else(|| anyhow!("failed to insert ssh project"))
         }
     }
 
     query! {
-        async fn get_ssh_project(host: String, port: Option<u16>, paths: String, user: Option<String>) -> Result<Option<SerializedSshProject>> {
-            SELECT id, host, port, paths, user
+        async fn get_ssh_project(host: String, port: Option<u16>, paths: String, user: Option<String>, shell_command: Option<String>) -> Result<Option<SerializedSshProject>> {
+            SELECT id, host, port, paths, user, shell_command
             FROM ssh_projects
-            WHERE host IS ? AND port IS ? AND paths IS ? AND user IS ?
+            WHERE host IS ? AND port IS ? AND paths IS ? AND user IS ? AND shell_command IS ?
             LIMIT 1
         }
     }
 
     query! {
-        async fn insert_ssh_project(host: String, port: Option<u16>, paths: String, user: Option<String>) -> Result<Option<SerializedSshProject>> {
+        async fn insert_ssh_project(host: String, port: Option<u16>, paths: String, user: Option<String>, shell_command: Option<String>) -> Result<Option<SerializedSshProject>> {
             INSERT INTO ssh_projects(
                 host,
                 port,
                 paths,
-                user
-            ) VALUES (?1, ?2, ?3, ?4)
-            RETURNING id, host, port, paths, user
+                user,
+                shell_command
+            ) VALUES (?1, ?2, ?3, ?4, ?5)
+            RETURNING id, host, port, paths, user, shell_command
         }
     }
 
@@ -978,14 +1051,14 @@ impl WorkspaceDb {
 
     query! {
         fn ssh_projects() -> Result<Vec<SerializedSshProject>> {
-            SELECT id, host, port, paths, user
+            SELECT id, host, port, paths, user, shell_command
             FROM ssh_projects
         }
     }
 
     query! {
         fn ssh_project(id: u64) -> Result<SerializedSshProject> {
-            SELECT id, host, port, paths, user
+            SELECT id, host, port, paths, user, shell_command
             FROM ssh_projects
             WHERE id = ?
         }
@@ -1295,7 +1368,7 @@ impl WorkspaceDb {
             INSERT INTO items(workspace_id, pane_id, position, kind, item_id, active, preview) VALUES (?, ?, ?, ?, ?, ?, ?)
         )).context("Preparing insertion")?;
         for (position, item) in items.iter().enumerate() {
-            insert((workspace_id, pane_id, position, item))?;
+            insert((workspace_id, pane_id, position, item.clone()))?;
         }
 
         Ok(())
@@ -1529,6 +1602,7 @@ mod tests {
                 );
                 map
             },
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
@@ -1648,6 +1722,7 @@ mod tests {
                 );
                 map
             },
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
@@ -1680,6 +1755,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: collections::BTreeMap::default(),
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
@@ -1774,6 +1850,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
@@ -1787,6 +1864,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
@@ -1892,6 +1970,7 @@ mod tests {
             center_group,
             window_bounds: Default::default(),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
@@ -1927,6 +2006,7 @@ mod tests {
             center_group: Default::default(),
             window_bounds: Default::default(),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
@@ -1943,6 +2023,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: None,
             window_id: Some(2),
         };
@@ -1983,6 +2064,7 @@ mod tests {
             center_group: Default::default(),
             window_bounds: Default::default(),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
@@ -2023,6 +2105,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: Some("session-id-1".to_owned()),
             window_id: Some(10),
         };
@@ -2036,6 +2119,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: Some("session-id-1".to_owned()),
             window_id: Some(20),
         };
@@ -2049,6 +2133,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: Some("session-id-2".to_owned()),
             window_id: Some(30),
         };
@@ -2062,12 +2147,13 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: None,
             window_id: None,
         };
 
         let ssh_project = db
-            .get_or_create_ssh_project("my-host".to_string(), Some(1234), vec![], None)
+            .get_or_create_ssh_project("my-host".to_string(), Some(1234), vec![], None, None)
             .await
             .unwrap();
 
@@ -2080,6 +2166,7 @@ mod tests {
             docks: Default::default(),
             centered_layout: false,
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             session_id: Some("session-id-2".to_owned()),
             window_id: Some(50),
         };
@@ -2093,6 +2180,7 @@ mod tests {
             center_group: Default::default(),
             window_bounds: Default::default(),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             display: Default::default(),
             docks: Default::default(),
             centered_layout: false,
@@ -2151,6 +2239,7 @@ mod tests {
             display: Default::default(),
             docks: Default::default(),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             centered_layout: false,
             session_id: None,
             window_id: None,
@@ -2199,6 +2288,7 @@ mod tests {
             centered_layout: false,
             session_id: Some("one-session".to_owned()),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             window_id: Some(window_id),
         })
         .collect::<Vec<_>>();
@@ -2266,9 +2356,15 @@ mod tests {
         ]
         .into_iter()
         .map(|(host, user)| async {
-            db.get_or_create_ssh_project(host.to_string(), None, vec![], Some(user.to_string()))
-                .await
-                .unwrap()
+            db.get_or_create_ssh_project(
+                host.to_string(),
+                None,
+                vec![],
+                Some(user.to_string()),
+                None,
+            )
+            .await
+            .unwrap()
         })
         .collect::<Vec<_>>();
 
@@ -2291,6 +2387,7 @@ mod tests {
             centered_layout: false,
             session_id: Some("one-session".to_owned()),
             breakpoints: Default::default(),
+            bookmarks: Default::default(),
             window_id: Some(window_id),
         })
         .collect::<Vec<_>>();
@@ -2340,7 +2437,7 @@ mod tests {
         );
 
         let project = db
-            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone())
+            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone(), None)
             .await
             .unwrap();
 
@@ -2350,7 +2447,7 @@ mod tests {
 
         // Test that calling the function again with the same parameters returns the same project
         let same_project = db
-            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone())
+            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone(), None)
             .await
             .unwrap();
 
@@ -2364,7 +2461,7 @@ mod tests {
         );
 
         let different_project = db
-            .get_or_create_ssh_project(host2.clone(), None, paths2.clone(), user2.clone())
+            .get_or_create_ssh_project(host2.clone(), None, paths2.clone(), user2.clone(), None)
             .await
             .unwrap();
 
@@ -2374,6 +2471,54 @@ mod tests {
         assert_eq!(different_project.user, user2);
     }
 
+    #[gpui::test]
+    async fn test_get_or_create_ssh_project_with_shell_command() {
+        let db =
+            WorkspaceDb(open_test_db("test_get_or_create_ssh_project_with_shell_command").await);
+
+        let (host, paths) = ("my-container".to_string(), vec!["/workspace".to_string()]);
+        let shell_command = Some(vec![
+            "docker".to_string(),
+            "exec".to_string(),
+            "-i".to_string(),
+            "my-container".to_string(),
+            "sh".to_string(),
+        ]);
+
+        let project = db
+            .get_or_create_ssh_project(
+                host.clone(),
+                None,
+                paths.clone(),
+                None,
+                shell_command.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(project.shell_command, shell_command);
+
+        let same_project = db
+            .get_or_create_ssh_project(
+                host.clone(),
+                None,
+                paths.clone(),
+                None,
+                shell_command.clone(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(project.id, same_project.id);
+
+        let ssh_project = db
+            .get_or_create_ssh_project(host.clone(), None, paths.clone(), None, None)
+            .await
+            .unwrap();
+        assert_ne!(project.id, ssh_project.id);
+        assert_eq!(ssh_project.shell_command, None);
+
+        assert_eq!(db.ssh_project(project.id.0).unwrap(), project);
+    }
+
     #[gpui::test]
     async fn test_get_or_create_ssh_project_with_null_user() {
         let db = WorkspaceDb(open_test_db("test_get_or_create_ssh_project_with_null_user").await);
@@ -2386,7 +2531,7 @@ mod tests {
         );
 
         let project = db
-            .get_or_create_ssh_project(host.clone(), port, paths.clone(), None)
+            .get_or_create_ssh_project(host.clone(), port, paths.clone(), None, None)
             .await
             .unwrap();
 
@@ -2396,7 +2541,7 @@ mod tests {
 
         // Test that calling the function again with the same parameters returns the same project
         let same_project = db
-            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone())
+            .get_or_create_ssh_project(host.clone(), port, paths.clone(), user.clone(), None)
             .await
             .unwrap();
 
@@ -2430,7 +2575,7 @@ mod tests {
 
         for (host, port, paths, user) in projects.iter() {
             let project = db
-                .get_or_create_ssh_project(host.clone(), *port, paths.clone(), user.clone())
+                .get_or_create_ssh_project(host.clone(), *port, paths.clone(), user.clone(), None)
                 .await
                 .unwrap();
 
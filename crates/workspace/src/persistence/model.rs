@@ -4,13 +4,18 @@ use crate::{
 };
 use anyhow::{Context as _, Result};
 use async_recursion::async_recursion;
-use db::sqlez::{
-    bindable::{Bind, Column, StaticColumnCount},
-    statement::Statement,
+use db::{
+    sqlez::{
+        bindable::{Bind, Column, StaticColumnCount},
+        statement::Statement,
+    },
+    sqlez_macros::{Bind, Column},
 };
 use gpui::{AsyncWindowContext, Entity, WeakEntity};
 use itertools::Itertools as _;
-use project::{Project, debugger::breakpoint_store::SourceBreakpoint};
+use project::{
+    Project, bookmark_store::SerializedBookmark, debugger::breakpoint_store::SourceBreakpoint,
+};
 use remote::ssh_session::SshProjectId;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -28,6 +33,8 @@ pub struct SerializedSshProject {
     pub port: Option<u16>,
     pub paths: Vec<String>,
     pub user: Option<String>,
+    /// The command used to reach the remote shell instead of `ssh`, if any.
+    pub shell_command: Option<Vec<String>>,
 }
 
 impl SerializedSshProject {
@@ -53,9 +60,7 @@ impl SerializedSshProject {
 }
 
 impl StaticColumnCount for SerializedSshProject {
-    fn column_count() -> usize {
-        5
-    }
+    const COLUMN_COUNT: usize = 6;
 }
 
 impl Bind for &SerializedSshProject {
@@ -65,7 +70,13 @@ impl Bind for &SerializedSshProject {
         let next_index = statement.bind(&self.port, next_index)?;
         let raw_paths = serde_json::to_string(&self.paths)?;
         let next_index = statement.bind(&raw_paths, next_index)?;
-        statement.bind(&self.user, next_index)
+        let next_index = statement.bind(&self.user, next_index)?;
+        let raw_shell_command = self
+            .shell_command
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        statement.bind(&raw_shell_command, next_index)
     }
 }
 
@@ -78,6 +89,10 @@ impl Column for SerializedSshProject {
         let paths: Vec<String> = serde_json::from_str(&raw_paths)?;
 
         let (user, _) = Option::<String>::column(statement, start_index + 4)?;
+        let (raw_shell_command, _) = Option::<String>::column(statement, start_index + 5)?;
+        let shell_command = raw_shell_command
+            .map(|raw_shell_command| serde_json::from_str(&raw_shell_command))
+            .transpose()?;
 
         Ok((
             Self {
@@ -86,8 +101,9 @@ impl Column for SerializedSshProject {
                 port,
                 paths,
                 user,
+                shell_command,
             },
-            start_index + 5,
+            start_index + 6,
         ))
     }
 }
@@ -265,6 +281,7 @@ pub(crate) struct SerializedWorkspace {
     pub(crate) docks: DockStructure,
     pub(crate) session_id: Option<String>,
     pub(crate) breakpoints: BTreeMap<Arc<Path>, Vec<SourceBreakpoint>>,
+    pub(crate) bookmarks: Vec<SerializedBookmark>,
     pub(crate) window_id: Option<u64>,
 }
 
@@ -516,7 +533,7 @@ pub type GroupId = i64;
 pub type PaneId = i64;
 pub type ItemId = u64;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Bind, Column)]
 pub struct SerializedItem {
     pub kind: Arc<str>,
     pub item_id: ItemId,
@@ -547,38 +564,6 @@ impl Default for SerializedItem {
     }
 }
 
-impl StaticColumnCount for SerializedItem {
-    fn column_count() -> usize {
-        4
-    }
-}
-impl Bind for &SerializedItem {
-    fn bind(&self, statement: &Statement, start_index: i32) -> Result<i32> {
-        let next_index = statement.bind(&self.kind, start_index)?;
-        let next_index = statement.bind(&self.item_id, next_index)?;
-        let next_index = statement.bind(&self.active, next_index)?;
-        statement.bind(&self.preview, next_index)
-    }
-}
-
-impl Column for SerializedItem {
-    fn column(statement: &mut Statement, start_index: i32) -> Result<(Self, i32)> {
-        let (kind, next_index) = Arc::<str>::column(statement, start_index)?;
-        let (item_id, next_index) = ItemId::column(statement, next_index)?;
-        let (active, next_index) = bool::column(statement, next_index)?;
-        let (preview, next_index) = bool::column(statement, next_index)?;
-        Ok((
-            SerializedItem {
-                kind,
-                item_id,
-                active,
-                preview,
-            },
-            next_index,
-        ))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -644,6 +629,7 @@ mod tests {
             port: Some(22),
             paths: urls.iter().map(|s| s.to_string()).collect(),
             user: Some("user".to_string()),
+            shell_command: None,
         });
         assert_eq!(
             serialized.sorted_paths(),
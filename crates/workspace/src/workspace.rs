@@ -67,7 +67,7 @@ pub use persistence::{
 use postage::stream::Stream;
 use project::{
     DirectoryLister, Project, ProjectEntryId, ProjectPath, ResolvedPath, Worktree, WorktreeId,
-    debugger::breakpoint_store::BreakpointStoreEvent,
+    bookmark_store::BookmarkStoreEvent, debugger::breakpoint_store::BreakpointStoreEvent,
 };
 use remote::{SshClientDelegate, SshConnectionOptions, ssh_session::ConnectionIdentifier};
 use schemars::JsonSchema;
@@ -978,6 +978,17 @@ impl Workspace {
         )
         .detach();
 
+        cx.subscribe_in(
+            &project.read(cx).bookmark_store(),
+            window,
+            |workspace, _, event, window, cx| match event {
+                BookmarkStoreEvent::BookmarksUpdated => {
+                    workspace.serialize_workspace(window, cx);
+                }
+            },
+        )
+        .detach();
+
         cx.on_focus_lost(window, |this, window, cx| {
             let focus_handle = this.focus_handle(cx);
             window.focus(&focus_handle);
@@ -4700,6 +4711,9 @@ impl Workspace {
             let breakpoints = self.project.update(cx, |project, cx| {
                 project.breakpoint_store().read(cx).all_breakpoints(cx)
             });
+            let bookmarks = self.project.update(cx, |project, cx| {
+                project.bookmark_store().read(cx).serialized_bookmarks(cx)
+            });
 
             let center_group = build_serialized_pane_group(&self.center.root, window, cx);
             let docks = build_serialized_docks(self, window, cx);
@@ -4714,6 +4728,7 @@ impl Workspace {
                 centered_layout: self.centered_layout,
                 session_id: self.session_id.clone(),
                 breakpoints,
+                bookmarks,
                 window_id: Some(window.window_handle().window_id().as_u64()),
             };
             return window.spawn(cx, async move |_| {
@@ -4862,6 +4877,15 @@ impl Workspace {
                 })?
                 .await;
 
+            project
+                .update(cx, |project, cx| {
+                    project.bookmark_store().update(cx, |bookmark_store, cx| {
+                        bookmark_store.with_serialized_bookmarks(serialized_workspace.bookmarks, cx)
+                    })
+                })?
+                .await
+                .log_err();
+
             // Clean up all the items that have _not_ been loaded. Our ItemIds aren't stable. That means
             // after loading the items, we might have different items and in order to avoid
             // the database filling up, we delete items that haven't been loaded now.
@@ -5366,6 +5390,48 @@ fn notify_if_database_failed(workspace: WindowHandle<Workspace>, cx: &mut AsyncA
                     },
                 );
             }
+
+            let backup_paths = db::take_reset_db_backups();
+            if !backup_paths.is_empty() {
+                struct DatabaseResetNotification;
+
+                let backups = backup_paths
+                    .iter()
+                    .map(|path| path.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let (message, reveal_message) = if backup_paths.len() == 1 {
+                    (
+                        format!(
+                            "Some saved state was reset because it didn't match this version of Zed. A backup of the database was saved to {backups}."
+                        ),
+                        "Reveal Backup",
+                    )
+                } else {
+                    (
+                        format!(
+                            "Some saved state was reset because it didn't match this version of Zed. Backups of the databases were saved to {backups}."
+                        ),
+                        "Reveal Backups",
+                    )
+                };
+                workspace.show_notification(
+                    NotificationId::unique::<DatabaseResetNotification>(),
+                    cx,
+                    |cx| {
+                        cx.new(|cx| {
+                            MessageNotification::new(message, cx)
+                                .primary_message(reveal_message)
+                                .primary_icon(IconName::FolderOpen)
+                                .primary_on_click(move |_window, cx| {
+                                    for backup_path in &backup_paths {
+                                        cx.reveal_path(backup_path);
+                                    }
+                                })
+                        })
+                    },
+                );
+            }
         })
         .log_err();
 }
@@ -6454,6 +6520,7 @@ fn serialize_ssh_project(
                     .map(|path| path.to_string_lossy().to_string())
                     .collect::<Vec<_>>(),
                 connection_options.username.clone(),
+                connection_options.shell_command.clone(),
             )
             .await?;
 
@@ -7,7 +7,7 @@ use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
 use anyhow::{Context as _, Result, anyhow};
 use clock::ReplicaId;
 use collections::{HashMap, HashSet, VecDeque};
-use fs::{Fs, MTime, PathEvent, RemoveOptions, Watcher, copy_recursive};
+use fs::{Encoding, Fs, MTime, PathEvent, RemoveOptions, Watcher, copy_recursive};
 use futures::{
     FutureExt as _, Stream, StreamExt,
     channel::{
@@ -100,6 +100,7 @@ pub enum CreatedEntry {
 pub struct LoadedFile {
     pub file: Arc<File>,
     pub text: String,
+    pub encoding: Encoding,
 }
 
 pub struct LoadedBinaryFile {
@@ -822,10 +823,11 @@ impl Worktree {
         path: &Path,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, encoding, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1503,15 +1505,19 @@ impl LocalWorktree {
             //       if it is too large
             //       5GB seems to be more reasonable, peaking at ~16GB, while 6GB jumps up to >24GB which seems like a
             //       reasonable limit
+            //       Files above `large_file_viewer.threshold_mb` are opened without loading them into a buffer,
+            //       so this only applies when that threshold is raised past the limit.
             {
                 const FILE_SIZE_MAX: u64 = 6 * 1024 * 1024 * 1024; // 6GB
                 if let Ok(Some(metadata)) = fs.metadata(&abs_path).await {
                     if metadata.len >= FILE_SIZE_MAX {
-                        anyhow::bail!("File is too large to load");
+                        anyhow::bail!(
+                            "File is too large to load, lower `large_file_viewer.threshold_mb` to open it read-only"
+                        );
                     }
                 }
             }
-            let text = fs.load(&abs_path).await?;
+            let (text, encoding) = fs.load_with_encoding(&abs_path, None).await?;
 
             let worktree = this
                 .upgrade()
@@ -1541,7 +1547,11 @@ impl LocalWorktree {
                 }
             };
 
-            Ok(LoadedFile { file, text })
+            Ok(LoadedFile {
+                file,
+                text,
+                encoding,
+            })
         })
     }
 
@@ -1627,6 +1637,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &Context<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1639,7 +1650,10 @@ impl LocalWorktree {
         let write = cx.background_spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move {
+                fs.save_with_encoding(&abs_path, &text, line_ending, encoding)
+                    .await
+            }
         });
 
         cx.spawn(async move |this, cx| {
@@ -3294,11 +3308,14 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &App) -> Task<Result<String>> {
+    fn load(&self, encoding: Encoding, cx: &App) -> Task<Result<String>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
-        cx.background_spawn(async move { fs.load(&abs_path?).await })
+        cx.background_spawn(async move {
+            let (text, _) = fs.load_with_encoding(&abs_path?, Some(encoding)).await?;
+            Ok(text)
+        })
     }
 
     fn load_bytes(&self, cx: &App) -> Task<Result<Vec<u8>>> {
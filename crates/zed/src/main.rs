@@ -504,11 +504,13 @@ fn main() {
             cx,
         );
         recent_projects::init(cx);
+        dev_container::init(cx);
 
         load_embedded_fonts(cx);
 
         app_state.languages.set_theme(cx.theme().clone());
         editor::init(cx);
+        large_file_viewer::init(cx);
         image_viewer::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
@@ -521,6 +523,7 @@ fn main() {
         file_finder::init(cx);
         tab_switcher::init(cx);
         outline::init(cx);
+        bookmarks::init(cx);
         project_symbols::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
@@ -532,9 +535,14 @@ fn main() {
         terminal_view::init(cx);
         journal::init(app_state.clone(), cx);
         language_selector::init(cx);
+        encoding_selector::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
+        settings_profile_selector::init(cx);
+        vscode_import::init(cx);
         language_tools::init(cx);
+        local_history::init(cx);
+        spellcheck::init(cx);
         call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         notifications::init(app_state.client.clone(), app_state.user_store.clone(), cx);
         collab_ui::init(&app_state, cx);
@@ -835,8 +843,12 @@ async fn restore_or_create_workspace(app_state: Arc<AppState>, cx: &mut AsyncApp
                 }
                 SerializedWorkspaceLocation::Ssh(ssh) => {
                     let connection_options = cx.update(|cx| {
-                        SshSettings::get_global(cx)
-                            .connection_options_for(ssh.host, ssh.port, ssh.user)
+                        SshSettings::get_global(cx).connection_options_for(
+                            ssh.host,
+                            ssh.port,
+                            ssh.user,
+                            ssh.shell_command,
+                        )
                     })?;
                     let app_state = app_state.clone();
                     cx.spawn(async move |cx| {
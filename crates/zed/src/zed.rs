@@ -1,4 +1,5 @@
 mod app_menus;
+mod explain_setting;
 pub mod inline_completion_registry;
 #[cfg(target_os = "macos")]
 pub(crate) mod mac_only_instance;
@@ -81,6 +82,7 @@ actions!(
     zed,
     [
         DebugElements,
+        ExplainSetting,
         Hide,
         HideOthers,
         Minimize,
@@ -761,6 +763,7 @@ fn register_actions(
             );
         })
         .register_action(open_project_settings_file)
+        .register_action(explain_setting::explain_setting)
         .register_action(open_project_tasks_file)
         .register_action(open_project_debug_tasks_file)
         .register_action(
@@ -1639,6 +1642,17 @@ fn open_bundled_file(
     window: &mut Window,
     cx: &mut Context<Workspace>,
 ) {
+    open_bundled_editor(workspace, text, title, language, window, cx).detach_and_log_err(cx);
+}
+
+fn open_bundled_editor(
+    workspace: &Workspace,
+    text: Cow<'static, str>,
+    title: &'static str,
+    language: &'static str,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) -> Task<anyhow::Result<Entity<Editor>>> {
     let language = workspace.app_state().languages.language_for_name(language);
     cx.spawn_in(window, async move |workspace, cx| {
         let language = language.await.log_err();
@@ -1651,24 +1665,25 @@ fn open_bundled_file(
                     });
                     let buffer =
                         cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.into()));
+                    let editor = cx.new(|cx| {
+                        let mut editor =
+                            Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                        editor.set_read_only(true);
+                        editor.set_breadcrumb_header(title.into());
+                        editor
+                    });
                     workspace.add_item_to_active_pane(
-                        Box::new(cx.new(|cx| {
-                            let mut editor =
-                                Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
-                            editor.set_read_only(true);
-                            editor.set_breadcrumb_header(title.into());
-                            editor
-                        })),
+                        Box::new(editor.clone()),
                         None,
                         true,
                         window,
                         cx,
                     );
+                    editor
                 })
             })?
             .await
     })
-    .detach_and_log_err(cx);
 }
 
 fn open_settings_file(
@@ -27,6 +27,10 @@ pub fn app_menus() -> Vec<Menu> {
                             "Select Theme...",
                             zed_actions::theme_selector::Toggle::default(),
                         ),
+                        MenuItem::action(
+                            "Select Settings Profile...",
+                            settings_profile_selector::Toggle,
+                        ),
                     ],
                 }),
                 MenuItem::separator(),
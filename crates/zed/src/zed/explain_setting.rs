@@ -0,0 +1,634 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use editor::{Editor, scroll::Autoscroll};
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, AppContext as _, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task,
+    WeakEntity, Window,
+};
+use language::{Point, ToOffset as _};
+use paths::{EDITORCONFIG_NAME, local_settings_file_relative_path};
+use picker::{Picker, PickerDelegate};
+use release_channel::RELEASE_CHANNEL;
+use settings::{SettingsLayer, SettingsLocation, SettingsStore, WorktreeId};
+use ui::{HighlightedLabel, ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, OpenOptions, OpenVisible, Workspace};
+
+use super::{ExplainSetting, open_bundled_editor};
+
+/// The setting to explain, and the file whose project settings apply to it.
+#[derive(Clone)]
+struct SettingQuery {
+    key_path: Vec<String>,
+    location: Option<(WorktreeId, Arc<Path>)>,
+}
+
+/// Shows where the value of the setting under the cursor in a settings file comes from, or lets
+/// the user search for a setting to explain.
+pub(crate) fn explain_setting(
+    workspace: &mut Workspace,
+    _: &ExplainSetting,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let mut query = SettingQuery {
+        key_path: Vec::new(),
+        location: None,
+    };
+    if let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    {
+        let editor = editor.read(cx);
+        if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+            let buffer = buffer.read(cx);
+            if let Some(file) = buffer.file() {
+                query.location = Some((file.worktree_id(cx), file.path().clone()));
+                if file.file_name(cx) == "settings.json" {
+                    let offset = editor
+                        .selections
+                        .newest_anchor()
+                        .head()
+                        .text_anchor
+                        .to_offset(buffer);
+                    query.key_path =
+                        setting_key_path(settings::json_key_path_at_offset(&buffer.text(), offset));
+                }
+            }
+        }
+    }
+
+    let workspace_handle = cx.entity().downgrade();
+    if query.key_path.is_empty() {
+        workspace.toggle_modal(window, cx, |window, cx| {
+            let delegate =
+                SettingKeyPickerDelegate::new(cx.entity().downgrade(), workspace_handle, query, cx);
+            SettingKeyPicker {
+                picker: cx.new(|cx| Picker::uniform_list(delegate, window, cx)),
+            }
+        });
+    } else {
+        show_provenance(workspace, query, window, cx);
+    }
+}
+
+/// Strips the settings profile or release channel a key path in the user settings is nested in.
+fn setting_key_path(mut key_path: Vec<String>) -> Vec<String> {
+    if key_path.len() > 2 && key_path[0] == "profiles" {
+        key_path.drain(..2);
+    } else if key_path.len() > 1 && key_path[0] == RELEASE_CHANNEL.dev_name() {
+        key_path.remove(0);
+    }
+    key_path
+}
+
+fn show_provenance(
+    workspace: &mut Workspace,
+    query: SettingQuery,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let workspace_handle = cx.entity().downgrade();
+    let project = workspace.project().read(cx);
+    let worktree_abs_path = |worktree_id: WorktreeId| {
+        project
+            .worktree_for_id(worktree_id, cx)
+            .map(|worktree| worktree.read(cx).abs_path())
+    };
+
+    let key_path = query
+        .key_path
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let provenance = cx.global::<SettingsStore>().setting_provenance(
+        &key_path,
+        query
+            .location
+            .as_ref()
+            .map(|(worktree_id, path)| SettingsLocation {
+                worktree_id: *worktree_id,
+                path,
+            }),
+    );
+
+    let contributions = provenance
+        .contributions
+        .into_iter()
+        .map(|contribution| {
+            let (label, source, source_key_path) = match &contribution.layer {
+                SettingsLayer::Default => (
+                    "Default".into(),
+                    Some(SettingSource::DefaultSettings),
+                    query.key_path.clone(),
+                ),
+                SettingsLayer::Extensions => ("Extensions".into(), None, Vec::new()),
+                SettingsLayer::User => (
+                    "User".into(),
+                    Some(SettingSource::File(paths::settings_file().clone())),
+                    query.key_path.clone(),
+                ),
+                SettingsLayer::Profile(profile) => (
+                    format!("Profile \"{profile}\"").into(),
+                    Some(SettingSource::File(paths::settings_file().clone())),
+                    ["profiles".to_string(), profile.clone()]
+                        .into_iter()
+                        .chain(query.key_path.iter().cloned())
+                        .collect(),
+                ),
+                SettingsLayer::ReleaseChannel => (
+                    format!("User ({})", RELEASE_CHANNEL.display_name()).into(),
+                    Some(SettingSource::File(paths::settings_file().clone())),
+                    [RELEASE_CHANNEL.dev_name().to_string()]
+                        .into_iter()
+                        .chain(query.key_path.iter().cloned())
+                        .collect(),
+                ),
+                SettingsLayer::Server => ("Server".into(), None, Vec::new()),
+                SettingsLayer::Project {
+                    worktree_id,
+                    directory,
+                } => (
+                    "Project".into(),
+                    worktree_abs_path(*worktree_id).map(|abs_path| {
+                        SettingSource::File(
+                            abs_path
+                                .join(directory)
+                                .join(local_settings_file_relative_path()),
+                        )
+                    }),
+                    query.key_path.clone(),
+                ),
+                SettingsLayer::Editorconfig {
+                    worktree_id,
+                    directory,
+                } => (
+                    "EditorConfig".into(),
+                    worktree_abs_path(*worktree_id).map(|abs_path| {
+                        SettingSource::File(abs_path.join(directory).join(EDITORCONFIG_NAME))
+                    }),
+                    Vec::new(),
+                ),
+            };
+            Contribution {
+                label,
+                value: contribution.value.to_string().into(),
+                source,
+                key_path: source_key_path,
+                row: None,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let fs = workspace.app_state().fs.clone();
+    let value = provenance
+        .value
+        .map_or_else(|| "not set".to_string(), |value| value.to_string());
+    workspace.toggle_modal(window, cx, move |window, cx| {
+        let delegate = SettingProvenanceDelegate {
+            modal: cx.entity().downgrade(),
+            workspace: workspace_handle,
+            key_path: query.key_path.join(".").into(),
+            value: value.into(),
+            contributions,
+            selected_index: 0,
+        };
+        let picker = cx.new(|cx| Picker::nonsearchable_uniform_list(delegate, window, cx));
+        let resolve_rows = resolve_rows(picker.downgrade(), fs, window, cx);
+        SettingProvenanceModal {
+            picker,
+            _resolve_rows: resolve_rows,
+        }
+    });
+}
+
+/// Finds the row at which each layer sets the setting, to link to it.
+fn resolve_rows(
+    picker: WeakEntity<Picker<SettingProvenanceDelegate>>,
+    fs: Arc<dyn fs::Fs>,
+    window: &mut Window,
+    cx: &mut Context<SettingProvenanceModal>,
+) -> Task<()> {
+    let Some(sources) = picker
+        .read_with(cx, |picker, _| {
+            picker
+                .delegate
+                .contributions
+                .iter()
+                .map(|contribution| (contribution.source.clone(), contribution.key_path.clone()))
+                .collect::<Vec<_>>()
+        })
+        .log_err()
+    else {
+        return Task::ready(());
+    };
+
+    cx.spawn_in(window, async move |_, cx| {
+        let mut rows = Vec::new();
+        for (source, key_path) in sources {
+            let text = match source {
+                Some(SettingSource::DefaultSettings) => Some(settings::default_settings().into()),
+                Some(SettingSource::File(path)) => fs.load(&path).await.log_err(),
+                None => None,
+            };
+            rows.push(text.and_then(|text| setting_row(&text, &key_path)));
+        }
+        picker
+            .update(cx, |picker, cx| {
+                for (contribution, row) in picker.delegate.contributions.iter_mut().zip(rows) {
+                    contribution.row = row;
+                }
+                cx.notify();
+            })
+            .ok();
+    })
+}
+
+/// Returns the row at which the given key path is set in a settings file. An empty key path
+/// stands for an `.editorconfig` file, whose properties don't map one-to-one to settings.
+fn setting_row(text: &str, key_path: &[String]) -> Option<u32> {
+    let offset = if key_path.is_empty() {
+        const PROPERTIES: [&str; 6] = [
+            "indent_size",
+            "indent_style",
+            "tab_width",
+            "insert_final_newline",
+            "trim_trailing_whitespace",
+            "root",
+        ];
+        let mut offset = 0;
+        text.split_inclusive('\n')
+            .find_map(|line| {
+                let line_offset = offset;
+                offset += line.len();
+                PROPERTIES
+                    .iter()
+                    .any(|property| line.trim_start().starts_with(property))
+                    .then_some(line_offset)
+            })
+            .unwrap_or(0)
+    } else {
+        let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+        settings::json_key_path_range(text, &key_path)?.start
+    };
+    Some(text[..offset].matches('\n').count() as u32)
+}
+
+#[derive(Clone)]
+enum SettingSource {
+    DefaultSettings,
+    File(PathBuf),
+}
+
+struct Contribution {
+    label: SharedString,
+    value: SharedString,
+    source: Option<SettingSource>,
+    /// The key path of the setting within the source file.
+    key_path: Vec<String>,
+    row: Option<u32>,
+}
+
+struct SettingProvenanceModal {
+    picker: Entity<Picker<SettingProvenanceDelegate>>,
+    _resolve_rows: Task<()>,
+}
+
+impl ModalView for SettingProvenanceModal {}
+
+impl EventEmitter<DismissEvent> for SettingProvenanceModal {}
+
+impl Focusable for SettingProvenanceModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingProvenanceModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+struct SettingProvenanceDelegate {
+    modal: WeakEntity<SettingProvenanceModal>,
+    workspace: WeakEntity<Workspace>,
+    key_path: SharedString,
+    value: SharedString,
+    contributions: Vec<Contribution>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for SettingProvenanceDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "".into()
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No settings layer sets this setting".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.contributions.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        _query: String,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(contribution) = self.contributions.get(self.selected_index) else {
+            return;
+        };
+        let Some(source) = contribution.source.clone() else {
+            return;
+        };
+        let row = contribution.row.unwrap_or(0);
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let editor = match source {
+                    SettingSource::DefaultSettings => open_bundled_editor(
+                        workspace,
+                        settings::default_settings(),
+                        "Default Settings",
+                        "JSON",
+                        window,
+                        cx,
+                    ),
+                    SettingSource::File(path) => {
+                        let item = workspace.open_abs_path(
+                            path,
+                            OpenOptions {
+                                visible: Some(OpenVisible::None),
+                                ..Default::default()
+                            },
+                            window,
+                            cx,
+                        );
+                        cx.spawn_in(window, async move |_, cx| {
+                            let item = item.await?;
+                            cx.update(|_, cx| item.act_as::<Editor>(cx))?
+                                .ok_or_else(|| anyhow::anyhow!("not an editor"))
+                        })
+                    }
+                };
+                cx.spawn_in(window, async move |_, cx| {
+                    let editor = editor.await?;
+                    editor.update_in(cx, |editor, window, cx| {
+                        let point = Point::new(row, 0);
+                        editor.change_selections(
+                            Some(Autoscroll::center()),
+                            window,
+                            cx,
+                            |selections| selections.select_ranges([point..point]),
+                        );
+                    })
+                })
+                .detach_and_log_err(cx);
+            })
+            .log_err();
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.modal.update(cx, |_, cx| cx.emit(DismissEvent)).ok();
+    }
+
+    fn render_header(&self, _: &mut Window, cx: &mut Context<Picker<Self>>) -> Option<AnyElement> {
+        Some(
+            v_flex()
+                .p_2()
+                .gap_0p5()
+                .border_b_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(Label::new(self.key_path.clone()))
+                .child(
+                    Label::new(self.value.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element(),
+        )
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let contribution = self.contributions.get(ix)?;
+        let location = match (&contribution.source, contribution.row) {
+            (Some(SettingSource::DefaultSettings), row) => Some(("default.json".into(), row)),
+            (Some(SettingSource::File(path)), row) => {
+                Some((path.to_string_lossy().into_owned(), row))
+            }
+            (None, _) => None,
+        }
+        .map(|(path, row)| match row {
+            Some(row) => format!("{path}:{}", row + 1),
+            None => path,
+        });
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    v_flex()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(Label::new(contribution.label.clone()))
+                                .child(
+                                    Label::new(contribution.value.clone())
+                                        .color(Color::Muted)
+                                        .truncate(),
+                                ),
+                        )
+                        .when_some(location, |this, location| {
+                            this.child(
+                                Label::new(location)
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            )
+                        }),
+                ),
+        )
+    }
+}
+
+struct SettingKeyPicker {
+    picker: Entity<Picker<SettingKeyPickerDelegate>>,
+}
+
+impl ModalView for SettingKeyPicker {}
+
+impl EventEmitter<DismissEvent> for SettingKeyPicker {}
+
+impl Focusable for SettingKeyPicker {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for SettingKeyPicker {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+struct SettingKeyPickerDelegate {
+    picker: WeakEntity<SettingKeyPicker>,
+    workspace: WeakEntity<Workspace>,
+    query: SettingQuery,
+    key_paths: Vec<Vec<String>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl SettingKeyPickerDelegate {
+    fn new(
+        picker: WeakEntity<SettingKeyPicker>,
+        workspace: WeakEntity<Workspace>,
+        query: SettingQuery,
+        cx: &App,
+    ) -> Self {
+        Self {
+            picker,
+            workspace,
+            query,
+            key_paths: cx.global::<SettingsStore>().setting_key_paths(),
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for SettingKeyPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Search for a setting to explain...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut Window, _: &mut Context<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .key_paths
+            .iter()
+            .enumerate()
+            .map(|(id, key_path)| StringMatchCandidate::new(id, &key_path.join(".")))
+            .collect::<Vec<_>>();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let query = SettingQuery {
+            key_path: self.key_paths[mat.candidate_id].clone(),
+            location: self.query.location.clone(),
+        };
+        let workspace = self.workspace.clone();
+        window.defer(cx, move |window, cx| {
+            workspace
+                .update(cx, |workspace, cx| {
+                    show_provenance(workspace, query, window, cx)
+                })
+                .log_err();
+        });
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.picker.update(cx, |_, cx| cx.emit(DismissEvent)).ok();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(HighlightedLabel::new(
+                    mat.string.clone(),
+                    mat.positions.clone(),
+                )),
+        )
+    }
+}
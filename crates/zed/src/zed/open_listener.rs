@@ -85,6 +85,7 @@ impl OpenRequest {
             host.clone(),
             port,
             username.clone(),
+            None,
         );
         if let Some(password) = url.password() {
             connection_options.password = Some(password.to_string());
@@ -376,8 +377,12 @@ async fn open_workspaces(
                 SerializedWorkspaceLocation::Ssh(ssh) => {
                     let app_state = app_state.clone();
                     let connection_options = cx.update(|cx| {
-                        SshSettings::get_global(cx)
-                            .connection_options_for(ssh.host, ssh.port, ssh.user)
+                        SshSettings::get_global(cx).connection_options_for(
+                            ssh.host,
+                            ssh.port,
+                            ssh.user,
+                            ssh.shell_command,
+                        )
                     });
                     if let Ok(connection_options) = connection_options {
                         cx.spawn(async move |mut cx| {